@@ -1,5 +1,8 @@
-use anyhow::Result;
-use installer_core::catalog::Catalog;
+use anyhow::{bail, Context, Result};
+use installer_core::catalog::{
+    self as core_catalog, Catalog, FixtureIndex, PackageIndex, SystemIndex,
+};
+use std::path::Path;
 
 pub fn catalog_to_text(catalog: &Catalog) -> String {
     let mut output = String::new();
@@ -21,6 +24,9 @@ pub fn catalog_to_text(catalog: &Catalog) -> String {
                     program.name, program.id, program.tier, rec_marker
                 ));
                 output.push_str(&format!("      {}\n", program.description));
+                if let Some(origin) = &program.origin {
+                    output.push_str(&format!("      from {}\n", origin));
+                }
             }
         }
         output.push('\n');
@@ -32,6 +38,59 @@ pub fn catalog_to_json(catalog: &Catalog) -> serde_json::Result<String> {
     serde_json::to_string_pretty(catalog)
 }
 
+/// Print the embedded S-tier catalog with this host's overlays applied.
+///
+/// Fails, listing every problem, if any overlay file is invalid.
+pub fn show_catalog(json_output: bool) -> Result<()> {
+    let (overlays, errors) = core_catalog::installed_overlays();
+    if !errors.is_empty() {
+        bail!("invalid catalog overlays:\n  - {}", errors.join("\n  - "));
+    }
+    let catalog = Catalog::load_s_tier()?.with_overlays(&overlays);
+    print_catalog(&catalog, json_output)
+}
+
+/// Print a JSON verification report for the catalogs' package mappings.
+///
+/// Fails after printing when any mapped package has a problem, so CI can gate on it.
+pub fn verify_catalog(
+    distro: Option<&str>,
+    arch: Option<&str>,
+    fixture: Option<&Path>,
+) -> Result<()> {
+    let distro = match distro {
+        Some(distro) => distro.to_string(),
+        None => {
+            installer_core::detect_platform()
+                .context("detecting host distro; pass --distro")?
+                .distro_family
+        }
+    };
+    let backend = core_catalog::backend_for_distro(&distro)?;
+    let arch = arch.unwrap_or(std::env::consts::ARCH);
+
+    let index: Box<dyn PackageIndex> = match fixture {
+        Some(path) => Box::new(FixtureIndex::load(path)?),
+        None => Box::new(SystemIndex::new(backend)),
+    };
+    let report = core_catalog::verify_catalogs(
+        &core_catalog::bundled_catalogs(),
+        backend,
+        arch,
+        index.as_ref(),
+    );
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.is_clean() {
+        bail!(
+            "{} of {} mapped packages failed verification",
+            report.issues.len(),
+            report.checked
+        );
+    }
+    Ok(())
+}
+
 pub fn print_catalog(catalog: &Catalog, json_output: bool) -> Result<()> {
     if json_output {
         println!("{}", catalog_to_json(catalog)?);
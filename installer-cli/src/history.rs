@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use installer_core::history::{self, HistoryDb, RunDiff, RunRecord, RunSummary};
+use installer_core::InstallOptions;
+use std::fmt::Write as _;
+
+fn open() -> Result<HistoryDb> {
+    HistoryDb::open(&history::history_path())
+}
+
+fn fetch(db: &HistoryDb, id: i64) -> Result<RunRecord> {
+    db.get(id)?
+        .with_context(|| format!("no run #{id} in {}", history::history_path().display()))
+}
+
+/// Options of the newest successful run, the baseline for `reconcile`.
+pub fn last_successful_options() -> Result<InstallOptions> {
+    open()?.last_successful_options()?.with_context(|| {
+        format!(
+            "no successful run in {}; pass --manifest instead",
+            history::history_path().display()
+        )
+    })
+}
+
+pub fn list_runs(limit: usize, json: bool) -> Result<()> {
+    let runs = open()?.list(limit)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&runs)?);
+    } else {
+        print!("{}", runs_to_text(&runs));
+    }
+    Ok(())
+}
+
+pub fn show_run(id: i64, json: bool) -> Result<()> {
+    let record = fetch(&open()?, id)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&record)?);
+    } else {
+        print!("{}", run_to_text(&record));
+    }
+    Ok(())
+}
+
+pub fn diff_runs(a: i64, b: i64, json: bool) -> Result<()> {
+    let db = open()?;
+    let diff = history::diff(&fetch(&db, a)?, &fetch(&db, b)?)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{}", diff_to_text(&diff));
+    }
+    Ok(())
+}
+
+pub fn runs_to_text(runs: &[RunSummary]) -> String {
+    if runs.is_empty() {
+        return "No runs recorded yet.\n".to_string();
+    }
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:>4}  {:<12}  {:<8}  {:<8}  {:<22}  {:>6}  {:>6}",
+        "ID", "STARTED", "DRIVER", "PROFILE", "OUTCOME", "PHASES", "ERRORS"
+    );
+    for run in runs {
+        let outcome = if run.dry_run {
+            format!("{} (dry run)", run.outcome.label())
+        } else {
+            run.outcome.label().to_string()
+        };
+        let _ = writeln!(
+            out,
+            "{:>4}  {:<12}  {:<8}  {:<8}  {:<22}  {:>6}  {:>6}",
+            run.id,
+            run.started_at,
+            run.driver,
+            run.profile,
+            outcome,
+            run.phases_completed,
+            run.error_count
+        );
+    }
+    out
+}
+
+pub fn run_to_text(record: &RunRecord) -> String {
+    let run = &record.summary;
+    let report = &record.report;
+    let mut out = String::new();
+    let _ = writeln!(out, "Run #{} ({})", run.id, run.outcome.label());
+    if let Some(run_id) = &run.run_id {
+        let _ = writeln!(out, "  Journal:  {run_id}");
+    }
+    let _ = writeln!(out, "  Started:  {}", run.started_at);
+    let _ = writeln!(out, "  Duration: {:.1}s", run.duration_ms as f64 / 1000.0);
+    let _ = writeln!(
+        out,
+        "  Driver:   {} ({})",
+        report.driver.name, report.driver.description
+    );
+    let _ = writeln!(
+        out,
+        "  Profile:  {}{}",
+        run.profile,
+        if run.dry_run { " (dry run)" } else { "" }
+    );
+    let _ = writeln!(out, "  Phases:");
+    for phase in &report.outputs {
+        let _ = writeln!(
+            out,
+            "    {:<24} {:<20} {:>7.1}s",
+            phase.name,
+            history::status_label(&phase.status),
+            phase.duration_ms as f64 / 1000.0
+        );
+    }
+    for err in &report.errors {
+        let _ = writeln!(out, "  Error in {}: {}", err.phase, err.developer_message());
+        if let Some(details) = err.command_output() {
+            let _ = writeln!(
+                out,
+                "    Command: {} (exit {})",
+                details.command,
+                details
+                    .status
+                    .map_or_else(|| "unknown".to_string(), |code| code.to_string())
+            );
+        }
+    }
+    out
+}
+
+pub fn diff_to_text(diff: &RunDiff) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Run #{} → run #{}", diff.a, diff.b);
+    if diff.is_empty() {
+        let _ = writeln!(out, "  No differences.");
+        return out;
+    }
+    if let Some((a, b)) = &diff.driver {
+        let _ = writeln!(out, "  driver: {a} → {b}");
+    }
+    let show = |value: &Option<serde_json::Value>| {
+        value
+            .as_ref()
+            .map_or_else(|| "-".to_string(), |v| v.to_string())
+    };
+    for change in &diff.options {
+        let _ = writeln!(
+            out,
+            "  option {}: {} → {}",
+            change.option,
+            show(&change.a),
+            show(&change.b)
+        );
+    }
+    for change in &diff.phases {
+        let _ = writeln!(
+            out,
+            "  phase {}: {} → {}",
+            change.phase,
+            change.a.as_deref().unwrap_or("-"),
+            change.b.as_deref().unwrap_or("-")
+        );
+    }
+    for phase in &diff.errors_only_in_a {
+        let _ = writeln!(out, "  failed only in #{}: {phase}", diff.a);
+    }
+    for phase in &diff.errors_only_in_b {
+        let _ = writeln!(out, "  failed only in #{}: {phase}", diff.b);
+    }
+    out
+}
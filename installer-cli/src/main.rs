@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use installer_core::cmd::CommandExecutionDetails;
+use installer_core::manifest::{self, InstallManifest, ManifestFormat};
 use installer_core::SystemProfileExt;
 use installer_core::{
     detect_platform, init_logging,
@@ -14,6 +15,7 @@
 use tracing::info;
 
 mod catalog;
+mod history;
 mod menu;
 mod software_catalog;
 mod software_tiers;
@@ -45,6 +47,30 @@ struct Cli {
     #[arg(long)]
     continue_on_error: bool,
 
+    /// Resume an interrupted run, skipping phases that already completed
+    #[arg(long)]
+    resume: bool,
+
+    /// Run up to N independent phases at once
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+
+    /// Finish with a phase that checks the machine against the chosen options
+    #[arg(long)]
+    verify: bool,
+
+    /// Allow upstream install scripts that are not pinned to a checksum
+    #[arg(long)]
+    allow_unverified_scripts: bool,
+
+    /// Run only these phases (comma-separated ids, e.g. system_packages,shell_ux)
+    #[arg(long, value_name = "IDS", value_delimiter = ',')]
+    only: Vec<String>,
+
+    /// Leave out these phases (comma-separated ids)
+    #[arg(long, value_name = "IDS", value_delimiter = ',')]
+    skip: Vec<String>,
+
     /// Profile to install: minimal, dev, full  (skips the profile menu)
     #[arg(long, value_name = "LEVEL")]
     profile: Option<String>,
@@ -69,6 +95,22 @@ struct Cli {
     #[arg(long, default_value_t = 3030)]
     scry_port: u16,
 
+    /// Listen on all interfaces instead of localhost only
+    #[arg(long)]
+    scry_open: bool,
+
+    /// Token scrying clients must present (default: a random pairing code)
+    #[arg(long, value_name = "TOKEN")]
+    scry_token: Option<String>,
+
+    /// Let scrying clients answer confirmation and authorization prompts
+    #[arg(long)]
+    scry_control: bool,
+
+    /// Seconds a prompt waits for a scrying client before asking locally
+    #[arg(long, value_name = "SECS", default_value_t = installer_core::ScryConfig::DEFAULT_PROMPT_TIMEOUT.as_secs())]
+    scry_timeout: u64,
+
     /// Environment tag: home, work, traveling
     #[arg(long, value_name = "TAG", default_value = "home")]
     env: String,
@@ -85,6 +127,9 @@ enum CliCommand {
         /// Output in JSON format
         #[arg(long)]
         json: bool,
+
+        #[command(subcommand)]
+        action: Option<CatalogAction>,
     },
     /// Show a quick status overview (platform, config, API keys, pre-flight)
     Status {
@@ -109,6 +154,145 @@ enum CliCommand {
         #[command(subcommand)]
         action: ConfigAction,
     },
+    /// Replay an install manifest (TOML or JSON) non-interactively
+    Apply {
+        /// Path to the manifest file
+        manifest: PathBuf,
+    },
+    /// Check that the machine still matches a manifest or the last session
+    Verify {
+        /// Manifest to check against (default: the last recorded session)
+        #[arg(long)]
+        manifest: Option<PathBuf>,
+        /// Output format (pretty or json)
+        #[arg(long, value_enum, default_value_t = installer_core::VerifyOutput::Pretty)]
+        format: installer_core::VerifyOutput,
+    },
+    /// Re-run only the phases whose results drifted since the last successful run
+    Reconcile {
+        /// Manifest to reconcile against (default: the last successful run)
+        #[arg(long)]
+        manifest: Option<PathBuf>,
+        /// Only report drift; exit non-zero if there is any
+        #[arg(long)]
+        check: bool,
+        /// Output format for the drift report (pretty or json)
+        #[arg(long, value_enum, default_value_t = installer_core::VerifyOutput::Pretty)]
+        format: installer_core::VerifyOutput,
+    },
+    /// Dry-run an install and list every package, repo, file, service, download and command it would touch
+    Plan {
+        /// Manifest to plan (default: the options given on the command line)
+        #[arg(long)]
+        manifest: Option<PathBuf>,
+        /// Output format (pretty or json)
+        #[arg(long, value_enum, default_value_t = installer_core::VerifyOutput::Pretty)]
+        format: installer_core::VerifyOutput,
+    },
+    /// Write the choices from the last TUI session as a manifest
+    Export {
+        /// Destination file (.toml or .json); prints TOML to stdout when omitted
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Browse the local record of past runs
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+    /// Replay a run's rollback journal in reverse
+    Undo {
+        /// Run id to undo (default: the newest run not yet undone)
+        #[arg(long = "run", value_name = "ID")]
+        run_id: Option<String>,
+    },
+    /// Restore the pre-install snapshot taken by a run
+    Rollback {
+        /// Run id shown in the failure report and the run journal
+        run_id: String,
+        /// Restore now instead of printing the restore command
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Raspberry Pi settings
+    Pi {
+        #[command(subcommand)]
+        action: PiAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum PiAction {
+    /// Read or change config.txt and cmdline.txt on the boot partition
+    Config {
+        #[command(subcommand)]
+        action: PiConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum PiConfigAction {
+    /// Show the active boot settings by filter section, and the kernel arguments
+    Show {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Set a boot setting (e.g. dtparam=i2c_arm=on), backing up the old file
+    Set {
+        /// name=value line for config.txt, or a kernel argument with --cmdline
+        setting: String,
+        /// Filter section to put the setting under (all, pi4, pi5, cm4, ...)
+        #[arg(long, default_value = installer_core::boot_config::ALL)]
+        section: String,
+        /// Add a kernel argument to cmdline.txt instead
+        #[arg(long, conflicts_with = "section")]
+        cmdline: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CatalogAction {
+    /// Check every mapped package against the distro's repositories (JSON report)
+    Verify {
+        /// Catalog distro key to check: arch, debian or fedora (default: this host)
+        #[arg(long)]
+        distro: Option<String>,
+        /// Target architecture (default: this host)
+        #[arg(long)]
+        arch: Option<String>,
+        /// Read the package list from a TOML/JSON fixture instead of the package database
+        #[arg(long, value_name = "PATH")]
+        fixture: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryAction {
+    /// List recorded runs, newest first
+    List {
+        /// Maximum number of runs to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show one run's options, phases and errors
+    Show {
+        id: i64,
+        /// Output in JSON format (the full stored report)
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare the options and phase outcomes of two runs
+    Diff {
+        a: i64,
+        b: i64,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -127,22 +311,30 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    match cli.command {
-        Some(CliCommand::Catalog { json }) => {
-            let catalog = installer_core::catalog::curated_catalog();
-            return catalog::print_catalog(&catalog, json);
+    match &cli.command {
+        Some(CliCommand::Catalog { json, action }) => {
+            return match action {
+                Some(CatalogAction::Verify {
+                    distro,
+                    arch,
+                    fixture,
+                }) => {
+                    catalog::verify_catalog(distro.as_deref(), arch.as_deref(), fixture.as_deref())
+                }
+                None => catalog::show_catalog(*json),
+            };
         }
         Some(CliCommand::Status { format }) => {
             let mut stdout = io::stdout();
-            return installer_core::run_status(format, &mut stdout);
+            return installer_core::run_status(*format, &mut stdout);
         }
         Some(CliCommand::Doctor { format }) => {
             let mut stdout = io::stdout();
-            return installer_core::run_doctor(format, &mut stdout);
+            return installer_core::run_doctor(*format, &mut stdout);
         }
         Some(CliCommand::Scry { json }) => {
             let profile = installer_core::SystemProfile::detect(&installer_core::REAL_SYSTEM)?;
-            if json {
+            if *json {
                 println!("{}", profile.to_json()?);
             } else {
                 print_scry_pretty(&profile);
@@ -156,7 +348,51 @@ fn main() -> Result<()> {
                 ConfigAction::Show => installer_core::show_config(&mut stdout),
             };
         }
-        None => {}
+        Some(CliCommand::Export { output }) => {
+            return export_last_session(output.as_deref());
+        }
+        Some(CliCommand::History { action }) => {
+            return match action {
+                HistoryAction::List { limit, json } => history::list_runs(*limit, *json),
+                HistoryAction::Show { id, json } => history::show_run(*id, *json),
+                HistoryAction::Diff { a, b, json } => history::diff_runs(*a, *b, *json),
+            };
+        }
+        Some(CliCommand::Undo { run_id }) => {
+            let mut stdout = io::stdout();
+            return installer_core::rollback::undo(run_id.as_deref(), &mut stdout);
+        }
+        Some(CliCommand::Rollback { run_id, yes }) => {
+            let mut stdout = io::stdout();
+            return installer_core::snapshots::rollback_run(run_id, *yes, &mut stdout);
+        }
+        Some(CliCommand::Pi {
+            action: PiAction::Config { action },
+        }) => {
+            let mut stdout = io::stdout();
+            let system = &installer_core::REAL_SYSTEM;
+            return match action {
+                PiConfigAction::Show { json } => {
+                    installer_core::boot_config::show(system, *json, &mut stdout)
+                }
+                PiConfigAction::Set {
+                    setting,
+                    section,
+                    cmdline,
+                } => installer_core::boot_config::set(
+                    system,
+                    section,
+                    setting,
+                    *cmdline,
+                    &mut stdout,
+                ),
+            };
+        }
+        Some(CliCommand::Apply { .. })
+        | Some(CliCommand::Verify { .. })
+        | Some(CliCommand::Reconcile { .. })
+        | Some(CliCommand::Plan { .. })
+        | None => {}
     }
 
     let config_service = ConfigService::load()?;
@@ -177,14 +413,46 @@ fn main() -> Result<()> {
         );
     }
 
+    let registry = installer_core::PhaseRegistry::default();
+    registry.check_ids(&cli.only)?;
+    registry.check_ids(&cli.skip)?;
+
+    // ── Manifest path (`apply`) ──────────────────────────────────────────────
+    if let Some(CliCommand::Apply { manifest }) = &cli.command {
+        return apply_manifest(&cli, manifest, &drivers);
+    }
+
+    // ── Drift check (`verify`) ───────────────────────────────────────────────
+    if let Some(CliCommand::Verify { manifest, format }) = &cli.command {
+        return verify_machine(&cli, manifest.as_deref(), *format, &drivers);
+    }
+
+    // ── Drift repair (`reconcile`) ───────────────────────────────────────────
+    if let Some(CliCommand::Reconcile {
+        manifest,
+        check,
+        format,
+    }) = &cli.command
+    {
+        return reconcile(&cli, manifest.as_deref(), *check, *format, &drivers);
+    }
+
+    // ── Pre-flight plan (`plan`) ─────────────────────────────────────────────
+    if let Some(CliCommand::Plan { manifest, format }) = &cli.command {
+        return plan(&cli, manifest.as_deref(), *format, &drivers);
+    }
+
     // ── TUI path (default) ───────────────────────────────────────────────────
     if !cli.no_tui && !cli.non_interactive {
         return tui::run(
             drivers,
             cli.dry_run || cli.demo,
             cli.continue_on_error,
-            cli.scry,
-            cli.scry_port,
+            cli.resume,
+            cli.jobs,
+            cli.allow_unverified_scripts,
+            cli.skipped_phases(),
+            cli.scry_config(),
         )
         .context("TUI exited with error");
     }
@@ -220,6 +488,198 @@ fn main() -> Result<()> {
         menu::run_profile_menu(&interaction)?
     };
 
+    let options = cli_options(&cli, profile, &modules, software_plan);
+
+    info!(
+        "Selected driver: {} ({}). Profile: {:?}. Modules: {:?}",
+        driver.name(),
+        driver.description(),
+        profile,
+        modules
+    );
+
+    let mut composite = installer_core::CompositeObserver::new();
+
+    // 1. CLI/TUI Observer
+    add_cli_observer(&mut composite, options.interactive);
+
+    // 2. Remote Scrying (Websocket)
+    attach_scryer(&cli, &mut composite)?;
+
+    run_installer_with_ui(driver, options, &mut composite).context("installer failed")
+}
+
+/// Picks the compiled-in driver for this host, for the non-interactive
+/// subcommands that have no driver menu to fall back on.
+fn detect_driver(drivers: &[&'static dyn DistroDriver]) -> Result<&'static dyn DistroDriver> {
+    let platform_info = detect_platform().context("detecting host platform")?;
+    menu::auto_detect_driver(drivers, &platform_info).with_context(|| {
+        format!(
+            "no compiled-in driver matches this host ({})",
+            platform_info.distro_family
+        )
+    })
+}
+
+fn apply_manifest(
+    cli: &Cli,
+    path: &std::path::Path,
+    drivers: &[&'static dyn DistroDriver],
+) -> Result<()> {
+    let manifest = InstallManifest::load(path)?;
+    let mut options = manifest.to_options(cli.dry_run);
+    if cli.staging_dir.is_some() {
+        options.staging_dir = cli.staging_dir.clone();
+    }
+    options.continue_on_error |= cli.continue_on_error;
+    options.resume = cli.resume;
+    options.jobs = cli.jobs;
+    options.verify = cli.verify;
+    options.allow_unverified_scripts = cli.allow_unverified_scripts;
+    options.only_phases = cli.only.clone();
+    options.skip_phases = cli.skip.clone();
+
+    let driver = detect_driver(drivers)?;
+
+    info!(
+        "Applying manifest {} with driver {}. Profile: {:?}",
+        path.display(),
+        driver.name(),
+        options.profile
+    );
+
+    let mut composite = installer_core::CompositeObserver::new();
+    add_cli_observer(&mut composite, options.interactive);
+    attach_scryer(cli, &mut composite)?;
+
+    run_installer_with_ui(driver, options, &mut composite).context("installer failed")
+}
+
+impl Cli {
+    /// Phase ids `--only` and `--skip` leave out, as the TUI's phase list
+    /// starts unticked.
+    fn skipped_phases(&self) -> Vec<String> {
+        let kept = installer_core::PhaseRegistry::default()
+            .only(&self.only)
+            .skip(&self.skip)
+            .ids();
+        installer_core::PhaseRegistry::default()
+            .ids()
+            .into_iter()
+            .filter(|id| !kept.contains(id))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn scry_config(&self) -> Option<installer_core::ScryConfig> {
+        self.scry.then(|| installer_core::ScryConfig {
+            open: self.scry_open,
+            token: self.scry_token.clone(),
+            remote_control: self.scry_control,
+            prompt_timeout: std::time::Duration::from_secs(self.scry_timeout),
+            ..installer_core::ScryConfig::new(self.scry_port)
+        })
+    }
+}
+
+/// Show progress on the console, and let it answer prompts when the run is
+/// allowed to ask.
+fn add_cli_observer(composite: &mut installer_core::CompositeObserver, interactive: bool) {
+    if interactive {
+        composite.add_prompter(ui::CliPhaseObserver::new());
+    } else {
+        composite.add(ui::CliPhaseObserver::new());
+    }
+}
+
+fn attach_scryer(cli: &Cli, composite: &mut installer_core::CompositeObserver) -> Result<()> {
+    let Some(config) = cli.scry_config() else {
+        return Ok(());
+    };
+    info!("🔮  Enabling remote scrying on port {}...", config.port);
+    let scryer = installer_core::WebsocketObserver::new(config)?;
+    eprintln!(
+        "🔮  Remote scrying at {} (pairing code {})",
+        scryer.dashboard_url(),
+        scryer.token()
+    );
+    composite.add_scryer(scryer);
+    Ok(())
+}
+
+fn verify_machine(
+    cli: &Cli,
+    manifest: Option<&std::path::Path>,
+    format: installer_core::VerifyOutput,
+    drivers: &[&'static dyn DistroDriver],
+) -> Result<()> {
+    let manifest = match manifest {
+        Some(path) => Some(InstallManifest::load(path)?),
+        None => manifest::load_last_session()?,
+    };
+    let mut options = manifest
+        .map(|manifest| manifest.to_options(false))
+        .unwrap_or_default();
+    if let Some(ref p) = cli.profile {
+        options.profile = parse_profile_level(p)?;
+    }
+
+    let driver = detect_driver(drivers)?;
+
+    let mut stdout = io::stdout();
+    let report = installer_core::run_verify(&options, driver, format, &mut stdout)?;
+    if !report.is_clean() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn reconcile(
+    cli: &Cli,
+    manifest: Option<&std::path::Path>,
+    check: bool,
+    format: installer_core::VerifyOutput,
+    drivers: &[&'static dyn DistroDriver],
+) -> Result<()> {
+    let mut options = match manifest {
+        Some(path) => InstallManifest::load(path)?.to_options(cli.dry_run),
+        None => history::last_successful_options()?,
+    };
+    options.dry_run = cli.dry_run;
+    if cli.staging_dir.is_some() {
+        options.staging_dir = cli.staging_dir.clone();
+    }
+    options.continue_on_error |= cli.continue_on_error;
+    options.jobs = cli.jobs;
+    options.allow_unverified_scripts |= cli.allow_unverified_scripts;
+
+    let driver = detect_driver(drivers)?;
+
+    let mut stdout = io::stdout();
+    let report = installer_core::run_verify(&options, driver, format, &mut stdout)?;
+    let Some(options) = installer_core::reconcile_options(&options, &report) else {
+        return Ok(());
+    };
+    if check {
+        std::process::exit(1);
+    }
+
+    eprintln!("Re-running: {}", options.only_phases.join(", "));
+    let mut composite = installer_core::CompositeObserver::new();
+    add_cli_observer(&mut composite, options.interactive);
+    attach_scryer(cli, &mut composite)?;
+
+    run_installer_with_ui(driver, options, &mut composite).context("reconcile failed")
+}
+
+/// Install options for this command line, once the menus (or their
+/// non-interactive defaults) have picked the profile, modules and tiers.
+fn cli_options(
+    cli: &Cli,
+    profile: ProfileLevel,
+    modules: &menu::ModuleSelection,
+    software_plan: SoftwareTierPlan,
+) -> InstallOptions {
     let environment = match cli.env.to_lowercase().as_str() {
         "home" => installer_core::model::options::EnvironmentTag::Home,
         "work" => installer_core::model::options::EnvironmentTag::Work,
@@ -227,9 +687,9 @@ fn main() -> Result<()> {
         _ => installer_core::model::options::EnvironmentTag::Home,
     };
 
-    let options = InstallOptions {
+    InstallOptions {
         profile,
-        staging_dir: cli.staging_dir,
+        staging_dir: cli.staging_dir.clone(),
         dry_run: cli.dry_run,
         interactive: !cli.non_interactive,
         argon: ArgonConfig {
@@ -242,36 +702,84 @@ fn main() -> Result<()> {
             data_root: None, // Or logic to set it
         },
         continue_on_error: cli.continue_on_error,
+        resume: cli.resume,
+        jobs: cli.jobs,
+        verify: cli.verify,
+        allow_unverified_scripts: cli.allow_unverified_scripts,
+        only_phases: cli.only.clone(),
+        skip_phases: cli.skip.clone(),
         software_plan,
         system_profile: None,
         environment,
         chezmoi: Default::default(),
         desktop_environment: None,
         display_protocol: installer_core::desktop::DisplayProtocol::Auto,
+        history_db: None,
+    }
+}
+
+fn plan(
+    cli: &Cli,
+    manifest: Option<&std::path::Path>,
+    format: installer_core::VerifyOutput,
+    drivers: &[&'static dyn DistroDriver],
+) -> Result<()> {
+    // Without a manifest, plan what the same non-interactive command line
+    // would install.
+    let options = match manifest {
+        Some(path) => {
+            let mut options = InstallManifest::load(path)?.to_options(true);
+            if let Some(ref p) = cli.profile {
+                options.profile = parse_profile_level(p)?;
+            }
+            if cli.staging_dir.is_some() {
+                options.staging_dir = cli.staging_dir.clone();
+            }
+            options.jobs = cli.jobs;
+            options.verify |= cli.verify;
+            options.allow_unverified_scripts = cli.allow_unverified_scripts;
+            options.only_phases = cli.only.clone();
+            options.skip_phases = cli.skip.clone();
+            options
+        }
+        None => {
+            let profile = match &cli.profile {
+                Some(p) => parse_profile_level(p)?,
+                None => ProfileLevel::Dev,
+            };
+            cli_options(
+                cli,
+                profile,
+                &menu::ModuleSelection::default(),
+                SoftwareTierPlan::default(),
+            )
+        }
     };
 
-    info!(
-        "Selected driver: {} ({}). Profile: {:?}. Modules: {:?}",
-        driver.name(),
-        driver.description(),
-        profile,
-        modules
-    );
+    let driver = detect_driver(drivers)?;
 
-    let mut composite = installer_core::CompositeObserver::new();
+    let mut quiet = installer_core::CompositeObserver::new();
+    let mut stdout = io::stdout();
+    installer_core::run_plan(driver, &options, format, &mut quiet, &mut stdout)?;
+    Ok(())
+}
 
-    // 1. CLI/TUI Observer
-    let cli_observer = ui::CliPhaseObserver::new();
-    composite.add(cli_observer);
+fn export_last_session(output: Option<&std::path::Path>) -> Result<()> {
+    let manifest = manifest::load_last_session()?.with_context(|| {
+        format!(
+            "no recorded session at {}; finish a TUI session first",
+            manifest::last_session_path().display()
+        )
+    })?;
 
-    // 2. Remote Scrying (Websocket)
-    if cli.scry {
-        info!("🔮  Enabling remote scrying on port {}...", cli.scry_port);
-        let scryer = installer_core::WebsocketObserver::new(cli.scry_port);
-        composite.add(scryer);
+    match output {
+        Some(path) => {
+            manifest.save(path)?;
+            println!("Wrote manifest to {}", path.display());
+        }
+        None => print!("{}", manifest.render(ManifestFormat::Toml)?),
     }
-
-    run_installer_with_ui(driver, options, &mut composite).context("installer failed")
+    Ok(())
 }
 
 fn print_scry_pretty(profile: &installer_core::SystemProfile) {
@@ -447,6 +955,18 @@ fn write_error_report(report: &InstallationReport, out: &mut dyn Write) -> std::
         writeln!(out)?;
     }
 
+    if let (Some(run_id), true) = (&report.run_id, report.has_fatal_error()) {
+        for snapshot in &report.snapshots {
+            writeln!(
+                out,
+                "Pre-install snapshot: {} ({})",
+                snapshot.id,
+                snapshot.backend.label()
+            )?;
+            writeln!(out, "  Restore it with: mash-setup rollback {run_id} --yes")?;
+        }
+    }
+
     Ok(())
 }
 
@@ -476,10 +996,11 @@ fn write_multiline(out: &mut dyn Write, label: &str, text: &str) -> std::io::Res
 
 fn run_installer_with_ui(
     driver: &'static dyn DistroDriver,
-    options: InstallOptions,
+    mut options: InstallOptions,
     observer: &mut dyn installer_core::PhaseObserver,
 ) -> Result<()> {
     ui::print_banner();
+    options.history_db = Some(installer_core::history::history_path());
     let dry_run = options.dry_run;
     let run_result = installer_core::run_with_driver(driver, options, observer);
 
@@ -534,6 +1055,8 @@ fn make_report_with_error() -> InstallationReport {
             },
             dry_run_log: Vec::new(),
             audit_report: installer_core::dry_run::PreflightAuditReport::default(),
+            run_id: None,
+            snapshots: Vec::new(),
         }
     }
 
@@ -549,6 +1072,26 @@ fn write_error_report_prints_phase_and_advice() {
         assert!(output.contains("Command: echo fail"));
     }
 
+    #[test]
+    fn write_error_report_points_at_snapshot_after_fatal_error() {
+        let mut report = make_report_with_error();
+        report.errors[0].severity = ErrorSeverity::Fatal;
+        report.run_id = Some("1700000000-42".into());
+        report.snapshots = vec![installer_core::snapshots::SnapshotRecord {
+            run_id: "1700000000-42".into(),
+            backend: installer_core::snapshots::SnapshotKind::Snapper,
+            id: "17".into(),
+            description: "MASH pre-install".into(),
+            created_at: 1_700_000_000,
+        }];
+
+        let mut buf = Vec::new();
+        write_error_report(&report, &mut buf).expect("write failed");
+        let output = String::from_utf8(buf).expect("invalid utf8");
+        assert!(output.contains("Pre-install snapshot: 17 (snapper)"));
+        assert!(output.contains("mash-setup rollback 1700000000-42 --yes"));
+    }
+
     #[test]
     fn write_error_report_outputs_no_errors_message() {
         let report = InstallationReport {
@@ -564,6 +1107,8 @@ fn write_error_report_outputs_no_errors_message() {
             },
             dry_run_log: Vec::new(),
             audit_report: installer_core::dry_run::PreflightAuditReport::default(),
+            run_id: None,
+            snapshots: Vec::new(),
         };
 
         let mut buf = Vec::new();
@@ -73,6 +73,8 @@ pub fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
             Screen::ArgonConfig => self.handle_argon_key(code),
             Screen::DockerConfig => self.handle_docker_key(code),
             Screen::ChezmoiConfig => self.handle_chezmoi_config_key(code),
+            Screen::PhaseSelect => self.handle_phase_select_key(code),
+            Screen::PlanReview => self.handle_plan_review_key(code),
             Screen::SystemSummary => self.handle_system_summary_key(code),
             Screen::Authorization => self.handle_auth_key(code),
             Screen::Installing => self.handle_installing_key(code),
@@ -257,6 +259,73 @@ fn handle_docker_key(&mut self, code: KeyCode) {
         }
     }
 
+    fn handle_phase_select_key(&mut self, code: KeyCode) {
+        // One row per phase, then "CONFIRM AND CONTINUE".
+        let max_cursor = self.phase_choices.len();
+
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                if self.menu_cursor > 0 {
+                    self.menu_cursor -= 1;
+                } else {
+                    self.menu_cursor = max_cursor;
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.menu_cursor < max_cursor {
+                    self.menu_cursor += 1;
+                } else {
+                    self.menu_cursor = 0;
+                }
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(phase) = self.phase_choices.get(self.menu_cursor) {
+                    let id = phase.id.to_string();
+                    if self.skipped_phases.contains(&id) {
+                        self.tick_phase(phase.id);
+                    } else {
+                        self.untick_phase(phase.id);
+                    }
+                } else {
+                    self.advance_from_list();
+                }
+            }
+            KeyCode::Esc => self.go_back(),
+            _ => {}
+        }
+    }
+
+    /// Tick a phase along with everything it depends on.
+    fn tick_phase(&mut self, id: &str) {
+        if !self.skipped_phases.remove(id) {
+            return;
+        }
+        let deps = self
+            .phase_choices
+            .iter()
+            .find(|phase| phase.id == id)
+            .map_or(&[][..], |phase| phase.deps);
+        for dep in deps {
+            self.tick_phase(dep);
+        }
+    }
+
+    /// Untick a phase along with every phase that depends on it.
+    fn untick_phase(&mut self, id: &str) {
+        if !self.skipped_phases.insert(id.to_string()) {
+            return;
+        }
+        let dependents: Vec<&'static str> = self
+            .phase_choices
+            .iter()
+            .filter(|phase| phase.deps.contains(&id))
+            .map(|phase| phase.id)
+            .collect();
+        for dependent in dependents {
+            self.untick_phase(dependent);
+        }
+    }
+
     fn handle_chezmoi_config_key(&mut self, code: KeyCode) {
         let max_cursor = if self.chezmoi_enabled { 3 } else { 1 };
 
@@ -341,6 +410,9 @@ fn handle_confirm_key(&mut self, code: KeyCode) {
                     self.advance_from_list();
                 }
             }
+            KeyCode::Char('p') | KeyCode::Char('P') if self.screen == Screen::Confirm => {
+                self.review_plan();
+            }
             KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
                 self.go_back();
             }
@@ -348,6 +420,26 @@ fn handle_confirm_key(&mut self, code: KeyCode) {
         }
     }
 
+    fn handle_plan_review_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.summary_scroll = self.summary_scroll.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.summary_scroll + 1 < self.plan_lines.len() {
+                    self.summary_scroll += 1;
+                }
+            }
+            KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => {
+                if !self.plan_lines.is_empty() {
+                    self.start_install();
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => self.go_back(),
+            _ => {}
+        }
+    }
+
     fn handle_system_summary_key(&mut self, code: KeyCode) {
         match code {
             KeyCode::Enter | KeyCode::Char(' ') => {
@@ -556,6 +648,7 @@ fn handle_landing_selection(&mut self) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tui::state::TuiMessage;
     use std::sync::mpsc;
 
     #[test]
@@ -602,4 +695,87 @@ fn test_landing_menu_back_navigation() {
         // Back from Landing should go to SystemScan
         assert_eq!(app.screen, Screen::SystemScan);
     }
+
+    #[test]
+    fn test_phase_select_toggles_skipped_ids() {
+        let (tx, _rx) = mpsc::channel();
+        let mut app = TuiApp::new(tx, vec![]);
+        app.screen = Screen::PhaseSelect;
+        app.menu_cursor = app
+            .phase_choices
+            .iter()
+            .position(|phase| phase.id == "wallpapers")
+            .unwrap();
+
+        app.handle_key(KeyCode::Char(' '), KeyModifiers::NONE);
+        assert_eq!(app.build_options().skip_phases, vec!["wallpapers"]);
+
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+        assert!(app.skipped_phases.is_empty());
+
+        app.menu_cursor = 0;
+        app.handle_key(KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(app.menu_cursor, app.phase_choices.len());
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.screen, Screen::Confirm);
+    }
+
+    #[test]
+    fn test_phase_select_keeps_dependencies_ticked() {
+        let (tx, _rx) = mpsc::channel();
+        let mut app = TuiApp::new(tx, vec![]);
+        app.screen = Screen::PhaseSelect;
+        let index = |app: &TuiApp, id: &str| {
+            app.phase_choices
+                .iter()
+                .position(|phase| phase.id == id)
+                .unwrap()
+        };
+
+        // Unticking a phase unticks what depends on it.
+        app.menu_cursor = index(&app, "git_cli");
+        app.handle_key(KeyCode::Char(' '), KeyModifiers::NONE);
+        for id in ["git_cli", "shell_ux", "software_tiers", "chezmoi", "verify"] {
+            assert!(app.skipped_phases.contains(id), "{id} should be skipped");
+        }
+        assert!(!app.skipped_phases.contains("system_packages"));
+
+        // Ticking a phase back ticks what it depends on.
+        app.menu_cursor = index(&app, "shell_ux");
+        app.handle_key(KeyCode::Char(' '), KeyModifiers::NONE);
+        assert!(!app.skipped_phases.contains("shell_ux"));
+        assert!(!app.skipped_phases.contains("git_cli"));
+        assert!(app.skipped_phases.contains("chezmoi"));
+        let options = app.build_options();
+        assert!(installer_core::PhaseRegistry::default()
+            .check_selection(
+                &options.only_phases,
+                &options.skip_phases,
+                &installer_core::UserOptionsContext::from_options(&options)
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_plan_review_scrolls_and_returns_to_confirm() {
+        let (tx, _rx) = mpsc::channel();
+        let mut app = TuiApp::new(tx, vec![]);
+        app.screen = Screen::PlanReview;
+
+        app.handle_message(TuiMessage::PlanReady(Ok(vec![
+            "== Fonts (fonts)".into(),
+            "  download: font.zip (1.0 MB)".into(),
+        ])));
+        assert_eq!(app.plan_lines.len(), 2);
+
+        app.handle_key(KeyCode::Down, KeyModifiers::NONE);
+        app.handle_key(KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(app.summary_scroll, 1);
+
+        app.handle_message(TuiMessage::PlanReady(Err("offline".into())));
+        assert_eq!(app.plan_lines, vec!["Could not build the plan: offline"]);
+
+        app.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(app.screen, Screen::Confirm);
+    }
 }
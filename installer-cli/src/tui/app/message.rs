@@ -2,6 +2,7 @@
     AuthState, ConfirmState, LogLevel, PasswordState, PhaseRow, PhaseStatus, Screen, TuiApp,
     TuiMessage,
 };
+use crate::ui::{download_detail, format_duration};
 use installer_core::PhaseEvent;
 
 impl TuiApp {
@@ -70,6 +71,12 @@ pub fn handle_message(&mut self, msg: TuiMessage) {
                     self.navigate_to(Screen::Landing, "Main Menu");
                 }
             }
+            TuiMessage::PlanReady(plan) => {
+                self.plan_lines = match plan {
+                    Ok(lines) => lines,
+                    Err(err) => vec![format!("Could not build the plan: {err}")],
+                };
+            }
             TuiMessage::Done(report) => {
                 self.report = Some(report);
                 self.screen = Screen::Done;
@@ -96,47 +103,137 @@ fn handle_phase_event(&mut self, event: PhaseEvent) {
             PhaseEvent::Started {
                 index,
                 phase,
+                label,
                 total: _,
             } => {
                 self.current_phase = index;
+                self.push_log(format!("STARTING: {}", label), LogLevel::Info);
                 self.phases.push(PhaseRow {
-                    name: phase.clone(),
+                    id: phase,
+                    name: label,
                     status: PhaseStatus::Running,
                     description: String::new(),
                 });
-                self.push_log(format!("STARTING: {}", phase), LogLevel::Info);
             }
             PhaseEvent::Completed {
                 index: _,
                 phase,
                 description,
+                duration_ms,
             } => {
-                if let Some(row) = self.phases.iter_mut().find(|r| r.name == phase) {
+                let mut label = phase;
+                if let Some(row) = self.phases.iter_mut().find(|r| r.id == label) {
                     row.status = PhaseStatus::Done;
                     row.description = description;
+                    label = row.name.clone();
                 }
-                self.push_log(format!("COMPLETED: {}", phase), LogLevel::Success);
+                self.push_log(
+                    format!("COMPLETED: {} ({})", label, format_duration(duration_ms)),
+                    LogLevel::Success,
+                );
                 self.progress_pct = (self.current_phase as f32 / self.total_phases as f32) * 100.0;
             }
             PhaseEvent::Failed {
                 index: _,
                 phase,
                 error,
+                duration_ms: _,
             } => {
-                if let Some(row) = self.phases.iter_mut().find(|r| r.name == phase) {
+                let mut label = phase;
+                if let Some(row) = self.phases.iter_mut().find(|r| r.id == label) {
                     row.status = PhaseStatus::Failed;
+                    label = row.name.clone();
                 }
-                self.push_log(format!("FAILED: {}: {}", phase, error), LogLevel::Error);
+                self.push_log(format!("FAILED: {}: {}", label, error), LogLevel::Error);
             }
-            PhaseEvent::Skipped { index: _, phase } => {
-                if let Some(row) = self.phases.iter_mut().find(|r| r.name == phase) {
+            PhaseEvent::Skipped {
+                index: _,
+                phase,
+                label,
+            } => {
+                if let Some(row) = self.phases.iter_mut().find(|r| r.id == phase) {
                     row.status = PhaseStatus::Skipped;
                 }
-                self.push_log(format!("SKIPPED: {}", phase), LogLevel::Info);
+                self.push_log(format!("SKIPPED: {}", label), LogLevel::Info);
             }
             PhaseEvent::Warning { message } => {
                 self.push_log(format!("WARNING: {}", message), LogLevel::Warning);
             }
+            PhaseEvent::StepStarted { phase, step } => {
+                self.set_phase_detail(&phase, step.clone());
+                self.push_log(format!("STEP: {}", step), LogLevel::Info);
+            }
+            PhaseEvent::StepFinished {
+                step,
+                success,
+                duration_ms,
+                ..
+            } => {
+                let (verdict, level) = if success {
+                    ("DONE", LogLevel::Success)
+                } else {
+                    ("FAILED", LogLevel::Error)
+                };
+                self.push_log(
+                    format!(
+                        "STEP {}: {} ({})",
+                        verdict,
+                        step,
+                        format_duration(duration_ms)
+                    ),
+                    level,
+                );
+            }
+            PhaseEvent::Action { message, .. } => {
+                self.push_log(format!("  · {}", message), LogLevel::Info);
+            }
+            PhaseEvent::CommandStarted { phase, command } => {
+                self.push_command_output(format!("$ {}", command));
+                self.set_phase_detail(&phase, format!("$ {}", command));
+            }
+            PhaseEvent::CommandOutput { line, .. } => {
+                self.push_command_output(line);
+            }
+            PhaseEvent::CommandFinished {
+                command,
+                status,
+                duration_ms,
+                ..
+            } => {
+                if status != Some(0) {
+                    let status = status.map_or_else(|| "signal".to_string(), |c| c.to_string());
+                    self.push_log(
+                        format!(
+                            "EXIT {} after {}: {}",
+                            status,
+                            format_duration(duration_ms),
+                            command
+                        ),
+                        LogLevel::Warning,
+                    );
+                }
+            }
+            PhaseEvent::DownloadProgress {
+                phase,
+                label,
+                bytes,
+                total,
+            } => {
+                self.set_phase_detail(&phase, download_detail(&label, bytes, total));
+            }
+            // Dry-run notes already arrive as actions; the plan is read from the report.
+            PhaseEvent::Planned { .. } => {}
+        }
+    }
+
+    /// Show what a running phase is busy with next to its name.
+    fn set_phase_detail(&mut self, phase: &str, detail: String) {
+        if let Some(row) = self
+            .phases
+            .iter_mut()
+            .find(|r| r.id == phase && matches!(r.status, PhaseStatus::Running))
+        {
+            row.description = detail;
         }
     }
 }
@@ -3,7 +3,7 @@
 pub mod navigation;
 pub mod software;
 
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, VecDeque};
 use std::io;
 use std::sync::mpsc::{self, Sender};
 use std::thread;
@@ -17,13 +17,18 @@
 use crate::tui::observer::RatatuiPhaseObserver;
 use crate::tui::render;
 use crate::tui::sysinfo_poller::spawn_sysinfo_poller;
-use installer_core::{detect_platform, DistroDriver, ProfileLevel, SystemProfileExt};
+use installer_core::{
+    detect_platform, DistroDriver, PhaseEvent, PhaseObserver, ProfileLevel, SystemProfileExt,
+};
 use std::collections::BTreeMap;
 
 pub use crate::tui::state::{
     LogEntry, LogLevel, Screen, SoftwareMode, SysStats, TuiApp, TuiMessage,
 };
 
+/// Lines of command output kept for the live output pane.
+const COMMAND_OUTPUT_LINES: usize = 200;
+
 impl TuiApp {
     pub fn new(tx: Sender<TuiMessage>, drivers: Vec<&'static dyn DistroDriver>) -> Self {
         Self {
@@ -41,14 +46,22 @@ pub fn new(tx: Sender<TuiMessage>, drivers: Vec<&'static dyn DistroDriver>) -> S
             display_protocol: installer_core::desktop::DisplayProtocol::Auto,
             theme_plan: installer_core::ThemePlan::None,
             software_mode: SoftwareMode::BardsRecommendations,
-            catalog: installer_core::catalog::Catalog::load_s_tier().unwrap_or_default(),
+            catalog: installer_core::catalog::curated_catalog(),
             software_picks: BTreeMap::new(),
             software_category_idx: 0,
             chezmoi_enabled: false,
             chezmoi_repo: String::new(),
             chezmoi_branch: String::new(),
+            phase_choices: installer_core::localization::Localization::load()
+                .map(|strings| installer_core::PhaseRegistry::default().phases(&strings))
+                .unwrap_or_default(),
+            skipped_phases: BTreeSet::new(),
+            plan_lines: Vec::new(),
             dry_run: false,
             continue_on_error: false,
+            resume: false,
+            jobs: 1,
+            allow_unverified_scripts: false,
             platform_info: installer_core::platform::PlatformInfo {
                 arch: std::env::consts::ARCH.to_string(),
                 distro: "unknown".to_string(),
@@ -67,6 +80,7 @@ pub fn new(tx: Sender<TuiMessage>, drivers: Vec<&'static dyn DistroDriver>) -> S
             start_time: Instant::now(),
             progress_pct: 0.0,
             log: VecDeque::with_capacity(500),
+            command_output: VecDeque::with_capacity(COMMAND_OUTPUT_LINES),
             sys_stats: SysStats::default(),
             bbs_msg: "⚡ Initialising the forge...".to_string(),
             available_presets: installer_core::preset::PresetRegistry::load_all()
@@ -82,13 +96,19 @@ pub fn new(tx: Sender<TuiMessage>, drivers: Vec<&'static dyn DistroDriver>) -> S
             tx,
             log_scroll: 0,
             summary_scroll: 0,
-            scry: false,
-            scry_port: 3030,
+            scry: None,
             environment: installer_core::model::options::EnvironmentTag::Home,
             should_quit: false,
         }
     }
 
+    pub fn push_command_output(&mut self, line: impl Into<String>) {
+        if self.command_output.len() >= COMMAND_OUTPUT_LINES {
+            self.command_output.pop_front();
+        }
+        self.command_output.push_back(line.into());
+    }
+
     pub fn push_log(&mut self, text: impl Into<String>, level: LogLevel) {
         if self.log.len() >= 500 {
             self.log.pop_front();
@@ -151,22 +171,34 @@ pub fn spawn_system_scan(&self) {
     }
 
     pub fn spawn_installer(&self, driver: &'static dyn DistroDriver) {
-        let options = self.build_options();
+        let mut options = self.build_options();
+        options.history_db = Some(installer_core::history::history_path());
         let tx = self.tx.clone();
-        let scry = self.scry;
-        let scry_port = self.scry_port;
+        let scry = self.scry.clone();
 
         thread::spawn(move || {
             let mut composite = installer_core::CompositeObserver::new();
 
             // 1. TUI Observer
             let tui_observer = RatatuiPhaseObserver::new(tx.clone());
-            composite.add(tui_observer);
+            composite.add_prompter(tui_observer);
 
             // 2. Remote Scrying (Websocket)
-            if scry {
-                let scryer = installer_core::WebsocketObserver::new(scry_port);
-                composite.add(scryer);
+            if let Some(config) = scry {
+                match installer_core::WebsocketObserver::new(config) {
+                    Ok(scryer) => {
+                        let message = format!(
+                            "🔮  Remote scrying at {} (pairing code {})",
+                            scryer.dashboard_url(),
+                            scryer.token()
+                        );
+                        composite.add_scryer(scryer);
+                        composite.on_event(PhaseEvent::Warning { message });
+                    }
+                    Err(err) => composite.on_event(PhaseEvent::Warning {
+                        message: format!("Remote scrying unavailable: {err:#}"),
+                    }),
+                }
             }
 
             match installer_core::run_with_driver(driver, options, &mut composite) {
@@ -181,6 +213,21 @@ pub fn spawn_installer(&self, driver: &'static dyn DistroDriver) {
             }
         });
     }
+
+    /// Dry-run the current choices in the background and send the plan back
+    /// as [`TuiMessage::PlanReady`].
+    pub fn spawn_planner(&self, driver: &'static dyn DistroDriver) {
+        let options = self.build_options();
+        let tx = self.tx.clone();
+
+        thread::spawn(move || {
+            let mut quiet = installer_core::CompositeObserver::new();
+            let plan = installer_core::run_preflight_audit(driver, &options, &mut quiet)
+                .map(|report| installer_core::plan::pretty_lines(&report))
+                .map_err(|err| format!("{err:#}"));
+            let _ = tx.send(TuiMessage::PlanReady(plan));
+        });
+    }
 }
 
 pub fn now_stamp() -> String {
@@ -216,12 +263,16 @@ fn drop(&mut self) {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     drivers: Vec<&'static dyn DistroDriver>,
     dry_run: bool,
     continue_on_error: bool,
-    scry: bool,
-    scry_port: u16,
+    resume: bool,
+    jobs: usize,
+    allow_unverified_scripts: bool,
+    skipped_phases: Vec<String>,
+    scry: Option<installer_core::ScryConfig>,
 ) -> anyhow::Result<()> {
     let _guard = TerminalGuard::enter()?;
     let backend = CrosstermBackend::new(io::stdout());
@@ -236,8 +287,11 @@ pub fn run(
     let mut app = TuiApp::new(tx, drivers);
     app.dry_run = dry_run;
     app.continue_on_error = continue_on_error;
+    app.resume = resume;
+    app.jobs = jobs;
+    app.allow_unverified_scripts = allow_unverified_scripts;
+    app.skipped_phases = skipped_phases.into_iter().collect();
     app.scry = scry;
-    app.scry_port = scry_port;
 
     // Start at Welcome screen
     app.screen = Screen::Welcome;
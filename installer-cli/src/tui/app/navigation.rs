@@ -135,6 +135,10 @@ pub fn advance_from_list(&mut self) {
                 self.menu_cursor = 0;
             }
             Screen::ChezmoiConfig => {
+                self.navigate_to(Screen::PhaseSelect, "Phase Selection");
+                self.menu_cursor = 0;
+            }
+            Screen::PhaseSelect => {
                 self.navigate_to(Screen::Confirm, "Final Provisioning Summary");
                 self.menu_cursor = 0;
             }
@@ -181,6 +185,8 @@ fn context_for_screen(&self, screen: Screen) -> &'static str {
             Screen::ArgonConfig => "Argon One Configuration",
             Screen::DockerConfig => "Docker Configuration",
             Screen::ChezmoiConfig => "Dotfile Restoration",
+            Screen::PhaseSelect => "Phase Selection",
+            Screen::PlanReview => "Pre-flight Plan",
             Screen::SystemSummary => "System Results & Wisdom",
             Screen::Password => "Password Prompt",
             Screen::Authorization => "Interactive Authorization",
@@ -228,10 +234,18 @@ pub fn go_back(&mut self) {
                     self.screen = Screen::SoftwareMode;
                 }
             }
-            Screen::Confirm => {
+            Screen::PhaseSelect => {
                 self.screen = Screen::ChezmoiConfig;
                 self.menu_cursor = 0;
             }
+            Screen::Confirm => {
+                self.screen = Screen::PhaseSelect;
+                self.menu_cursor = 0;
+            }
+            Screen::PlanReview => {
+                self.screen = Screen::Confirm;
+                self.menu_cursor = 0;
+            }
             _ => {}
         }
 
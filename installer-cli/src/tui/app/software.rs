@@ -18,6 +18,12 @@ pub fn build_options(&self) -> InstallOptions {
             enable_p10k: self.enable_p10k,
             docker: self.docker.clone(),
             continue_on_error: self.continue_on_error,
+            resume: self.resume,
+            jobs: self.jobs,
+            verify: false,
+            allow_unverified_scripts: self.allow_unverified_scripts,
+            only_phases: Vec::new(),
+            skip_phases: self.skipped_phases.iter().cloned().collect(),
             software_plan: self.build_software_plan(),
             system_profile: self.system_profile.clone(),
             environment: self.environment,
@@ -36,6 +42,7 @@ pub fn build_options(&self) -> InstallOptions {
             },
             desktop_environment: self.desktop_environment,
             display_protocol: self.display_protocol,
+            history_db: None,
         }
     }
 
@@ -166,6 +173,21 @@ pub fn start_install(&mut self) {
             format!("Software plan: {}", self.software_plan_label()),
             LogLevel::Info,
         );
+        if let Err(e) = installer_core::manifest::record_last_session(&self.build_options()) {
+            self.push_log(
+                format!("Could not record session manifest: {e}"),
+                LogLevel::Warning,
+            );
+        }
         self.spawn_installer(driver);
     }
+
+    /// Open the plan review and start the dry run that fills it.
+    pub fn review_plan(&mut self) {
+        let driver = self.drivers[self.selected_driver_idx];
+        self.plan_lines.clear();
+        self.summary_scroll = 0;
+        self.navigate_to(Screen::PlanReview, "Pre-flight Plan");
+        self.spawn_planner(driver);
+    }
 }
@@ -196,6 +196,22 @@ fn build_info_lines(app: &TuiApp) -> Vec<Line<'static>> {
             )),
         ],
 
+        Screen::PhaseSelect => vec![
+            Line::from(Span::styled("Phase Selection", theme::accent_style())),
+            Line::from(Span::styled(
+                "Untick phases to leave them out of this run",
+                theme::dim_style(),
+            )),
+        ],
+
+        Screen::PlanReview => vec![
+            Line::from(Span::styled("Pre-flight plan", theme::accent_style())),
+            Line::from(Span::styled(
+                "Every package, file and service this run would touch",
+                theme::dim_style(),
+            )),
+        ],
+
         Screen::Confirm => vec![
             Line::from(Span::styled(
                 "Final provision confirmation",
@@ -5,6 +5,7 @@
 
 use crate::tui::app::{LogLevel, TuiApp};
 use crate::tui::menus::helpers::station_block;
+use crate::tui::state::PhaseStatus;
 use crate::tui::theme;
 
 pub fn draw_installing(f: &mut Frame, area: Rect, app: &TuiApp) {
@@ -17,6 +18,7 @@ pub fn draw_installing(f: &mut Frame, area: Rect, app: &TuiApp) {
         .constraints([
             Constraint::Length(3),
             Constraint::Min(0),
+            Constraint::Length(8),
             Constraint::Length(3),
         ])
         .split(inner);
@@ -69,12 +71,31 @@ pub fn draw_installing(f: &mut Frame, area: Rect, app: &TuiApp) {
     // Manual scroll handling for log
     f.render_widget(list, chunks[1]);
 
+    // Live command output, newest lines at the bottom
+    let output_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme::dim_style())
+        .title(Span::styled(" COMMAND_OUTPUT ", theme::dim_style()));
+    let visible = output_block.inner(chunks[2]).height as usize;
+    let skip = app.command_output.len().saturating_sub(visible);
+    let output: Vec<Line> = app
+        .command_output
+        .iter()
+        .skip(skip)
+        .map(|line| Line::from(Span::styled(line.as_str(), theme::dim_style())))
+        .collect();
+    f.render_widget(Paragraph::new(output).block(output_block), chunks[2]);
+
     // Current phase
     if let Some(row) = app.phases.get(app.current_phase) {
-        let phase_info = Paragraph::new(format!("EXECUTING: {}", row.name.to_uppercase()))
+        let mut executing = format!("EXECUTING: {}", row.name.to_uppercase());
+        if matches!(row.status, PhaseStatus::Running) && !row.description.is_empty() {
+            executing.push_str(&format!(" · {}", row.description));
+        }
+        let phase_info = Paragraph::new(executing)
             .style(theme::warning_style())
             .alignment(Alignment::Center);
-        f.render_widget(phase_info, chunks[2]);
+        f.render_widget(phase_info, chunks[3]);
     }
 }
 
@@ -296,6 +296,58 @@ pub fn draw_docker_config(f: &mut Frame, area: Rect, app: &TuiApp) {
     draw_navigation_info(f, area, app);
 }
 
+pub fn draw_phase_select(f: &mut Frame, area: Rect, app: &TuiApp) {
+    let block = station_block("PHASE_MANIFEST");
+    f.render_widget(&block, area);
+    let inner = block.inner(area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(inner);
+
+    f.render_widget(Paragraph::new("SELECT PHASES TO RUN:"), chunks[0]);
+
+    let options: Vec<String> = app
+        .phase_choices
+        .iter()
+        .map(|phase| {
+            let mark = if app.skipped_phases.contains(phase.id) {
+                "[ ]"
+            } else {
+                "[X]"
+            };
+            format!("{mark} {} ({})", phase.label, phase.id)
+        })
+        .chain(std::iter::once("CONFIRM AND CONTINUE".to_string()))
+        .collect();
+
+    // Keep the cursor on screen when the list is taller than the pane.
+    let visible = chunks[1].height.max(1) as usize;
+    let offset = (app.menu_cursor + 1).saturating_sub(visible);
+    let items: Vec<ListItem> = options
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .map(|(i, label)| command_prompt_line(label, i + 1, i == app.menu_cursor))
+        .collect();
+
+    let list = List::new(items);
+    f.render_widget(list, chunks[1]);
+
+    f.render_widget(
+        Paragraph::new("INTEL: Phases your other choices do not need are skipped either way.")
+            .style(theme::dim_style()),
+        chunks[2],
+    );
+
+    draw_navigation_info(f, area, app);
+}
+
 pub fn draw_protocol_select(f: &mut Frame, area: Rect, app: &TuiApp) {
     let block = station_block("DISPLAY_PROTOCOL");
     f.render_widget(&block, area);
@@ -467,8 +519,32 @@ pub fn draw_pre_install_confirm(f: &mut Frame, area: Rect, app: &TuiApp) {
                 theme::success_style(),
             ),
         ]),
+        Line::from(vec![
+            Span::styled("PHASES:      ", theme::dim_style()),
+            if app.skipped_phases.is_empty() {
+                Span::styled("ALL", theme::success_style())
+            } else {
+                Span::styled(
+                    format!(
+                        "SKIPPING {}",
+                        app.skipped_phases
+                            .iter()
+                            .map(String::as_str)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    theme::warning_style(),
+                )
+            },
+        ]),
     ];
 
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "PRESS [P] TO REVIEW EVERY CHANGE BEFORE COMMITTING",
+        theme::dim_style(),
+    )));
+
     if app.dry_run {
         lines.push(Line::from(""));
         lines.push(Line::from(Span::styled(
@@ -515,6 +591,52 @@ pub fn draw_pre_install_confirm(f: &mut Frame, area: Rect, app: &TuiApp) {
     );
 }
 
+pub fn draw_plan_review(f: &mut Frame, area: Rect, app: &TuiApp) {
+    let block = station_block("PREFLIGHT_PLAN");
+    f.render_widget(&block, area);
+    let inner = block.inner(area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(inner);
+
+    if app.plan_lines.is_empty() {
+        f.render_widget(
+            Paragraph::new("DRY-RUNNING PHASES. STAND BY...")
+                .style(theme::warning_style())
+                .alignment(Alignment::Center),
+            chunks[0],
+        );
+    } else {
+        let lines: Vec<Line> = app
+            .plan_lines
+            .iter()
+            .skip(app.summary_scroll)
+            .map(|line| {
+                let style = if line.starts_with("== ") {
+                    theme::accent_style()
+                } else if line.trim_start().starts_with('+') {
+                    theme::success_style()
+                } else if line.trim_start().starts_with('-') {
+                    theme::warning_style()
+                } else {
+                    theme::default_style()
+                };
+                Line::from(Span::styled(line.as_str(), style))
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), chunks[0]);
+    }
+
+    f.render_widget(
+        Paragraph::new("[ENTER] INSTALL   [UP/DOWN] SCROLL   [ESC] BACK")
+            .style(theme::dim_style())
+            .alignment(Alignment::Center),
+        chunks[1],
+    );
+}
+
 pub fn draw_font_prep(f: &mut Frame, area: Rect, app: &TuiApp) {
     let block = station_block("FONT_PREPARATION");
     f.render_widget(&block, area);
@@ -82,6 +82,8 @@ pub fn draw(f: &mut Frame, app: &TuiApp) {
         Screen::ArgonConfig => menus::draw_argon_config(f, main_area, app),
         Screen::DockerConfig => menus::draw_docker_config(f, main_area, app),
         Screen::ChezmoiConfig => menus::draw_chezmoi_config(f, main_area, app),
+        Screen::PhaseSelect => menus::draw_phase_select(f, main_area, app),
+        Screen::PlanReview => menus::draw_plan_review(f, main_area, app),
         Screen::SystemSummary => menus::draw_system_summary(f, main_area, app),
         Screen::Password => menus::draw_password_screen(f, main_area, app),
         Screen::Authorization => menus::draw_auth_screen(f, main_area, app),
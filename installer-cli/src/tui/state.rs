@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::sync::mpsc::Sender;
 use std::time::{Duration, Instant};
 
@@ -8,7 +8,7 @@
 pub use installer_core::preset::Preset;
 pub use installer_core::SystemProfile;
 pub use installer_core::{
-    AuthType, DistroDriver, InstallationReport, PhaseEvent, SoftwareCategory, ThemePlan,
+    AuthType, DistroDriver, InstallationReport, PhaseEvent, PhaseInfo, SoftwareCategory, ThemePlan,
 };
 
 // ── Message bus ──────────────────────────────────────────────────────────────
@@ -40,6 +40,9 @@ pub enum TuiMessage {
         profile: Box<SystemProfile>,
     },
 
+    /// The pre-flight plan as display lines, or why it could not be made.
+    PlanReady(Result<Vec<String>, String>),
+
     Done(Box<InstallationReport>),
     InstallError(String),
 }
@@ -67,6 +70,8 @@ pub enum Screen {
     ArgonConfig,
     DockerConfig,
     ChezmoiConfig,
+    PhaseSelect,
+    PlanReview,
     SystemSummary,
     Password,
     Authorization,
@@ -89,6 +94,8 @@ pub enum PhaseStatus {
 
 #[derive(Debug, Clone)]
 pub struct PhaseRow {
+    /// Stable phase id the runner's events refer to.
+    pub id: String,
     pub name: String,
     pub status: PhaseStatus,
     pub description: String,
@@ -231,9 +238,17 @@ pub struct TuiApp {
     pub chezmoi_enabled: bool,
     pub chezmoi_repo: String,
     pub chezmoi_branch: String,
+    // Phase selection: every registered phase, and the ids left unticked
+    pub phase_choices: Vec<PhaseInfo>,
+    pub skipped_phases: BTreeSet<String>,
+    // Pre-flight plan lines; empty while the dry run is still going
+    pub plan_lines: Vec<String>,
     // Dry-run flag
     pub dry_run: bool,
     pub continue_on_error: bool,
+    pub resume: bool,
+    pub jobs: usize,
+    pub allow_unverified_scripts: bool,
     pub platform_info: PlatformInfo,
     pub system_profile: Option<SystemProfile>,
     // Installing phase state
@@ -244,6 +259,8 @@ pub struct TuiApp {
     pub progress_pct: f32,
     // Log
     pub log: VecDeque<LogEntry>,
+    // Live output of the commands phases run
+    pub command_output: VecDeque<String>,
     // Stats
     pub sys_stats: SysStats,
     // BBS message
@@ -269,8 +286,7 @@ pub struct TuiApp {
     // Summary scroll
     pub summary_scroll: usize,
     // Scry state
-    pub scry: bool,
-    pub scry_port: u16,
+    pub scry: Option<installer_core::ScryConfig>,
     pub environment: installer_core::model::options::EnvironmentTag,
     // Should quit
     pub should_quit: bool,
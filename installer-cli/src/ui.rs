@@ -11,11 +11,35 @@
 use std::thread;
 use std::time::Duration;
 
+/// `1.5s`, or `2m05s` once a phase runs past a minute.
+pub fn format_duration(duration_ms: u64) -> String {
+    let secs = duration_ms / 1000;
+    if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{:.1}s", duration_ms as f64 / 1000.0)
+    }
+}
+
+/// `label: 1.2/3.4 MiB`, without the total when the server did not send one.
+pub fn download_detail(label: &str, bytes: u64, total: Option<u64>) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    match total {
+        Some(total) => format!(
+            "{label}: {:.1}/{:.1} MiB",
+            bytes as f64 / MIB,
+            total as f64 / MIB
+        ),
+        None => format!("{label}: {:.1} MiB", bytes as f64 / MIB),
+    }
+}
+
 pub struct CliPhaseObserver {
     mp: MultiProgress,
     overall: ProgressBar,
     spinner: Option<ProgressBar>,
     message_updater: Option<Arc<Mutex<bool>>>, // Signal to stop message updates
+    current: String,
 }
 
 impl CliPhaseObserver {
@@ -39,16 +63,29 @@ pub fn new() -> Self {
             overall,
             spinner: None,
             message_updater: None,
+            current: String::new(),
         }
     }
 
-    fn finish_spinner(&mut self, prefix: &'static str, msg: &str) {
+    fn stop_message_updater(&mut self) {
         // Signal message updater thread to stop
         if let Some(updater) = self.message_updater.take() {
             if let Ok(mut stop) = updater.lock() {
                 *stop = true;
             }
         }
+    }
+
+    /// Replace the rotating flavour text with what the phase is actually doing.
+    fn show_detail(&mut self, detail: &str) {
+        self.stop_message_updater();
+        if let Some(pb) = &self.spinner {
+            pb.set_message(format!("{} · {detail}", self.current));
+        }
+    }
+
+    fn finish_spinner(&mut self, prefix: &'static str, msg: &str) {
+        self.stop_message_updater();
 
         if let Some(pb) = self.spinner.take() {
             pb.set_style(ProgressStyle::with_template("{prefix} {msg}").unwrap());
@@ -107,14 +144,21 @@ fn on_event(&mut self, event: PhaseEvent) {
             PhaseEvent::Started {
                 index,
                 total,
-                phase,
+                label,
+                ..
             } => {
                 self.finish_spinner(" ", "");
-                let display = format!("Phase {}/{} · {}", index, total, phase);
+                let display = format!("Phase {}/{} · {}", index, total, label);
                 self.start_spinner(&display);
+                self.current = display;
             }
-            PhaseEvent::Completed { description, .. } => {
-                self.finish_spinner("✓", &description);
+            PhaseEvent::Completed {
+                description,
+                duration_ms,
+                ..
+            } => {
+                let message = format!("{description} ({})", format_duration(duration_ms));
+                self.finish_spinner("✓", &message);
                 self.overall.inc(1);
             }
             PhaseEvent::Failed { error, .. } => {
@@ -122,8 +166,8 @@ fn on_event(&mut self, event: PhaseEvent) {
                 self.finish_spinner("✗", &message);
                 self.overall.inc(1);
             }
-            PhaseEvent::Skipped { phase, .. } => {
-                self.finish_spinner("–", &phase);
+            PhaseEvent::Skipped { label, .. } => {
+                self.finish_spinner("–", &label);
                 self.overall.inc(1);
             }
             PhaseEvent::Warning { message } => {
@@ -133,6 +177,29 @@ fn on_event(&mut self, event: PhaseEvent) {
                     eprintln!();
                 });
             }
+            PhaseEvent::StepStarted { step, .. } => self.show_detail(&step),
+            PhaseEvent::CommandStarted { command, .. } => self.show_detail(&command),
+            PhaseEvent::CommandOutput { line, .. } if !line.trim().is_empty() => {
+                self.show_detail(line.trim())
+            }
+            PhaseEvent::DownloadProgress {
+                label,
+                bytes,
+                total,
+                ..
+            } => self.show_detail(&download_detail(&label, bytes, total)),
+            PhaseEvent::StepFinished {
+                step,
+                success: false,
+                ..
+            } => {
+                self.mp.suspend(|| eprintln!("  ✗ {step}"));
+            }
+            PhaseEvent::StepFinished { .. }
+            | PhaseEvent::CommandOutput { .. }
+            | PhaseEvent::CommandFinished { .. }
+            | PhaseEvent::Action { .. }
+            | PhaseEvent::Planned { .. } => {}
         }
     }
 
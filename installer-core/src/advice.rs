@@ -78,6 +78,9 @@ fn default() -> Self {
             Box::new(PiWaylandWarning),
             Box::new(PiGnomeWarning),
             Box::new(PiSdCardWarning),
+            Box::new(Pi5ActiveCoolerRule),
+            Box::new(PiNvmeBootRule),
+            Box::new(Cm4UsbRule),
             Box::new(LaptopDetectedRule),
             Box::new(HighCoreCountOptimization),
             Box::new(BtrfsSnapshotRule),
@@ -416,7 +419,9 @@ fn name(&self) -> &'static str {
     }
     fn check(&self, profile: &SystemProfile, options: &UserOptionsContext) -> Option<AdviceEntry> {
         use crate::profile::PlatformType;
-        let is_pi = profile.platform.platform_type == PlatformType::RaspberryPi;
+        // Wayland (labwc) is the accelerated default desktop on the Pi 5.
+        let is_pi5 = profile.platform.pi.as_ref().is_some_and(|pi| pi.is_pi_5());
+        let is_pi = profile.platform.platform_type == PlatformType::RaspberryPi && !is_pi5;
         let intended_wayland = options.display_protocol == DisplayProtocol::Wayland;
         let current_wayland = profile.session.session_type == "wayland";
 
@@ -490,6 +495,75 @@ fn check(&self, profile: &SystemProfile, _options: &UserOptionsContext) -> Optio
     }
 }
 
+struct Pi5ActiveCoolerRule;
+impl Rule for Pi5ActiveCoolerRule {
+    fn name(&self) -> &'static str {
+        "pi5_active_cooler"
+    }
+    fn check(&self, profile: &SystemProfile, _options: &UserOptionsContext) -> Option<AdviceEntry> {
+        use crate::pi_board::PiBoard;
+        let pi = profile.platform.pi.as_ref()?;
+        if matches!(pi.board, PiBoard::Pi5 | PiBoard::Cm5) && !pi.active_cooler {
+            Some(AdviceEntry {
+                name: "todo",
+                level: Severity::Warning,
+                message: format!("{} running without an active cooler.", pi.board.label()),
+                advice: "The BCM2712 throttles within minutes of a full build. Fit the Active Cooler (or a fan on the FAN header) and the Pi 5 tuning phase will set a fan curve for it.".into(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+struct PiNvmeBootRule;
+impl Rule for PiNvmeBootRule {
+    fn name(&self) -> &'static str {
+        "pi_nvme_boot"
+    }
+    fn check(&self, profile: &SystemProfile, _options: &UserOptionsContext) -> Option<AdviceEntry> {
+        let pi = profile.platform.pi.as_ref()?;
+        let drive = pi.nvme.first()?;
+        let root = profile
+            .storage
+            .mounts
+            .iter()
+            .find(|m| m.destination == "/")?;
+        if root.device.starts_with("/dev/nvme") {
+            return None;
+        }
+        Some(AdviceEntry {
+            name: "todo",
+            level: Severity::Info,
+            message: format!(
+                "NVMe drive {} ({}) found on PCIe, but the system boots from {}.",
+                drive.name, drive.model, root.device
+            ),
+            advice: "Copy the system to NVMe with 'rpi-clone' or 'SD Card Copier' and put NVMe first in the boot order ('sudo rpi-eeprom-config --edit', BOOT_ORDER=0xf416) for several times the I/O of an SD card.".into(),
+        })
+    }
+}
+
+struct Cm4UsbRule;
+impl Rule for Cm4UsbRule {
+    fn name(&self) -> &'static str {
+        "cm4_usb2"
+    }
+    fn check(&self, profile: &SystemProfile, _options: &UserOptionsContext) -> Option<AdviceEntry> {
+        let pi = profile.platform.pi.as_ref()?;
+        if pi.board == crate::pi_board::PiBoard::Cm4 {
+            Some(AdviceEntry {
+                name: "todo",
+                level: Severity::Info,
+                message: "Compute Module 4 only exposes USB 2.0.".into(),
+                advice: "USB disks top out around 35MB/s on this module. Put your workspace on an NVMe drive on the carrier board's PCIe slot instead.".into(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
 struct LaptopDetectedRule;
 impl Rule for LaptopDetectedRule {
     fn name(&self) -> &'static str {
@@ -572,6 +646,8 @@ fn default_options() -> UserOptionsContext {
             chezmoi: crate::model::options::ChezmoiOptions::default(),
             desktop_environment: None,
             display_protocol: DisplayProtocol::Auto,
+            verify: false,
+            allow_unverified_scripts: false,
         }
     }
 
@@ -706,6 +782,72 @@ fn test_pi_gnome_rule() {
         assert!(advice.iter().any(|a| a.name() == "pi_gnome"));
     }
 
+    fn pi_hardware(board: crate::pi_board::PiBoard) -> crate::pi_board::PiHardware {
+        crate::pi_board::PiHardware {
+            board,
+            model: board.label().into(),
+            revision: None,
+            soc: None,
+            ram_mb: Some(8192),
+            nvme: Vec::new(),
+            active_cooler: false,
+        }
+    }
+
+    #[test]
+    fn test_pi5_and_compute_module_rules() {
+        use crate::pi_board::{NvmeDrive, PiBoard};
+        let engine = AdviceEngine::default();
+        let mut profile = SystemProfile::default();
+        let mut options = default_options();
+        profile.platform.platform_type = PlatformType::RaspberryPi;
+
+        // Pi 5 without a fan, booting from SD with an NVMe drive fitted
+        let mut pi5 = pi_hardware(PiBoard::Pi5);
+        pi5.nvme.push(NvmeDrive {
+            name: "nvme0n1".into(),
+            model: "WD_BLACK SN770".into(),
+            size_bytes: 500 * 1024 * 1024 * 1024,
+        });
+        profile.platform.pi = Some(pi5);
+        profile.storage.mounts.push(crate::profile::MountInfo {
+            device: "/dev/mmcblk0p2".into(),
+            destination: "/".into(),
+            fstype: "ext4".into(),
+            options: vec!["noatime".into()],
+        });
+        options.display_protocol = DisplayProtocol::Wayland;
+        let names: Vec<&str> = engine
+            .run(&profile, &options)
+            .iter()
+            .map(|a| a.name())
+            .collect();
+        assert!(names.contains(&"pi5_active_cooler"));
+        assert!(names.contains(&"pi_nvme_boot"));
+        assert!(!names.contains(&"pi_wayland"));
+        assert!(!names.contains(&"cm4_usb2"));
+
+        // Cooled Pi 5 booting from NVMe: nothing to say
+        if let Some(pi) = profile.platform.pi.as_mut() {
+            pi.active_cooler = true;
+        }
+        profile.storage.mounts[0].device = "/dev/nvme0n1p2".into();
+        let advice = engine.run(&profile, &options);
+        assert!(!advice
+            .iter()
+            .any(|a| a.name() == "pi5_active_cooler" || a.name() == "pi_nvme_boot"));
+
+        // CM4 keeps the Pi 4 class Wayland advice and gets the USB 2.0 hint
+        profile.platform.pi = Some(pi_hardware(PiBoard::Cm4));
+        let names: Vec<&str> = engine
+            .run(&profile, &options)
+            .iter()
+            .map(|a| a.name())
+            .collect();
+        assert!(names.contains(&"cm4_usb2"));
+        assert!(names.contains(&"pi_wayland"));
+    }
+
     #[test]
     fn test_pi_wayland_rule_prescriptive() {
         let engine = AdviceEngine::default();
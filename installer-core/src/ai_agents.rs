@@ -9,7 +9,7 @@
 
 use crate::model::software::SoftwareCategory;
 use crate::system::cmd;
-use crate::{package_manager, PhaseContext, PhaseResult};
+use crate::{package_manager, scrubber, PhaseContext, PhaseResult};
 use anyhow::Result;
 use std::process::Command;
 
@@ -167,6 +167,7 @@ fn find_github_token(config: &serde_json::Value, key: &str) -> Option<String> {
     if token.trim().is_empty() {
         None
     } else {
+        scrubber::register_secret(token);
         Some(token.to_string())
     }
 }
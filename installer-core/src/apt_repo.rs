@@ -2,11 +2,11 @@
 use std::path::Path;
 use std::process::Command;
 
-use crate::system::cmd;
+use crate::system::{cmd, dry_run};
 use crate::{
     context::PhaseContext,
     driver::{AptRepoConfig, RepoKind},
-    package_manager,
+    package_manager, PlannedChange,
 };
 
 /// Ensure the named apt repository is configured according to the distro driver.
@@ -23,6 +23,16 @@ pub fn ensure_repo(ctx: &mut PhaseContext, repo: RepoKind) -> Result<()> {
             config.label,
             config.sources_path
         );
+        if !Path::new(config.sources_path).exists() {
+            dry_run::plan(PlannedChange::Repo {
+                name: config.label.to_string(),
+                path: config.sources_path.into(),
+                entry: (config.repo_line)(&ctx.platform.platform)?,
+            });
+        }
+        if !Path::new(config.key_path).exists() {
+            dry_run::plan_download(&(config.key_url)(&ctx.platform.platform)?);
+        }
         return Ok(());
     }
 
@@ -68,13 +78,14 @@ fn add_sources_list(config: &AptRepoConfig, ctx: &mut PhaseContext) -> Result<bo
         std::fs::create_dir_all(parent)?;
     }
 
+    // apt reads the sources list on every update, so write it between
+    // package-manager runs.
     let repo_line = (config.repo_line)(&ctx.platform.platform)?;
-    let mut cmd = Command::new("sh");
-    cmd.arg("-c").arg(format!(
+    package_manager::execute(cmd::Command::new("sh").arg("-c").arg(format!(
         "echo '{repo_line}' | sudo tee {path} > /dev/null",
         path = config.sources_path
-    ));
-    cmd::run(&mut cmd).context("writing apt sources list")?;
+    )))
+    .context("writing apt sources list")?;
 
     Ok(true)
 }
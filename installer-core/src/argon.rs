@@ -3,14 +3,20 @@
 use std::process::Command;
 
 use crate::{
-    cmd, driver::ServiceName, package_manager, systemd, AuthType, AuthorizationService,
-    PhaseContext, PhaseResult, PkgBackend,
+    boot_config, cmd, driver::ServiceName, dry_run, package_manager, rollback::RollbackOp, systemd,
+    AuthType, AuthorizationService, PhaseContext, PhaseResult, PkgBackend, PlannedChange,
 };
 
 /// Clone target for the argononed C daemon.
 const ARGONONED_REPO: &str = "https://gitlab.com/DarkElvenAngel/argononed.git";
+/// Argon40's install script for the Debian/Ubuntu path.
+const OEM_SCRIPT_URL: &str = "https://download.argon40.com/argon1.sh";
 /// Where we clone the source for building.
 const ARGONONED_SRC: &str = "/usr/local/src/argononed";
+/// Command the OEM script installs; its presence marks the Debian/Ubuntu path done.
+pub const OEM_CONFIG_COMMAND: &str = "argonone-config";
+/// Boot setting that enables the I2C bus the case MCU sits on.
+const I2C_ARM: &str = "dtparam=i2c_arm=on";
 
 pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
     // Quick gate: only relevant on Raspberry Pi hardware
@@ -19,6 +25,11 @@ pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
         return Ok(PhaseResult::Success);
     }
 
+    // The case MCU talks to the fan daemon over I2C
+    boot_config::edit_config(ctx, "argon_one", |config| {
+        config.ensure(boot_config::ALL, I2C_ARM).map(|_| ())
+    })?;
+
     match ctx.platform.pkg_backend {
         PkgBackend::Pacman | PkgBackend::Dnf => install_argononed(ctx)?,
         PkgBackend::Apt => install_argon_oem(ctx)?,
@@ -55,6 +66,13 @@ fn install_argononed(ctx: &mut PhaseContext) -> Result<()> {
 
     if ctx.options.dry_run {
         tracing::info!("[dry-run] would clone, build, and install argononed");
+        dry_run::plan(PlannedChange::Service {
+            unit: ctx
+                .platform
+                .driver
+                .service_unit(ServiceName::ArgonOne)
+                .to_string(),
+        });
         return Ok(());
     }
 
@@ -131,7 +149,7 @@ fn enable_argononed_service(ctx: &mut PhaseContext) -> Result<()> {
 // ── Debian/Ubuntu path: OEM script ──────────────────────────────
 
 fn install_argon_oem(ctx: &mut PhaseContext) -> Result<()> {
-    if which::which("argonone-config").is_ok() {
+    if which::which(OEM_CONFIG_COMMAND).is_ok() {
         tracing::info!("Argon One OEM scripts already installed");
         return Ok(());
     }
@@ -139,24 +157,25 @@ fn install_argon_oem(ctx: &mut PhaseContext) -> Result<()> {
     tracing::info!("Installing Argon One fan control via OEM script (Debian/Ubuntu path)");
     if ctx.options.dry_run {
         tracing::info!("[dry-run] would run Argon40 OEM install script");
+        dry_run::plan_download(OEM_SCRIPT_URL);
         return Ok(());
     }
 
-    ctx.register_rollback_action("remove argon one config files", || {
-        // The OEM script creates these config files; clean up on rollback
-        for path in &["/etc/argon/argon1.conf", "/etc/argon/argononed.conf"] {
-            let p = std::path::Path::new(path);
-            if p.exists() {
-                let _ = std::fs::remove_file(p);
-            }
-        }
-        Ok(())
-    });
-
-    let mut cmd = Command::new("sh");
-    cmd.arg("-c")
-        .arg("curl -fsSL --proto '=https' --tlsv1.2 https://download.argon40.com/argon1.sh | bash");
-    if let Err(err) = cmd::run(&mut cmd).context("running Argon One OEM install script") {
+    // The OEM script creates these config files; clean up on rollback
+    for path in ["/etc/argon/argon1.conf", "/etc/argon/argononed.conf"] {
+        ctx.register_rollback_action(
+            format!("remove {path}"),
+            RollbackOp::RemoveFile { path: path.into() },
+        );
+    }
+
+    // The script installs its dependencies with apt-get.
+    let script = cmd::Command::new("sh").arg("-c").arg(format!(
+        "curl -fsSL --proto '=https' --tlsv1.2 {OEM_SCRIPT_URL} | bash"
+    ));
+    if let Err(err) =
+        package_manager::execute(script).context("running Argon One OEM install script")
+    {
         tracing::warn!("Argon One OEM install script failed; this is non-critical ({err})");
     }
 
@@ -1,6 +1,20 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PkgBackend {
     Apt,
     Pacman,
     Dnf,
 }
+
+impl PkgBackend {
+    /// The package manager's command name.
+    pub fn name(self) -> &'static str {
+        match self {
+            PkgBackend::Apt => "apt",
+            PkgBackend::Pacman => "pacman",
+            PkgBackend::Dnf => "dnf",
+        }
+    }
+}
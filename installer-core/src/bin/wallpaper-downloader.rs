@@ -35,8 +35,8 @@ fn on_event(&mut self, event: PhaseEvent) {
             PhaseEvent::Warning { message } => {
                 println!("{}", message);
             }
-            PhaseEvent::Started { phase, .. } => {
-                println!("🚀 Starting phase: {}", phase);
+            PhaseEvent::Started { label, .. } => {
+                println!("🚀 Starting phase: {}", label);
             }
             PhaseEvent::Completed { phase, .. } => {
                 println!("✅ Completed phase: {}", phase);
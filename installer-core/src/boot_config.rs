@@ -0,0 +1,724 @@
+//! Raspberry Pi boot configuration: `config.txt` and `cmdline.txt`.
+//!
+//! [`BootConfig`] keeps every line of `config.txt` as written, comments and
+//! blank lines included, and tracks the conditional filter section (`[all]`,
+//! `[pi4]`, `[cm5]`, ...) each setting sits under, so an edit only touches the
+//! lines it changes. [`CmdLine`] does the same for the single line of kernel
+//! arguments in `cmdline.txt`. Phases edit both through [`edit_config`] and
+//! [`edit_cmdline`], which back up the file and register the restore with the
+//! rollback manager.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::rollback::RollbackOp;
+use crate::system::system_ops::SystemOps;
+use crate::PhaseContext;
+
+/// Boot partitions: Bookworm and later mount it at `/boot/firmware`.
+pub const BOOT_DIRS: &[&str] = &["/boot/firmware", "/boot"];
+
+/// The section settings land in when no filter applies.
+pub const ALL: &str = "all";
+
+/// Kernel arguments that may legitimately appear more than once.
+const REPEATABLE_ARGS: &[&str] = &["console"];
+
+#[derive(Debug, Clone)]
+enum Line {
+    /// Comment, blank line, or a directive we do not interpret (`include`).
+    Text(String),
+    /// A conditional filter such as `[pi4]`; the name is stored lowercase.
+    Filter {
+        raw: String,
+        name: String,
+    },
+    Setting {
+        raw: String,
+        key: String,
+    },
+}
+
+/// Parsed `config.txt` that renders back byte for byte when left untouched.
+#[derive(Debug, Clone, Default)]
+pub struct BootConfig {
+    lines: Vec<Line>,
+    trailing_newline: bool,
+}
+
+/// One active setting and the filter section it sits under.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BootEntry {
+    pub section: String,
+    pub setting: String,
+}
+
+/// Identity of a setting: `dtparam` and `dtoverlay` lines are told apart by
+/// the parameter or overlay they name, everything else by the property name.
+fn setting_key(setting: &str) -> Option<String> {
+    let (name, value) = setting.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    let first = value.split(',').next().unwrap_or_default().trim();
+    Some(match name {
+        "dtparam" => format!("dtparam:{}", first.split('=').next().unwrap_or_default()),
+        "dtoverlay" => format!("dtoverlay:{first}"),
+        _ => name.to_string(),
+    })
+}
+
+fn parse_filter(line: &str) -> Option<String> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    Some(inner.trim().to_ascii_lowercase())
+}
+
+impl BootConfig {
+    pub fn parse(text: &str) -> Self {
+        let lines = text
+            .lines()
+            .map(|raw| {
+                let trimmed = raw.trim();
+                if trimmed.starts_with('#') {
+                    Line::Text(raw.to_string())
+                } else if let Some(name) = parse_filter(trimmed) {
+                    Line::Filter {
+                        raw: raw.to_string(),
+                        name,
+                    }
+                } else if let Some(key) = setting_key(trimmed) {
+                    Line::Setting {
+                        raw: raw.to_string(),
+                        key,
+                    }
+                } else {
+                    Line::Text(raw.to_string())
+                }
+            })
+            .collect();
+        Self {
+            lines,
+            trailing_newline: text.is_empty() || text.ends_with('\n'),
+        }
+    }
+
+    /// Section name for every line, in order.
+    fn sections(&self) -> Vec<&str> {
+        let mut current = ALL;
+        self.lines
+            .iter()
+            .map(|line| {
+                if let Line::Filter { name, .. } = line {
+                    current = name;
+                }
+                current
+            })
+            .collect()
+    }
+
+    /// Active settings grouped by the section they sit under.
+    pub fn entries(&self) -> Vec<BootEntry> {
+        self.lines
+            .iter()
+            .zip(self.sections())
+            .filter_map(|(line, section)| match line {
+                Line::Setting { raw, .. } => Some(BootEntry {
+                    section: section.to_string(),
+                    setting: raw.trim().to_string(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The line that currently sets the same thing as `setting` in `section`,
+    /// falling back to `[all]`. Later lines win, as they do for the firmware.
+    pub fn value(&self, section: &str, setting: &str) -> Option<&str> {
+        let key = setting_key(setting)?;
+        let section = section.to_ascii_lowercase();
+        let matching = |wanted: &str| {
+            self.lines
+                .iter()
+                .zip(self.sections())
+                .filter(|(_, line_section)| *line_section == wanted)
+                .filter_map(|(line, _)| match line {
+                    Line::Setting { raw, key: k } if *k == key => Some(raw.trim()),
+                    _ => None,
+                })
+                .next_back()
+        };
+        matching(&section).or_else(|| matching(ALL))
+    }
+
+    /// Make `setting` (`name=value`) active in `section`. Replaces the value
+    /// of an existing line for the same key, adds the line to the last block
+    /// of that section, or appends a new `[section]` block. Returns whether
+    /// anything changed.
+    pub fn ensure(&mut self, section: &str, setting: &str) -> Result<bool> {
+        let setting = setting.trim();
+        let Some(key) = setting_key(setting) else {
+            bail!("'{setting}' is not a name=value boot setting");
+        };
+        let section = section.trim_matches(['[', ']']).to_ascii_lowercase();
+        if self.value(&section, setting) == Some(setting) {
+            return Ok(false);
+        }
+
+        let sections: Vec<String> = self.sections().into_iter().map(str::to_string).collect();
+        let in_section = |i: &usize| sections[*i] == section;
+        let existing = (0..self.lines.len())
+            .filter(in_section)
+            .rfind(|i| matches!(&self.lines[*i], Line::Setting { key: k, .. } if *k == key));
+        let new_line = Line::Setting {
+            raw: setting.to_string(),
+            key,
+        };
+        if let Some(i) = existing {
+            self.lines[i] = new_line;
+            return Ok(true);
+        }
+
+        let anchor = (0..self.lines.len())
+            .filter(in_section)
+            .rfind(|i| !matches!(self.lines[*i], Line::Text(_)));
+        match anchor {
+            Some(i) => self.lines.insert(i + 1, new_line),
+            None if section == ALL => {
+                let first_filter = self
+                    .lines
+                    .iter()
+                    .position(|line| matches!(line, Line::Filter { .. }))
+                    .unwrap_or(self.lines.len());
+                self.lines.insert(first_filter, new_line);
+            }
+            None => {
+                if self
+                    .lines
+                    .last()
+                    .is_some_and(|line| !matches!(line, Line::Text(t) if t.trim().is_empty()))
+                {
+                    self.lines.push(Line::Text(String::new()));
+                }
+                self.lines.push(Line::Filter {
+                    raw: format!("[{section}]"),
+                    name: section.clone(),
+                });
+                self.lines.push(new_line);
+                self.lines.push(Line::Filter {
+                    raw: format!("[{ALL}]"),
+                    name: ALL.to_string(),
+                });
+            }
+        }
+        self.trailing_newline = true;
+        Ok(true)
+    }
+
+    /// Drop every line in `section` that sets the same thing as `setting`.
+    pub fn remove(&mut self, section: &str, setting: &str) -> bool {
+        let Some(key) = setting_key(setting).or_else(|| setting_key(&format!("{setting}="))) else {
+            return false;
+        };
+        let section = section.trim_matches(['[', ']']).to_ascii_lowercase();
+        let sections: Vec<String> = self.sections().into_iter().map(str::to_string).collect();
+        let before = self.lines.len();
+        let mut index = 0;
+        self.lines.retain(|line| {
+            let keep = !(sections[index] == section
+                && matches!(line, Line::Setting { key: k, .. } if *k == key));
+            index += 1;
+            keep
+        });
+        self.lines.len() != before
+    }
+}
+
+impl fmt::Display for BootConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            let raw = match line {
+                Line::Text(raw) | Line::Filter { raw, .. } | Line::Setting { raw, .. } => raw,
+            };
+            f.write_str(raw)?;
+            if i + 1 < self.lines.len() || self.trailing_newline {
+                f.write_str("\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Kernel command line from `cmdline.txt`: one line of space-separated args.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CmdLine {
+    args: Vec<String>,
+}
+
+fn arg_key(arg: &str) -> &str {
+    arg.split_once('=').map_or(arg, |(key, _)| key)
+}
+
+impl CmdLine {
+    pub fn parse(text: &str) -> Self {
+        Self {
+            args: text.split_whitespace().map(str::to_string).collect(),
+        }
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Make `arg` (`key=value` or a bare flag) present, replacing the value of
+    /// an existing argument with the same key. Returns whether anything changed.
+    pub fn ensure(&mut self, arg: &str) -> bool {
+        let arg = arg.trim();
+        if arg.is_empty() || self.args.iter().any(|a| a == arg) {
+            return false;
+        }
+        let key = arg_key(arg);
+        match self.args.iter().position(|a| arg_key(a) == key) {
+            Some(i) if !REPEATABLE_ARGS.contains(&key) => self.args[i] = arg.to_string(),
+            _ => self.args.push(arg.to_string()),
+        }
+        true
+    }
+
+    /// Drop every argument whose key is `key`.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.args.len();
+        self.args.retain(|a| arg_key(a) != key);
+        self.args.len() != before
+    }
+}
+
+impl fmt::Display for CmdLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.args.join(" "))
+    }
+}
+
+/// The first boot partition holding a `config.txt`.
+pub fn boot_dir(system: &dyn SystemOps) -> Option<PathBuf> {
+    BOOT_DIRS
+        .iter()
+        .map(PathBuf::from)
+        .find(|dir| system.read_to_string(&dir.join("config.txt")).is_ok())
+}
+
+pub fn config_path(system: &dyn SystemOps) -> Option<PathBuf> {
+    boot_dir(system).map(|dir| dir.join("config.txt"))
+}
+
+pub fn cmdline_path(system: &dyn SystemOps) -> Option<PathBuf> {
+    boot_dir(system).map(|dir| dir.join("cmdline.txt"))
+}
+
+/// A backup name not used yet: `config.txt.mash-bak`, then `.mash-bak.1`, ...
+/// so several edits in one run each restore to the state they started from.
+fn next_backup(system: &dyn SystemOps, path: &Path) -> PathBuf {
+    let base = format!("{}.mash-bak", path.display());
+    (0..)
+        .map(|n| match n {
+            0 => PathBuf::from(&base),
+            n => PathBuf::from(format!("{base}.{n}")),
+        })
+        .find(|candidate| system.read_to_string(candidate).is_err())
+        .expect("unbounded backup names")
+}
+
+/// Lines removed (`-`) and added (`+`) between two versions of a file.
+fn line_changes(old: &str, new: &str) -> Vec<String> {
+    let removed = old
+        .lines()
+        .filter(|line| !new.lines().any(|n| n == *line))
+        .map(|line| format!("-{line}"));
+    let added = new
+        .lines()
+        .filter(|line| !old.lines().any(|o| o == *line))
+        .map(|line| format!("+{line}"));
+    removed.chain(added).collect()
+}
+
+/// Back up `path`, write `next` in its place and return the backup.
+fn replace_with_backup(
+    system: &dyn SystemOps,
+    path: &Path,
+    original: &str,
+    next: &str,
+) -> Result<PathBuf> {
+    let backup = next_backup(system, path);
+    system.install_file(&backup, original.as_bytes(), 0o644)?;
+    system.install_file(path, next.as_bytes(), 0o644)?;
+    Ok(backup)
+}
+
+fn apply_edit(
+    ctx: &mut PhaseContext,
+    phase: &str,
+    path: &Path,
+    original: &str,
+    next: &str,
+) -> Result<bool> {
+    if next == original {
+        return Ok(false);
+    }
+    let changes = line_changes(original, next);
+    if ctx.options.dry_run {
+        ctx.record_dry_run(
+            phase,
+            format!("Would update {}", path.display()),
+            Some(changes.join(", ")),
+        );
+        ctx.plan_file(path, next);
+        return Ok(true);
+    }
+
+    let backup = replace_with_backup(ctx.platform.system, path, original, next)?;
+    ctx.register_rollback_action(
+        format!("restore {}", path.display()),
+        RollbackOp::RestoreFile {
+            path: path.to_path_buf(),
+            backup: backup.clone(),
+        },
+    );
+    ctx.record_configured(format!(
+        "Updated {} ({}); previous version at {}",
+        path.display(),
+        changes.join(", "),
+        backup.display()
+    ));
+    ctx.record_warning(format!("Reboot to apply the {} changes", path.display()));
+    Ok(true)
+}
+
+/// Apply `edit` to `config.txt`. When it changes anything the old file is
+/// backed up and restored on rollback. Returns whether the file changed.
+pub fn edit_config(
+    ctx: &mut PhaseContext,
+    phase: &str,
+    edit: impl FnOnce(&mut BootConfig) -> Result<()>,
+) -> Result<bool> {
+    let Some(path) = config_path(ctx.platform.system) else {
+        ctx.record_warning(format!(
+            "No config.txt under {}; boot settings left alone",
+            BOOT_DIRS.join(" or ")
+        ));
+        return Ok(false);
+    };
+    let original = ctx.platform.system.read_to_string(&path)?;
+    let mut config = BootConfig::parse(&original);
+    edit(&mut config)?;
+    apply_edit(ctx, phase, &path, &original, &config.to_string())
+}
+
+/// Apply `edit` to `cmdline.txt`, with the same backup and rollback as
+/// [`edit_config`].
+pub fn edit_cmdline(
+    ctx: &mut PhaseContext,
+    phase: &str,
+    edit: impl FnOnce(&mut CmdLine),
+) -> Result<bool> {
+    let Some(path) = cmdline_path(ctx.platform.system) else {
+        ctx.record_warning(format!(
+            "No cmdline.txt under {}; kernel arguments left alone",
+            BOOT_DIRS.join(" or ")
+        ));
+        return Ok(false);
+    };
+    let original = ctx.platform.system.read_to_string(&path)?;
+    let mut cmdline = CmdLine::parse(&original);
+    edit(&mut cmdline);
+    apply_edit(ctx, phase, &path, &original, &cmdline.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// `mash-setup pi config`
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+struct BootReport {
+    config_path: PathBuf,
+    settings: Vec<BootEntry>,
+    cmdline_path: Option<PathBuf>,
+    cmdline: Vec<String>,
+}
+
+fn require_config(system: &dyn SystemOps) -> Result<PathBuf> {
+    config_path(system).with_context(|| {
+        format!(
+            "no config.txt under {}; is this a Raspberry Pi?",
+            BOOT_DIRS.join(" or ")
+        )
+    })
+}
+
+/// Print the active settings of `config.txt`, grouped by section, and the
+/// kernel arguments from `cmdline.txt`.
+pub fn show(system: &dyn SystemOps, json: bool, out: &mut dyn Write) -> Result<()> {
+    let config_path = require_config(system)?;
+    let config = BootConfig::parse(&system.read_to_string(&config_path)?);
+    let cmdline_path = cmdline_path(system).filter(|path| system.read_to_string(path).is_ok());
+    let cmdline = cmdline_path
+        .as_deref()
+        .and_then(|path| system.read_to_string(path).ok())
+        .map(|text| CmdLine::parse(&text).args().to_vec())
+        .unwrap_or_default();
+    let report = BootReport {
+        config_path,
+        settings: config.entries(),
+        cmdline_path,
+        cmdline,
+    };
+
+    if json {
+        writeln!(out, "{}", serde_json::to_string_pretty(&report)?)?;
+        return Ok(());
+    }
+    writeln!(out, "{}", report.config_path.display())?;
+    let mut section = None;
+    for entry in &report.settings {
+        if section != Some(&entry.section) {
+            writeln!(out, "  [{}]", entry.section)?;
+            section = Some(&entry.section);
+        }
+        writeln!(out, "    {}", entry.setting)?;
+    }
+    if let Some(path) = &report.cmdline_path {
+        writeln!(out, "{}", path.display())?;
+        for arg in &report.cmdline {
+            writeln!(out, "    {arg}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Ensure `setting` in `config.txt` under `section`, or as a kernel argument
+/// in `cmdline.txt` when `cmdline` is set. The previous file is backed up.
+pub fn set(
+    system: &dyn SystemOps,
+    section: &str,
+    setting: &str,
+    cmdline: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let path = if cmdline {
+        require_config(system)?;
+        cmdline_path(system).expect("boot dir exists")
+    } else {
+        require_config(system)?
+    };
+    let original = system
+        .read_to_string(&path)
+        .with_context(|| format!("reading {}", path.display()))?;
+    let next = if cmdline {
+        let mut args = CmdLine::parse(&original);
+        args.ensure(setting);
+        args.to_string()
+    } else {
+        let mut config = BootConfig::parse(&original);
+        config.ensure(section, setting)?;
+        config.to_string()
+    };
+    if next == original {
+        writeln!(out, "{} already has {setting}", path.display())?;
+        return Ok(());
+    }
+    let backup = replace_with_backup(system, &path, &original, &next)?;
+    for change in line_changes(&original, &next) {
+        writeln!(out, "  {change}")?;
+    }
+    writeln!(
+        out,
+        "Updated {} (previous version at {}). Reboot to apply.",
+        path.display(),
+        backup.display()
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::fixture::{run_pi_phase, FixtureSystem};
+    use crate::PhaseResult;
+
+    const CONFIG: &str = "\
+# For more options and information see
+# http://rptl.io/configtxt
+dtparam=audio=on
+camera_auto_detect=1
+dtoverlay=vc4-kms-v3d
+max_framebuffers=2
+
+[cm4]
+# Enable host mode on the 2711 built-in XHCI USB controller.
+otg_mode=1
+
+[pi4]
+arm_boost=1
+
+[all]
+enable_uart=1
+";
+
+    const CMDLINE: &str =
+        "console=serial0,115200 console=tty1 root=PARTUUID=4e639091-02 rootfstype=ext4 fsck.repair=yes rootwait\n";
+
+    #[test]
+    fn round_trips_comments_and_sections() {
+        let config = BootConfig::parse(CONFIG);
+        assert_eq!(config.to_string(), CONFIG);
+        let no_newline = "dtparam=audio=on\n# end";
+        assert_eq!(BootConfig::parse(no_newline).to_string(), no_newline);
+
+        let entries = config.entries();
+        let sections: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|e| (e.section.as_str(), e.setting.as_str()))
+            .collect();
+        assert_eq!(sections[0], ("all", "dtparam=audio=on"));
+        assert!(sections.contains(&("cm4", "otg_mode=1")));
+        assert!(sections.contains(&("pi4", "arm_boost=1")));
+        assert_eq!(sections.last(), Some(&("all", "enable_uart=1")));
+    }
+
+    #[test]
+    fn ensure_updates_in_place_and_is_idempotent() -> Result<()> {
+        let mut config = BootConfig::parse(CONFIG);
+        assert!(config.ensure("pi4", "arm_boost=0")?);
+        assert!(!config.ensure("pi4", "arm_boost=0")?);
+        assert!(config.to_string().contains("[pi4]\narm_boost=0\n"));
+
+        // dtparam lines are keyed by the parameter they set
+        assert!(config.ensure("all", "dtparam=i2c_arm=on")?);
+        assert!(!config.ensure("all", "dtparam=i2c_arm=on")?);
+        assert!(config.ensure("all", "dtparam=audio=off")?);
+        let text = config.to_string();
+        assert!(text.contains("dtparam=audio=off\n"));
+        assert!(!text.contains("dtparam=audio=on"));
+        assert!(text.contains("enable_uart=1\ndtparam=i2c_arm=on\n"));
+
+        // a setting already active through [all] needs no section copy
+        assert!(!config.ensure("pi5", "enable_uart=1")?);
+        assert_eq!(config.value("pi5", "enable_uart=0"), Some("enable_uart=1"));
+
+        assert!(config.ensure("all", "gpu_mem").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn ensure_appends_new_sections_and_remove_drops_lines() -> Result<()> {
+        let mut config = BootConfig::parse(CONFIG);
+        assert!(config.ensure("[pi5]", "dtparam=pciex1_gen=3")?);
+        assert!(config.ensure("pi5", "dtparam=fan_temp0=45000,fan_temp0_speed=75")?);
+        let text = config.to_string();
+        assert!(text.starts_with(CONFIG));
+        assert!(text.ends_with(
+            "enable_uart=1\n\n[pi5]\ndtparam=pciex1_gen=3\ndtparam=fan_temp0=45000,fan_temp0_speed=75\n[all]\n"
+        ));
+
+        assert!(config.remove("pi5", "dtparam=pciex1_gen"));
+        assert!(!config.remove("pi5", "dtparam=pciex1_gen"));
+        assert!(config.remove("cm4", "otg_mode"));
+        let text = config.to_string();
+        assert!(!text.contains("pciex1_gen") && !text.contains("otg_mode"));
+        assert!(text.contains("# Enable host mode"));
+        Ok(())
+    }
+
+    #[test]
+    fn cmdline_ensure_replaces_values_but_keeps_consoles() {
+        let mut cmdline = CmdLine::parse(CMDLINE);
+        assert!(!cmdline.ensure("rootwait"));
+        assert!(cmdline.ensure("fsck.repair=no"));
+        assert!(cmdline.ensure("cgroup_enable=memory"));
+        assert!(cmdline.ensure("console=ttyAMA10"));
+        assert!(cmdline.remove("console"));
+        assert_eq!(
+            cmdline.to_string(),
+            "root=PARTUUID=4e639091-02 rootfstype=ext4 fsck.repair=no rootwait cgroup_enable=memory\n"
+        );
+    }
+
+    fn enable_i2c(ctx: &mut PhaseContext) -> Result<PhaseResult> {
+        edit_config(ctx, "test", |config| {
+            config.ensure(ALL, "dtparam=i2c_arm=on").map(|_| ())
+        })?;
+        edit_cmdline(ctx, "test", |cmdline| {
+            cmdline.ensure("cgroup_enable=memory");
+        })?;
+        Ok(PhaseResult::Success)
+    }
+
+    #[test]
+    fn phase_edits_are_backed_up_and_rolled_back() -> Result<()> {
+        let system = FixtureSystem::new()
+            .file("/boot/firmware/config.txt", CONFIG)
+            .file("/boot/firmware/config.txt.mash-bak", "older backup\n")
+            .file("/boot/firmware/cmdline.txt", CMDLINE)
+            .leak();
+        let run = run_pi_phase(system, "Raspberry Pi 4 Model B Rev 1.5", false, enable_i2c)?;
+
+        let config = system.read("/boot/firmware/config.txt").expect("config");
+        assert!(config.contains("enable_uart=1\ndtparam=i2c_arm=on\n"));
+        assert_eq!(
+            system
+                .read("/boot/firmware/config.txt.mash-bak.1")
+                .as_deref(),
+            Some(CONFIG)
+        );
+        assert_eq!(
+            system
+                .read("/boot/firmware/cmdline.txt.mash-bak")
+                .as_deref(),
+            Some(CMDLINE)
+        );
+        assert!(system
+            .read("/boot/firmware/cmdline.txt")
+            .expect("cmdline")
+            .ends_with("rootwait cgroup_enable=memory\n"));
+        assert_eq!(
+            run.rollback,
+            [
+                "restore /boot/firmware/config.txt",
+                "restore /boot/firmware/cmdline.txt"
+            ]
+        );
+        assert_eq!(run.metadata.warnings.len(), 2);
+
+        // Running again changes nothing
+        let rerun = run_pi_phase(system, "Raspberry Pi 4 Model B Rev 1.5", false, enable_i2c)?;
+        assert!(rerun.rollback.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn show_and_set_work_on_the_boot_partition() -> Result<()> {
+        let system = FixtureSystem::new()
+            .file("/boot/config.txt", CONFIG)
+            .file("/boot/cmdline.txt", CMDLINE);
+
+        let mut out = Vec::new();
+        set(&system, "pi4", "arm_freq=2000", false, &mut out)?;
+        let text = String::from_utf8(out)?;
+        assert!(text.contains("+arm_freq=2000"));
+        assert!(text.contains("previous version at /boot/config.txt.mash-bak"));
+
+        let mut out = Vec::new();
+        show(&system, false, &mut out)?;
+        let text = String::from_utf8(out)?;
+        assert!(text.starts_with("/boot/config.txt\n  [all]\n    dtparam=audio=on\n"));
+        assert!(text.contains("  [pi4]\n    arm_boost=1\n    arm_freq=2000\n"));
+        assert!(text.contains("/boot/cmdline.txt\n    console=serial0,115200\n"));
+
+        let mut out = Vec::new();
+        set(&system, ALL, "rootwait", true, &mut out)?;
+        assert!(String::from_utf8(out)?.contains("already has rootwait"));
+        Ok(())
+    }
+}
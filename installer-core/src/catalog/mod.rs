@@ -2,6 +2,20 @@
 use std::collections::HashMap;
 
 use crate::model::software::{SoftwareCategory, Tier};
+use crate::sources::ProgramSources;
+
+mod overlay;
+mod resolver;
+mod verify;
+
+pub use overlay::{
+    load_overlays, overlay_dirs, CatalogOverlay, ProgramOverride, OVERLAY_DIR, SYSTEM_OVERLAY_DIR,
+};
+pub use resolver::{distro_key, CatalogResolver, Resolution, ResolvedProgram};
+pub use verify::{
+    backend_for_distro, normalize_arch, verify_catalogs, FixtureIndex, PackageIndex, PackageStatus,
+    SystemIndex, VerifyIssue, VerifyReport,
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Program {
@@ -10,10 +24,17 @@ pub struct Program {
     pub description: String,
     pub tier: Tier,
     pub packages: HashMap<String, Vec<String>>,
+    /// Alternatives to `packages`, tried per the distro's source priority.
+    #[serde(default)]
+    pub sources: ProgramSources,
     #[serde(default)]
     pub recommended: bool,
     pub reasoning: Option<String>,
     pub post_install: Option<String>,
+    /// Overlay file that added or last changed this program; `None` for
+    /// entries from the embedded catalogs.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -56,10 +77,59 @@ pub fn load_languages() -> anyhow::Result<Self> {
         let catalog: Catalog = toml::from_str(toml_str)?;
         Ok(catalog)
     }
+
+    /// Iterate over every program in every category.
+    pub fn programs(&self) -> impl Iterator<Item = &Program> {
+        self.categories
+            .iter()
+            .flat_map(|cat| cat.subcategories.iter())
+            .flat_map(|sub| sub.programs.iter())
+    }
+
+    pub fn program(&self, id: &str) -> Option<&Program> {
+        self.programs().find(|prog| prog.id == id)
+    }
+}
+
+/// Overlays installed on this host, validated against the embedded catalogs.
+///
+/// Returns the overlays that loaded cleanly and the problems found in the rest.
+pub fn installed_overlays() -> (Vec<CatalogOverlay>, Vec<String>) {
+    let embedded = [
+        Catalog::load_s_tier().unwrap_or_default(),
+        Catalog::load_full().unwrap_or_default(),
+        Catalog::load_languages().unwrap_or_default(),
+    ];
+    let base: Vec<&Catalog> = embedded.iter().collect();
+    load_overlays(&overlay_dirs(), &base)
+}
+
+/// Installed overlays, with problems logged rather than returned.
+pub fn usable_overlays() -> Vec<CatalogOverlay> {
+    let (overlays, errors) = installed_overlays();
+    for error in errors {
+        tracing::warn!("Ignoring catalog overlay: {error}");
+    }
+    overlays
+}
+
+/// Every embedded catalog, S-tier first, with the host's overlays applied.
+pub fn bundled_catalogs() -> Vec<Catalog> {
+    let overlays = usable_overlays();
+    [
+        Catalog::load_s_tier(),
+        Catalog::load_full(),
+        Catalog::load_languages(),
+    ]
+    .into_iter()
+    .map(|catalog| catalog.unwrap_or_default().with_overlays(&overlays))
+    .collect()
 }
 
 pub fn curated_catalog() -> Catalog {
-    Catalog::load_s_tier().unwrap_or_default()
+    Catalog::load_s_tier()
+        .unwrap_or_default()
+        .with_overlays(&usable_overlays())
 }
 
 #[cfg(test)]
@@ -86,22 +156,30 @@ fn can_load_languages_catalog() {
 
     #[test]
     fn enforce_driver_mappings_s_tier() {
+        use crate::backend::PkgBackend;
+        use crate::sources::ordered_sources;
+
         let catalog = Catalog::load_s_tier().expect("Failed to load S-tier catalog");
-        let required_drivers = ["arch", "debian", "fedora"];
+        let required_drivers = [
+            ("arch", PkgBackend::Pacman),
+            ("debian", PkgBackend::Apt),
+            ("fedora", PkgBackend::Dnf),
+        ];
         let mut missing = Vec::new();
 
         for cat in &catalog.categories {
             for sub in &cat.subcategories {
                 for prog in &sub.programs {
-                    for driver in &required_drivers {
-                        if !prog.packages.contains_key(*driver) {
+                    for (driver, backend) in &required_drivers {
+                        let native = prog.packages.get(*driver).map(Vec::as_slice);
+                        if native.is_some_and(|pkgs| pkgs.is_empty()) {
                             missing.push(format!(
-                                "{}::{}::{} missing mapping for '{}'",
+                                "{}::{}::{} has empty mapping for '{}'",
                                 cat.display_name, sub.name, prog.name, driver
                             ));
-                        } else if prog.packages.get(*driver).unwrap().is_empty() {
+                        } else if ordered_sources(*backend, native, &prog.sources).is_empty() {
                             missing.push(format!(
-                                "{}::{}::{} has empty mapping for '{}'",
+                                "{}::{}::{} missing mapping for '{}'",
                                 cat.display_name, sub.name, prog.name, driver
                             ));
                         }
@@ -0,0 +1,406 @@
+//! Catalog overlays read from `catalog.d` directories.
+//!
+//! Each `*.toml` file may add categories and programs, override fields of an
+//! existing program, or remove programs by id. System overlays are applied
+//! first, then the user's, so a user file has the last word.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::config_path;
+use crate::model::software::Tier;
+use crate::sources::ProgramSources;
+
+use super::{Catalog, Category, Program};
+
+pub const OVERLAY_DIR: &str = "catalog.d";
+pub const SYSTEM_OVERLAY_DIR: &str = "/etc/mash-installer/catalog.d";
+
+/// Distro keys a `packages` map may use.
+const DISTRO_KEYS: &[&str] = &["arch", "debian", "fedora"];
+
+/// Overlay directories in the order they are applied.
+pub fn overlay_dirs() -> Vec<PathBuf> {
+    let user = config_path()
+        .parent()
+        .map(|dir| dir.join(OVERLAY_DIR))
+        .unwrap_or_else(|| PathBuf::from(OVERLAY_DIR));
+    vec![PathBuf::from(SYSTEM_OVERLAY_DIR), user]
+}
+
+/// Field-level changes to a program defined elsewhere.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProgramOverride {
+    pub id: String,
+    /// Per-distro mappings to replace; an empty list drops that distro.
+    #[serde(default)]
+    pub packages: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub sources: Option<ProgramSources>,
+    #[serde(default)]
+    pub tier: Option<Tier>,
+    #[serde(default)]
+    pub recommended: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogOverlay {
+    #[serde(default)]
+    pub categories: Vec<Category>,
+    #[serde(default, rename = "override")]
+    pub overrides: Vec<ProgramOverride>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl CatalogOverlay {
+    pub fn parse(text: &str, path: &Path) -> Result<Self> {
+        let mut overlay: Self = toml::from_str(text)
+            .with_context(|| format!("{}: invalid catalog overlay", path.display()))?;
+        overlay.path = path.to_path_buf();
+        let origin = overlay.origin();
+        for program in overlay.programs_mut() {
+            program.origin = Some(origin.clone());
+        }
+        Ok(overlay)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading catalog overlay {}", path.display()))?;
+        Self::parse(&text, path)
+    }
+
+    fn origin(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn programs(&self) -> impl Iterator<Item = &Program> {
+        self.categories
+            .iter()
+            .flat_map(|cat| cat.subcategories.iter())
+            .flat_map(|sub| sub.programs.iter())
+    }
+
+    fn programs_mut(&mut self) -> impl Iterator<Item = &mut Program> {
+        self.categories
+            .iter_mut()
+            .flat_map(|cat| cat.subcategories.iter_mut())
+            .flat_map(|sub| sub.programs.iter_mut())
+    }
+
+    /// Check the overlay on its own and against the ids in `known`.
+    pub fn validate(&self, known: &HashSet<String>) -> Vec<String> {
+        let file = self.path.display();
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+
+        for program in self.programs() {
+            if program.id.trim().is_empty() {
+                errors.push(format!("{file}: program '{}' has no id", program.name));
+                continue;
+            }
+            if !seen.insert(program.id.as_str()) {
+                errors.push(format!(
+                    "{file}: program '{}' is defined more than once",
+                    program.id
+                ));
+            }
+            errors.extend(bad_distro_keys(&program.packages).map(|key| {
+                format!(
+                    "{file}: program '{}' maps unknown distro '{key}'",
+                    program.id
+                )
+            }));
+            if program.packages.values().all(Vec::is_empty) && program.sources.is_empty() {
+                errors.push(format!(
+                    "{file}: program '{}' has no packages or sources",
+                    program.id
+                ));
+            }
+        }
+
+        for over in &self.overrides {
+            if !known.contains(&over.id) {
+                errors.push(format!(
+                    "{file}: override targets unknown program '{}'",
+                    over.id
+                ));
+            }
+            errors.extend(
+                bad_distro_keys(&over.packages).map(|key| {
+                    format!("{file}: override '{}' maps unknown distro '{key}'", over.id)
+                }),
+            );
+        }
+
+        for id in &self.remove {
+            if !known.contains(id) {
+                errors.push(format!("{file}: cannot remove unknown program '{id}'"));
+            }
+        }
+
+        errors
+    }
+}
+
+fn bad_distro_keys(packages: &HashMap<String, Vec<String>>) -> impl Iterator<Item = &String> {
+    packages
+        .keys()
+        .filter(|key| !DISTRO_KEYS.contains(&key.as_str()))
+}
+
+/// Load every `*.toml` overlay under `dirs`, validating against `base`.
+///
+/// Invalid files are left out; their problems are returned alongside the
+/// overlays that loaded cleanly.
+pub fn load_overlays(dirs: &[PathBuf], base: &[&Catalog]) -> (Vec<CatalogOverlay>, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut parsed = Vec::new();
+
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match CatalogOverlay::load(&path) {
+                Ok(overlay) => parsed.push(overlay),
+                Err(err) => errors.push(format!("{err:#}")),
+            }
+        }
+    }
+
+    let mut known: HashSet<String> = base
+        .iter()
+        .flat_map(|catalog| catalog.programs())
+        .map(|program| program.id.clone())
+        .collect();
+    known.extend(
+        parsed
+            .iter()
+            .flat_map(CatalogOverlay::programs)
+            .map(|program| program.id.clone()),
+    );
+
+    let mut overlays = Vec::new();
+    for overlay in parsed {
+        let problems = overlay.validate(&known);
+        if problems.is_empty() {
+            overlays.push(overlay);
+        } else {
+            errors.extend(problems);
+        }
+    }
+
+    (overlays, errors)
+}
+
+impl Catalog {
+    /// Merge `overlay` into this catalog.
+    pub fn apply_overlay(&mut self, overlay: &CatalogOverlay) {
+        for category in &overlay.categories {
+            match self.categories.iter_mut().find(|c| c.id == category.id) {
+                Some(existing) => merge_category(existing, category),
+                None => self.categories.push(category.clone()),
+            }
+        }
+
+        let origin = overlay.origin();
+        for over in &overlay.overrides {
+            for program in self.programs_mut().filter(|p| p.id == over.id) {
+                for (distro, packages) in &over.packages {
+                    if packages.is_empty() {
+                        program.packages.remove(distro);
+                    } else {
+                        program.packages.insert(distro.clone(), packages.clone());
+                    }
+                }
+                if let Some(sources) = &over.sources {
+                    program.sources = sources.clone();
+                }
+                if let Some(tier) = over.tier {
+                    program.tier = tier;
+                }
+                if let Some(recommended) = over.recommended {
+                    program.recommended = recommended;
+                }
+                program.origin = Some(origin.clone());
+            }
+        }
+
+        if !overlay.remove.is_empty() {
+            for category in &mut self.categories {
+                for sub in &mut category.subcategories {
+                    sub.programs.retain(|p| !overlay.remove.contains(&p.id));
+                }
+            }
+        }
+    }
+
+    pub fn with_overlays(mut self, overlays: &[CatalogOverlay]) -> Self {
+        for overlay in overlays {
+            self.apply_overlay(overlay);
+        }
+        self
+    }
+
+    fn programs_mut(&mut self) -> impl Iterator<Item = &mut Program> {
+        self.categories
+            .iter_mut()
+            .flat_map(|cat| cat.subcategories.iter_mut())
+            .flat_map(|sub| sub.programs.iter_mut())
+    }
+}
+
+fn merge_category(existing: &mut Category, overlay: &Category) {
+    for sub in &overlay.subcategories {
+        match existing
+            .subcategories
+            .iter_mut()
+            .find(|s| s.name == sub.name)
+        {
+            Some(target) => {
+                for program in &sub.programs {
+                    match target.programs.iter_mut().find(|p| p.id == program.id) {
+                        Some(slot) => *slot = program.clone(),
+                        None => target.programs.push(program.clone()),
+                    }
+                }
+            }
+            None => existing.subcategories.push(sub.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn base() -> Catalog {
+        toml::from_str(
+            r#"
+            [[categories]]
+            name = "internet"
+            display_name = "Internet"
+            description = "Internet"
+
+            [[categories.subcategories]]
+            name = "browsers"
+            description = "Browsers"
+
+            [[categories.subcategories.programs]]
+            id = "brave"
+            name = "Brave"
+            description = "Browser"
+            tier = "S"
+            packages = { arch = ["brave-bin"], debian = ["brave-browser"] }
+
+            [[categories.subcategories.programs]]
+            id = "falkon"
+            name = "Falkon"
+            description = "Browser"
+            tier = "B"
+            packages = { debian = ["falkon"] }
+            "#,
+        )
+        .expect("base catalog parses")
+    }
+
+    const TEAM_OVERLAY: &str = r#"
+        remove = ["falkon"]
+
+        [[categories]]
+        name = "internet"
+        display_name = "Internet"
+        description = "Internet"
+
+        [[categories.subcategories]]
+        name = "browsers"
+        description = "Browsers"
+
+        [[categories.subcategories.programs]]
+        id = "team-browser"
+        name = "Team Browser"
+        description = "Internal"
+        tier = "A"
+        packages = { debian = ["team-browser"] }
+
+        [[override]]
+        id = "brave"
+        packages = { debian = ["brave-nightly"], arch = [] }
+    "#;
+
+    #[test]
+    fn overlay_adds_overrides_and_removes_programs() -> Result<()> {
+        let overlay = CatalogOverlay::parse(TEAM_OVERLAY, Path::new("/etc/team.toml"))?;
+        let catalog = base().with_overlays(&[overlay]);
+
+        let brave = catalog.program("brave").expect("brave kept");
+        assert_eq!(
+            brave.packages.get("debian"),
+            Some(&vec!["brave-nightly".into()])
+        );
+        assert!(!brave.packages.contains_key("arch"));
+        assert_eq!(brave.origin.as_deref(), Some("/etc/team.toml"));
+
+        let team = catalog.program("team-browser").expect("program added");
+        assert_eq!(team.origin.as_deref(), Some("/etc/team.toml"));
+        assert!(catalog.program("falkon").is_none());
+        assert_eq!(catalog.categories.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn validation_names_file_and_program() -> Result<()> {
+        let overlay = CatalogOverlay::parse(
+            r#"
+            remove = ["ghost"]
+
+            [[override]]
+            id = "brave"
+            packages = { gentoo = ["brave"] }
+            "#,
+            Path::new("/tmp/bad.toml"),
+        )?;
+        let known: HashSet<String> = ["brave".to_string()].into();
+        let errors = overlay.validate(&known);
+
+        assert!(errors
+            .iter()
+            .any(|e| e == "/tmp/bad.toml: cannot remove unknown program 'ghost'"));
+        assert!(errors
+            .iter()
+            .any(|e| e == "/tmp/bad.toml: override 'brave' maps unknown distro 'gentoo'"));
+        Ok(())
+    }
+
+    #[test]
+    fn load_overlays_skips_invalid_files() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("10-team.toml"), TEAM_OVERLAY)?;
+        fs::write(dir.path().join("20-broken.toml"), "remove = 3")?;
+        fs::write(dir.path().join("notes.txt"), "ignored")?;
+
+        let base = base();
+        let (overlays, errors) = load_overlays(&[dir.path().to_path_buf()], &[&base]);
+
+        assert_eq!(overlays.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("20-broken.toml"));
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+//! Turn catalog program IDs into native package names for a distro driver.
+
+use std::collections::BTreeSet;
+
+use crate::backend::PkgBackend;
+use crate::driver::DistroDriver;
+use crate::sources::{self, InstallSource};
+
+use super::{Catalog, Program};
+
+/// Key used in a program's `packages` map for the given backend.
+pub fn distro_key(backend: PkgBackend) -> &'static str {
+    match backend {
+        PkgBackend::Apt => "debian",
+        PkgBackend::Pacman => "arch",
+        PkgBackend::Dnf => "fedora",
+    }
+}
+
+/// A catalog program and the sources it can be installed from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedProgram {
+    pub id: String,
+    /// Candidates in the distro's priority order; never empty.
+    pub sources: Vec<InstallSource>,
+}
+
+impl ResolvedProgram {
+    /// Native packages, when the preferred source is the package manager.
+    pub fn native_packages(&self) -> Option<&[String]> {
+        match self.sources.first() {
+            Some(InstallSource::Native(pkgs)) => Some(pkgs),
+            _ => None,
+        }
+    }
+
+    /// Whether anything besides the package manager can install this program.
+    pub fn has_alternatives(&self) -> bool {
+        self.sources
+            .iter()
+            .any(|source| !matches!(source, InstallSource::Native(_)))
+    }
+}
+
+/// Outcome of resolving a set of IDs against the catalog.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Resolution {
+    /// Catalog programs with at least one source usable on the active distro.
+    pub programs: Vec<ResolvedProgram>,
+    /// IDs that are not catalog programs, installed by their canonical name
+    /// (e.g. packages added by heuristics).
+    pub passthrough: Vec<String>,
+    /// Catalog programs with no package mapping or alternative source for
+    /// the active distro.
+    pub unmapped: Vec<String>,
+}
+
+impl Resolution {
+    /// Every native package to install in one package-manager batch,
+    /// de-duplicated and sorted.
+    pub fn packages(&self) -> Vec<String> {
+        self.programs
+            .iter()
+            .filter_map(ResolvedProgram::native_packages)
+            .flatten()
+            .cloned()
+            .chain(self.passthrough.iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Looks program IDs up across one or more catalogs, first match wins.
+#[derive(Clone, Debug, Default)]
+pub struct CatalogResolver {
+    catalogs: Vec<Catalog>,
+}
+
+impl CatalogResolver {
+    pub fn new(catalogs: Vec<Catalog>) -> Self {
+        Self { catalogs }
+    }
+
+    /// Resolver over every catalog shipped with the installer, with the
+    /// host's overlays applied.
+    pub fn bundled() -> Self {
+        Self::new(super::bundled_catalogs())
+    }
+
+    pub fn program(&self, id: &str) -> Option<&Program> {
+        self.catalogs.iter().find_map(|catalog| catalog.program(id))
+    }
+
+    pub fn resolve<'i>(
+        &self,
+        ids: impl IntoIterator<Item = &'i str>,
+        driver: &dyn DistroDriver,
+    ) -> Resolution {
+        let backend = driver.pkg_backend();
+        let key = distro_key(backend);
+        let mut resolution = Resolution::default();
+
+        for id in ids {
+            match self.program(id) {
+                Some(program) => {
+                    let native = program.packages.get(key).map(Vec::as_slice);
+                    let candidates = sources::ordered_sources(backend, native, &program.sources);
+                    if candidates.is_empty() {
+                        resolution.unmapped.push(id.to_string());
+                    } else {
+                        resolution.programs.push(ResolvedProgram {
+                            id: id.to_string(),
+                            sources: candidates,
+                        });
+                    }
+                }
+                None => {
+                    if let Some(native) = driver.translate_package(id) {
+                        resolution.passthrough.push(native);
+                    }
+                }
+            }
+        }
+
+        resolution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::PlatformInfo;
+
+    struct BackendDriver(PkgBackend);
+
+    impl DistroDriver for BackendDriver {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn description(&self) -> &'static str {
+            "test"
+        }
+
+        fn matches(&self, _: &PlatformInfo) -> bool {
+            true
+        }
+
+        fn pkg_backend(&self) -> PkgBackend {
+            self.0
+        }
+
+        fn translate_package(&self, canonical: &str) -> Option<String> {
+            match canonical {
+                "software-properties-common" => None,
+                _ => Some(canonical.to_string()),
+            }
+        }
+    }
+
+    fn sample_catalog() -> Catalog {
+        toml::from_str(
+            r#"
+            [[categories]]
+            name = "internet"
+            display_name = "Internet"
+            description = "Internet"
+
+            [[categories.subcategories]]
+            name = "browsers"
+            description = "Browsers"
+
+            [[categories.subcategories.programs]]
+            id = "brave"
+            name = "Brave"
+            description = "Browser"
+            tier = "S"
+            packages = { arch = ["brave-bin"], debian = ["brave-browser"] }
+            "#,
+        )
+        .expect("sample catalog parses")
+    }
+
+    #[test]
+    fn resolves_per_distro_package_names() {
+        let resolver = CatalogResolver::new(vec![sample_catalog()]);
+
+        let arch = resolver.resolve(["brave"], &BackendDriver(PkgBackend::Pacman));
+        assert_eq!(arch.packages(), vec!["brave-bin".to_string()]);
+
+        let debian = resolver.resolve(["brave"], &BackendDriver(PkgBackend::Apt));
+        assert_eq!(debian.packages(), vec!["brave-browser".to_string()]);
+    }
+
+    #[test]
+    fn reports_programs_without_a_mapping() {
+        let resolver = CatalogResolver::new(vec![sample_catalog()]);
+        let fedora = resolver.resolve(["brave"], &BackendDriver(PkgBackend::Dnf));
+
+        assert!(fedora.packages().is_empty());
+        assert_eq!(fedora.unmapped, vec!["brave".to_string()]);
+    }
+
+    #[test]
+    fn alternative_sources_cover_missing_mappings() {
+        let mut catalog = sample_catalog();
+        catalog.categories[0].subcategories[0].programs[0]
+            .sources
+            .flatpak = Some("com.brave.Browser".into());
+        let resolver = CatalogResolver::new(vec![catalog]);
+
+        let fedora = resolver.resolve(["brave"], &BackendDriver(PkgBackend::Dnf));
+        assert!(fedora.unmapped.is_empty());
+        assert!(fedora.packages().is_empty());
+        assert_eq!(
+            fedora.programs[0].sources,
+            vec![InstallSource::Flatpak("com.brave.Browser".into())]
+        );
+    }
+
+    #[test]
+    fn unknown_ids_pass_through_driver_translation() {
+        let resolver = CatalogResolver::new(vec![sample_catalog()]);
+        let resolution = resolver.resolve(
+            ["zram-tools", "software-properties-common"],
+            &BackendDriver(PkgBackend::Apt),
+        );
+
+        assert_eq!(resolution.passthrough, vec!["zram-tools".to_string()]);
+        assert!(resolution.unmapped.is_empty());
+    }
+
+    #[test]
+    fn bundled_catalogs_resolve_brave_on_arch() {
+        let resolution =
+            CatalogResolver::bundled().resolve(["brave"], &BackendDriver(PkgBackend::Pacman));
+        assert!(resolution.packages().is_empty());
+        assert_eq!(
+            resolution.programs[0].sources.first(),
+            Some(&InstallSource::Aur("brave-bin".into()))
+        );
+    }
+}
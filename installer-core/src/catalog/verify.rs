@@ -0,0 +1,375 @@
+//! Check catalog package mappings against real package repositories.
+//!
+//! The live [`SystemIndex`] asks the local package database (`apt-cache`,
+//! `pacman -Si`, `dnf info`), which only knows the host architecture. For
+//! cross-architecture checks and offline tests, a [`FixtureIndex`] reads the
+//! package list from a TOML or JSON file instead.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::backend::PkgBackend;
+use crate::system::cmd;
+
+use super::{distro_key, Catalog};
+
+/// What a repository knows about one package name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PackageStatus {
+    Available,
+    Missing,
+    /// The name only resolves through another package that provides or
+    /// replaces it.
+    Renamed {
+        replacement: String,
+    },
+    ArchUnavailable {
+        available: Vec<String>,
+    },
+}
+
+/// A source of package metadata for one distro.
+pub trait PackageIndex {
+    fn lookup(&self, package: &str, arch: &str) -> PackageStatus;
+}
+
+/// Normalize distro architecture names to the kernel's spelling.
+pub fn normalize_arch(arch: &str) -> &str {
+    match arch {
+        "amd64" => "x86_64",
+        "arm64" => "aarch64",
+        other => other,
+    }
+}
+
+fn status_for(archs: &[String], arch: &str) -> PackageStatus {
+    let wanted = normalize_arch(arch);
+    let portable = ["all", "any", "noarch"];
+    if archs.is_empty()
+        || archs
+            .iter()
+            .any(|a| portable.contains(&a.as_str()) || normalize_arch(a) == wanted)
+    {
+        PackageStatus::Available
+    } else {
+        PackageStatus::ArchUnavailable {
+            available: archs.to_vec(),
+        }
+    }
+}
+
+/// Package list read from a fixture file.
+///
+/// ```toml
+/// [packages]
+/// firefox = ["x86_64", "aarch64"]
+/// fonts-noto = ["all"]
+///
+/// [provides]
+/// signal-desktop = "signal-desktop-bin"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct FixtureIndex {
+    /// Package name to the architectures it is built for.
+    #[serde(default)]
+    pub packages: HashMap<String, Vec<String>>,
+    /// Old or virtual name to the package that now provides it.
+    #[serde(default)]
+    pub provides: HashMap<String, String>,
+}
+
+impl FixtureIndex {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading package fixture {}", path.display()))?;
+        let index = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&text)?,
+            _ => toml::from_str(&text)?,
+        };
+        Ok(index)
+    }
+}
+
+impl PackageIndex for FixtureIndex {
+    fn lookup(&self, package: &str, arch: &str) -> PackageStatus {
+        if let Some(archs) = self.packages.get(package) {
+            return status_for(archs, arch);
+        }
+        match self.provides.get(package) {
+            Some(replacement) => PackageStatus::Renamed {
+                replacement: replacement.clone(),
+            },
+            None => PackageStatus::Missing,
+        }
+    }
+}
+
+/// Queries the host's package database.
+pub struct SystemIndex {
+    backend: PkgBackend,
+}
+
+impl SystemIndex {
+    pub fn new(backend: PkgBackend) -> Self {
+        Self { backend }
+    }
+
+    fn query(program: &str, args: &[&str]) -> Option<String> {
+        cmd::Command::new(program)
+            .args(args)
+            .execute()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .filter(|stdout| !stdout.trim().is_empty())
+    }
+
+    fn architectures(info: &str) -> Vec<String> {
+        let mut archs: Vec<String> = info
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .filter(|(key, _)| key.trim() == "Architecture")
+            .map(|(_, value)| value.trim().to_string())
+            .collect();
+        archs.dedup();
+        archs
+    }
+
+    fn provider(&self, package: &str) -> Option<String> {
+        let output = match self.backend {
+            PkgBackend::Apt => {
+                let showpkg = Self::query("apt-cache", &["showpkg", package])?;
+                return showpkg
+                    .split("Reverse Provides:")
+                    .nth(1)?
+                    .lines()
+                    .find_map(|line| line.split_whitespace().next().map(str::to_string));
+            }
+            PkgBackend::Pacman => Self::query("pacman", &["-Sp", "--print-format", "%n", package])?,
+            PkgBackend::Dnf => Self::query(
+                "dnf",
+                &[
+                    "repoquery",
+                    "-q",
+                    "--whatprovides",
+                    package,
+                    "--qf",
+                    "%{name}",
+                ],
+            )?,
+        };
+        output
+            .lines()
+            .map(str::trim)
+            .find(|name| !name.is_empty())
+            .map(str::to_string)
+    }
+}
+
+impl PackageIndex for SystemIndex {
+    fn lookup(&self, package: &str, arch: &str) -> PackageStatus {
+        let info = match self.backend {
+            PkgBackend::Apt => Self::query("apt-cache", &["show", "--no-all-versions", package]),
+            PkgBackend::Pacman => Self::query("pacman", &["-Si", package]),
+            PkgBackend::Dnf => Self::query("dnf", &["info", "-q", package]),
+        };
+        if let Some(info) = info {
+            return status_for(&Self::architectures(&info), arch);
+        }
+        match self.provider(package) {
+            Some(replacement) if replacement != package => PackageStatus::Renamed { replacement },
+            _ => PackageStatus::Missing,
+        }
+    }
+}
+
+/// A mapped package that did not check out.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct VerifyIssue {
+    pub program: String,
+    pub package: String,
+    #[serde(flatten)]
+    pub status: PackageStatus,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VerifyReport {
+    pub distro: String,
+    pub arch: String,
+    /// Number of (program, package) pairs checked.
+    pub checked: usize,
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl VerifyReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Look up every native package the catalogs map for `distro`.
+///
+/// A program listed in several catalogs is checked once, using the first
+/// definition, matching how installs resolve it.
+pub fn verify_catalogs(
+    catalogs: &[Catalog],
+    backend: PkgBackend,
+    arch: &str,
+    index: &dyn PackageIndex,
+) -> VerifyReport {
+    let key = distro_key(backend);
+    let mut seen = HashSet::new();
+    let mut checked = 0;
+    let mut issues = Vec::new();
+
+    for program in catalogs.iter().flat_map(Catalog::programs) {
+        if !seen.insert(program.id.as_str()) {
+            continue;
+        }
+        for package in program.packages.get(key).into_iter().flatten() {
+            checked += 1;
+            let status = index.lookup(package, arch);
+            if status != PackageStatus::Available {
+                issues.push(VerifyIssue {
+                    program: program.id.clone(),
+                    package: package.clone(),
+                    status,
+                });
+            }
+        }
+    }
+
+    VerifyReport {
+        distro: key.to_string(),
+        arch: normalize_arch(arch).to_string(),
+        checked,
+        issues,
+    }
+}
+
+/// Parse a catalog distro key (`arch`, `debian`, `fedora`) into its backend.
+pub fn backend_for_distro(distro: &str) -> Result<PkgBackend> {
+    match distro {
+        "arch" => Ok(PkgBackend::Pacman),
+        "debian" => Ok(PkgBackend::Apt),
+        "fedora" => Ok(PkgBackend::Dnf),
+        other => bail!("unknown distro '{other}' (expected arch, debian or fedora)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn catalog() -> Catalog {
+        toml::from_str(
+            r#"
+            [[categories]]
+            name = "internet"
+            display_name = "Internet"
+            description = "Internet"
+
+            [[categories.subcategories]]
+            name = "browsers"
+            description = "Browsers"
+
+            [[categories.subcategories.programs]]
+            id = "firefox"
+            name = "Firefox"
+            description = "Browser"
+            tier = "S"
+            packages = { arch = ["firefox"] }
+
+            [[categories.subcategories.programs]]
+            id = "signal"
+            name = "Signal"
+            description = "Messenger"
+            tier = "A"
+            packages = { arch = ["signal-desktop"] }
+
+            [[categories.subcategories.programs]]
+            id = "chrome"
+            name = "Chrome"
+            description = "Browser"
+            tier = "B"
+            packages = { arch = ["google-chrome", "chrome-extras"] }
+            "#,
+        )
+        .expect("catalog parses")
+    }
+
+    const FIXTURE: &str = r#"
+        [packages]
+        firefox = ["x86_64", "aarch64"]
+        google-chrome = ["x86_64"]
+
+        [provides]
+        signal-desktop = "signal-desktop-bin"
+    "#;
+
+    #[test]
+    fn fixture_reports_missing_renamed_and_arch_gaps() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("arch.toml");
+        fs::write(&path, FIXTURE)?;
+        let index = FixtureIndex::load(&path)?;
+
+        let report = verify_catalogs(&[catalog()], PkgBackend::Pacman, "aarch64", &index);
+
+        assert_eq!(report.checked, 4);
+        assert_eq!(report.distro, "arch");
+        assert_eq!(
+            report.issues,
+            vec![
+                VerifyIssue {
+                    program: "signal".into(),
+                    package: "signal-desktop".into(),
+                    status: PackageStatus::Renamed {
+                        replacement: "signal-desktop-bin".into()
+                    },
+                },
+                VerifyIssue {
+                    program: "chrome".into(),
+                    package: "google-chrome".into(),
+                    status: PackageStatus::ArchUnavailable {
+                        available: vec!["x86_64".into()]
+                    },
+                },
+                VerifyIssue {
+                    program: "chrome".into(),
+                    package: "chrome-extras".into(),
+                    status: PackageStatus::Missing,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn report_serializes_status_inline() -> Result<()> {
+        let index: FixtureIndex = toml::from_str(FIXTURE)?;
+        let report = verify_catalogs(&[catalog()], PkgBackend::Pacman, "x86_64", &index);
+        let json = serde_json::to_value(&report)?;
+
+        assert_eq!(json["issues"][0]["status"], "renamed");
+        assert_eq!(json["issues"][0]["replacement"], "signal-desktop-bin");
+        assert_eq!(json["issues"][1]["status"], "missing");
+        Ok(())
+    }
+
+    #[test]
+    fn debian_arch_names_are_normalized() {
+        assert_eq!(
+            status_for(&["arm64".into()], "aarch64"),
+            PackageStatus::Available
+        );
+        assert_eq!(
+            status_for(&["all".into()], "x86_64"),
+            PackageStatus::Available
+        );
+    }
+}
@@ -1,16 +1,20 @@
 //! Chezmoi dotfile management integration
 //!
 //! Enables automated restoration of personal dotfiles during provisioning.
-//! Chezmoi is installed (via package manager or official script), then
-//! initialized from a provided Git repository URL and optionally a branch.
+//! Chezmoi is installed (via package manager, falling back to the official
+//! script), then initialized from a provided Git repository URL and
+//! optionally a branch.
 
 use anyhow::{Context, Result};
 use std::process::Command;
 use tracing::info;
 
+use crate::sources::{self, InstallSource, ScriptSource};
 use crate::system::cmd;
 use crate::{PhaseContext, PhaseResult};
 
+const INSTALL_SCRIPT_URL: &str = "https://get.chezmoi.io";
+
 /// Main entry point for the chezmoi dotfile restoration phase.
 pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
     let opts = &ctx.options.chezmoi;
@@ -47,32 +51,25 @@ fn ensure_installed(ctx: &mut PhaseContext) -> Result<()> {
         return Ok(());
     }
 
-    info!("chezmoi not found via package manager; installing via official script to ~/.local/bin");
-
-    if ctx.options.dry_run {
-        ctx.record_dry_run(
-            "chezmoi",
-            "Would install chezmoi via official script",
-            Some("sh -c 'curl -sfL https://git.io/chezmoi | sh -s -- -b ~/.local/bin'".to_string()),
-        );
-        return Ok(());
-    }
-
-    // Ensure ~/.local/bin exists
+    // The official script drops the binary in ~/.local/bin
     let home = dirs::home_dir().context("Determining home directory")?;
     let bin_dir = home.join(".local/bin");
-    if !bin_dir.exists() {
+    if !ctx.options.dry_run && !bin_dir.exists() {
         std::fs::create_dir_all(&bin_dir).context("Creating ~/.local/bin")?;
     }
 
-    let mut install_cmd = Command::new("sh");
-    install_cmd
-        .arg("-c")
-        .arg("curl -sfL https://git.io/chezmoi | sh -s -- -b ~/.local/bin");
-
-    cmd::run(&mut install_cmd).context("Executing chezmoi installation script")?;
-
-    ctx.record_action("Installed chezmoi to ~/.local/bin");
+    // The official script is unpinned, so it is only a fallback for users
+    // who pass --allow-unverified-scripts
+    let candidates = [
+        InstallSource::native_for(ctx.platform.driver, &["chezmoi"]),
+        InstallSource::Script(ScriptSource {
+            url: INSTALL_SCRIPT_URL.to_string(),
+            sha256: None,
+            args: vec!["-b".to_string(), bin_dir.display().to_string()],
+            sudo: false,
+        }),
+    ];
+    sources::install(ctx, "chezmoi", "chezmoi", &candidates).context("Installing chezmoi")?;
     Ok(())
 }
 
@@ -169,6 +166,7 @@ fn build_test_context(chezmoi: ChezmoiOptions) -> Result<InstallContext> {
             driver,
             pkg_backend: driver.pkg_backend(),
             system: &crate::sys_ops::REAL_SYSTEM,
+            commands: &crate::cmd::REAL_RUNNER,
         };
         let options = UserOptionsContext {
             profile: ProfileLevel::Dev,
@@ -190,6 +188,8 @@ fn build_test_context(chezmoi: ChezmoiOptions) -> Result<InstallContext> {
             chezmoi,
             desktop_environment: None,
             display_protocol: crate::desktop::DisplayProtocol::Auto,
+            verify: false,
+            allow_unverified_scripts: false,
         };
         let localization = Localization::load_default()?;
         let cache = crate::ArtifactCache::new(&PathBuf::from("/tmp/mash-test-cache"));
@@ -1,15 +1,19 @@
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use crate::backend::PkgBackend;
 use crate::config::{self, ConfigError};
 use crate::driver::DistroDriver;
 use crate::localization::Localization;
 pub use crate::model::options::UserOptionsContext;
+use crate::model::phase::{ActionKind, PhaseEvent, PlannedChange};
+use crate::pi_board::PiBoard;
 use crate::platform::PlatformInfo;
-use crate::rollback::RollbackManager;
+use crate::rollback::{RollbackManager, RollbackOp};
 use crate::staging;
 pub use crate::system::artifact_cache::ArtifactCache;
-use crate::system::dry_run::DryRunLog;
+use crate::system::dry_run::{self, DryRunLog};
+use crate::system::progress;
 use anyhow::Result;
 
 /// Options that override values in the persisted Mash config.
@@ -77,6 +81,9 @@ pub struct PlatformContext {
     pub driver: &'static dyn DistroDriver,
     pub pkg_backend: PkgBackend,
     pub system: &'static dyn crate::sys_ops::SystemOps,
+    /// Executes the external commands phases run. Tests swap in a
+    /// [`RecordingRunner`](crate::RecordingRunner).
+    pub commands: &'static dyn crate::cmd::CommandRunner,
 }
 
 impl PlatformContext {
@@ -94,24 +101,24 @@ pub fn is_pi(&self) -> bool {
         self.pi_model().is_some()
     }
 
+    /// Return the Raspberry Pi board if it can be inferred from the model text.
+    pub fn pi_board(&self) -> Option<PiBoard> {
+        self.pi_model().and_then(PiBoard::from_model)
+    }
+
     /// Return the Raspberry Pi generation if it can be inferred from the model text.
     pub fn pi_generation(&self) -> Option<u8> {
-        self.pi_model().and_then(|model| {
-            if model.contains("Pi 4") || model.contains("Raspberry Pi 4") {
-                Some(4)
-            } else if model.contains("Pi 3") || model.contains("Raspberry Pi 3") {
-                Some(3)
-            } else if model.contains("Pi 2") || model.contains("Raspberry Pi 2") {
-                Some(2)
-            } else {
-                None
-            }
-        })
+        self.pi_board().and_then(|board| board.generation())
     }
 
     /// Return true if the platform exposes USB 3.0 at its main host controller.
     pub fn supports_usb3(&self) -> bool {
-        self.is_pi_4b()
+        self.pi_board().is_some_and(|board| board.has_usb3())
+    }
+
+    /// Is the detected device a BCM2712 board: Pi 5, Pi 500 or Compute Module 5?
+    pub fn is_pi_5(&self) -> bool {
+        self.pi_generation() == Some(5)
     }
 
     /// Is the detected device some variant of Raspberry Pi 4? We consider "Pi 4"/"Raspberry Pi 4" matches as 4B units.
@@ -207,6 +214,7 @@ fn build_platform_ctx(model: Option<&'static str>) -> PlatformContext {
             driver: &TEST_DRIVER,
             pkg_backend: TEST_DRIVER.pkg_backend(),
             system: &crate::sys_ops::REAL_SYSTEM,
+            commands: &crate::cmd::REAL_RUNNER,
         }
     }
 
@@ -218,11 +226,16 @@ fn pi_generation_detects_boards() {
         assert_eq!(pi3.pi_generation(), Some(3));
         let unknown = build_platform_ctx(Some("Raspberry Pi Zero"));
         assert_eq!(unknown.pi_generation(), None);
+        let cm5 = build_platform_ctx(Some("Raspberry Pi Compute Module 5 Rev 1.0"));
+        assert_eq!(cm5.pi_generation(), Some(5));
+        assert!(cm5.is_pi_5() && !cm5.is_pi_4b());
     }
 
     #[test]
-    fn supports_usb3_only_on_pi4b() {
+    fn supports_usb3_on_pi4b_and_pi5() {
         assert!(build_platform_ctx(Some("Raspberry Pi 4 Model B")).supports_usb3());
+        assert!(build_platform_ctx(Some("Raspberry Pi 5 Model B Rev 1.0")).supports_usb3());
+        assert!(!build_platform_ctx(Some("Raspberry Pi Compute Module 4 Rev 1.1")).supports_usb3());
         assert!(!build_platform_ctx(Some("Raspberry Pi 3 Model B")).supports_usb3());
         assert!(!build_platform_ctx(None).supports_usb3());
     }
@@ -298,30 +311,32 @@ pub fn from_ctx(
         )
     }
 
-    /// Register a rollback action associated with the provided label.
-    pub fn register_rollback_action(
-        &mut self,
-        label: impl Into<String>,
-        action: impl Fn() -> Result<()> + 'static,
-    ) {
+    /// Register a rollback step associated with the provided label.
+    pub fn register_rollback_action(&mut self, label: impl Into<String>, op: RollbackOp) {
         let label = label.into();
         self.rollback_actions.push(label.clone());
-        self.rollback.register_action(label, action);
+        self.rollback.register_action(label, op);
     }
 
     /// Record an action that should be represented in `PhaseOutput`.
     pub fn record_action(&mut self, action: impl Into<String>) {
-        self.actions_taken.push(action.into());
+        let action = action.into();
+        announce(ActionKind::Action, &action);
+        self.actions_taken.push(action);
     }
 
     /// Record a configuration action.
     pub fn record_configured(&mut self, action: impl Into<String>) {
-        self.configured_actions.push(action.into());
+        let action = action.into();
+        announce(ActionKind::Configured, &action);
+        self.configured_actions.push(action);
     }
 
     /// Record a tweak or setting change.
     pub fn record_tweaked(&mut self, action: impl Into<String>) {
-        self.tweaked_actions.push(action.into());
+        let action = action.into();
+        announce(ActionKind::Tweaked, &action);
+        self.tweaked_actions.push(action);
     }
 
     /// Record a non-fatal warning that will appear in the phase output.
@@ -338,10 +353,55 @@ pub fn record_dry_run(
         detail: Option<String>,
     ) {
         if self.options.dry_run {
+            let action = action.into();
+            announce(ActionKind::DryRun, &action);
+            dry_run::plan(PlannedChange::Note {
+                action: action.clone(),
+                detail: detail.clone(),
+            });
             self.dry_run_log.record(phase, action, detail);
         }
     }
 
+    /// In a dry run, plan writing `contents` to `path`, diffed against what
+    /// the file holds now. Nothing is planned when the file already matches.
+    pub fn plan_file(&self, path: &Path, contents: &str) {
+        if !self.options.dry_run {
+            return;
+        }
+        let current = self.platform.system.read_to_string(path).ok();
+        if current.as_deref() == Some(contents) {
+            return;
+        }
+        dry_run::plan(PlannedChange::File {
+            path: path.to_path_buf(),
+            exists: current.is_some(),
+            diff: dry_run::line_diff(current.as_deref().unwrap_or(""), contents),
+        });
+    }
+
+    /// Run `work` as a named sub-step so observers can follow long phases.
+    pub fn step<R>(
+        &mut self,
+        step: impl Into<String>,
+        work: impl FnOnce(&mut Self) -> Result<R>,
+    ) -> Result<R> {
+        let step = step.into();
+        progress::emit(|phase| PhaseEvent::StepStarted {
+            phase,
+            step: step.clone(),
+        });
+        let started = Instant::now();
+        let result = work(self);
+        progress::emit(|phase| PhaseEvent::StepFinished {
+            phase,
+            step,
+            success: result.is_ok(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+        result
+    }
+
     pub fn run_or_record<F, R>(
         &mut self,
         phase: impl Into<String>,
@@ -369,10 +429,19 @@ pub fn take_metadata(&mut self) -> PhaseMetadata {
             rollback_actions: std::mem::take(&mut self.rollback_actions),
             warnings: std::mem::take(&mut self.warnings),
             dry_run: self.options.dry_run,
+            duration_ms: 0,
         }
     }
 }
 
+fn announce(kind: ActionKind, message: &str) {
+    progress::emit(|phase| PhaseEvent::Action {
+        phase,
+        kind,
+        message: message.to_string(),
+    });
+}
+
 pub use crate::model::phase::PhaseMetadata;
 
 pub trait DryRunDefault {
@@ -3,28 +3,36 @@
 
 /// A simple dependency graph for installer phases.
 pub struct DependencyGraph {
-    nodes: HashMap<String, HashSet<String>>,
+    nodes: HashMap<String, Vec<String>>,
+    /// Node names in the order they were added.
+    order: Vec<String>,
 }
 
 impl DependencyGraph {
     pub fn new() -> Self {
         Self {
             nodes: HashMap::new(),
+            order: Vec::new(),
         }
     }
 
     pub fn add_node(&mut self, name: String, dependencies: Vec<String>) {
-        self.nodes.insert(name, dependencies.into_iter().collect());
+        if !self.nodes.contains_key(&name) {
+            self.order.push(name.clone());
+        }
+        self.nodes.insert(name, dependencies);
     }
 
     /// Performs a topological sort on the graph.
     /// Returns a list of names in an order that satisfies all dependencies.
+    /// Independent nodes keep the order they were added in, so the result is
+    /// the same on every run.
     pub fn topological_sort(&self) -> Result<Vec<String>> {
         let mut sorted = Vec::new();
         let mut visited = HashSet::new();
         let mut visiting = HashSet::new();
 
-        for name in self.nodes.keys() {
+        for name in &self.order {
             self.visit(name, &mut visited, &mut visiting, &mut sorted)?;
         }
 
@@ -110,6 +118,20 @@ fn test_circular_dependency() {
             .contains("Circular dependency"));
     }
 
+    #[test]
+    fn test_independent_nodes_keep_insertion_order() -> Result<()> {
+        let mut graph = DependencyGraph::new();
+        graph.add_node("base".into(), vec![]);
+        for name in ["fonts", "docker", "rust", "git"] {
+            graph.add_node(name.into(), vec!["base".into()]);
+        }
+        graph.add_node("shell".into(), vec!["git".into(), "base".into()]);
+
+        let sorted = graph.topological_sort()?;
+        assert_eq!(sorted, ["base", "fonts", "docker", "rust", "git", "shell"]);
+        Ok(())
+    }
+
     #[test]
     fn test_missing_dependency_is_ignored() -> Result<()> {
         let mut graph = DependencyGraph::new();
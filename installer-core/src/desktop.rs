@@ -4,13 +4,19 @@
 //! and Raspberry Pi compatibility warnings.
 
 use crate::{
+    cmd,
     driver::DistroDriver,
+    dry_run,
     error::{ErrorSeverity, InstallerError, InstallerStateSnapshot},
     package_manager,
+    rollback::RollbackOp,
+    systemd, PhaseContext, PhaseResult, PkgBackend, PlannedChange,
 };
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 /// Supported desktop environments
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -180,9 +186,65 @@ pub fn validate(&self) -> Result<(), InstallerError> {
     }
 }
 
-/// Package mappings for desktop environments across different distros
+/// Native packages and package groups that make up a desktop on one
+/// distro family.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DesktopPackageSet {
+    /// pacman groups, dnf groups/environments (`@id`) or apt metapackages.
+    pub groups: Vec<&'static str>,
+    pub packages: Vec<&'static str>,
+}
+
+impl DesktopPackageSet {
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty() && self.packages.is_empty()
+    }
+}
+
+/// Per-distro-family package sets for one desktop environment.
+#[derive(Debug, Clone, Default)]
+pub struct DesktopMapping {
+    pub apt: DesktopPackageSet,
+    pub pacman: DesktopPackageSet,
+    pub dnf: DesktopPackageSet,
+}
+
+impl DesktopMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apt(mut self, packages: &[&'static str]) -> Self {
+        self.apt.packages.extend_from_slice(packages);
+        self
+    }
+
+    pub fn pacman(mut self, groups: &[&'static str], packages: &[&'static str]) -> Self {
+        self.pacman.groups.extend_from_slice(groups);
+        self.pacman.packages.extend_from_slice(packages);
+        self
+    }
+
+    pub fn dnf(mut self, groups: &[&'static str], packages: &[&'static str]) -> Self {
+        self.dnf.groups.extend_from_slice(groups);
+        self.dnf.packages.extend_from_slice(packages);
+        self
+    }
+
+    pub fn for_backend(&self, backend: PkgBackend) -> &DesktopPackageSet {
+        match backend {
+            PkgBackend::Apt => &self.apt,
+            PkgBackend::Pacman => &self.pacman,
+            PkgBackend::Dnf => &self.dnf,
+        }
+    }
+}
+
+/// Package mappings for desktop environments across different distros.
+/// Names are native to each backend; the display manager comes from
+/// [`DisplayManager::packages`].
 pub struct DesktopPackages {
-    packages: HashMap<DesktopEnvironment, Vec<&'static str>>,
+    mappings: HashMap<DesktopEnvironment, DesktopMapping>,
 }
 
 impl Default for DesktopPackages {
@@ -193,65 +255,513 @@ fn default() -> Self {
 
 impl DesktopPackages {
     pub fn new() -> Self {
-        let mut packages = HashMap::new();
+        let mut mappings = HashMap::new();
 
-        // Debian/Ubuntu packages
-        packages.insert(
+        mappings.insert(
             DesktopEnvironment::Gnome,
-            vec!["gnome", "gdm3", "ubuntu-session"],
+            DesktopMapping::new()
+                .apt(&["gnome"])
+                .pacman(&["gnome"], &[])
+                .dnf(&["@gnome-desktop"], &[]),
         );
-        packages.insert(
+        mappings.insert(
             DesktopEnvironment::Kde,
-            vec!["kde-plasma-desktop", "sddm", "kde-standard"],
+            DesktopMapping::new()
+                .apt(&["kde-plasma-desktop", "kde-standard"])
+                .pacman(&["plasma"], &[])
+                .dnf(&["@kde-desktop-environment"], &[]),
+        );
+        mappings.insert(
+            DesktopEnvironment::Xfce,
+            DesktopMapping::new()
+                .apt(&["xfce4", "xfce4-goodies"])
+                .pacman(&["xfce4", "xfce4-goodies"], &[])
+                .dnf(&["@xfce-desktop-environment"], &[]),
+        );
+        mappings.insert(
+            DesktopEnvironment::Lxqt,
+            DesktopMapping::new()
+                .apt(&["lxqt"])
+                .pacman(&["lxqt"], &[])
+                .dnf(&["@lxqt-desktop-environment"], &[]),
         );
-        packages.insert(DesktopEnvironment::Xfce, vec!["xfce4", "xfce4-goodies"]);
-        packages.insert(DesktopEnvironment::Lxqt, vec!["lxqt", "sddm"]);
-        packages.insert(DesktopEnvironment::Mate, vec!["ubuntu-mate-desktop"]);
-        packages.insert(
+        mappings.insert(
+            DesktopEnvironment::Mate,
+            DesktopMapping::new()
+                .apt(&["mate-desktop-environment"])
+                .pacman(&["mate", "mate-extra"], &[])
+                .dnf(&["@mate-desktop-environment"], &[]),
+        );
+        mappings.insert(
             DesktopEnvironment::Cinnamon,
-            vec!["cinnamon-desktop-environment"],
+            DesktopMapping::new()
+                .apt(&["cinnamon-desktop-environment"])
+                .pacman(&[], &["cinnamon"])
+                .dnf(&["@cinnamon-desktop-environment"], &[]),
         );
-        packages.insert(
+        mappings.insert(
             DesktopEnvironment::Budgie,
-            vec!["budgie-desktop", "lightdm"],
+            DesktopMapping::new()
+                .apt(&["budgie-desktop"])
+                .pacman(&[], &["budgie"])
+                .dnf(&["@budgie-desktop-environment"], &[]),
         );
-        packages.insert(
+        mappings.insert(
             DesktopEnvironment::Enlightenment,
-            vec!["enlightenment", "lightdm"],
+            DesktopMapping::new()
+                .apt(&["enlightenment"])
+                .pacman(&[], &["enlightenment", "terminology"])
+                .dnf(&[], &["enlightenment"]),
+        );
+        mappings.insert(
+            DesktopEnvironment::Lxde,
+            DesktopMapping::new()
+                .apt(&["lxde"])
+                .pacman(&["lxde"], &[])
+                .dnf(&["@lxde-desktop-environment"], &[]),
         );
-        packages.insert(DesktopEnvironment::Lxde, vec!["lxde", "lightdm"]);
-        packages.insert(DesktopEnvironment::None, vec![]);
+        // COSMIC is not packaged for Debian/Ubuntu outside Pop!_OS.
+        mappings.insert(
+            DesktopEnvironment::Cosmic,
+            DesktopMapping::new()
+                .pacman(&["cosmic"], &[])
+                .dnf(&["@cosmic-desktop-environment"], &[]),
+        );
+        mappings.insert(
+            DesktopEnvironment::Hyprland,
+            DesktopMapping::new()
+                .apt(&["hyprland"])
+                .pacman(&[], &["hyprland"])
+                .dnf(&[], &["hyprland"]),
+        );
+        mappings.insert(DesktopEnvironment::None, DesktopMapping::new());
 
-        Self { packages }
+        Self { mappings }
     }
 
-    /// Get packages for a specific desktop environment
-    pub fn get_packages(&self, de: DesktopEnvironment) -> Vec<&'static str> {
-        self.packages.get(&de).cloned().unwrap_or_default()
+    pub fn get_mapping(&self, de: DesktopEnvironment) -> Option<&DesktopMapping> {
+        self.mappings.get(&de)
     }
 
-    /// Get packages translated for a specific distro driver
-    pub fn get_translated_packages(
-        &self,
-        de: DesktopEnvironment,
-        driver: &dyn DistroDriver,
-    ) -> Vec<String> {
-        self.get_packages(de)
-            .iter()
-            .filter_map(|pkg| driver.translate_package(pkg))
-            .collect()
+    /// Native package set for `de` on `backend`; empty when unsupported.
+    pub fn for_backend(&self, de: DesktopEnvironment, backend: PkgBackend) -> DesktopPackageSet {
+        self.get_mapping(de)
+            .map(|mapping| mapping.for_backend(backend).clone())
+            .unwrap_or_default()
     }
 }
 
 /// Check if a desktop environment is currently installed
 pub fn is_de_installed(de: DesktopEnvironment, driver: &dyn DistroDriver) -> bool {
-    let packages = DesktopPackages::new();
-    let de_packages = packages.get_packages(de);
+    let backend = driver.pkg_backend();
+    let set = DesktopPackages::new().for_backend(de, backend);
 
-    // Check if any of the core packages are installed
-    de_packages
+    // Any core group or package counts; a partial install still owns the DE.
+    set.groups
         .iter()
-        .any(|pkg| package_manager::is_installed(driver, pkg))
+        .any(|group| package_manager::is_group_installed(backend, group))
+        || set
+            .packages
+            .iter()
+            .any(|pkg| driver.is_package_installed(pkg))
+}
+
+/// Display managers the desktop phase knows how to enable and configure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayManager {
+    Gdm,
+    Sddm,
+    LightDm,
+}
+
+impl DisplayManager {
+    /// The display manager that pairs with `de`, or `None` for headless.
+    pub fn for_environment(de: DesktopEnvironment) -> Option<Self> {
+        match de {
+            DesktopEnvironment::Gnome => Some(DisplayManager::Gdm),
+            DesktopEnvironment::Kde
+            | DesktopEnvironment::Lxqt
+            | DesktopEnvironment::Cosmic
+            | DesktopEnvironment::Hyprland => Some(DisplayManager::Sddm),
+            DesktopEnvironment::Xfce
+            | DesktopEnvironment::Mate
+            | DesktopEnvironment::Cinnamon
+            | DesktopEnvironment::Budgie
+            | DesktopEnvironment::Enlightenment
+            | DesktopEnvironment::Lxde => Some(DisplayManager::LightDm),
+            DesktopEnvironment::None => None,
+        }
+    }
+
+    /// Native packages for `backend`, greeter included where the display
+    /// manager does not pull one in.
+    pub fn packages(&self, backend: PkgBackend) -> &'static [&'static str] {
+        match (self, backend) {
+            (DisplayManager::Gdm, PkgBackend::Apt) => &["gdm3"],
+            (DisplayManager::Gdm, _) => &["gdm"],
+            (DisplayManager::Sddm, _) => &["sddm"],
+            (DisplayManager::LightDm, PkgBackend::Pacman) => &["lightdm", "lightdm-gtk-greeter"],
+            (DisplayManager::LightDm, _) => &["lightdm"],
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            DisplayManager::Gdm => "gdm.service",
+            DisplayManager::Sddm => "sddm.service",
+            DisplayManager::LightDm => "lightdm.service",
+        }
+    }
+}
+
+/// Session file name (without `.desktop`) that starts `de` under `protocol`.
+/// `Auto` leaves the display manager's own default alone.
+pub fn session_name(de: DesktopEnvironment, protocol: DisplayProtocol) -> Option<&'static str> {
+    let wayland = match protocol {
+        DisplayProtocol::Auto => return None,
+        DisplayProtocol::Wayland => true,
+        DisplayProtocol::X11 => false,
+    };
+    match de {
+        DesktopEnvironment::Gnome => Some(if wayland { "gnome" } else { "gnome-xorg" }),
+        DesktopEnvironment::Kde => Some(if wayland { "plasma" } else { "plasmax11" }),
+        DesktopEnvironment::Xfce => Some("xfce"),
+        DesktopEnvironment::Lxqt => Some("lxqt"),
+        DesktopEnvironment::Mate => Some("mate"),
+        DesktopEnvironment::Cinnamon => Some("cinnamon"),
+        DesktopEnvironment::Budgie => Some("budgie-desktop"),
+        DesktopEnvironment::Enlightenment => Some("enlightenment"),
+        DesktopEnvironment::Lxde => Some("LXDE"),
+        DesktopEnvironment::Cosmic => Some("cosmic"),
+        DesktopEnvironment::Hyprland => Some("hyprland"),
+        DesktopEnvironment::None => None,
+    }
+}
+
+/// Set `key=value` inside `[section]` of an INI-style file, replacing an
+/// existing (or commented-out) assignment and appending the section if it
+/// is missing.
+pub fn set_ini_key(contents: &str, section: &str, key: &str, value: &str) -> String {
+    let header = format!("[{section}]");
+    let assignment = format!("{key}={value}");
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+
+    let Some(start) = lines.iter().position(|line| line.trim() == header) else {
+        if !lines.is_empty() && !lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push(header);
+        lines.push(assignment);
+        return lines.join("\n") + "\n";
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |offset| start + 1 + offset);
+
+    let existing = (start + 1..end).find(|&i| {
+        let line = lines[i].trim_start().trim_start_matches('#').trim_start();
+        line.split_once('=')
+            .is_some_and(|(name, _)| name.trim() == key)
+    });
+    match existing {
+        Some(i) => lines[i] = assignment,
+        None => {
+            // Keep the assignment ahead of any blank lines closing the section.
+            let mut at = end;
+            while at > start + 1 && lines[at - 1].trim().is_empty() {
+                at -= 1;
+            }
+            lines.insert(at, assignment);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Install the desktop environment chosen in the TUI, enable its display
+/// manager and boot into the graphical target.
+pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
+    let de = match ctx.options.desktop_environment {
+        Some(de) if de != DesktopEnvironment::None => de,
+        _ => {
+            tracing::info!("No desktop environment selected; skipping");
+            return Ok(PhaseResult::Success);
+        }
+    };
+    let protocol = ctx.options.display_protocol;
+    if let Err(err) = DesktopSelection::new(de, protocol).validate() {
+        bail!("{}", err.description);
+    }
+    if let Some(warning) = de.pi_warning(ctx.platform.is_pi()) {
+        ctx.record_warning(warning);
+    }
+
+    let driver = ctx.platform.driver;
+    let backend = ctx.platform.pkg_backend;
+    let dm =
+        DisplayManager::for_environment(de).context("headless selection has no display manager")?;
+    let mut set = DesktopPackages::new().for_backend(de, backend);
+    if set.is_empty() {
+        return Ok(PhaseResult::RecoverableFailure(format!(
+            "No package set known for {} on {}",
+            de.display_name(),
+            driver.name()
+        )));
+    }
+    set.packages.extend_from_slice(dm.packages(backend));
+
+    if ctx.options.dry_run {
+        let mut detail = format!("Packages: {}", set.packages.join(", "));
+        if !set.groups.is_empty() {
+            detail.push_str(&format!("; groups: {}", set.groups.join(", ")));
+        }
+        ctx.record_dry_run(
+            "desktop",
+            format!("Would install {}", de.display_name()),
+            Some(detail),
+        );
+    }
+    let missing_groups: Vec<String> = set
+        .groups
+        .iter()
+        .filter(|group| !package_manager::is_group_installed(backend, group))
+        .map(|group| group.to_string())
+        .collect();
+    let missing: Vec<String> = set
+        .packages
+        .iter()
+        .filter(|pkg| !driver.is_package_installed(pkg))
+        .map(|pkg| pkg.to_string())
+        .collect();
+    package_manager::ensure_groups(driver, &set.groups, ctx.options.dry_run)?;
+    package_manager::ensure_native_packages(driver, &set.packages, ctx.options.dry_run)?;
+    // Only `mash-setup undo` replays these; a later failure in the run
+    // keeps the desktop installed.
+    if !ctx.options.dry_run {
+        if !missing_groups.is_empty() {
+            ctx.register_rollback_action(
+                format!("remove {} groups", de.display_name()),
+                RollbackOp::RemoveGroups {
+                    backend,
+                    groups: missing_groups,
+                },
+            );
+        }
+        if !missing.is_empty() {
+            ctx.register_rollback_action(
+                format!("remove {} packages", de.display_name()),
+                RollbackOp::RemovePackages {
+                    backend,
+                    packages: missing,
+                },
+            );
+        }
+    }
+    ctx.record_action(format!("Installed {}", de.display_name()));
+
+    configure_session(ctx, de, protocol, dm)?;
+    enable_display_manager(ctx, dm)?;
+    Ok(PhaseResult::Success)
+}
+
+fn enable_display_manager(ctx: &mut PhaseContext, dm: DisplayManager) -> Result<()> {
+    let unit = dm.unit();
+    if ctx.options.dry_run {
+        ctx.record_dry_run(
+            "desktop",
+            "Would enable display manager and graphical target",
+            Some(format!("Service: {unit}")),
+        );
+        dry_run::plan(PlannedChange::Service {
+            unit: unit.to_string(),
+        });
+        return Ok(());
+    }
+    if !systemd::is_available() {
+        ctx.record_warning(format!("systemd not detected; enable {unit} manually"));
+        return Ok(());
+    }
+
+    let was_enabled = cmd::Command::new("systemctl")
+        .args(["is-enabled", "--quiet", unit])
+        .execute()
+        .is_ok();
+    // `--force` replaces the display-manager.service alias left by any
+    // previously enabled display manager.
+    match cmd::Command::new("sudo")
+        .args(["systemctl", "enable", "--force", unit])
+        .execute()
+    {
+        Ok(_) if !was_enabled => ctx.register_rollback_action(
+            format!("disable {unit}"),
+            RollbackOp::DisableUnit {
+                unit: unit.to_string(),
+            },
+        ),
+        Ok(_) => {}
+        Err(err) => {
+            ctx.record_warning(format!("Failed to enable {unit} ({err})"));
+            return Ok(());
+        }
+    }
+
+    let previous = cmd::Command::new("systemctl")
+        .arg("get-default")
+        .execute()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .unwrap_or_default();
+    if previous != "graphical.target" {
+        cmd::Command::new("sudo")
+            .args(["systemctl", "set-default", "graphical.target"])
+            .execute()
+            .context("setting graphical.target as the default boot target")?;
+        if !previous.is_empty() {
+            ctx.register_rollback_action(
+                format!("restore default target {previous}"),
+                RollbackOp::SetDefaultTarget { target: previous },
+            );
+        }
+    }
+    ctx.record_configured(format!("Enabled {unit}"));
+    Ok(())
+}
+
+/// The INI key a display manager reads the default session from.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SessionSetting {
+    pub path: PathBuf,
+    pub section: &'static str,
+    pub key: &'static str,
+    pub value: String,
+    /// Account that owns the file, when the display manager rewrites it.
+    pub owner: Option<&'static str>,
+}
+
+/// Where `dm` keeps its default session and what to write there so `de`
+/// starts under `protocol`. `None` when `protocol` is `Auto`.
+pub fn session_setting(
+    de: DesktopEnvironment,
+    protocol: DisplayProtocol,
+    dm: DisplayManager,
+    backend: PkgBackend,
+) -> Option<SessionSetting> {
+    let session = session_name(de, protocol)?;
+    let wayland = protocol == DisplayProtocol::Wayland;
+    Some(match dm {
+        DisplayManager::Gdm => {
+            let dir = match backend {
+                PkgBackend::Apt => "/etc/gdm3",
+                _ => "/etc/gdm",
+            };
+            SessionSetting {
+                path: PathBuf::from(dir).join("custom.conf"),
+                section: "daemon",
+                key: "WaylandEnable",
+                value: wayland.to_string(),
+                owner: None,
+            }
+        }
+        // SDDM preselects the session it last started, recorded in its
+        // state file by full path.
+        DisplayManager::Sddm => {
+            let dir = if wayland {
+                "/usr/share/wayland-sessions"
+            } else {
+                "/usr/share/xsessions"
+            };
+            SessionSetting {
+                path: PathBuf::from("/var/lib/sddm/state.conf"),
+                section: "Last",
+                key: "Session",
+                value: format!("{dir}/{session}.desktop"),
+                owner: Some("sddm"),
+            }
+        }
+        DisplayManager::LightDm => SessionSetting {
+            path: PathBuf::from("/etc/lightdm/lightdm.conf.d/50-mash-session.conf"),
+            section: "Seat:*",
+            key: "user-session",
+            value: session.to_string(),
+            owner: None,
+        },
+    })
+}
+
+/// Point the display manager at the X11 or Wayland session.
+fn configure_session(
+    ctx: &mut PhaseContext,
+    de: DesktopEnvironment,
+    protocol: DisplayProtocol,
+    dm: DisplayManager,
+) -> Result<()> {
+    let Some(session) = session_name(de, protocol) else {
+        return Ok(());
+    };
+    let Some(SessionSetting {
+        path,
+        section,
+        key,
+        value,
+        owner,
+    }) = session_setting(de, protocol, dm, ctx.platform.pkg_backend)
+    else {
+        return Ok(());
+    };
+
+    if ctx.options.dry_run {
+        ctx.record_dry_run(
+            "desktop",
+            format!("Would set default session to {session}"),
+            Some(format!("{}: {key}={value}", path.display())),
+        );
+        let current = fs::read_to_string(&path).unwrap_or_default();
+        ctx.plan_file(&path, &set_ini_key(&current, section, key, &value));
+        return Ok(());
+    }
+
+    let original = fs::read_to_string(&path).ok();
+    let updated = set_ini_key(original.as_deref().unwrap_or(""), section, key, &value);
+    if original.as_deref() == Some(updated.as_str()) {
+        return Ok(());
+    }
+    if let Some(original) = &original {
+        let backup = path.with_extension("conf.mash-bak");
+        write_system_file(&backup, original, None)?;
+        ctx.register_rollback_action(
+            format!("restore {}", path.display()),
+            RollbackOp::RestoreFile {
+                path: path.clone(),
+                backup,
+            },
+        );
+    } else {
+        ctx.register_rollback_action(
+            format!("remove {}", path.display()),
+            RollbackOp::RemoveFile { path: path.clone() },
+        );
+    }
+    write_system_file(&path, &updated, owner)?;
+    ctx.record_configured(format!("Default session set to {session}"));
+    Ok(())
+}
+
+/// Write a system file by staging it in the temp dir and installing it with
+/// sudo, owned by root unless `owner` names another account.
+fn write_system_file(path: &Path, contents: &str, owner: Option<&str>) -> Result<()> {
+    let staged = tempfile::NamedTempFile::new().context("staging system file")?;
+    fs::write(staged.path(), contents)?;
+    let mut install = cmd::Command::new("sudo")
+        .arg("install")
+        .args(["-D", "-m", "0644"]);
+    if let Some(owner) = owner {
+        install = install.args(["-o", owner, "-g", owner]);
+    }
+    install
+        .arg(staged.path())
+        .arg(path)
+        .execute()
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(())
 }
 
 /// Get the current display protocol (if detectable)
@@ -290,7 +800,6 @@ pub fn get_pi_recommendations(is_pi: bool) -> Vec<DesktopEnvironment> {
 
 #[cfg(test)]
 mod tests {
-    #[allow(unused_imports)]
     use super::testing::MockDriver;
     use super::*;
 
@@ -338,9 +847,124 @@ fn test_selection_validation() {
     #[test]
     fn test_package_mappings() {
         let packages = DesktopPackages::new();
-        let gnome_packages = packages.get_packages(DesktopEnvironment::Gnome);
-        assert!(gnome_packages.contains(&"gnome"));
-        assert!(gnome_packages.contains(&"gdm3"));
+        let gnome = packages.for_backend(DesktopEnvironment::Gnome, PkgBackend::Apt);
+        assert!(gnome.packages.contains(&"gnome"));
+        assert!(gnome.groups.is_empty());
+
+        let plasma = packages.for_backend(DesktopEnvironment::Kde, PkgBackend::Pacman);
+        assert_eq!(plasma.groups, vec!["plasma"]);
+
+        let kde = packages.for_backend(DesktopEnvironment::Kde, PkgBackend::Dnf);
+        assert_eq!(kde.groups, vec!["@kde-desktop-environment"]);
+
+        assert!(packages
+            .for_backend(DesktopEnvironment::Cosmic, PkgBackend::Apt)
+            .is_empty());
+    }
+
+    #[test]
+    fn every_desktop_has_packages_on_arch_and_fedora() {
+        let packages = DesktopPackages::new();
+        for de in [
+            DesktopEnvironment::Gnome,
+            DesktopEnvironment::Kde,
+            DesktopEnvironment::Xfce,
+            DesktopEnvironment::Lxqt,
+            DesktopEnvironment::Mate,
+            DesktopEnvironment::Cinnamon,
+            DesktopEnvironment::Budgie,
+            DesktopEnvironment::Enlightenment,
+            DesktopEnvironment::Lxde,
+            DesktopEnvironment::Cosmic,
+            DesktopEnvironment::Hyprland,
+        ] {
+            for backend in [PkgBackend::Pacman, PkgBackend::Dnf] {
+                assert!(
+                    !packages.for_backend(de, backend).is_empty(),
+                    "{de:?} {backend:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn is_de_installed_checks_native_packages() {
+        assert!(is_de_installed(DesktopEnvironment::Gnome, &MockDriver));
+        assert!(!is_de_installed(DesktopEnvironment::Mate, &MockDriver));
+    }
+
+    #[test]
+    fn session_follows_protocol() {
+        assert_eq!(
+            session_name(DesktopEnvironment::Kde, DisplayProtocol::X11),
+            Some("plasmax11")
+        );
+        assert_eq!(
+            session_name(DesktopEnvironment::Gnome, DisplayProtocol::Wayland),
+            Some("gnome")
+        );
+        assert_eq!(
+            session_name(DesktopEnvironment::Gnome, DisplayProtocol::Auto),
+            None
+        );
+        assert_eq!(
+            DisplayManager::for_environment(DesktopEnvironment::Xfce),
+            Some(DisplayManager::LightDm)
+        );
+        assert_eq!(DisplayManager::Gdm.packages(PkgBackend::Pacman), &["gdm"]);
+    }
+
+    #[test]
+    fn sddm_session_is_preselected_through_its_state_file() {
+        let setting = session_setting(
+            DesktopEnvironment::Kde,
+            DisplayProtocol::X11,
+            DisplayManager::Sddm,
+            PkgBackend::Apt,
+        )
+        .expect("explicit protocol");
+        assert_eq!(setting.path, PathBuf::from("/var/lib/sddm/state.conf"));
+        assert_eq!((setting.section, setting.key), ("Last", "Session"));
+        assert_eq!(setting.value, "/usr/share/xsessions/plasmax11.desktop");
+        assert_eq!(setting.owner, Some("sddm"));
+
+        let wayland = session_setting(
+            DesktopEnvironment::Kde,
+            DisplayProtocol::Wayland,
+            DisplayManager::Sddm,
+            PkgBackend::Pacman,
+        )
+        .expect("explicit protocol");
+        assert_eq!(wayland.value, "/usr/share/wayland-sessions/plasma.desktop");
+        assert!(session_setting(
+            DesktopEnvironment::Kde,
+            DisplayProtocol::Auto,
+            DisplayManager::Sddm,
+            PkgBackend::Apt
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn set_ini_key_replaces_commented_value_in_section() {
+        let conf = "[daemon]\n#WaylandEnable=false\n\n[security]\n";
+        assert_eq!(
+            set_ini_key(conf, "daemon", "WaylandEnable", "false"),
+            "[daemon]\nWaylandEnable=false\n\n[security]\n"
+        );
+        assert_eq!(
+            set_ini_key(
+                "[daemon]\nAutomaticLogin=pi\n\n[x]\n",
+                "daemon",
+                "WaylandEnable",
+                "true"
+            ),
+            "[daemon]\nAutomaticLogin=pi\nWaylandEnable=true\n\n[x]\n"
+        );
+        assert_eq!(
+            set_ini_key("", "Seat:*", "user-session", "xfce"),
+            "[Seat:*]\nuser-session=xfce\n"
+        );
     }
 }
 
@@ -350,7 +974,6 @@ mod testing {
     use super::*;
     use crate::{PkgBackend, PlatformInfo};
 
-    #[allow(dead_code)]
     pub struct MockDriver;
 
     impl DistroDriver for MockDriver {
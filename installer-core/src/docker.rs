@@ -8,8 +8,9 @@
 use crate::{
     apt_repo, cmd,
     driver::{RepoKind, ServiceName},
-    package_manager, systemd, AuthType, AuthorizationService, PhaseContext, PhaseResult,
-    PkgBackend,
+    dry_run, package_manager,
+    rollback::RollbackOp,
+    systemd, AuthType, AuthorizationService, PhaseContext, PhaseResult, PkgBackend, PlannedChange,
 };
 
 pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
@@ -66,27 +67,61 @@ pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
     Ok(PhaseResult::Success)
 }
 
+const APT_PACKAGES: &[&str] = &[
+    "docker-ce",
+    "docker-ce-cli",
+    "containerd.io",
+    "docker-buildx-plugin",
+    "docker-compose-plugin",
+];
+
+// On Arch/Manjaro/Fedora, Docker is available in standard repos
+const GENERIC_PACKAGES: &[&str] = &["docker", "docker-buildx", "docker-compose"];
+
+/// Packages the phase installs on `backend`.
+pub(crate) fn packages(backend: PkgBackend) -> &'static [&'static str] {
+    match backend {
+        PkgBackend::Apt => APT_PACKAGES,
+        PkgBackend::Pacman | PkgBackend::Dnf => GENERIC_PACKAGES,
+    }
+}
+
 fn install_docker_apt(ctx: &mut PhaseContext) -> Result<()> {
-    let pkgs = [
-        "docker-ce",
-        "docker-ce-cli",
-        "containerd.io",
-        "docker-buildx-plugin",
-        "docker-compose-plugin",
-    ];
-    package_manager::ensure_packages(ctx.platform.driver, &pkgs, ctx.options.dry_run)
+    install_packages(ctx, APT_PACKAGES)
 }
 
 // ── Pacman path ─────────────────────────────────────────────────
 
 fn install_docker_generic(ctx: &mut PhaseContext) -> Result<()> {
-    // On Arch/Manjaro/Fedora, Docker is available in standard repos
-    let pkgs = ["docker", "docker-buildx", "docker-compose"];
-    package_manager::ensure_packages(ctx.platform.driver, &pkgs, ctx.options.dry_run)
+    install_packages(ctx, GENERIC_PACKAGES)
 }
 
 // ── Common ──────────────────────────────────────────────────────
 
+/// Install `pkgs`, registering the ones that were missing for removal on
+/// rollback.
+fn install_packages(ctx: &mut PhaseContext, pkgs: &[&str]) -> Result<()> {
+    let driver = ctx.platform.driver;
+    let missing: Vec<String> = pkgs
+        .iter()
+        .filter(|pkg| !package_manager::is_installed(driver, pkg))
+        .filter_map(|pkg| driver.translate_package(pkg))
+        .collect();
+
+    package_manager::ensure_packages(driver, pkgs, ctx.options.dry_run)?;
+
+    if !ctx.options.dry_run && !missing.is_empty() {
+        ctx.register_rollback_action(
+            "remove docker packages",
+            RollbackOp::RemovePackages {
+                backend: ctx.platform.pkg_backend,
+                packages: missing,
+            },
+        );
+    }
+    Ok(())
+}
+
 fn add_user_to_docker_group(ctx: &mut PhaseContext) -> Result<()> {
     let user = std::env::var("SUDO_USER")
         .or_else(|_| std::env::var("USER"))
@@ -117,8 +152,15 @@ fn add_user_to_docker_group(ctx: &mut PhaseContext) -> Result<()> {
 
     let mut usermod = Command::new("sudo");
     usermod.args(["usermod", "-aG", "docker", &user]);
-    if let Err(err) = cmd::run(&mut usermod).context("adding user to docker group") {
-        ctx.record_warning(format!("Failed to add user to docker group ({err})"));
+    match cmd::run(&mut usermod).context("adding user to docker group") {
+        Ok(_) => ctx.register_rollback_action(
+            "remove user from docker group",
+            RollbackOp::RemoveUserFromGroup {
+                user,
+                group: "docker".into(),
+            },
+        ),
+        Err(err) => ctx.record_warning(format!("Failed to add user to docker group ({err})")),
     }
     Ok(())
 }
@@ -131,6 +173,9 @@ fn enable_docker_service(ctx: &mut PhaseContext) -> Result<()> {
             "Would enable docker service",
             Some(format!("Service: {service}")),
         );
+        dry_run::plan(PlannedChange::Service {
+            unit: service.to_string(),
+        });
         tracing::info!("[dry-run] would enable {service}");
         return Ok(());
     }
@@ -139,10 +184,21 @@ fn enable_docker_service(ctx: &mut PhaseContext) -> Result<()> {
         return Ok(());
     }
     let service = ctx.platform.driver.service_unit(ServiceName::Docker);
+    let was_enabled = cmd::Command::new("systemctl")
+        .args(["is-enabled", "--quiet", service])
+        .execute()
+        .is_ok();
     let mut enable_cmd = Command::new("sudo");
     enable_cmd.args(["systemctl", "enable", "--now", service]);
-    if let Err(err) = cmd::run(&mut enable_cmd) {
-        ctx.record_warning(format!("Failed to enable docker service ({err})"));
+    match cmd::run(&mut enable_cmd) {
+        Ok(_) if !was_enabled => ctx.register_rollback_action(
+            "disable docker service",
+            RollbackOp::DisableUnit {
+                unit: service.to_string(),
+            },
+        ),
+        Ok(_) => {}
+        Err(err) => ctx.record_warning(format!("Failed to enable docker service ({err})")),
     }
     Ok(())
 }
@@ -176,6 +232,7 @@ fn configure_data_root(ctx: &mut PhaseContext, data_root: &std::path::Path) -> R
                 "Would configure Docker data-root",
                 Some(format!("Path: {}", data_root.display())),
             );
+            ctx.plan_file(&daemon_json_path, &serde_json::to_string_pretty(&config)?);
             return Ok(());
         }
 
@@ -186,20 +243,32 @@ fn configure_data_root(ctx: &mut PhaseContext, data_root: &std::path::Path) -> R
             data_root.display()
         ));
 
-        let rollback_contents = original_daemon.clone();
-        let rollback_daemon = daemon_json_path.clone();
-        let rollback_backup = backup_path.clone();
-        ctx.register_rollback_action("restore docker daemon config", move || {
-            if let Some(contents) = &rollback_contents {
-                fs::write(&rollback_daemon, contents)?;
-            } else if rollback_daemon.exists() {
-                fs::remove_file(&rollback_daemon)?;
-            }
-            if rollback_backup.exists() {
-                fs::remove_file(&rollback_backup)?;
-            }
-            Ok(())
-        });
+        if original_daemon.is_none() {
+            ctx.register_rollback_action(
+                "remove docker daemon config",
+                RollbackOp::RemoveFile {
+                    path: daemon_json_path.clone(),
+                },
+            );
+        } else if !backup_path.exists() {
+            ctx.register_rollback_action(
+                "remove docker daemon config backup",
+                RollbackOp::RemoveFile {
+                    path: backup_path.clone(),
+                },
+            );
+        }
+        ctx.register_rollback_action(
+            "restore docker data-root",
+            RollbackOp::RevertJsonKey {
+                path: daemon_json_path.clone(),
+                key: "data-root".into(),
+                previous: original_daemon
+                    .as_deref()
+                    .and_then(|text| serde_json::from_str::<Value>(text).ok())
+                    .and_then(|config| config.get("data-root").cloned()),
+            },
+        );
 
         fs::create_dir_all(data_root)?;
         let content = serde_json::to_string_pretty(&config)?;
@@ -252,6 +321,14 @@ fn load_daemon_config(path: &Path) -> Result<Option<Value>> {
     }
 }
 
+/// Whether daemon.json points `data-root` at `data_root`; `None` when there
+/// is no daemon.json.
+pub(crate) fn data_root_configured(data_root: &Path) -> Result<Option<bool>> {
+    let desired = data_root.display().to_string();
+    Ok(load_daemon_config(&daemon_config_path())?
+        .map(|config| is_data_root_configured(&config, &desired)))
+}
+
 fn is_data_root_configured(config: &Value, desired: &str) -> bool {
     match config.get("data-root") {
         Some(Value::String(existing)) => existing == desired,
@@ -343,6 +420,7 @@ fn new(dry_run: bool) -> Result<Self> {
                 driver: &TEST_DRIVER,
                 pkg_backend: PkgBackend::Apt,
                 system: &crate::sys_ops::REAL_SYSTEM,
+                commands: &crate::cmd::REAL_RUNNER,
             };
             let options = UserOptionsContext {
                 profile: ProfileLevel::Minimal,
@@ -358,6 +436,8 @@ fn new(dry_run: bool) -> Result<Self> {
                 chezmoi: Default::default(),
                 desktop_environment: None,
                 display_protocol: crate::desktop::DisplayProtocol::Auto,
+                verify: false,
+                allow_unverified_scripts: false,
             };
 
             let localization = Localization::load_default()?;
@@ -691,7 +691,7 @@ fn check_directory_writeable(path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn format_bytes(bytes: u64) -> String {
+pub(crate) fn format_bytes(bytes: u64) -> String {
     const KB: f64 = 1024.0;
     let amount = bytes as f64;
     if amount >= KB.powi(3) {
@@ -786,7 +786,7 @@ mod tests {
     use super::*;
     use crate::system::system_ops::{RealSystem, SystemOps};
     use std::net::{TcpListener, TcpStream};
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
     use std::process::Command;
     use std::process::Output;
     use std::thread;
@@ -868,6 +868,14 @@ fn rename(&self, _from: &Path, _to: &Path) -> Result<()> {
         fn create_dir_all(&self, _path: &Path) -> Result<()> {
             Err(anyhow!("not implemented"))
         }
+
+        fn read_dir(&self, _path: &Path) -> Result<Vec<PathBuf>> {
+            Err(anyhow!("not implemented"))
+        }
+
+        fn install_file(&self, _path: &Path, _content: &[u8], _mode: u32) -> Result<()> {
+            Err(anyhow!("not implemented"))
+        }
     }
 
     #[test]
@@ -0,0 +1,901 @@
+//! Post-install verification.
+//!
+//! Re-derives what a run with a given set of options should have left on the
+//! machine and reports every item that drifted: packages and catalog programs
+//! that are missing, services that are disabled or stopped, dotfiles that were
+//! changed, and toolchain components that were removed. Used by `mash-setup verify` and by
+//! the optional final `verify` phase.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::catalog::CatalogResolver;
+use crate::desktop::{self, DesktopEnvironment, DesktopPackages, DisplayManager};
+use crate::driver::{DistroDriver, ServiceName};
+use crate::model::options::{InstallOptions, ProfileLevel, UserOptionsContext};
+use crate::sources::{self, InstallSource};
+use crate::system::cmd;
+use crate::{
+    argon, docker, fonts, package_manager, pkg, rust, software_tiers, systemd, zsh, PhaseContext,
+    PhaseResult, PkgBackend,
+};
+
+#[derive(Clone, Copy, Debug, ValueEnum, Default)]
+#[value(rename_all = "lower")]
+pub enum VerifyOutput {
+    #[default]
+    Pretty,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Package,
+    Group,
+    Program,
+    RustComponent,
+    Service,
+    Dotfile,
+    File,
+    Setting,
+}
+
+impl ItemKind {
+    fn label(self) -> &'static str {
+        match self {
+            ItemKind::Package => "package",
+            ItemKind::Group => "package group",
+            ItemKind::Program => "program",
+            ItemKind::RustComponent => "rust component",
+            ItemKind::Service => "service",
+            ItemKind::Dotfile => "dotfile",
+            ItemKind::File => "file",
+            ItemKind::Setting => "setting",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftStatus {
+    Ok,
+    Missing,
+    Disabled,
+    Inactive,
+    Modified,
+    /// The host cannot answer (no systemd, for instance).
+    Skipped,
+}
+
+impl DriftStatus {
+    pub fn is_drift(self) -> bool {
+        !matches!(self, DriftStatus::Ok | DriftStatus::Skipped)
+    }
+}
+
+/// One thing a run should have produced, and the phase that produces it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expectation {
+    /// Registry key of the phase to re-run when the check fails.
+    pub phase: &'static str,
+    pub check: Check,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Check {
+    /// Installed, by canonical package name.
+    Package(String),
+    /// Installed, by the driver's native package name.
+    NativePackage(String),
+    /// A native package group.
+    Group(String),
+    /// A catalog program, present from any of its sources.
+    Program {
+        id: String,
+        sources: Vec<InstallSource>,
+    },
+    RustComponent(String),
+    /// Enabled and running.
+    Service(String),
+    /// Enabled to start at boot; for units that only run after a reboot.
+    Enabled(String),
+    /// A dotfile containing `marker`.
+    Contains {
+        path: PathBuf,
+        marker: String,
+    },
+    /// A config file deployed verbatim.
+    Matches {
+        path: PathBuf,
+        contents: &'static str,
+    },
+    /// Docker's `data-root` in daemon.json.
+    DockerDataRoot(PathBuf),
+    /// A file or directory a phase leaves behind.
+    Exists(PathBuf),
+    /// A command on `PATH`.
+    Command(String),
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DriftItem {
+    pub phase: &'static str,
+    pub kind: ItemKind,
+    pub item: String,
+    pub status: DriftStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VerifyReport {
+    pub driver: String,
+    pub profile: ProfileLevel,
+    pub items: Vec<DriftItem>,
+}
+
+impl VerifyReport {
+    pub fn drift(&self) -> impl Iterator<Item = &DriftItem> {
+        self.items.iter().filter(|item| item.status.is_drift())
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.drift().next().is_none()
+    }
+
+    /// Keys of the phases whose outputs drifted, in the order first seen.
+    pub fn drifted_phases(&self) -> Vec<&'static str> {
+        let mut phases = Vec::new();
+        for item in self.drift() {
+            if !phases.contains(&item.phase) {
+                phases.push(item.phase);
+            }
+        }
+        phases
+    }
+}
+
+/// Everything a run with `options` should have left behind on `driver`.
+///
+/// Packages the driver has no name for are left out, as the install phases
+/// skip them too. `is_pi` gates the Argon One checks, which the phase only
+/// applies on a Raspberry Pi.
+pub fn expected_state(
+    options: &UserOptionsContext,
+    driver: &dyn DistroDriver,
+    home: &Path,
+    is_pi: bool,
+) -> Vec<Expectation> {
+    let dev = options.profile >= ProfileLevel::Dev;
+    let mut packages: Vec<(&'static str, &'static str)> = pkg::required_packages(options.profile)
+        .into_iter()
+        .map(|name| ("system_packages", name))
+        .collect();
+    packages.extend(
+        fonts::BASE_FONT_PACKAGES
+            .iter()
+            .map(|name| ("fonts", *name)),
+    );
+    if dev {
+        packages.push(("shell_ux", "zsh"));
+        packages.extend(
+            docker::packages(driver.pkg_backend())
+                .iter()
+                .map(|name| ("docker_engine", *name)),
+        );
+    }
+    let mut seen = Vec::new();
+    let mut expected: Vec<Expectation> = packages
+        .into_iter()
+        .filter(|(_, name)| driver.translate_package(name).is_some())
+        .filter(|(_, name)| {
+            let first = !seen.contains(name);
+            seen.push(*name);
+            first
+        })
+        .map(|(phase, name)| Expectation {
+            phase,
+            check: Check::Package(name.to_string()),
+        })
+        .collect();
+
+    expected.extend(rust::COMPONENTS.iter().map(|name| Expectation {
+        phase: "rust_toolchain",
+        check: Check::RustComponent(name.to_string()),
+    }));
+    expected.push(Expectation {
+        phase: "fonts",
+        check: Check::Exists(fonts::nerd_font_path(home, fonts::DEFAULT_NERD_FONT)),
+    });
+
+    if dev {
+        expected.push(Expectation {
+            phase: "docker_engine",
+            check: Check::Service(driver.service_unit(ServiceName::Docker).to_string()),
+        });
+        if options.docker.enabled {
+            expected.push(Expectation {
+                phase: "docker_engine",
+                check: Check::DockerDataRoot(options.staging_dir.join("docker")),
+            });
+        }
+        expected.push(Expectation {
+            phase: "shell_ux",
+            check: Check::Contains {
+                path: home.join(".zshrc"),
+                marker: zsh::STARSHIP_MARKER.to_string(),
+            },
+        });
+        expected.push(Expectation {
+            phase: "shell_ux",
+            check: Check::Matches {
+                path: home.join(zsh::STARSHIP_CONFIG_PATH),
+                contents: zsh::STARSHIP_CONFIG,
+            },
+        });
+    }
+
+    expected.extend(software_expectations(options, driver));
+    if let Some(de) = options
+        .desktop_environment
+        .filter(|de| *de != DesktopEnvironment::None)
+    {
+        expected.extend(desktop_expectations(options, driver, de));
+    }
+    if options.argon.enabled && is_pi {
+        let check = match driver.pkg_backend() {
+            PkgBackend::Apt => Check::Command(argon::OEM_CONFIG_COMMAND.to_string()),
+            PkgBackend::Pacman | PkgBackend::Dnf => {
+                Check::Enabled(driver.service_unit(ServiceName::ArgonOne).to_string())
+            }
+        };
+        expected.push(Expectation {
+            phase: "argon_one",
+            check,
+        });
+    }
+    if options.chezmoi.enabled && options.chezmoi.repo_url.is_some() {
+        // `chezmoi init --purge-binary` removes the binary; the source
+        // directory it cloned is what stays.
+        expected.push(Expectation {
+            phase: "chezmoi",
+            check: Check::Exists(home.join(".local/share/chezmoi")),
+        });
+    }
+    expected
+}
+
+/// Catalog programs the software plan picks, resolved for `driver` the same
+/// way the `software_tiers` phase resolves them.
+fn software_expectations(
+    options: &UserOptionsContext,
+    driver: &dyn DistroDriver,
+) -> Vec<Expectation> {
+    if options.software_plan.is_empty() {
+        return Vec::new();
+    }
+    let ids = software_tiers::selected_programs(&options.software_plan);
+    let resolution = CatalogResolver::bundled().resolve(ids.iter().map(String::as_str), driver);
+    let programs = resolution
+        .programs
+        .into_iter()
+        .map(|program| Check::Program {
+            id: program.id,
+            sources: program.sources,
+        });
+    let passthrough = resolution.passthrough.into_iter().map(Check::NativePackage);
+    programs
+        .chain(passthrough)
+        .map(|check| Expectation {
+            phase: "software_tiers",
+            check,
+        })
+        .collect()
+}
+
+/// Packages, display manager and default session of the chosen desktop.
+fn desktop_expectations(
+    options: &UserOptionsContext,
+    driver: &dyn DistroDriver,
+    de: DesktopEnvironment,
+) -> Vec<Expectation> {
+    let backend = driver.pkg_backend();
+    let set = DesktopPackages::new().for_backend(de, backend);
+    let Some(dm) = DisplayManager::for_environment(de).filter(|_| !set.is_empty()) else {
+        return Vec::new();
+    };
+    let mut checks: Vec<Check> = set
+        .groups
+        .iter()
+        .map(|group| Check::Group(group.to_string()))
+        .chain(
+            set.packages
+                .iter()
+                .chain(dm.packages(backend))
+                .map(|pkg| Check::NativePackage(pkg.to_string())),
+        )
+        .collect();
+    checks.push(Check::Enabled(dm.unit().to_string()));
+    // SDDM records whichever session the user last picked, so its state
+    // file is expected to change after the first login.
+    if dm != DisplayManager::Sddm {
+        if let Some(setting) = desktop::session_setting(de, options.display_protocol, dm, backend) {
+            checks.push(Check::Contains {
+                path: setting.path,
+                marker: format!("{}={}", setting.key, setting.value),
+            });
+        }
+    }
+    checks
+        .into_iter()
+        .map(|check| Expectation {
+            phase: "desktop_environment",
+            check,
+        })
+        .collect()
+}
+
+/// Check each expectation against the machine.
+pub fn check(driver: &dyn DistroDriver, expected: &[Expectation]) -> Vec<DriftItem> {
+    let mut components: Option<Result<Vec<String>, String>> = None;
+    expected
+        .iter()
+        .map(|expectation| DriftItem {
+            phase: expectation.phase,
+            ..check_one(driver, &expectation.check, &mut components)
+        })
+        .collect()
+}
+
+fn check_one(
+    driver: &dyn DistroDriver,
+    check: &Check,
+    components: &mut Option<Result<Vec<String>, String>>,
+) -> DriftItem {
+    match check {
+        Check::Package(name) => {
+            let installed = package_manager::is_installed(driver, name);
+            item(
+                ItemKind::Package,
+                name,
+                ok_or(installed, DriftStatus::Missing),
+            )
+        }
+        Check::NativePackage(name) => item(
+            ItemKind::Package,
+            name,
+            ok_or(driver.is_package_installed(name), DriftStatus::Missing),
+        ),
+        Check::Group(name) => item(
+            ItemKind::Group,
+            name,
+            ok_or(
+                package_manager::is_group_installed(driver.pkg_backend(), name),
+                DriftStatus::Missing,
+            ),
+        ),
+        Check::Program { id, sources } => {
+            let present = sources
+                .iter()
+                .any(|source| sources::is_installed(driver, id, source));
+            item(ItemKind::Program, id, ok_or(present, DriftStatus::Missing))
+        }
+        Check::RustComponent(name) => match components.get_or_insert_with(installed_components) {
+            Ok(list) => {
+                let present = list
+                    .iter()
+                    .any(|line| line == name || line.starts_with(&format!("{name}-")));
+                item(
+                    ItemKind::RustComponent,
+                    name,
+                    ok_or(present, DriftStatus::Missing),
+                )
+            }
+            Err(reason) => DriftItem {
+                detail: Some(reason.clone()),
+                ..item(ItemKind::RustComponent, name, DriftStatus::Missing)
+            },
+        },
+        Check::Service(unit) => check_service(unit, true),
+        Check::Enabled(unit) => check_service(unit, false),
+        Check::Contains { path, marker } => {
+            let status = match fs::read_to_string(path) {
+                Ok(text) if text.contains(marker.as_str()) => DriftStatus::Ok,
+                Ok(_) => DriftStatus::Modified,
+                Err(_) => DriftStatus::Missing,
+            };
+            DriftItem {
+                detail: (status == DriftStatus::Modified).then(|| format!("no '{marker}' line")),
+                ..item(ItemKind::Dotfile, &path.display().to_string(), status)
+            }
+        }
+        Check::Matches { path, contents } => {
+            let status = match fs::read_to_string(path) {
+                Ok(text) if text == *contents => DriftStatus::Ok,
+                Ok(_) => DriftStatus::Modified,
+                Err(_) => DriftStatus::Missing,
+            };
+            DriftItem {
+                detail: (status == DriftStatus::Modified)
+                    .then(|| "differs from the deployed version".to_string()),
+                ..item(ItemKind::Dotfile, &path.display().to_string(), status)
+            }
+        }
+        Check::DockerDataRoot(data_root) => {
+            let (status, detail) = match docker::data_root_configured(data_root) {
+                Ok(Some(true)) => (DriftStatus::Ok, None),
+                Ok(Some(false)) => (
+                    DriftStatus::Modified,
+                    Some(format!("data-root is not {}", data_root.display())),
+                ),
+                Ok(None) => (DriftStatus::Missing, Some("no daemon.json".to_string())),
+                Err(err) => (DriftStatus::Modified, Some(format!("{err:#}"))),
+            };
+            DriftItem {
+                detail,
+                ..item(ItemKind::Setting, "docker data-root", status)
+            }
+        }
+        Check::Exists(path) => item(
+            ItemKind::File,
+            &path.display().to_string(),
+            ok_or(path.exists(), DriftStatus::Missing),
+        ),
+        Check::Command(name) => item(
+            ItemKind::Program,
+            name,
+            ok_or(which::which(name).is_ok(), DriftStatus::Missing),
+        ),
+    }
+}
+
+/// Derive and check the expected state for `options` on this machine.
+pub fn verify(options: &InstallOptions, driver: &dyn DistroDriver) -> VerifyReport {
+    let options = UserOptionsContext::from_options(options);
+    let home = dirs::home_dir().unwrap_or_default();
+    let is_pi = crate::platform::detect().is_ok_and(|platform| platform.pi_model.is_some());
+    let expected = expected_state(&options, driver, &home, is_pi);
+    VerifyReport {
+        driver: driver.name().to_string(),
+        profile: options.profile,
+        items: check(driver, &expected),
+    }
+}
+
+/// `mash-setup verify`: print the report and return it.
+pub fn run_verify(
+    options: &InstallOptions,
+    driver: &dyn DistroDriver,
+    format: VerifyOutput,
+    out: &mut dyn Write,
+) -> Result<VerifyReport> {
+    let report = verify(options, driver);
+    match format {
+        VerifyOutput::Json => writeln!(out, "{}", serde_json::to_string_pretty(&report)?)?,
+        VerifyOutput::Pretty => write_pretty(&report, out)?,
+    }
+    Ok(report)
+}
+
+/// Options for a run that re-applies only the phases whose outputs drifted;
+/// `None` when nothing drifted.
+pub fn reconcile_options(
+    options: &InstallOptions,
+    report: &VerifyReport,
+) -> Option<InstallOptions> {
+    let phases = report.drifted_phases();
+    if phases.is_empty() {
+        return None;
+    }
+    Some(InstallOptions {
+        only_phases: phases.iter().map(|phase| phase.to_string()).collect(),
+        skip_phases: Vec::new(),
+        interactive: false,
+        resume: false,
+        ..options.clone()
+    })
+}
+
+/// Final phase: report drift as warnings without failing the run.
+pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
+    if ctx.options.dry_run {
+        ctx.record_dry_run("verify", "Would verify the installed state", None);
+        return Ok(PhaseResult::Success);
+    }
+    let home = dirs::home_dir().unwrap_or_default();
+    let expected = expected_state(
+        ctx.options,
+        ctx.platform.driver,
+        &home,
+        ctx.platform.is_pi(),
+    );
+    let items = check(ctx.platform.driver, &expected);
+
+    let drifted: Vec<_> = items.iter().filter(|item| item.status.is_drift()).collect();
+    for item in &drifted {
+        ctx.record_warning(describe(item));
+    }
+    ctx.record_action(format!(
+        "Verified {} items; {} drifted",
+        items.len(),
+        drifted.len()
+    ));
+    Ok(PhaseResult::Success)
+}
+
+fn write_pretty(report: &VerifyReport, out: &mut dyn Write) -> Result<()> {
+    writeln!(out, "mash-setup verify")?;
+    writeln!(out, "=================")?;
+    writeln!(
+        out,
+        "Driver: {}  Profile: {:?}",
+        report.driver, report.profile
+    )?;
+    writeln!(out)?;
+    for item in &report.items {
+        let mark = match item.status {
+            DriftStatus::Ok => "ok  ",
+            DriftStatus::Skipped => "skip",
+            _ => "DRIFT",
+        };
+        writeln!(out, "[{mark}] {}", describe(item))?;
+    }
+    writeln!(out)?;
+    let drifted = report.drift().count();
+    if drifted == 0 {
+        writeln!(out, "No drift: the machine matches the plan.")?;
+    } else {
+        writeln!(out, "{drifted} of {} items drifted.", report.items.len())?;
+    }
+    Ok(())
+}
+
+fn describe(item: &DriftItem) -> String {
+    let status = match item.status {
+        DriftStatus::Ok => "ok",
+        DriftStatus::Missing => "missing",
+        DriftStatus::Disabled => "disabled",
+        DriftStatus::Inactive => "not running",
+        DriftStatus::Modified => "modified",
+        DriftStatus::Skipped => "not checked",
+    };
+    match &item.detail {
+        Some(detail) => format!("{} {}: {status} ({detail})", item.kind.label(), item.item),
+        None => format!("{} {}: {status}", item.kind.label(), item.item),
+    }
+}
+
+fn item(kind: ItemKind, name: &str, status: DriftStatus) -> DriftItem {
+    DriftItem {
+        phase: "",
+        kind,
+        item: name.to_string(),
+        status,
+        detail: None,
+    }
+}
+
+fn ok_or(ok: bool, drift: DriftStatus) -> DriftStatus {
+    if ok {
+        DriftStatus::Ok
+    } else {
+        drift
+    }
+}
+
+fn installed_components() -> Result<Vec<String>, String> {
+    let output = cmd::Command::new(rust::rustup_bin())
+        .args(["component", "list", "--installed"])
+        .execute()
+        .map_err(|_| "rustup not available".to_string())?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .collect())
+}
+
+/// Enabled, and running too when `running` is set.
+fn check_service(unit: &str, running: bool) -> DriftItem {
+    if !systemd::is_available() {
+        return DriftItem {
+            detail: Some("systemd not available".into()),
+            ..item(ItemKind::Service, unit, DriftStatus::Skipped)
+        };
+    }
+    let systemctl = |verb: &str| {
+        cmd::Command::new("systemctl")
+            .args([verb, "--quiet", unit])
+            .execute()
+            .is_ok()
+    };
+    let status = if !systemctl("is-enabled") {
+        DriftStatus::Disabled
+    } else if running && !systemctl("is-active") {
+        DriftStatus::Inactive
+    } else {
+        DriftStatus::Ok
+    };
+    item(ItemKind::Service, unit, status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::use_runner;
+    use crate::desktop::DisplayProtocol;
+    use crate::localization::Localization;
+    use crate::model::software::SoftwareCategory;
+    use crate::phase_registry::PhaseRegistry;
+    use crate::{RecordingRunner, ScriptedOutput};
+    use tempfile::tempdir;
+
+    struct AptDriver;
+
+    impl DistroDriver for AptDriver {
+        fn name(&self) -> &'static str {
+            "apt-test"
+        }
+
+        fn description(&self) -> &'static str {
+            "apt test driver"
+        }
+
+        fn matches(&self, _: &crate::PlatformInfo) -> bool {
+            true
+        }
+
+        fn pkg_backend(&self) -> PkgBackend {
+            PkgBackend::Apt
+        }
+    }
+
+    fn options(profile: ProfileLevel) -> UserOptionsContext {
+        UserOptionsContext::from_options(&InstallOptions {
+            profile,
+            ..InstallOptions::default()
+        })
+    }
+
+    fn checks<'a>(expected: &'a [Expectation], phase: &str) -> Vec<&'a Check> {
+        expected
+            .iter()
+            .filter(|e| e.phase == phase)
+            .map(|e| &e.check)
+            .collect()
+    }
+
+    fn owned(phase: &'static str, check: Check) -> Expectation {
+        Expectation { phase, check }
+    }
+
+    #[test]
+    fn dev_profile_expects_shell_docker_and_toolchain() {
+        let home = Path::new("/home/mash");
+        let minimal = expected_state(&options(ProfileLevel::Minimal), &AptDriver, home, false);
+        let dev = expected_state(&options(ProfileLevel::Dev), &AptDriver, home, false);
+
+        assert!(checks(&minimal, "system_packages").contains(&&Check::Package("git".into())));
+        assert!(
+            checks(&minimal, "rust_toolchain").contains(&&Check::RustComponent("clippy".into()))
+        );
+        assert!(checks(&minimal, "docker_engine").is_empty());
+
+        let docker = checks(&dev, "docker_engine");
+        assert!(docker.contains(&&Check::Package("docker-ce".into())));
+        assert!(docker.contains(&&Check::Service("docker.service".into())));
+        assert!(checks(&dev, "shell_ux").contains(&&Check::Contains {
+            path: home.join(".zshrc"),
+            marker: "starship init zsh".into(),
+        }));
+        let zsh = dev
+            .iter()
+            .filter(|e| e.check == Check::Package("zsh".into()))
+            .count();
+        assert_eq!(zsh, 1, "packages are listed once");
+    }
+
+    #[test]
+    fn plan_picks_desktop_and_modules_are_expected() {
+        let home = Path::new("/home/mash");
+        let mut options = options(ProfileLevel::Minimal);
+        options.software_plan.target_tier = None;
+        options.software_plan.selections =
+            [(SoftwareCategory::Internet, vec!["brave".to_string()])].into();
+        options.desktop_environment = Some(DesktopEnvironment::Gnome);
+        options.display_protocol = DisplayProtocol::X11;
+        options.argon.enabled = true;
+        options.chezmoi.enabled = true;
+        options.chezmoi.repo_url = Some("https://example.com/dotfiles.git".into());
+
+        let expected = expected_state(&options, &AptDriver, home, true);
+
+        let software = checks(&expected, "software_tiers");
+        assert!(matches!(
+            software.as_slice(),
+            [Check::Program { id, sources }]
+                if id == "brave"
+                    && sources.first() == Some(&InstallSource::Native(vec!["brave-browser".into()]))
+                    && sources.contains(&InstallSource::Flatpak("com.brave.Browser".into()))
+        ));
+
+        let desktop = checks(&expected, "desktop_environment");
+        assert!(desktop.contains(&&Check::NativePackage("gnome".into())));
+        assert!(desktop.contains(&&Check::NativePackage("gdm3".into())));
+        assert!(desktop.contains(&&Check::Enabled("gdm.service".into())));
+        assert!(desktop.contains(&&Check::Contains {
+            path: PathBuf::from("/etc/gdm3/custom.conf"),
+            marker: "WaylandEnable=false".into(),
+        }));
+
+        assert!(checks(&expected, "fonts").contains(&&Check::Exists(
+            home.join(".local/share/fonts/JetBrainsMonoNerdFont-Regular.ttf")
+        )));
+        assert_eq!(
+            checks(&expected, "argon_one"),
+            [&Check::Command("argonone-config".into())]
+        );
+        assert_eq!(
+            checks(&expected, "chezmoi"),
+            [&Check::Exists(home.join(".local/share/chezmoi"))]
+        );
+
+        // Argon One is only set up on a Pi; SDDM rewrites its own session.
+        options.desktop_environment = Some(DesktopEnvironment::Kde);
+        let expected = expected_state(&options, &AptDriver, home, false);
+        assert!(checks(&expected, "argon_one").is_empty());
+        let desktop = checks(&expected, "desktop_environment");
+        assert!(desktop.contains(&&Check::Enabled("sddm.service".into())));
+        assert!(!desktop
+            .iter()
+            .any(|check| matches!(check, Check::Contains { .. })));
+    }
+
+    #[test]
+    fn verify_depends_on_every_phase_it_checks() -> Result<()> {
+        let mut options = options(ProfileLevel::Full);
+        options.desktop_environment = Some(DesktopEnvironment::Gnome);
+        options.argon.enabled = true;
+        options.chezmoi.enabled = true;
+        let strings = Localization::load_default()?;
+        let phases = PhaseRegistry::default().phases(&strings);
+        let verify = phases
+            .iter()
+            .find(|phase| phase.id == "verify")
+            .expect("verify is registered");
+
+        let expected = expected_state(&options, &AptDriver, Path::new("/home/mash"), true);
+        for expectation in &expected {
+            assert!(
+                verify.deps.contains(&expectation.phase),
+                "verify does not wait for {}",
+                expectation.phase
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn programs_count_as_installed_from_any_source() {
+        let runner: &'static RecordingRunner = Box::leak(Box::new(
+            RecordingRunner::new()
+                .on("dpkg -s", ScriptedOutput::failure(1, "not installed"))
+                .on(
+                    "flatpak info com.brave.Browser",
+                    ScriptedOutput::success(""),
+                )
+                .on("flatpak info", ScriptedOutput::failure(1, "not installed")),
+        ));
+        let _guard = use_runner(runner);
+        let program = |id: &str, flatpak: &str| Check::Program {
+            id: id.into(),
+            sources: vec![
+                InstallSource::Native(vec![id.into()]),
+                InstallSource::Flatpak(flatpak.into()),
+            ],
+        };
+
+        let items = check(
+            &AptDriver,
+            &[
+                owned("software_tiers", program("brave", "com.brave.Browser")),
+                owned("software_tiers", program("gimp", "org.gimp.GIMP")),
+            ],
+        );
+        let statuses: Vec<_> = items.iter().map(|item| item.status).collect();
+        assert_eq!(statuses, [DriftStatus::Ok, DriftStatus::Missing]);
+        assert_eq!(items[1].kind, ItemKind::Program);
+    }
+
+    #[test]
+    fn drift_is_reported_per_item() {
+        let runner: &'static RecordingRunner = Box::leak(Box::new(
+            RecordingRunner::new()
+                .on("dpkg -s jq", ScriptedOutput::failure(1, "not installed"))
+                .on(
+                    "",
+                    ScriptedOutput::success("rustfmt-x86_64-unknown-linux-gnu\nrust-src\n"),
+                ),
+        ));
+        let _guard = use_runner(runner);
+        let home = tempdir().unwrap();
+        fs::write(home.path().join(".zshrc"), "export EDITOR=nvim\n").unwrap();
+
+        let expected = vec![
+            owned("system_packages", Check::Package("git".into())),
+            owned("system_packages", Check::Package("jq".into())),
+            owned("rust_toolchain", Check::RustComponent("rustfmt".into())),
+            owned("rust_toolchain", Check::RustComponent("clippy".into())),
+            owned(
+                "shell_ux",
+                Check::Contains {
+                    path: home.path().join(".zshrc"),
+                    marker: "starship init zsh".into(),
+                },
+            ),
+            owned(
+                "shell_ux",
+                Check::Matches {
+                    path: home.path().join("starship.toml"),
+                    contents: "format = \"$all\"\n",
+                },
+            ),
+        ];
+        let items = check(&AptDriver, &expected);
+        let statuses: Vec<_> = items.iter().map(|item| item.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                DriftStatus::Ok,
+                DriftStatus::Missing,
+                DriftStatus::Ok,
+                DriftStatus::Missing,
+                DriftStatus::Modified,
+                DriftStatus::Missing,
+            ]
+        );
+        // The component list is read once for all components.
+        let listings = runner
+            .calls()
+            .iter()
+            .filter(|line| line.contains("component list"))
+            .count();
+        assert_eq!(listings, 1);
+
+        let report = VerifyReport {
+            driver: "apt-test".into(),
+            profile: ProfileLevel::Minimal,
+            items,
+        };
+        assert_eq!(report.drift().count(), 4);
+        assert_eq!(
+            report.drifted_phases(),
+            ["system_packages", "rust_toolchain", "shell_ux"]
+        );
+        let rerun = reconcile_options(&InstallOptions::default(), &report).expect("drift");
+        assert_eq!(
+            rerun.only_phases,
+            ["system_packages", "rust_toolchain", "shell_ux"]
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["items"][1]["kind"], "package");
+        assert_eq!(json["items"][1]["status"], "missing");
+        assert_eq!(json["items"][1]["phase"], "system_packages");
+        assert_eq!(json["items"][4]["kind"], "dotfile");
+
+        let clean = VerifyReport {
+            items: report
+                .items
+                .into_iter()
+                .filter(|i| !i.status.is_drift())
+                .collect(),
+            ..report
+        };
+        assert!(reconcile_options(&InstallOptions::default(), &clean).is_none());
+    }
+}
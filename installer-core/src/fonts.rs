@@ -3,11 +3,12 @@
 //! This module provides comprehensive Nerd Fonts integration, allowing users to
 //! select and install Nerd Fonts from the official GitHub repository.
 
-use crate::system::cmd;
+use crate::system::{cmd, dry_run};
 use crate::{package_manager, PhaseContext, PhaseResult};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Available Nerd Fonts from the official repository
@@ -86,32 +87,52 @@ pub fn find_font_by_name(name: &str) -> Option<NerdFont> {
     available_fonts().into_iter().find(|f| f.name == name)
 }
 
+/// Canonical names of the Terminus and emoji font packages.
+pub const BASE_FONT_PACKAGES: &[&str] = &[
+    "fonts-terminus",
+    "fonts-noto-color-emoji",
+    "xfonts-terminus",
+];
+
+/// Nerd Font every run installs.
+pub const DEFAULT_NERD_FONT: &str = "JetBrainsMono";
+
+/// Per-user font directory, relative to the home directory.
+const FONT_DIR: &str = ".local/share/fonts";
+
+/// Regular face of the Nerd Font `name` once installed under `home`.
+pub fn nerd_font_path(home: &Path, name: &str) -> PathBuf {
+    home.join(FONT_DIR)
+        .join(format!("{name}NerdFont-Regular.ttf"))
+}
+
 /// Install base terminus fonts via package manager
 fn install_base_fonts(ctx: &mut PhaseContext) -> Result<()> {
-    let pkgs = [
-        "fonts-terminus",
-        "fonts-noto-color-emoji",
-        "xfonts-terminus",
-    ];
-
     ctx.record_action("Installing base Terminus and Emoji fonts");
-    package_manager::ensure_packages(ctx.platform.driver, &pkgs, ctx.options.dry_run)?;
+    package_manager::ensure_packages(ctx.platform.driver, BASE_FONT_PACKAGES, ctx.options.dry_run)?;
 
     Ok(())
 }
 
 /// Install a specific Nerd Font
 pub fn install_nerd_font(ctx: &mut PhaseContext, font: &NerdFont) -> Result<()> {
-    let font_dir = dirs::home_dir()
-        .unwrap_or_default()
-        .join(".local/share/fonts");
-    let target_font = font_dir.join(format!("{}-NerdFont-Regular.ttf", font.name));
+    let home = dirs::home_dir().unwrap_or_default();
+    let font_dir = home.join(FONT_DIR);
+    let target_font = nerd_font_path(&home, &font.name);
 
     if target_font.exists() {
         tracing::info!("{} Nerd Font already installed.", font.display_name);
         return Ok(());
     }
 
+    let url = format!(
+        "https://github.com/ryanoasis/nerd-fonts/releases/download/{}/{}",
+        NERD_FONT_VERSION, font.filename
+    );
+    if ctx.options.dry_run {
+        dry_run::plan_download(&url);
+    }
+
     ctx.run_or_record(
         "fonts",
         format!("Install {} Nerd Font", font.display_name),
@@ -126,11 +147,6 @@ pub fn install_nerd_font(ctx: &mut PhaseContext, font: &NerdFont) -> Result<()>
 
             fs::create_dir_all(&font_dir).context("Failed to create font directory")?;
 
-            let url = format!(
-                "https://github.com/ryanoasis/nerd-fonts/releases/download/{}/{}",
-                NERD_FONT_VERSION, font.filename
-            );
-
             let tmp_dir = tempfile::tempdir()?;
             let zip_path = tmp_dir.path().join(&font.filename);
 
@@ -178,7 +194,7 @@ pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
 
     // 2. Install default JetBrainsMono Nerd Font (maintains backward compatibility)
     let default_font = NerdFont::new(
-        "JetBrainsMono",
+        DEFAULT_NERD_FONT,
         "JetBrains Mono",
         "JetBrainsMono.zip",
         "Mono",
@@ -0,0 +1,615 @@
+//! Local audit trail of installer runs.
+//!
+//! Every `InstallationReport` is written to a SQLite database next to the
+//! config file. Runs, phases and errors get their own tables so they can be
+//! queried directly; the full report is kept as JSON alongside them.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::config_path;
+use crate::model::options::InstallOptions;
+use crate::model::phase::PhaseStatus;
+use crate::scrubber;
+use crate::system::error::InstallationReport;
+
+pub const HISTORY_DB: &str = "history.db";
+
+pub fn history_path() -> PathBuf {
+    config_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(HISTORY_DB)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Completed,
+    CompletedWithErrors,
+    Failed,
+}
+
+impl RunOutcome {
+    pub fn label(self) -> &'static str {
+        match self {
+            RunOutcome::Completed => "completed",
+            RunOutcome::CompletedWithErrors => "completed_with_errors",
+            RunOutcome::Failed => "failed",
+        }
+    }
+
+    fn parse(label: &str) -> Self {
+        match label {
+            "completed" => RunOutcome::Completed,
+            "completed_with_errors" => RunOutcome::CompletedWithErrors,
+            _ => RunOutcome::Failed,
+        }
+    }
+
+    /// Outcome of a report returned by `run_with_driver`; `aborted` is set
+    /// when it came back as an error.
+    pub fn of(report: &InstallationReport, aborted: bool) -> Self {
+        if aborted {
+            RunOutcome::Failed
+        } else if report.has_errors() {
+            RunOutcome::CompletedWithErrors
+        } else {
+            RunOutcome::Completed
+        }
+    }
+}
+
+/// One row of `history list`.
+#[derive(Clone, Debug, Serialize)]
+pub struct RunSummary {
+    pub id: i64,
+    pub run_id: Option<String>,
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub driver: String,
+    pub profile: String,
+    pub dry_run: bool,
+    pub outcome: RunOutcome,
+    pub phases_completed: usize,
+    pub error_count: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RunRecord {
+    #[serde(flatten)]
+    pub summary: RunSummary,
+    pub report: InstallationReport,
+}
+
+pub struct HistoryDb {
+    conn: Connection,
+}
+
+impl HistoryDb {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("opening history database {}", path.display()))?;
+        // SQLite leaves foreign keys off per connection; `ON DELETE CASCADE`
+        // only fires when they are on.
+        conn.execute_batch(
+            "PRAGMA foreign_keys = ON;
+            CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                run_id TEXT,
+                started_at INTEGER NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                driver TEXT NOT NULL,
+                profile TEXT NOT NULL,
+                dry_run INTEGER NOT NULL,
+                outcome TEXT NOT NULL,
+                phases_completed INTEGER NOT NULL,
+                error_count INTEGER NOT NULL,
+                options TEXT NOT NULL,
+                report TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS phases (
+                run INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+                seq INTEGER NOT NULL,
+                phase TEXT NOT NULL,
+                label TEXT NOT NULL,
+                status TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                output TEXT NOT NULL,
+                PRIMARY KEY (run, seq)
+            );
+            CREATE TABLE IF NOT EXISTS errors (
+                run INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+                seq INTEGER NOT NULL,
+                phase TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                message TEXT NOT NULL,
+                command TEXT,
+                exit_status INTEGER,
+                stdout TEXT,
+                stderr TEXT,
+                PRIMARY KEY (run, seq)
+            );",
+        )
+        .context("initializing history schema")?;
+        Ok(Self { conn })
+    }
+
+    /// Store `report` and return its history id.
+    pub fn record(
+        &mut self,
+        report: &InstallationReport,
+        outcome: RunOutcome,
+        started_at: u64,
+        duration_ms: u64,
+    ) -> Result<i64> {
+        // Everything below lands on disk; store the scrubbed copy only.
+        let report = &scrubber::scrubbed(report)?;
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (run_id, started_at, duration_ms, driver, profile, dry_run,
+                outcome, phases_completed, error_count, options, report)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                report.run_id,
+                started_at as i64,
+                duration_ms as i64,
+                report.driver.name,
+                format!("{:?}", report.options.profile),
+                report.options.dry_run,
+                outcome.label(),
+                report.completed_phases.len() as i64,
+                report.errors.len() as i64,
+                serde_json::to_string(&report.options)?,
+                serde_json::to_string(report)?,
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+
+        // Key phases by id; the label is localized and only for display.
+        for (seq, output) in report.outputs.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO phases (run, seq, phase, label, status, duration_ms, output)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    id,
+                    seq as i64,
+                    output.id,
+                    output.name,
+                    status_label(&output.status),
+                    output.duration_ms as i64,
+                    serde_json::to_string(output)?,
+                ],
+            )?;
+        }
+
+        for (seq, err) in report.errors.iter().enumerate() {
+            let command = err.command_output();
+            tx.execute(
+                "INSERT INTO errors (run, seq, phase, severity, message, command,
+                    exit_status, stdout, stderr)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    id,
+                    seq as i64,
+                    err.phase,
+                    format!("{:?}", err.severity),
+                    err.developer_message(),
+                    command.map(|c| c.command.clone()),
+                    command.and_then(|c| c.status),
+                    command.map(|c| c.stdout.clone()),
+                    command.map(|c| c.stderr.clone()),
+                ],
+            )?;
+        }
+
+        tx.commit().context("committing run history")?;
+        Ok(id)
+    }
+
+    /// Most recent runs first.
+    pub fn list(&self, limit: usize) -> Result<Vec<RunSummary>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {SUMMARY_COLUMNS} FROM runs ORDER BY id DESC LIMIT ?1"
+        ))?;
+        let rows = stmt.query_map(params![limit as i64], summary_from_row)?;
+        let mut runs = Vec::new();
+        for run in rows {
+            runs.push(run?);
+        }
+        Ok(runs)
+    }
+
+    /// Options of the newest run that completed without errors and was not
+    /// a dry run, with any phase restriction dropped.
+    pub fn last_successful_options(&self) -> Result<Option<InstallOptions>> {
+        let options = self
+            .conn
+            .query_row(
+                "SELECT options FROM runs WHERE outcome = ?1 AND dry_run = 0
+                 ORDER BY id DESC LIMIT 1",
+                params![RunOutcome::Completed.label()],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+        options
+            .map(|text| {
+                let mut options: InstallOptions =
+                    serde_json::from_str(&text).context("parsing stored run options")?;
+                options.only_phases.clear();
+                options.skip_phases.clear();
+                Ok(options)
+            })
+            .transpose()
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<RunRecord>> {
+        let row = self
+            .conn
+            .query_row(
+                &format!("SELECT {SUMMARY_COLUMNS}, report FROM runs WHERE id = ?1"),
+                params![id],
+                |row| Ok((summary_from_row(row)?, row.get::<_, String>(10)?)),
+            )
+            .optional()?;
+        match row {
+            Some((summary, report)) => {
+                let report = serde_json::from_str(&report)
+                    .with_context(|| format!("parsing stored report for run {id}"))?;
+                Ok(Some(RunRecord { summary, report }))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+const SUMMARY_COLUMNS: &str = "id, run_id, started_at, duration_ms, driver, profile, dry_run, \
+                               outcome, phases_completed, error_count";
+
+fn summary_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<RunSummary> {
+    Ok(RunSummary {
+        id: row.get(0)?,
+        run_id: row.get(1)?,
+        started_at: row.get::<_, i64>(2)? as u64,
+        duration_ms: row.get::<_, i64>(3)? as u64,
+        driver: row.get(4)?,
+        profile: row.get(5)?,
+        dry_run: row.get(6)?,
+        outcome: RunOutcome::parse(&row.get::<_, String>(7)?),
+        phases_completed: row.get::<_, i64>(8)? as usize,
+        error_count: row.get::<_, i64>(9)? as usize,
+    })
+}
+
+pub fn status_label(status: &PhaseStatus) -> &'static str {
+    match status {
+        PhaseStatus::Completed => "completed",
+        PhaseStatus::PartialSuccess(_) => "partial_success",
+        PhaseStatus::RecoverableFailure(_) => "recoverable_failure",
+        PhaseStatus::Failed(_) => "failed",
+        PhaseStatus::Skipped => "skipped",
+    }
+}
+
+/// An option whose value differs between two runs, by dotted path.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OptionChange {
+    pub option: String,
+    pub a: Option<Value>,
+    pub b: Option<Value>,
+}
+
+/// A phase whose status differs between two runs; `None` means the phase
+/// did not appear in that run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PhaseChange {
+    pub phase: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RunDiff {
+    pub a: i64,
+    pub b: i64,
+    pub driver: Option<(String, String)>,
+    pub options: Vec<OptionChange>,
+    pub phases: Vec<PhaseChange>,
+    /// Phases that failed only in run `a` / only in run `b`.
+    pub errors_only_in_a: Vec<String>,
+    pub errors_only_in_b: Vec<String>,
+}
+
+impl RunDiff {
+    pub fn is_empty(&self) -> bool {
+        self.driver.is_none()
+            && self.options.is_empty()
+            && self.phases.is_empty()
+            && self.errors_only_in_a.is_empty()
+            && self.errors_only_in_b.is_empty()
+    }
+}
+
+pub fn diff(a: &RunRecord, b: &RunRecord) -> Result<RunDiff> {
+    let mut options = BTreeMap::new();
+    flatten(
+        "",
+        &serde_json::to_value(&a.report.options)?,
+        &mut options,
+        true,
+    );
+    flatten(
+        "",
+        &serde_json::to_value(&b.report.options)?,
+        &mut options,
+        false,
+    );
+    let options = options
+        .into_iter()
+        .filter(|(_, (va, vb))| va != vb)
+        .map(|(option, (a, b))| OptionChange { option, a, b })
+        .collect();
+
+    let statuses = |record: &RunRecord| -> BTreeMap<String, String> {
+        record
+            .report
+            .outputs
+            .iter()
+            .map(|o| {
+                // Reports from before phase ids only carry the label
+                let key = if o.id.is_empty() { &o.name } else { &o.id };
+                (key.clone(), status_label(&o.status).to_string())
+            })
+            .collect()
+    };
+    let (pa, pb) = (statuses(a), statuses(b));
+    let mut names: Vec<&String> = pa.keys().chain(pb.keys()).collect();
+    names.sort();
+    names.dedup();
+    let phases = names
+        .into_iter()
+        .filter(|name| pa.get(*name) != pb.get(*name))
+        .map(|name| PhaseChange {
+            phase: name.clone(),
+            a: pa.get(name).cloned(),
+            b: pb.get(name).cloned(),
+        })
+        .collect();
+
+    let failed = |record: &RunRecord| -> Vec<String> {
+        record
+            .report
+            .errors
+            .iter()
+            .map(|e| e.phase.clone())
+            .collect()
+    };
+    let (fa, fb) = (failed(a), failed(b));
+
+    let driver = (a.summary.driver != b.summary.driver)
+        .then(|| (a.summary.driver.clone(), b.summary.driver.clone()));
+
+    Ok(RunDiff {
+        a: a.summary.id,
+        b: b.summary.id,
+        driver,
+        options,
+        phases,
+        errors_only_in_a: fa.iter().filter(|p| !fb.contains(p)).cloned().collect(),
+        errors_only_in_b: fb.iter().filter(|p| !fa.contains(p)).cloned().collect(),
+    })
+}
+
+type Pair = (Option<Value>, Option<Value>);
+
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, Pair>, left: bool) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&path, child, out, left);
+            }
+        }
+        leaf => {
+            let slot = out.entry(prefix.to_string()).or_default();
+            if left {
+                slot.0 = Some(leaf.clone());
+            } else {
+                slot.1 = Some(leaf.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::phase::PhaseOutput;
+    use crate::system::dry_run::PreflightAuditReport;
+    use crate::system::error::{DriverInfo, ErrorSeverity, InstallerError, InstallerStateSnapshot};
+    use crate::ProfileLevel;
+    use tempfile::tempdir;
+
+    fn report(profile: ProfileLevel, failing: Option<&str>) -> InstallationReport {
+        let mut docker = PhaseOutput::skipped("docker", "Docker", "Docker", false);
+        docker.status = PhaseStatus::Completed;
+        docker.duration_ms = 1200;
+        let errors = failing
+            .map(|phase| {
+                vec![InstallerError::new(
+                    phase,
+                    "phase failed",
+                    ErrorSeverity::Fatal,
+                    anyhow::anyhow!("boom"),
+                    InstallerStateSnapshot::default(),
+                    None,
+                )]
+            })
+            .unwrap_or_default();
+        InstallationReport {
+            completed_phases: vec!["docker".into()],
+            staging_dir: PathBuf::from("/tmp/staging"),
+            errors,
+            outputs: vec![docker],
+            events: Vec::new(),
+            options: InstallOptions {
+                profile,
+                ..InstallOptions::default()
+            },
+            driver: DriverInfo {
+                name: "debian".into(),
+                description: "Debian".into(),
+            },
+            dry_run_log: Vec::new(),
+            audit_report: PreflightAuditReport::default(),
+            run_id: Some("1700000000-1".into()),
+            snapshots: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn records_and_reads_back_runs() -> Result<()> {
+        let dir = tempdir()?;
+        let mut db = HistoryDb::open(&dir.path().join(HISTORY_DB))?;
+        let ok = report(ProfileLevel::Dev, None);
+        let bad = report(ProfileLevel::Full, Some("rust"));
+
+        let first = db.record(&ok, RunOutcome::of(&ok, false), 100, 5_000)?;
+        let second = db.record(&bad, RunOutcome::of(&bad, true), 200, 7_000)?;
+
+        let runs = db.list(10)?;
+        assert_eq!(
+            runs.iter().map(|r| r.id).collect::<Vec<_>>(),
+            [second, first]
+        );
+        assert_eq!(runs[0].outcome, RunOutcome::Failed);
+        assert_eq!(runs[0].error_count, 1);
+        assert_eq!(runs[1].profile, "Dev");
+
+        let shown = db.get(first)?.expect("run stored");
+        assert_eq!(shown.report.outputs[0].duration_ms, 1200);
+        assert!(db.get(999)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn phases_are_keyed_by_id_not_label() -> Result<()> {
+        let dir = tempdir()?;
+        let mut db = HistoryDb::open(&dir.path().join(HISTORY_DB))?;
+        let ok = report(ProfileLevel::Dev, None);
+        let id = db.record(&ok, RunOutcome::of(&ok, false), 100, 1)?;
+
+        let (phase, label): (String, String) = db.conn.query_row(
+            "SELECT phase, label FROM phases WHERE run = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        assert_eq!((phase.as_str(), label.as_str()), ("docker", "Docker"));
+        Ok(())
+    }
+
+    #[test]
+    fn deleting_a_run_removes_its_phases_and_errors() -> Result<()> {
+        let dir = tempdir()?;
+        let mut db = HistoryDb::open(&dir.path().join(HISTORY_DB))?;
+        let bad = report(ProfileLevel::Full, Some("rust"));
+        let id = db.record(&bad, RunOutcome::of(&bad, false), 100, 1)?;
+
+        db.conn
+            .execute("DELETE FROM runs WHERE id = ?1", params![id])?;
+        let left: i64 = db.conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM phases) + (SELECT COUNT(*) FROM errors)",
+            [],
+            |row| row.get(0),
+        )?;
+        assert_eq!(left, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn last_successful_options_skips_failed_and_dry_runs() -> Result<()> {
+        let dir = tempdir()?;
+        let mut db = HistoryDb::open(&dir.path().join(HISTORY_DB))?;
+        assert!(db.last_successful_options()?.is_none());
+
+        let mut ok = report(ProfileLevel::Dev, None);
+        ok.options.only_phases = vec!["shell_ux".into()];
+        ok.options.skip_phases = vec!["fonts".into()];
+        let bad = report(ProfileLevel::Full, Some("rust"));
+        let mut dry = report(ProfileLevel::Minimal, None);
+        dry.options.dry_run = true;
+        db.record(&ok, RunOutcome::of(&ok, false), 100, 1)?;
+        db.record(&bad, RunOutcome::of(&bad, false), 200, 1)?;
+        db.record(&dry, RunOutcome::of(&dry, false), 300, 1)?;
+
+        let options = db.last_successful_options()?.expect("completed run");
+        assert_eq!(options.profile, ProfileLevel::Dev);
+        assert!(options.only_phases.is_empty() && options.skip_phases.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn stored_reports_are_scrubbed() -> Result<()> {
+        let dir = tempdir()?;
+        let mut db = HistoryDb::open(&dir.path().join(HISTORY_DB))?;
+        let mut bad = report(ProfileLevel::Dev, Some("github"));
+        let token = format!("ghp_{}", "h".repeat(36));
+        bad.errors[0].developer_detail = format!("git push https://{token}@github.com");
+        if let Some(output) = bad.outputs.first_mut() {
+            output.actions_taken.push(format!("password={token}"));
+        }
+
+        let id = db.record(&bad, RunOutcome::of(&bad, false), 100, 1)?;
+        let raw: String =
+            db.conn
+                .query_row("SELECT report FROM runs WHERE id = ?1", [id], |row| {
+                    row.get(0)
+                })?;
+        let messages: String =
+            db.conn
+                .query_row("SELECT message FROM errors WHERE run = ?1", [id], |row| {
+                    row.get(0)
+                })?;
+        assert!(!raw.contains(&token));
+        assert!(!messages.contains(&token));
+        assert!(db.get(id)?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn diff_reports_changed_options_and_failures() -> Result<()> {
+        let dir = tempdir()?;
+        let mut db = HistoryDb::open(&dir.path().join(HISTORY_DB))?;
+        let ok = report(ProfileLevel::Dev, None);
+        let bad = report(ProfileLevel::Full, Some("rust"));
+        let a = db.record(&ok, RunOutcome::Completed, 100, 1)?;
+        let b = db.record(&bad, RunOutcome::Failed, 200, 1)?;
+
+        let diff = diff(&db.get(a)?.unwrap(), &db.get(b)?.unwrap())?;
+        assert_eq!(
+            diff.options,
+            vec![OptionChange {
+                option: "profile".into(),
+                a: Some(Value::from("Dev")),
+                b: Some(Value::from("Full")),
+            }]
+        );
+        assert!(diff.phases.is_empty());
+        assert!(diff.errors_only_in_a.is_empty());
+        assert_eq!(diff.errors_only_in_b, vec!["rust".to_string()]);
+        Ok(())
+    }
+}
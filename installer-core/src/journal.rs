@@ -0,0 +1,252 @@
+//! Persisted run journal used to resume interrupted installs.
+//!
+//! The journal lives in the staging directory and records, per phase, when it
+//! started, when it finished and its final `PhaseStatus`. A later run started
+//! with `--resume` and the same options skips phases already `Completed`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::manifest::InstallManifest;
+use crate::model::options::InstallOptions;
+use crate::model::phase::PhaseStatus;
+
+pub const JOURNAL_FILE: &str = "run-journal.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub phase: String,
+    /// `None` while the phase is pending or still running.
+    pub status: Option<PhaseStatus>,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunJournal {
+    pub run_id: String,
+    pub options_hash: String,
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub phases: Vec<JournalEntry>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl RunJournal {
+    pub fn new(staging_dir: &Path, options_hash: impl Into<String>) -> Self {
+        let now = unix_now();
+        Self {
+            run_id: format!("{}-{}", now, std::process::id()),
+            options_hash: options_hash.into(),
+            started_at: now,
+            updated_at: now,
+            phases: Vec::new(),
+            path: Self::path_in(staging_dir),
+        }
+    }
+
+    pub fn path_in(staging_dir: &Path) -> PathBuf {
+        staging_dir.join(JOURNAL_FILE)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the journal stored in `staging_dir`, if one exists.
+    pub fn load(staging_dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path_in(staging_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("reading run journal {}", path.display()))?;
+        let mut journal: Self = serde_json::from_str(&text)
+            .with_context(|| format!("parsing run journal {}", path.display()))?;
+        journal.path = path;
+        Ok(Some(journal))
+    }
+
+    /// Open the journal for a new run.
+    ///
+    /// With `resume` set, the existing journal is reused when it was written
+    /// for the same options; otherwise a fresh journal is started.
+    pub fn open(staging_dir: &Path, options_hash: &str, resume: bool) -> Self {
+        if resume {
+            match Self::load(staging_dir) {
+                Ok(Some(journal)) if journal.options_hash == options_hash => {
+                    info!(
+                        "Resuming run {} ({} phases already completed)",
+                        journal.run_id,
+                        journal.completed_phases().len()
+                    );
+                    return journal;
+                }
+                Ok(Some(journal)) => warn!(
+                    "Run journal {} was written for different options; starting a fresh run",
+                    journal.run_id
+                ),
+                Ok(None) => info!("No run journal found; starting a fresh run"),
+                Err(e) => warn!("Ignoring unreadable run journal: {e:#}"),
+            }
+        }
+        Self::new(staging_dir, options_hash)
+    }
+
+    pub fn entry(&self, phase: &str) -> Option<&JournalEntry> {
+        self.phases.iter().find(|entry| entry.phase == phase)
+    }
+
+    pub fn is_completed(&self, phase: &str) -> bool {
+        matches!(
+            self.entry(phase).and_then(|entry| entry.status.as_ref()),
+            Some(PhaseStatus::Completed)
+        )
+    }
+
+    pub fn completed_phases(&self) -> Vec<&str> {
+        self.phases
+            .iter()
+            .filter(|entry| entry.status == Some(PhaseStatus::Completed))
+            .map(|entry| entry.phase.as_str())
+            .collect()
+    }
+
+    pub fn mark_started(&mut self, phase: &str) {
+        let now = unix_now();
+        let entry = self.entry_mut(phase);
+        entry.status = None;
+        entry.started_at = Some(now);
+        entry.finished_at = None;
+        self.updated_at = now;
+    }
+
+    pub fn mark_finished(&mut self, phase: &str, status: PhaseStatus) {
+        let now = unix_now();
+        let entry = self.entry_mut(phase);
+        entry.status = Some(status);
+        entry.finished_at = Some(now);
+        self.updated_at = now;
+    }
+
+    /// Drop `phase` from the journal, as if it never ran.
+    pub fn forget(&mut self, phase: &str) {
+        self.phases.retain(|entry| entry.phase != phase);
+        self.updated_at = unix_now();
+    }
+
+    /// Write the journal atomically next to its final location.
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("writing run journal {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path)
+            .with_context(|| format!("replacing run journal {}", self.path.display()))?;
+        Ok(())
+    }
+
+    fn entry_mut(&mut self, phase: &str) -> &mut JournalEntry {
+        if let Some(pos) = self.phases.iter().position(|entry| entry.phase == phase) {
+            return &mut self.phases[pos];
+        }
+        self.phases.push(JournalEntry {
+            phase: phase.to_string(),
+            status: None,
+            started_at: None,
+            finished_at: None,
+        });
+        self.phases.last_mut().expect("entry just pushed")
+    }
+}
+
+/// Stable hash of the options that decide what a run does.
+///
+/// Run-scoped flags (dry run, interactivity, error policy, resume) are
+/// excluded so toggling them does not invalidate a journal.
+pub fn options_hash(opts: &InstallOptions) -> String {
+    let mut manifest = InstallManifest::from_options(opts);
+    manifest.continue_on_error = false;
+    manifest.staging_dir = None;
+    let json = serde_json::to_vec(&manifest).unwrap_or_default();
+    let digest = Sha256::digest(&json);
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::options::ProfileLevel;
+    use tempfile::tempdir;
+
+    #[test]
+    fn journal_round_trips_through_disk() -> Result<()> {
+        let dir = tempdir()?;
+        let mut journal = RunJournal::new(dir.path(), "abc");
+        journal.mark_started("snapshots");
+        journal.mark_finished("snapshots", PhaseStatus::Completed);
+        journal.mark_started("software_tiers");
+        journal.mark_finished("software_tiers", PhaseStatus::Failed("boom".into()));
+        journal.save()?;
+
+        let loaded = RunJournal::load(dir.path())?.expect("journal should exist");
+        assert_eq!(loaded.run_id, journal.run_id);
+        assert!(loaded.is_completed("snapshots"));
+        assert!(!loaded.is_completed("software_tiers"));
+        assert_eq!(loaded.completed_phases(), vec!["snapshots"]);
+        Ok(())
+    }
+
+    #[test]
+    fn open_only_resumes_matching_options() -> Result<()> {
+        let dir = tempdir()?;
+        let mut journal = RunJournal::new(dir.path(), "abc");
+        journal.mark_finished("snapshots", PhaseStatus::Completed);
+        journal.save()?;
+
+        let resumed = RunJournal::open(dir.path(), "abc", true);
+        assert_eq!(resumed.run_id, journal.run_id);
+        assert!(resumed.is_completed("snapshots"));
+
+        let changed = RunJournal::open(dir.path(), "def", true);
+        assert!(changed.phases.is_empty());
+
+        let fresh = RunJournal::open(dir.path(), "abc", false);
+        assert!(fresh.phases.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn options_hash_ignores_run_scoped_flags() {
+        let base = InstallOptions::default();
+        let toggled = InstallOptions {
+            dry_run: true,
+            interactive: true,
+            continue_on_error: true,
+            resume: true,
+            ..InstallOptions::default()
+        };
+        let different = InstallOptions {
+            profile: ProfileLevel::Full,
+            ..InstallOptions::default()
+        };
+
+        assert_eq!(options_hash(&base), options_hash(&toggled));
+        assert_ne!(options_hash(&base), options_hash(&different));
+    }
+}
@@ -8,6 +8,7 @@
 mod argon;
 pub mod authorization;
 mod backend;
+pub mod boot_config;
 mod buildroot;
 pub mod catalog;
 pub mod chezmoi;
@@ -20,14 +21,18 @@
 mod docker;
 mod doctor;
 pub mod dotfiles;
+mod drift;
 mod driver;
 pub use crate::system::error;
 
 pub mod fonts;
 mod github;
+pub mod history;
 pub mod interaction;
+mod journal;
 pub mod localization;
 pub mod logging;
+pub mod manifest;
 
 mod options;
 mod orchestrator;
@@ -37,18 +42,22 @@
 mod phase_runner;
 pub mod phases;
 pub mod pi4b;
+mod pi5;
+pub mod pi_board;
 pub mod pi_overlord;
 mod pkg;
+pub mod plan;
 pub mod platform;
 pub mod preset;
 pub mod profile;
 mod rclone;
-mod rollback;
+pub mod rollback;
 mod rust;
 pub mod scrubber;
 mod signal;
-mod snapshots;
+pub mod snapshots;
 mod software_tiers;
+pub mod sources;
 mod staging;
 mod status;
 
@@ -60,8 +69,10 @@
 use crate::localization::Localization;
 pub use advice::{AdviceEngine, AdviceEntry, Rule, Severity as AdviceSeverity};
 pub use system::artifact_cache::ArtifactCache;
-pub use system::ws_observer::{CompositeObserver, WebsocketObserver};
-pub use system::{cmd, dry_run, logging as sys_logging, sudo, system_ops as sys_ops};
+pub use system::cmd::{CommandRunner, Invocation, RealRunner, REAL_RUNNER};
+pub use system::recording_runner::{RecordingRunner, ScriptedOutput};
+pub use system::ws_observer::{CompositeObserver, ScryConfig, WebsocketObserver};
+pub use system::{cmd, dry_run, logging as sys_logging, progress, sudo, system_ops as sys_ops};
 
 // --- Core API ---
 pub use authorization::AuthorizationService;
@@ -71,24 +82,30 @@
     ConfigOverrides, ConfigService, PhaseContext, PlatformContext, UIContext, UserOptionsContext,
 };
 pub use doctor::{run_doctor, DoctorOutput};
+pub use drift::{
+    reconcile_options, run_verify, DriftItem, DriftStatus, ItemKind as DriftItemKind, VerifyOutput,
+    VerifyReport,
+};
 pub use driver::{AptRepoConfig, DistroDriver, RepoKind, ServiceName};
-pub use model::phase::AuthType;
+pub use journal::{JournalEntry, RunJournal};
+pub use model::phase::{ActionKind, AuthType, OutputStream, PlannedChange};
 pub use model::software::{SoftwareCategory, SoftwareTierPlan, ThemePlan, Tier};
 pub use options::{ArgonConfig, DockerConfig, EnvironmentTag, InstallOptions, ProfileLevel};
-pub use orchestrator::run_with_driver;
+pub use orchestrator::{run_preflight_audit, run_with_driver};
 pub use package_spec::{PackageIntent, PackageSpec};
-pub use phase_registry::PhaseRegistry;
+pub use phase_registry::{PhaseInfo, PhaseRegistry};
 pub use phase_runner::{
     Phase, PhaseErrorPolicy, PhaseEvent, PhaseObserver, PhaseOutput, PhaseResult, PhaseRunError,
-    PhaseRunResult, PhaseRunner,
+    PhaseRunResult, PhaseRunner, PhaseSchedule,
 };
 pub use pi_overlord::{PackageCategory, PackageMapping, PiOverlord};
+pub use plan::run_plan;
 pub use platform::{detect as detect_platform, PlatformInfo};
 pub use profile::{
     BlockDevice, CpuInfo, DistroInfo, MemoryInfo, MountInfo, PlatformInfo as ProfilePlatformInfo,
     PlatformType, SessionInfo, StorageInfo, SystemProfile, SystemProfileExt,
 };
-pub use rollback::RollbackManager;
+pub use rollback::{RollbackManager, RollbackOp};
 pub use software_tiers::install_phase as run_software_install;
 pub use status::{run_status, StatusOutput};
 pub use system::error::{
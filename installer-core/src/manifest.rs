@@ -0,0 +1,294 @@
+//! Declarative install manifests.
+//!
+//! A manifest captures every choice that feeds `InstallOptions` so a machine's
+//! setup can be checked into git and replayed with `mash-setup apply`.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::config_path;
+use crate::desktop::{DesktopEnvironment, DesktopSelection, DisplayProtocol};
+use crate::model::options::{
+    ArgonConfig, ChezmoiOptions, DockerConfig, EnvironmentTag, InstallOptions, ProfileLevel,
+};
+use crate::model::software::SoftwareTierPlan;
+use crate::model::Validator;
+
+/// Current manifest schema version.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Serialized form of an install run's choices.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InstallManifest {
+    pub version: u32,
+    pub profile: ProfileLevel,
+    #[serde(default)]
+    pub environment: EnvironmentTag,
+    #[serde(default)]
+    pub staging_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub continue_on_error: bool,
+    #[serde(default)]
+    pub enable_p10k: bool,
+    #[serde(default)]
+    pub argon: ArgonConfig,
+    #[serde(default)]
+    pub docker: DockerConfig,
+    #[serde(default)]
+    pub chezmoi: ChezmoiOptions,
+    #[serde(default)]
+    pub desktop_environment: Option<DesktopEnvironment>,
+    #[serde(default = "default_display_protocol")]
+    pub display_protocol: DisplayProtocol,
+    #[serde(default)]
+    pub software_plan: SoftwareTierPlan,
+}
+
+fn default_display_protocol() -> DisplayProtocol {
+    DisplayProtocol::Auto
+}
+
+/// On-disk encoding, chosen from the file extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Toml,
+    Json,
+}
+
+impl ManifestFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ManifestFormat::Json,
+            _ => ManifestFormat::Toml,
+        }
+    }
+}
+
+impl InstallManifest {
+    /// Capture the reproducible subset of `opts`.
+    ///
+    /// Run-scoped flags (`dry_run`, `interactive`, `resume`, `jobs`) and the detected
+    /// `system_profile` are deliberately left out.
+    pub fn from_options(opts: &InstallOptions) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            profile: opts.profile,
+            environment: opts.environment,
+            staging_dir: opts.staging_dir.clone(),
+            continue_on_error: opts.continue_on_error,
+            enable_p10k: opts.enable_p10k,
+            argon: opts.argon.clone(),
+            docker: opts.docker.clone(),
+            chezmoi: opts.chezmoi.clone(),
+            desktop_environment: opts.desktop_environment,
+            display_protocol: opts.display_protocol,
+            software_plan: opts.software_plan.clone(),
+        }
+    }
+
+    /// Expand the manifest into non-interactive install options.
+    pub fn to_options(&self, dry_run: bool) -> InstallOptions {
+        InstallOptions {
+            profile: self.profile,
+            staging_dir: self.staging_dir.clone(),
+            dry_run,
+            interactive: false,
+            argon: self.argon.clone(),
+            enable_p10k: self.enable_p10k,
+            docker: self.docker.clone(),
+            continue_on_error: self.continue_on_error,
+            resume: false,
+            jobs: 1,
+            verify: false,
+            allow_unverified_scripts: false,
+            only_phases: Vec::new(),
+            skip_phases: Vec::new(),
+            software_plan: self.software_plan.clone(),
+            system_profile: None,
+            environment: self.environment,
+            chezmoi: self.chezmoi.clone(),
+            desktop_environment: self.desktop_environment,
+            display_protocol: self.display_protocol,
+            history_db: None,
+        }
+    }
+
+    pub fn parse(text: &str, format: ManifestFormat) -> Result<Self> {
+        let manifest = match format {
+            ManifestFormat::Toml => toml::from_str(text)?,
+            ManifestFormat::Json => serde_json::from_str(text)?,
+        };
+        Ok(manifest)
+    }
+
+    pub fn render(&self, format: ManifestFormat) -> Result<String> {
+        let text = match format {
+            ManifestFormat::Toml => toml::to_string_pretty(self)?,
+            ManifestFormat::Json => serde_json::to_string_pretty(self)?,
+        };
+        Ok(text)
+    }
+
+    /// Read and validate a manifest file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading manifest {}", path.display()))?;
+        let manifest = Self::parse(&text, ManifestFormat::from_path(path))
+            .with_context(|| format!("parsing manifest {}", path.display()))?;
+
+        let errors = manifest.validate();
+        if !errors.is_empty() {
+            bail!(
+                "manifest {} is invalid:\n  - {}",
+                path.display(),
+                errors.join("\n  - ")
+            );
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+        }
+        let text = self.render(ManifestFormat::from_path(path))?;
+        fs::write(path, text).with_context(|| format!("writing manifest {}", path.display()))?;
+        Ok(())
+    }
+}
+
+impl Validator for InstallManifest {
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.version == 0 || self.version > MANIFEST_VERSION {
+            errors.push(format!(
+                "unsupported manifest version {} (this build understands up to {})",
+                self.version, MANIFEST_VERSION
+            ));
+        }
+
+        if let Some(de) = self.desktop_environment {
+            if let Err(e) = DesktopSelection::new(de, self.display_protocol).validate() {
+                errors.push(e.description);
+            }
+        }
+
+        errors.extend(self.to_options(false).validate());
+        errors
+    }
+}
+
+/// Where the most recent TUI session's choices are kept for `mash-setup export`.
+pub fn last_session_path() -> PathBuf {
+    config_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("last-session.toml")
+}
+
+/// Persist `opts` as the last session manifest.
+pub fn record_last_session(opts: &InstallOptions) -> Result<()> {
+    InstallManifest::from_options(opts).save(&last_session_path())
+}
+
+/// Load the manifest recorded by the last session, if any.
+pub fn load_last_session() -> Result<Option<InstallManifest>> {
+    let path = last_session_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    InstallManifest::load(&path).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::software::{SoftwareCategory, Tier};
+    use tempfile::tempdir;
+
+    fn sample_options() -> InstallOptions {
+        let mut opts = InstallOptions {
+            profile: ProfileLevel::Dev,
+            enable_p10k: true,
+            environment: EnvironmentTag::Work,
+            desktop_environment: Some(DesktopEnvironment::Kde),
+            display_protocol: DisplayProtocol::Wayland,
+            ..InstallOptions::default()
+        };
+        opts.argon.enabled = true;
+        opts.docker.enabled = true;
+        opts.software_plan
+            .selections
+            .insert(SoftwareCategory::Editors, vec!["neovim".into()]);
+        opts.software_plan.target_tier = Some(Tier::A);
+        opts
+    }
+
+    #[test]
+    fn toml_round_trip_preserves_options() -> Result<()> {
+        let manifest = InstallManifest::from_options(&sample_options());
+        let text = manifest.render(ManifestFormat::Toml)?;
+        let parsed = InstallManifest::parse(&text, ManifestFormat::Toml)?;
+        let opts = parsed.to_options(true);
+
+        assert_eq!(opts.profile, ProfileLevel::Dev);
+        assert_eq!(opts.environment, EnvironmentTag::Work);
+        assert_eq!(opts.desktop_environment, Some(DesktopEnvironment::Kde));
+        assert_eq!(opts.display_protocol, DisplayProtocol::Wayland);
+        assert!(opts.argon.enabled && opts.docker.enabled && opts.enable_p10k);
+        assert_eq!(opts.software_plan.target_tier, Some(Tier::A));
+        assert_eq!(
+            opts.software_plan
+                .selections
+                .get(&SoftwareCategory::Editors),
+            Some(&vec!["neovim".to_string()])
+        );
+        assert!(opts.dry_run);
+        assert!(!opts.interactive);
+        Ok(())
+    }
+
+    #[test]
+    fn save_and_load_json_by_extension() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("box.json");
+        InstallManifest::from_options(&sample_options()).save(&path)?;
+
+        let text = fs::read_to_string(&path)?;
+        assert!(serde_json::from_str::<serde_json::Value>(&text).is_ok());
+        let loaded = InstallManifest::load(&path)?;
+        assert_eq!(loaded.profile, ProfileLevel::Dev);
+        Ok(())
+    }
+
+    #[test]
+    fn minimal_manifest_uses_defaults() -> Result<()> {
+        let manifest =
+            InstallManifest::parse("version = 1\nprofile = \"Minimal\"\n", ManifestFormat::Toml)?;
+        assert!(manifest.is_valid());
+        assert_eq!(manifest.display_protocol, DisplayProtocol::Auto);
+        assert_eq!(manifest.software_plan.target_tier, Some(Tier::S));
+        Ok(())
+    }
+
+    #[test]
+    fn validation_rejects_bad_manifests() {
+        let mut manifest = InstallManifest::from_options(&sample_options());
+        manifest.version = MANIFEST_VERSION + 1;
+        manifest.desktop_environment = Some(DesktopEnvironment::Xfce);
+        manifest.chezmoi.enabled = true;
+
+        let errors = manifest.validate();
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("unsupported manifest version")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("does not support Wayland")));
+        assert!(errors.iter().any(|e| e.contains("Chezmoi")));
+    }
+}
@@ -64,12 +64,37 @@ pub struct InstallOptions {
     pub enable_p10k: bool,
     pub docker: DockerConfig,
     pub continue_on_error: bool,
+    /// Skip phases already completed by a previous run with the same options.
+    #[serde(default)]
+    pub resume: bool,
+    /// Maximum number of phases run at once; `1` keeps the sequential order.
+    #[serde(default = "default_jobs")]
+    pub jobs: usize,
+    /// Append a phase that checks the machine against these options.
+    #[serde(default)]
+    pub verify: bool,
+    /// Run upstream install scripts that are not pinned to a checksum.
+    #[serde(default)]
+    pub allow_unverified_scripts: bool,
+    /// Run only the phases with these registry keys; empty runs them all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only_phases: Vec<String>,
+    /// Leave out the phases with these registry keys.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_phases: Vec<String>,
     pub software_plan: SoftwareTierPlan,
     pub system_profile: Option<SystemProfile>,
     pub environment: EnvironmentTag,
     pub chezmoi: ChezmoiOptions,
     pub desktop_environment: Option<DesktopEnvironment>,
     pub display_protocol: DisplayProtocol,
+    /// History database the run is recorded in; `None` leaves no record.
+    #[serde(skip)]
+    pub history_db: Option<PathBuf>,
+}
+
+fn default_jobs() -> usize {
+    1
 }
 
 impl Validator for InstallOptions {
@@ -82,10 +107,31 @@ fn validate(&self) -> Vec<String> {
             }
         }
 
+        if self.jobs == 0 {
+            errors.push("jobs must be at least 1".to_string());
+        }
+
         if self.chezmoi.enabled && self.chezmoi.repo_url.is_none() {
             errors.push("Chezmoi is enabled but no repository URL was provided.".to_string());
         }
 
+        let selected: Vec<String> = self
+            .only_phases
+            .iter()
+            .chain(&self.skip_phases)
+            .cloned()
+            .collect();
+        let registry = crate::PhaseRegistry::default();
+        if let Err(e) = registry.check_ids(&selected).and_then(|()| {
+            registry.check_selection(
+                &self.only_phases,
+                &self.skip_phases,
+                &UserOptionsContext::from_options(self),
+            )
+        }) {
+            errors.push(e.to_string());
+        }
+
         errors.extend(self.software_plan.validate());
 
         errors
@@ -103,12 +149,19 @@ fn default() -> Self {
             enable_p10k: false,
             docker: DockerConfig::default(),
             continue_on_error: false,
+            resume: false,
+            jobs: default_jobs(),
+            verify: false,
+            allow_unverified_scripts: false,
+            only_phases: Vec::new(),
+            skip_phases: Vec::new(),
             software_plan: SoftwareTierPlan::default(),
             system_profile: None,
             environment: EnvironmentTag::Home,
             chezmoi: ChezmoiOptions::default(),
             desktop_environment: None,
             display_protocol: DisplayProtocol::Auto,
+            history_db: None,
         }
     }
 }
@@ -137,6 +190,8 @@ pub struct UserOptionsContext {
     pub chezmoi: ChezmoiOptions,
     pub desktop_environment: Option<DesktopEnvironment>,
     pub display_protocol: DisplayProtocol,
+    pub verify: bool,
+    pub allow_unverified_scripts: bool,
 }
 
 impl UserOptionsContext {
@@ -158,6 +213,8 @@ pub fn from_options(opts: &InstallOptions) -> Self {
             chezmoi: opts.chezmoi.clone(),
             desktop_environment: opts.desktop_environment,
             display_protocol: opts.display_protocol,
+            verify: opts.verify,
+            allow_unverified_scripts: opts.allow_unverified_scripts,
         }
     }
 }
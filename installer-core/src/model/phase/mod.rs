@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PhaseOutput {
+    /// Stable phase id (registry key); empty in reports written before ids.
+    #[serde(default)]
+    pub id: String,
+    /// Display label, localized.
     pub name: String,
     pub description: String,
     pub actions_taken: Vec<String>,
@@ -11,16 +16,21 @@ pub struct PhaseOutput {
     pub warnings: Vec<String>,
     pub dry_run: bool,
     pub status: PhaseStatus,
+    /// Wall-clock time the phase took; 0 when it did not run.
+    #[serde(default)]
+    pub duration_ms: u64,
 }
 
 impl PhaseOutput {
     pub fn from_metadata(
+        id: impl Into<String>,
         name: impl Into<String>,
         description: impl Into<String>,
         metadata: PhaseMetadata,
         status: PhaseStatus,
     ) -> Self {
         Self {
+            id: id.into(),
             name: name.into(),
             description: description.into(),
             actions_taken: metadata.actions_taken,
@@ -30,11 +40,18 @@ pub fn from_metadata(
             warnings: metadata.warnings,
             dry_run: metadata.dry_run,
             status,
+            duration_ms: metadata.duration_ms,
         }
     }
 
-    pub fn skipped(name: impl Into<String>, description: impl Into<String>, dry_run: bool) -> Self {
+    pub fn skipped(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        dry_run: bool,
+    ) -> Self {
         Self {
+            id: id.into(),
             name: name.into(),
             description: description.into(),
             actions_taken: Vec::new(),
@@ -44,6 +61,7 @@ pub fn skipped(name: impl Into<String>, description: impl Into<String>, dry_run:
             warnings: Vec::new(),
             dry_run,
             status: PhaseStatus::Skipped,
+            duration_ms: 0,
         }
     }
 }
@@ -57,6 +75,8 @@ pub enum PhaseStatus {
     Skipped,
 }
 
+/// Progress of a run. `phase` is always the stable phase id; the localized
+/// label travels with the events that introduce a phase.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PhaseEvent {
     Total {
@@ -66,24 +86,138 @@ pub enum PhaseEvent {
         index: usize,
         total: usize,
         phase: String,
+        #[serde(default)]
+        label: String,
     },
     Completed {
         index: usize,
         phase: String,
         description: String,
+        #[serde(default)]
+        duration_ms: u64,
     },
     Failed {
         index: usize,
         phase: String,
         error: String,
+        #[serde(default)]
+        duration_ms: u64,
     },
     Skipped {
         index: usize,
         phase: String,
+        #[serde(default)]
+        label: String,
     },
     Warning {
         message: String,
     },
+    /// A named sub-step of a running phase has begun.
+    StepStarted {
+        phase: String,
+        step: String,
+    },
+    StepFinished {
+        phase: String,
+        step: String,
+        success: bool,
+        duration_ms: u64,
+    },
+    /// Something the phase did, as it is recorded in the phase output.
+    Action {
+        phase: String,
+        kind: ActionKind,
+        message: String,
+    },
+    CommandStarted {
+        phase: String,
+        command: String,
+    },
+    /// One line a running command wrote, already scrubbed.
+    CommandOutput {
+        phase: String,
+        stream: OutputStream,
+        line: String,
+    },
+    CommandFinished {
+        phase: String,
+        command: String,
+        status: Option<i32>,
+        duration_ms: u64,
+    },
+    DownloadProgress {
+        phase: String,
+        label: String,
+        bytes: u64,
+        total: Option<u64>,
+    },
+    /// Something a dry run found the phase would change.
+    Planned {
+        phase: String,
+        change: PlannedChange,
+    },
+}
+
+impl PhaseEvent {
+    /// Progress ticks and command output are only interesting live and are
+    /// left out of reports.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            PhaseEvent::DownloadProgress { .. } | PhaseEvent::CommandOutput { .. }
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Which `PhaseContext::record_*` call produced a [`PhaseEvent::Action`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    Action,
+    Configured,
+    Tweaked,
+    DryRun,
+}
+
+/// One concrete change a dry run would make, as `mash-setup plan` lists it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlannedChange {
+    /// Packages `backend` would install that are not installed yet.
+    Packages {
+        backend: String,
+        packages: Vec<String>,
+    },
+    /// A package repository that would be added.
+    Repo {
+        name: String,
+        path: PathBuf,
+        entry: String,
+    },
+    /// A file that would be written; `diff` is against what is there now.
+    File {
+        path: PathBuf,
+        exists: bool,
+        diff: String,
+    },
+    /// A systemd unit that would be enabled.
+    Service { unit: String },
+    /// A file that would be fetched; `size` is in bytes when the server says.
+    Download { url: String, size: Option<u64> },
+    /// A command the dry run held back.
+    Command { command: String },
+    /// Anything else the phase recorded with `record_dry_run`.
+    Note {
+        action: String,
+        detail: Option<String>,
+    },
 }
 
 /// Collected metadata that each phase can report to the runner.
@@ -95,6 +229,8 @@ pub struct PhaseMetadata {
     pub rollback_actions: Vec<String>,
     pub warnings: Vec<String>,
     pub dry_run: bool,
+    #[serde(default)]
+    pub duration_ms: u64,
 }
 
 /// Types of interactive authorizations supported by the forge.
@@ -141,4 +277,16 @@ fn sudo_password(&mut self) -> anyhow::Result<String> {
     fn request_auth(&mut self, _auth_type: AuthType) -> anyhow::Result<bool> {
         Ok(false)
     }
+
+    /// Like [`confirm`](Self::confirm), but `None` when no answer came and
+    /// another prompter should ask instead.
+    fn try_confirm(&mut self, prompt: &str) -> Option<bool> {
+        Some(self.confirm(prompt))
+    }
+
+    /// Like [`request_auth`](Self::request_auth), with `None` meaning the
+    /// same as in [`try_confirm`](Self::try_confirm).
+    fn try_request_auth(&mut self, auth_type: AuthType) -> anyhow::Result<Option<bool>> {
+        self.request_auth(auth_type).map(Some)
+    }
 }
@@ -1,3 +1,4 @@
+use crate::pi_board::PiHardware;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::PathBuf;
@@ -51,6 +52,9 @@ pub struct PlatformInfo {
     pub model: String,
     pub board_revision: Option<String>,
     pub is_laptop: bool,
+    /// Board, RAM, NVMe and cooling details when running on a Raspberry Pi.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pi: Option<PiHardware>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
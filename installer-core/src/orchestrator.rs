@@ -1,7 +1,8 @@
 use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, Result};
-use tracing::info;
+use tracing::{error, info, warn};
 
 use crate::system::{
     dry_run::DryRunLog,
@@ -15,11 +16,13 @@
 use crate::{
     context::{ConfigService, PlatformContext, UIContext, UserOptionsContext},
     driver::DistroDriver,
+    history::{HistoryDb, RunOutcome},
+    journal::{self, RunJournal},
     localization::Localization,
     logging,
     options::InstallOptions,
     phase_registry::PhaseRegistry,
-    phase_runner::{PhaseErrorPolicy, PhaseObserver, PhaseRunner},
+    phase_runner::{PhaseErrorPolicy, PhaseObserver, PhaseRunner, PhaseSchedule},
     platform::detect as detect_platform,
     rollback::RollbackManager,
     signal::SignalGuard,
@@ -31,6 +34,46 @@ pub fn run_with_driver(
     opts: InstallOptions,
     observer: &mut dyn PhaseObserver,
 ) -> Result<InstallationReport, Box<InstallerRunError>> {
+    let started_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let started = Instant::now();
+    // Dry runs change nothing, so they are left out of the audit trail.
+    let history_db = opts.history_db.clone().filter(|_| !opts.dry_run);
+    let result = run_installer(driver, opts, observer);
+
+    let (report, outcome) = match &result {
+        Ok(report) => (report, RunOutcome::of(report, false)),
+        Err(err) => (err.report.as_ref(), RunOutcome::Failed),
+    };
+    let duration_ms = started.elapsed().as_millis() as u64;
+    if let Some(path) = history_db {
+        match HistoryDb::open(&path)
+            .and_then(|mut db| db.record(report, outcome, started_at, duration_ms))
+        {
+            Ok(id) => info!("Run recorded in history as #{id}"),
+            Err(e) => warn!("Could not record run history: {e:#}"),
+        }
+    }
+
+    result
+}
+
+fn run_installer(
+    driver: &'static dyn DistroDriver,
+    opts: InstallOptions,
+    observer: &mut dyn PhaseObserver,
+) -> Result<InstallationReport, Box<InstallerRunError>> {
+    // Which phases run depends on the final options, so --skip is checked here
+    PhaseRegistry::default()
+        .check_selection(
+            &opts.only_phases,
+            &opts.skip_phases,
+            &UserOptionsContext::from_options(&opts),
+        )
+        .map_err(Box::<InstallerRunError>::from)?;
+
     // Initialize sudo password storage
     sudo_password::init_sudo_password();
 
@@ -58,6 +101,8 @@ pub fn run_with_driver(
                 },
                 dry_run_log: Vec::new(),
                 audit_report: crate::system::dry_run::PreflightAuditReport::default(),
+                run_id: None,
+                snapshots: Vec::new(),
             }),
             source: err,
         }
@@ -87,6 +132,8 @@ pub fn run_with_driver(
                 },
                 dry_run_log: Vec::new(),
                 audit_report: crate::system::dry_run::PreflightAuditReport::default(),
+                run_id: None,
+                snapshots: Vec::new(),
             }),
             source: err,
         })
@@ -111,6 +158,7 @@ pub fn run_with_driver(
         driver,
         pkg_backend: driver.pkg_backend(),
         system: &crate::sys_ops::REAL_SYSTEM,
+        commands: &crate::cmd::REAL_RUNNER,
     };
 
     let localization = Localization::load_default().map_err(Box::<InstallerRunError>::from)?;
@@ -132,7 +180,7 @@ pub fn run_with_driver(
         Box::new(InstallerRunError {
             report: Box::new(InstallationReport {
                 completed_phases: Vec::new(),
-                staging_dir,
+                staging_dir: staging_dir.clone(),
                 errors: vec![err.clone()],
                 outputs: Vec::new(),
                 events: Vec::new(),
@@ -143,11 +191,29 @@ pub fn run_with_driver(
                 },
                 dry_run_log: Vec::new(),
                 audit_report: crate::system::dry_run::PreflightAuditReport::default(),
+                run_id: None,
+                snapshots: Vec::new(),
             }),
             source: err,
         })
     })?;
 
+    // Dry runs change nothing, so they neither read nor write the run journal.
+    let journal = (!opts.dry_run).then(|| {
+        let journal = RunJournal::open(&staging_dir, &journal::options_hash(&opts), opts.resume);
+        info!(
+            "Run journal {} at {}",
+            journal.run_id,
+            journal.path().display()
+        );
+        journal
+    });
+    let run_id = journal.as_ref().map(|journal| journal.run_id.clone());
+    let rollback = match &run_id {
+        Some(id) => RollbackManager::for_run(id.clone()),
+        None => RollbackManager::new(),
+    };
+
     let ctx = InstallContext {
         options: UserOptionsContext::from_options(&opts),
         platform: platform_ctx,
@@ -157,7 +223,7 @@ pub fn run_with_driver(
             Default::default(),
         ),
         localization,
-        rollback: RollbackManager::new(),
+        rollback,
         dry_run_log: DryRunLog::new(),
         cache,
     };
@@ -177,7 +243,9 @@ pub fn run_with_driver(
     let _sudo_keepalive = crate::sudo::start_sudo_keepalive();
 
     // Registry populates phases based on the active profile level
-    let registry = PhaseRegistry::default();
+    let registry = PhaseRegistry::default()
+        .only(&opts.only_phases)
+        .skip(&opts.skip_phases);
     let phases = registry.build_phases(&ctx.options, &ctx.localization);
 
     let policy = if opts.continue_on_error {
@@ -186,7 +254,15 @@ pub fn run_with_driver(
         PhaseErrorPolicy::FailFast
     };
 
-    let runner = PhaseRunner::with_policy(phases, policy);
+    let mut runner = PhaseRunner::with_policy(phases, policy);
+    if opts.jobs > 1 {
+        info!("Running up to {} phases in parallel", opts.jobs);
+        runner = runner.with_schedule(PhaseSchedule::Parallel { workers: opts.jobs });
+    }
+
+    if let Some(journal) = journal {
+        runner = runner.with_journal(journal);
+    }
 
     // Create a signal guard to handle SIGINT/SIGTERM during installation
     let signal_guard = SignalGuard::new().map_err(Box::<InstallerRunError>::from)?;
@@ -211,6 +287,10 @@ pub fn run_with_driver(
         .run(&ctx, observer, Some(&signal_guard))
         .map_err(|e| {
             let run_err = *e;
+            let audit_report = ctx
+                .dry_run_log
+                .audit_report()
+                .with_plan(&run_err.result.events);
             Box::new(InstallerRunError {
                 report: Box::new(InstallationReport {
                     completed_phases: run_err.result.completed_phases,
@@ -224,17 +304,21 @@ pub fn run_with_driver(
                         description: driver.description().to_string(),
                     },
                     dry_run_log: ctx.dry_run_log.entries(),
-                    audit_report: crate::system::dry_run::PreflightAuditReport::default(),
+                    audit_report,
+                    run_id: run_id.clone(),
+                    snapshots: ctx.rollback.snapshots(),
                 }),
                 source: run_err.source,
             })
-        })?;
+        })
+        .inspect_err(|err| point_at_snapshots(&err.report))?;
 
     info!(
         "Installation completed successfully: {} phases completed.",
         result.completed_phases.len()
     );
 
+    let audit_report = ctx.dry_run_log.audit_report().with_plan(&result.events);
     let report = InstallationReport {
         completed_phases: result.completed_phases,
         staging_dir: staging_final,
@@ -247,7 +331,9 @@ pub fn run_with_driver(
             description: driver.description().to_string(),
         },
         dry_run_log: ctx.dry_run_log.entries(),
-        audit_report: crate::system::dry_run::PreflightAuditReport::default(),
+        audit_report,
+        run_id,
+        snapshots: ctx.rollback.snapshots(),
     };
 
     // ── Telemetry (Roaming Agent feature) ───────────────────────────────────
@@ -258,13 +344,42 @@ pub fn run_with_driver(
     Ok(report)
 }
 
-#[allow(dead_code)]
+/// After a fatal failure, tell the user how to get back to the pre-install
+/// snapshot.
+fn point_at_snapshots(report: &InstallationReport) {
+    let Some(run_id) = report.run_id.as_deref() else {
+        return;
+    };
+    if !report.has_fatal_error() {
+        return;
+    }
+    for snapshot in &report.snapshots {
+        error!(
+            "Pre-install {} snapshot {} is available. Restore it with `mash-setup rollback {run_id} --yes`.",
+            snapshot.backend.label(),
+            snapshot.id
+        );
+    }
+}
+
+/// Dry-run the phases `opts` selects and collect what each would change.
+/// Used by `mash-setup plan`: nothing is written, no prompt is shown and the
+/// run is not recorded in the history.
 pub fn run_preflight_audit(
     driver: &'static dyn DistroDriver,
     opts: &InstallOptions,
+    observer: &mut dyn PhaseObserver,
 ) -> Result<crate::system::dry_run::PreflightAuditReport> {
-    let registry = PhaseRegistry::default();
-    // Use dummy context for audit
+    let opts = InstallOptions {
+        dry_run: true,
+        interactive: false,
+        ..opts.clone()
+    };
+    PhaseRegistry::default().check_selection(
+        &opts.only_phases,
+        &opts.skip_phases,
+        &UserOptionsContext::from_options(&opts),
+    )?;
     let platform = detect_platform()?;
     let config_service = ConfigService::load()?;
     let platform_ctx = PlatformContext {
@@ -274,32 +389,29 @@ pub fn run_preflight_audit(
         driver,
         pkg_backend: driver.pkg_backend(),
         system: &crate::sys_ops::REAL_SYSTEM,
+        commands: &crate::cmd::REAL_RUNNER,
     };
     let ctx = InstallContext {
-        options: UserOptionsContext::from_options(opts),
+        options: UserOptionsContext::from_options(&opts),
         platform: platform_ctx,
         ui: UIContext,
-        interaction: crate::interaction::InteractionService::new(
-            opts.interactive,
-            Default::default(),
-        ),
+        interaction: crate::interaction::InteractionService::new(false, Default::default()),
         localization: Localization::load_default()?,
         rollback: RollbackManager::new(),
         dry_run_log: DryRunLog::new(),
-        cache: crate::ArtifactCache::new(&UserOptionsContext::from_options(opts).staging_dir),
+        cache: crate::ArtifactCache::new(&UserOptionsContext::from_options(&opts).staging_dir),
     };
 
-    let phases = registry.build_phases(&ctx.options, &ctx.localization);
-
-    for phase in phases {
-        if phase.should_run(&ctx) {
-            ctx.dry_run_log.record(
-                phase.name().to_string(),
-                "Planned for execution".to_string(),
-                Some(phase.description().to_string()),
-            );
-        }
-    }
+    let phases = PhaseRegistry::default()
+        .only(&opts.only_phases)
+        .skip(&opts.skip_phases)
+        .build_phases(&ctx.options, &ctx.localization);
+    let runner =
+        PhaseRunner::with_policy(phases, PhaseErrorPolicy::ContinueOnError).with_planning();
+    let events = match runner.run(&ctx, observer, None) {
+        Ok(result) => result.events,
+        Err(err) => err.result.events,
+    };
 
-    Ok(ctx.dry_run_log.audit_report())
+    Ok(ctx.dry_run_log.audit_report().with_plan(&events))
 }
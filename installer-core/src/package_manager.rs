@@ -1,16 +1,59 @@
 use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeSet;
+use std::process::Output;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 
-use crate::system::cmd;
+use crate::model::phase::PlannedChange;
+use crate::system::{cmd, dry_run};
 use crate::{backend::PkgBackend, distro, driver::DistroDriver};
 
 static PACMAN_SYNCED: AtomicBool = AtomicBool::new(false);
 
+/// Longest an install, upgrade or removal may run before it is cancelled.
+const INSTALL_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Longest a package database refresh may run.
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// apt, pacman and dnf all hold an exclusive database lock, so phases running
+/// in parallel must take turns at the package manager.
+static PACKAGE_MANAGER_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock() -> MutexGuard<'static, ()> {
+    PACKAGE_MANAGER_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Run a command that changes the system package manager's state, holding
+/// the package-manager lock until it exits. Every install, removal, refresh
+/// and repository change goes through here, including scripts that call the
+/// package manager themselves.
+pub fn execute(command: cmd::Command) -> Result<Output> {
+    let _guard = lock();
+    command.execute()
+}
+
 pub trait PackageInstaller {
     fn is_installed(&self, pkg: &str) -> bool;
     fn update(&self, dry_run: bool) -> Result<()>;
     fn ensure_packages(&self, pkgs: &[&str], dry_run: bool) -> Result<()>;
     fn try_optional(&self, pkg: &str, dry_run: bool);
+    fn remove_packages(&self, pkgs: &[&str]) -> Result<()>;
+
+    /// Whether a package group (pacman group, dnf `@group`/environment) is
+    /// installed. apt has no groups; its metapackages are plain packages.
+    fn is_group_installed(&self, group: &str) -> bool {
+        self.is_installed(group)
+    }
+    fn ensure_groups(&self, groups: &[&str], dry_run: bool) -> Result<()> {
+        self.ensure_packages(groups, dry_run)
+    }
+    fn remove_groups(&self, groups: &[&str]) -> Result<()> {
+        self.remove_packages(groups)
+    }
 }
 
 struct AptInstaller;
@@ -22,7 +65,11 @@ pub trait PackageInstaller {
 static DNF_INSTALLER: DnfInstaller = DnfInstaller;
 
 pub fn installer_for(driver: &dyn DistroDriver) -> &'static dyn PackageInstaller {
-    match driver.pkg_backend() {
+    installer_for_backend(driver.pkg_backend())
+}
+
+fn installer_for_backend(backend: PkgBackend) -> &'static dyn PackageInstaller {
+    match backend {
         PkgBackend::Apt => &APT_INSTALLER,
         PkgBackend::Pacman => &PACMAN_INSTALLER,
         PkgBackend::Dnf => &DNF_INSTALLER,
@@ -40,11 +87,12 @@ pub fn is_installed(driver: &dyn DistroDriver, pkg: &str) -> bool {
 /// Backend-specific check for whether a package is installed.
 /// This is used by the default implementation of DistroDriver::is_package_installed.
 pub fn check_installed(backend: PkgBackend, pkg: &str) -> bool {
-    match backend {
-        PkgBackend::Apt => APT_INSTALLER.is_installed(pkg),
-        PkgBackend::Pacman => PACMAN_INSTALLER.is_installed(pkg),
-        PkgBackend::Dnf => DNF_INSTALLER.is_installed(pkg),
-    }
+    installer_for_backend(backend).is_installed(pkg)
+}
+
+/// Whether a native package group is installed on `backend`.
+pub fn is_group_installed(backend: PkgBackend, group: &str) -> bool {
+    installer_for_backend(backend).is_group_installed(group)
 }
 
 pub fn update(driver: &dyn DistroDriver, dry_run: bool) -> Result<()> {
@@ -54,7 +102,86 @@ pub fn update(driver: &dyn DistroDriver, dry_run: bool) -> Result<()> {
 pub fn ensure_packages(driver: &dyn DistroDriver, pkgs: &[&str], dry_run: bool) -> Result<()> {
     let native = distro::translate_names(driver, pkgs);
     let native_refs: Vec<&str> = native.iter().map(String::as_str).collect();
-    installer_for(driver).ensure_packages(&native_refs, dry_run)
+    ensure_native_packages(driver, &native_refs, dry_run)
+}
+
+/// Install packages whose names are already native to `driver` (for example
+/// names taken from a catalog mapping), bypassing canonical-name translation.
+pub fn ensure_native_packages(
+    driver: &dyn DistroDriver,
+    pkgs: &[&str],
+    dry_run: bool,
+) -> Result<()> {
+    let installer = installer_for(driver);
+    if dry_run {
+        plan_missing(driver.pkg_backend(), pkgs, |pkg| {
+            installer.is_installed(pkg)
+        });
+    }
+    installer.ensure_packages(pkgs, dry_run)
+}
+
+/// Put the entries of `pkgs` that `installed` does not report on the plan.
+fn plan_missing(backend: PkgBackend, pkgs: &[&str], installed: impl Fn(&str) -> bool) {
+    let missing: Vec<String> = pkgs
+        .iter()
+        .filter(|pkg| !installed(pkg))
+        .map(|pkg| pkg.to_string())
+        .collect();
+    if !missing.is_empty() {
+        dry_run::plan(PlannedChange::Packages {
+            backend: backend.name().to_string(),
+            packages: missing,
+        });
+    }
+}
+
+/// Uninstall native packages; used to undo a run after the driver that
+/// installed them is gone.
+pub fn remove_native_packages(backend: PkgBackend, pkgs: &[&str]) -> Result<()> {
+    let installer = installer_for_backend(backend);
+    let installed: Vec<&str> = pkgs
+        .iter()
+        .copied()
+        .filter(|pkg| installer.is_installed(pkg))
+        .collect();
+    if installed.is_empty() {
+        return Ok(());
+    }
+    installer.remove_packages(&installed)
+}
+
+/// Install native package groups (pacman groups, dnf `@groups`, apt
+/// metapackages), skipping the ones already present.
+pub fn ensure_groups(driver: &dyn DistroDriver, groups: &[&str], dry_run: bool) -> Result<()> {
+    let installer = installer_for(driver);
+    let missing: Vec<&str> = groups
+        .iter()
+        .copied()
+        .filter(|group| !installer.is_group_installed(group))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    if dry_run {
+        plan_missing(driver.pkg_backend(), &missing, |_| false);
+    }
+    installer.ensure_groups(&missing, dry_run)
+}
+
+/// Uninstall native package groups; the group counterpart of
+/// [`remove_native_packages`].
+pub fn remove_native_groups(backend: PkgBackend, groups: &[&str]) -> Result<()> {
+    let installer = installer_for_backend(backend);
+    let installed: Vec<&str> = groups
+        .iter()
+        .copied()
+        .filter(|group| installer.is_group_installed(group))
+        .collect();
+    if installed.is_empty() {
+        return Ok(());
+    }
+    installer.remove_groups(&installed)
 }
 
 pub fn try_optional(driver: &dyn DistroDriver, pkg: &str, dry_run: bool) {
@@ -62,7 +189,13 @@ pub fn try_optional(driver: &dyn DistroDriver, pkg: &str, dry_run: bool) {
         Some(n) => n,
         None => return,
     };
-    installer_for(driver).try_optional(&native, dry_run);
+    let installer = installer_for(driver);
+    if dry_run {
+        plan_missing(driver.pkg_backend(), &[&native], |pkg| {
+            installer.is_installed(pkg)
+        });
+    }
+    installer.try_optional(&native, dry_run);
 }
 
 impl PackageInstaller for AptInstaller {
@@ -76,12 +209,14 @@ fn is_installed(&self, pkg: &str) -> bool {
     }
 
     fn update(&self, dry_run: bool) -> Result<()> {
-        cmd::Command::new("apt-get")
-            .sudo()
-            .args(["update", "-qq"])
-            .dry_run(dry_run)
-            .execute()
-            .context("running apt-get update")?;
+        execute(
+            cmd::Command::new("apt-get")
+                .sudo()
+                .timeout(REFRESH_TIMEOUT)
+                .args(["update", "-qq"])
+                .dry_run(dry_run),
+        )
+        .context("running apt-get update")?;
         Ok(())
     }
 
@@ -99,15 +234,17 @@ fn ensure_packages(&self, pkgs: &[&str], dry_run: bool) -> Result<()> {
 
         tracing::info!("Installing packages: {}", missing.join(", "));
 
-        cmd::Command::new("apt-get")
-            .sudo()
-            .args(["install", "-y", "--install-recommends"])
-            .args(&missing)
-            .env("DEBIAN_FRONTEND", "noninteractive")
-            .dry_run(dry_run)
-            .execute()
-            .context("running apt-get install")
-            .map_err(|err| anyhow!("apt-get install failed for {}: {err}", missing.join(" ")))?;
+        execute(
+            cmd::Command::new("apt-get")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["install", "-y", "--install-recommends"])
+                .args(&missing)
+                .env("DEBIAN_FRONTEND", "noninteractive")
+                .dry_run(dry_run),
+        )
+        .context("running apt-get install")
+        .map_err(|err| anyhow!("apt-get install failed for {}: {err}", missing.join(" ")))?;
         Ok(())
     }
 
@@ -115,20 +252,36 @@ fn try_optional(&self, pkg: &str, dry_run: bool) {
         if self.is_installed(pkg) {
             return;
         }
-        let res = cmd::Command::new("apt-get")
-            .sudo()
-            .args(["install", "-y", "--install-recommends", pkg])
-            .env("DEBIAN_FRONTEND", "noninteractive")
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .dry_run(dry_run)
-            .execute();
+        let res = execute(
+            cmd::Command::new("apt-get")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["install", "-y", "--install-recommends", pkg])
+                .env("DEBIAN_FRONTEND", "noninteractive")
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .dry_run(dry_run),
+        );
 
         match res {
             Ok(_) => tracing::info!("Installed optional package: {pkg}"),
             Err(_) => tracing::warn!("Optional package '{pkg}' not available; skipping"),
         }
     }
+
+    fn remove_packages(&self, pkgs: &[&str]) -> Result<()> {
+        tracing::info!("Removing packages: {}", pkgs.join(", "));
+        execute(
+            cmd::Command::new("apt-get")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["remove", "-y"])
+                .args(pkgs)
+                .env("DEBIAN_FRONTEND", "noninteractive"),
+        )
+        .context("running apt-get remove")?;
+        Ok(())
+    }
 }
 
 impl PackageInstaller for PacmanInstaller {
@@ -145,12 +298,14 @@ fn update(&self, dry_run: bool) -> Result<()> {
         if PACMAN_SYNCED.load(Ordering::SeqCst) {
             return Ok(());
         }
-        cmd::Command::new("pacman")
-            .sudo()
-            .args(["-Syu", "--noconfirm"])
-            .dry_run(dry_run)
-            .execute()
-            .context("running pacman -Syu")?;
+        execute(
+            cmd::Command::new("pacman")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["-Syu", "--noconfirm"])
+                .dry_run(dry_run),
+        )
+        .context("running pacman -Syu")?;
 
         PACMAN_SYNCED.store(true, Ordering::SeqCst);
         Ok(())
@@ -164,14 +319,16 @@ fn ensure_packages(&self, pkgs: &[&str], dry_run: bool) -> Result<()> {
 
         tracing::info!("Ensuring packages via pacman: {}", pkgs.join(", "));
 
-        cmd::Command::new("pacman")
-            .sudo()
-            .args(["-S", "--noconfirm", "--needed"])
-            .args(pkgs)
-            .dry_run(dry_run)
-            .execute()
-            .context("running pacman -S")
-            .map_err(|err| anyhow!("pacman -S failed for {}: {err}", pkgs.join(" ")))?;
+        execute(
+            cmd::Command::new("pacman")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["-S", "--noconfirm", "--needed"])
+                .args(pkgs)
+                .dry_run(dry_run),
+        )
+        .context("running pacman -S")
+        .map_err(|err| anyhow!("pacman -S failed for {}: {err}", pkgs.join(" ")))?;
         Ok(())
     }
 
@@ -179,19 +336,59 @@ fn try_optional(&self, pkg: &str, dry_run: bool) {
         if self.is_installed(pkg) {
             return;
         }
-        let res = cmd::Command::new("pacman")
-            .sudo()
-            .args(["-S", "--noconfirm", "--needed", pkg])
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .dry_run(dry_run)
-            .execute();
+        let res = execute(
+            cmd::Command::new("pacman")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["-S", "--noconfirm", "--needed", pkg])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .dry_run(dry_run),
+        );
 
         match res {
             Ok(_) => tracing::info!("Installed optional package: {pkg}"),
             Err(_) => tracing::warn!("Optional package '{pkg}' not available; skipping"),
         }
     }
+
+    fn remove_packages(&self, pkgs: &[&str]) -> Result<()> {
+        tracing::info!("Removing packages: {}", pkgs.join(", "));
+        execute(
+            cmd::Command::new("pacman")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["-Rns", "--noconfirm"])
+                .args(pkgs),
+        )
+        .context("running pacman -Rns")?;
+        Ok(())
+    }
+
+    /// `pacman -Qg` succeeds once any member is installed, so the group
+    /// counts only when every member the sync database lists is present.
+    fn is_group_installed(&self, group: &str) -> bool {
+        let members = |query: &str| {
+            cmd::Command::new("pacman")
+                .args([query, group])
+                .stderr(std::process::Stdio::null())
+                .execute()
+                .ok()
+                .map(|out| {
+                    String::from_utf8_lossy(&out.stdout)
+                        .split_whitespace()
+                        .map(str::to_string)
+                        .collect::<BTreeSet<_>>()
+                })
+        };
+        match (members("-Sgq"), members("-Qgq")) {
+            (Some(all), Some(installed)) => !all.is_empty() && all.is_subset(&installed),
+            _ => false,
+        }
+    }
+
+    // `pacman -S` and `-Rns` expand group names to every member under
+    // `--noconfirm`, so the package paths handle groups as-is.
 }
 
 impl PackageInstaller for DnfInstaller {
@@ -207,11 +404,13 @@ fn is_installed(&self, pkg: &str) -> bool {
     fn update(&self, dry_run: bool) -> Result<()> {
         // dnf check-update returns 100 if updates are available, 0 if none, error otherwise.
         // We just run it to sync metadata.
-        let _ = cmd::Command::new("dnf")
-            .sudo()
-            .args(["check-update", "-q"])
-            .dry_run(dry_run)
-            .execute();
+        let _ = execute(
+            cmd::Command::new("dnf")
+                .sudo()
+                .timeout(REFRESH_TIMEOUT)
+                .args(["check-update", "-q"])
+                .dry_run(dry_run),
+        );
         Ok(())
     }
 
@@ -229,14 +428,16 @@ fn ensure_packages(&self, pkgs: &[&str], dry_run: bool) -> Result<()> {
 
         tracing::info!("Installing packages via dnf: {}", missing.join(", "));
 
-        cmd::Command::new("dnf")
-            .sudo()
-            .args(["install", "-y"])
-            .args(&missing)
-            .dry_run(dry_run)
-            .execute()
-            .context("running dnf install")
-            .map_err(|err| anyhow!("dnf install failed for {}: {err}", missing.join(" ")))?;
+        execute(
+            cmd::Command::new("dnf")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["install", "-y"])
+                .args(&missing)
+                .dry_run(dry_run),
+        )
+        .context("running dnf install")
+        .map_err(|err| anyhow!("dnf install failed for {}: {err}", missing.join(" ")))?;
         Ok(())
     }
 
@@ -244,17 +445,150 @@ fn try_optional(&self, pkg: &str, dry_run: bool) {
         if self.is_installed(pkg) {
             return;
         }
-        let res = cmd::Command::new("dnf")
-            .sudo()
-            .args(["install", "-y", pkg])
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .dry_run(dry_run)
-            .execute();
+        let res = execute(
+            cmd::Command::new("dnf")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["install", "-y", pkg])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .dry_run(dry_run),
+        );
 
         match res {
             Ok(_) => tracing::info!("Installed optional package: {pkg}"),
             Err(_) => tracing::warn!("Optional package '{pkg}' not available; skipping"),
         }
     }
+
+    fn remove_packages(&self, pkgs: &[&str]) -> Result<()> {
+        tracing::info!("Removing packages: {}", pkgs.join(", "));
+        execute(
+            cmd::Command::new("dnf")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["remove", "-y"])
+                .args(pkgs),
+        )
+        .context("running dnf remove")?;
+        Ok(())
+    }
+
+    fn is_group_installed(&self, group: &str) -> bool {
+        let id = group.trim_start_matches('@');
+        // dnf4 lists environments under `group list`; dnf5 splits them out.
+        [["group", "list"], ["environment", "list"]]
+            .iter()
+            .any(|listing| {
+                cmd::Command::new("dnf")
+                    .args(["-q"])
+                    .args(listing)
+                    .args(["--installed", "--ids"])
+                    .stderr(std::process::Stdio::null())
+                    .execute()
+                    .is_ok_and(|out| group_listed(&String::from_utf8_lossy(&out.stdout), id))
+            })
+    }
+
+    fn ensure_groups(&self, groups: &[&str], dry_run: bool) -> Result<()> {
+        let specs: Vec<String> = groups.iter().map(|group| group_spec(group)).collect();
+        tracing::info!("Installing groups via dnf: {}", specs.join(", "));
+        execute(
+            cmd::Command::new("dnf")
+                .sudo()
+                .timeout(INSTALL_TIMEOUT)
+                .args(["install", "-y"])
+                .args(&specs)
+                .dry_run(dry_run),
+        )
+        .context("running dnf install")
+        .map_err(|err| anyhow!("dnf install failed for {}: {err}", specs.join(" ")))?;
+        Ok(())
+    }
+
+    fn remove_groups(&self, groups: &[&str]) -> Result<()> {
+        let specs: Vec<String> = groups.iter().map(|group| group_spec(group)).collect();
+        self.remove_packages(&specs.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+}
+
+fn group_spec(group: &str) -> String {
+    format!("@{}", group.trim_start_matches('@'))
+}
+
+/// Whether a `dnf group list --ids` style listing mentions group `id`,
+/// either bare (dnf5) or in parentheses after the name (dnf4).
+fn group_listed(listing: &str, id: &str) -> bool {
+    listing
+        .split_whitespace()
+        .any(|token| token.trim_start_matches('(').trim_end_matches(')') == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RecordingRunner, ScriptedOutput};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    #[test]
+    fn package_manager_commands_wait_for_the_lock() {
+        let guard = lock();
+        let done = Arc::new(AtomicBool::new(false));
+        let worker = {
+            let done = Arc::clone(&done);
+            std::thread::spawn(move || {
+                execute(cmd::Command::new("apt-get").sudo().dry_run(true)).unwrap();
+                done.store(true, Ordering::SeqCst);
+            })
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!done.load(Ordering::SeqCst), "ran while the lock was held");
+        drop(guard);
+        worker.join().unwrap();
+        assert!(done.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn partly_installed_pacman_groups_are_not_installed() {
+        let partial: &'static RecordingRunner = Box::leak(Box::new(
+            RecordingRunner::new()
+                .on(
+                    "pacman -Sgq gnome",
+                    ScriptedOutput::success("gdm\nmutter\nnautilus\n"),
+                )
+                .on(
+                    "pacman -Qgq gnome",
+                    ScriptedOutput::success("gdm\nnautilus\n"),
+                ),
+        ));
+        let _guard = cmd::use_runner(partial);
+        assert!(!PACMAN_INSTALLER.is_group_installed("gnome"));
+
+        let complete: &'static RecordingRunner = Box::leak(Box::new(
+            RecordingRunner::new()
+                .on(
+                    "pacman -Sgq gnome",
+                    ScriptedOutput::success("gdm\nmutter\nnautilus\n"),
+                )
+                .on(
+                    "pacman -Qgq gnome",
+                    ScriptedOutput::success("gdm\nmutter\nnautilus\n"),
+                ),
+        ));
+        let _guard = cmd::use_runner(complete);
+        assert!(PACMAN_INSTALLER.is_group_installed("gnome"));
+    }
+
+    #[test]
+    fn dnf_group_listing_matches_both_formats() {
+        let dnf4 =
+            "Installed Environment Groups:\n   KDE Plasma Workspaces (kde-desktop-environment)\n";
+        let dnf5 = "ID                     Name           Installed\nxfce-desktop           Xfce                 yes\n";
+        assert!(group_listed(dnf4, "kde-desktop-environment"));
+        assert!(group_listed(dnf5, "xfce-desktop"));
+        assert!(!group_listed(dnf5, "kde-desktop"));
+        assert_eq!(group_spec("kde-desktop"), "@kde-desktop");
+        assert_eq!(group_spec("@kde-desktop"), "@kde-desktop");
+    }
 }
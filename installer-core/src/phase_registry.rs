@@ -3,7 +3,9 @@
 use crate::buildroot;
 use crate::chezmoi;
 use crate::context::UserOptionsContext;
+use crate::desktop::{self, DesktopEnvironment};
 use crate::docker;
+use crate::drift;
 use crate::fonts;
 use crate::github;
 use crate::localization::Localization;
@@ -11,6 +13,7 @@
 use crate::phase_runner::{FunctionPhase, Phase, PhaseResult};
 use crate::phases::wallpapers;
 use crate::pi4b;
+use crate::pi5;
 use crate::pkg;
 use crate::rclone;
 use crate::rust;
@@ -18,7 +21,7 @@
 use crate::software_tiers;
 use crate::zsh;
 use crate::PhaseContext;
-use anyhow::Result;
+use anyhow::{bail, Result};
 
 /// Metadata-driven registry of installer phases.
 pub struct PhaseRegistry {
@@ -33,6 +36,106 @@ fn new(entries: Vec<PhaseEntry>) -> Self {
         Self { entries }
     }
 
+    /// Keep only the entries with the given keys, plus everything they depend on;
+    /// an empty list keeps all.
+    pub fn only(mut self, keys: &[String]) -> Self {
+        if !keys.is_empty() {
+            let mut wanted: Vec<&str> = keys.iter().map(String::as_str).collect();
+            let mut i = 0;
+            while let Some(key) = wanted.get(i).copied() {
+                if let Some(entry) = self.entries.iter().find(|entry| entry.key == key) {
+                    for dep in entry.deps {
+                        if !wanted.contains(dep) {
+                            wanted.push(dep);
+                        }
+                    }
+                }
+                i += 1;
+            }
+            self.entries.retain(|entry| wanted.contains(&entry.key));
+        }
+        self
+    }
+
+    /// Drop the entries with the given keys.
+    pub fn skip(mut self, keys: &[String]) -> Self {
+        self.entries
+            .retain(|entry| !keys.iter().any(|key| key == entry.key));
+        self
+    }
+
+    /// Ids of the registered phases, in registry order.
+    pub fn ids(&self) -> Vec<&'static str> {
+        self.entries.iter().map(|entry| entry.key).collect()
+    }
+
+    /// Every registered phase in registry order, labelled through `strings`.
+    pub fn phases(&self, strings: &Localization) -> Vec<PhaseInfo> {
+        self.entries
+            .iter()
+            .map(|entry| PhaseInfo {
+                id: entry.key,
+                label: strings
+                    .phase_or_default(entry.key, entry.label, entry.description)
+                    .label,
+                deps: entry.deps,
+            })
+            .collect()
+    }
+
+    /// Fail on any key that names no registered phase.
+    pub fn check_ids(&self, keys: &[String]) -> Result<()> {
+        let unknown: Vec<&str> = keys
+            .iter()
+            .filter(|key| !self.entries.iter().any(|entry| entry.key == key.as_str()))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            bail!(
+                "unknown phase id(s): {}; known ids: {}",
+                unknown.join(", "),
+                self.ids().join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Fail when `skip` leaves out a phase that another phase depends on,
+    /// where both would otherwise run under `options`.
+    pub fn check_selection(
+        &self,
+        only: &[String],
+        skip: &[String],
+        options: &UserOptionsContext,
+    ) -> Result<()> {
+        let kept = Self::new(self.entries.clone()).only(only).skip(skip);
+        let gated_in = |key: &str| {
+            self.entries
+                .iter()
+                .any(|entry| entry.key == key && entry.should_run(options))
+        };
+        let missing: Vec<String> = kept
+            .entries
+            .iter()
+            .filter(|entry| entry.should_run(options))
+            .flat_map(|entry| {
+                entry
+                    .deps
+                    .iter()
+                    .filter(|dep| !kept.entries.iter().any(|kept| kept.key == **dep))
+                    .filter(|dep| gated_in(dep))
+                    .map(move |dep| format!("{} (needed by {})", dep, entry.key))
+            })
+            .collect();
+        if !missing.is_empty() {
+            bail!(
+                "cannot skip phase(s) that other selected phases depend on: {}; skip the dependent phases too",
+                missing.join(", ")
+            );
+        }
+        Ok(())
+    }
+
     pub fn build_phases(
         &self,
         options: &UserOptionsContext,
@@ -92,7 +195,8 @@ fn default() -> Self {
                 "Pre-install snapshot ready",
                 snapshots::install_phase,
                 PhaseGate::Always,
-            ),
+            )
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "system_packages",
                 "System packages",
@@ -100,7 +204,8 @@ fn default() -> Self {
                 pkg::install_phase,
                 PhaseGate::Always,
             )
-            .with_deps(&["snapshots"]),
+            .with_deps(&["snapshots"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "rust_toolchain",
                 "Rust toolchain + cargo tools",
@@ -108,7 +213,8 @@ fn default() -> Self {
                 rust::install_phase,
                 PhaseGate::Always,
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "git_cli",
                 "Git, GitHub CLI, SSH",
@@ -116,7 +222,8 @@ fn default() -> Self {
                 github::install_phase,
                 PhaseGate::Always,
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "fonts",
                 "Fonts",
@@ -124,7 +231,8 @@ fn default() -> Self {
                 fonts::install_phase,
                 PhaseGate::Always,
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "pi4b_hdd_tuning",
                 "Pi 4B HDD Tuning",
@@ -132,7 +240,17 @@ fn default() -> Self {
                 pi4b::install_phase,
                 PhaseGate::Always,
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
+            PhaseEntry::new(
+                "pi5_tuning",
+                "Pi 5 Tuning",
+                "Pi 5 boot config tuned",
+                pi5::install_phase,
+                PhaseGate::Always,
+            )
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "shell_ux",
                 "Shell & UX (zsh, starship)",
@@ -140,7 +258,8 @@ fn default() -> Self {
                 zsh::install_phase,
                 PhaseGate::Profile(ProfileLevel::Dev),
             )
-            .with_deps(&["system_packages", "git_cli"]),
+            .with_deps(&["system_packages", "git_cli"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "docker_engine",
                 "Docker Engine",
@@ -148,7 +267,8 @@ fn default() -> Self {
                 docker::install_phase,
                 PhaseGate::Profile(ProfileLevel::Dev),
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "buildroot_dependencies",
                 "Buildroot dependencies",
@@ -156,7 +276,8 @@ fn default() -> Self {
                 buildroot::install_phase,
                 PhaseGate::Profile(ProfileLevel::Dev),
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "software_tiers",
                 "Curated software tiers",
@@ -164,7 +285,8 @@ fn default() -> Self {
                 software_tiers::install_phase,
                 PhaseGate::SoftwareTiers,
             )
-            .with_deps(&["system_packages", "rust_toolchain", "git_cli"]),
+            .with_deps(&["system_packages", "rust_toolchain", "git_cli"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "wallpapers",
                 "Wallpapers",
@@ -172,7 +294,8 @@ fn default() -> Self {
                 wallpapers::install_phase,
                 PhaseGate::SoftwareTiers,
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "ai_spirits",
                 "AI Spirits",
@@ -180,7 +303,8 @@ fn default() -> Self {
                 ai_agents::install_phase,
                 PhaseGate::Always,
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "rclone",
                 "rclone",
@@ -188,7 +312,17 @@ fn default() -> Self {
                 rclone::install_phase,
                 PhaseGate::Profile(ProfileLevel::Dev),
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
+            PhaseEntry::new(
+                "desktop_environment",
+                "Desktop environment",
+                "Desktop environment installed",
+                desktop::install_phase,
+                PhaseGate::Desktop,
+            )
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "argon_one",
                 "Argon One fan script",
@@ -196,7 +330,8 @@ fn default() -> Self {
                 argon::install_phase,
                 PhaseGate::ModuleArgon,
             )
-            .with_deps(&["system_packages"]),
+            .with_deps(&["system_packages"])
+            .with_dry_run_gate(),
             PhaseEntry::new(
                 "chezmoi",
                 "Chezmoi dotfile restoration",
@@ -204,11 +339,43 @@ fn default() -> Self {
                 chezmoi::install_phase,
                 PhaseGate::Chezmoi,
             )
-            .with_deps(&["system_packages", "git_cli"]),
+            .with_deps(&["system_packages", "git_cli"])
+            .with_dry_run_gate(),
+            PhaseEntry::new(
+                "verify",
+                "Post-install verification",
+                "Machine matches the plan",
+                drift::install_phase,
+                PhaseGate::Verify,
+            )
+            // Every phase `drift::expected_state` checks, so parallel runs
+            // only verify once they have finished.
+            .with_deps(&[
+                "system_packages",
+                "rust_toolchain",
+                "fonts",
+                "shell_ux",
+                "docker_engine",
+                "software_tiers",
+                "desktop_environment",
+                "argon_one",
+                "chezmoi",
+            ])
+            .with_dry_run_gate(),
         ])
     }
 }
 
+/// A registered phase as the CLI and TUI list it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhaseInfo {
+    /// Stable id, as accepted by `--only` and `--skip`.
+    pub id: &'static str,
+    pub label: String,
+    pub deps: &'static [&'static str],
+}
+
+#[derive(Clone)]
 struct PhaseEntry {
     key: &'static str,
     label: &'static str,
@@ -216,6 +383,7 @@ struct PhaseEntry {
     run: fn(&mut PhaseContext) -> Result<PhaseResult>,
     gate: PhaseGate,
     deps: &'static [&'static str],
+    dry_run_gate: bool,
 }
 
 impl PhaseEntry {
@@ -233,6 +401,7 @@ fn new(
             run,
             gate,
             deps: &[],
+            dry_run_gate: false,
         }
     }
 
@@ -241,16 +410,26 @@ fn with_deps(mut self, deps: &'static [&'static str]) -> Self {
         self
     }
 
+    /// The phase function checks `dry_run` itself; see
+    /// [`Phase::has_dry_run_gate`].
+    fn with_dry_run_gate(mut self) -> Self {
+        self.dry_run_gate = true;
+        self
+    }
+
     fn should_run(&self, options: &UserOptionsContext) -> bool {
         self.gate.should_run(options)
     }
 
     fn to_phase(&self, strings: &Localization) -> Box<dyn Phase> {
         let entry = strings.phase_or_default(self.key, self.label, self.description);
-        Box::new(
-            FunctionPhase::new(entry.label, entry.description, self.run)
-                .with_deps(self.deps.to_vec()),
-        )
+        let phase = FunctionPhase::new(self.key, entry.label, entry.description, self.run)
+            .with_deps(self.deps.to_vec());
+        if self.dry_run_gate {
+            Box::new(phase.with_dry_run_gate())
+        } else {
+            Box::new(phase)
+        }
     }
 }
 
@@ -261,6 +440,8 @@ enum PhaseGate {
     ModuleArgon,
     SoftwareTiers,
     Chezmoi,
+    Desktop,
+    Verify,
 }
 
 impl PhaseGate {
@@ -271,6 +452,10 @@ fn should_run(&self, options: &UserOptionsContext) -> bool {
             PhaseGate::ModuleArgon => options.argon.enabled,
             PhaseGate::SoftwareTiers => !options.software_plan.is_empty(),
             PhaseGate::Chezmoi => options.chezmoi.enabled,
+            PhaseGate::Desktop => options
+                .desktop_environment
+                .is_some_and(|de| de != DesktopEnvironment::None),
+            PhaseGate::Verify => options.verify,
         }
     }
 }
@@ -307,6 +492,8 @@ fn test_phase_registry_sorting() -> Result<()> {
             chezmoi: Default::default(),
             desktop_environment: None,
             display_protocol: crate::desktop::DisplayProtocol::Auto,
+            verify: false,
+            allow_unverified_scripts: false,
         };
 
         let strings = Localization::load_default()?;
@@ -320,4 +507,160 @@ fn test_phase_registry_sorting() -> Result<()> {
 
         Ok(())
     }
+
+    #[test]
+    fn only_keeps_the_listed_phases_and_their_dependencies() -> Result<()> {
+        let strings = Localization::load_default()?;
+        let options = UserOptionsContext::from_options(&crate::InstallOptions {
+            profile: ProfileLevel::Dev,
+            ..Default::default()
+        });
+        let phases = PhaseRegistry::default()
+            .only(&["shell_ux".to_string(), "rust_toolchain".to_string()])
+            .build_phases(&options, &strings);
+        let mut ids: Vec<_> = phases.iter().map(|phase| phase.id()).collect();
+        ids.sort_unstable();
+        assert_eq!(
+            ids,
+            [
+                "git_cli",
+                "rust_toolchain",
+                "shell_ux",
+                "snapshots",
+                "system_packages"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn skipping_a_needed_dependency_is_rejected() {
+        let registry = PhaseRegistry::default();
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        let options = |profile, verify, software_plan| {
+            UserOptionsContext::from_options(&crate::InstallOptions {
+                profile,
+                verify,
+                software_plan,
+                ..Default::default()
+            })
+        };
+        let no_tiers = SoftwareTierPlan {
+            target_tier: None,
+            ..SoftwareTierPlan::default()
+        };
+        let dev = options(ProfileLevel::Dev, false, SoftwareTierPlan::default());
+
+        let err = registry
+            .check_selection(&[], &ids(&["system_packages"]), &dev)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("system_packages (needed by rust_toolchain)"));
+        let err = registry
+            .check_selection(&ids(&["shell_ux"]), &ids(&["git_cli"]), &dev)
+            .unwrap_err();
+        assert!(err.to_string().contains("git_cli (needed by shell_ux)"));
+
+        assert!(registry
+            .check_selection(&ids(&["shell_ux"]), &[], &dev)
+            .is_ok());
+        assert!(registry
+            .check_selection(&[], &ids(&["wallpapers", "rclone"]), &dev)
+            .is_ok());
+        assert!(registry
+            .check_selection(
+                &[],
+                &ids(&["git_cli", "shell_ux", "software_tiers", "chezmoi"]),
+                &dev
+            )
+            .is_ok());
+
+        // Only phases that will run hold on to their dependencies.
+        assert!(registry
+            .check_selection(&[], &ids(&["docker_engine"]), &dev)
+            .is_ok());
+        let verifying = options(ProfileLevel::Dev, true, SoftwareTierPlan::default());
+        let err = registry
+            .check_selection(&[], &ids(&["docker_engine"]), &verifying)
+            .unwrap_err();
+        assert!(err.to_string().contains("docker_engine (needed by verify)"));
+        let err = registry
+            .check_selection(&[], &ids(&["rust_toolchain"]), &dev)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("rust_toolchain (needed by software_tiers)"));
+        assert!(registry
+            .check_selection(
+                &[],
+                &ids(&["rust_toolchain"]),
+                &options(ProfileLevel::Dev, false, no_tiers.clone())
+            )
+            .is_ok());
+        // shell_ux is gated off on minimal, so leaving it out changes nothing.
+        assert!(registry
+            .check_selection(
+                &[],
+                &ids(&["shell_ux"]),
+                &options(ProfileLevel::Minimal, true, no_tiers)
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn ids_stay_stable_under_localized_labels() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("strings.toml");
+        std::fs::write(
+            &path,
+            "[phases.system_packages]\nlabel = \"Systempakete\"\ndescription = \"Systempakete installiert\"\n",
+        )?;
+        let strings = Localization::load_from_path(&path)?;
+        let options = UserOptionsContext::from_options(&crate::InstallOptions::default());
+
+        let phases = PhaseRegistry::default()
+            .skip(&["snapshots".to_string()])
+            .build_phases(&options, &strings);
+        assert_eq!(phases[0].id(), "system_packages");
+        assert_eq!(phases[0].name(), "Systempakete");
+        assert!(phases.iter().all(|phase| phase.id() != "snapshots"));
+
+        let registry = PhaseRegistry::default();
+        let listed = registry.phases(&strings);
+        assert_eq!(listed[1].id, "system_packages");
+        assert_eq!(listed[1].label, "Systempakete");
+        assert!(registry.check_ids(&["shell_ux".to_string()]).is_ok());
+        let err = registry
+            .check_ids(&["Shell & UX (zsh, starship)".to_string()])
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("unknown phase id(s): Shell & UX (zsh, starship); known ids: snapshots,"));
+        Ok(())
+    }
+
+    #[test]
+    fn verify_phase_is_opt_in_and_runs_last() -> Result<()> {
+        let strings = Localization::load_default()?;
+        let mut options = UserOptionsContext::from_options(&crate::InstallOptions {
+            profile: ProfileLevel::Dev,
+            ..Default::default()
+        });
+        let names = |options: &UserOptionsContext| -> Vec<String> {
+            PhaseRegistry::default()
+                .build_phases(options, &strings)
+                .iter()
+                .map(|phase| phase.name().to_string())
+                .collect()
+        };
+
+        assert!(!names(&options).contains(&"Post-install verification".to_string()));
+        options.verify = true;
+        assert_eq!(
+            names(&options).last().map(String::as_str),
+            Some("Post-install verification")
+        );
+        Ok(())
+    }
 }
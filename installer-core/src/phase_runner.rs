@@ -1,14 +1,21 @@
 use std::fmt;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
 
 pub use crate::model::phase::{PhaseEvent, PhaseObserver, PhaseOutput, PhaseStatus};
 use anyhow::Result as AnyhowResult;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::{
-    context::PhaseContext,
+    context::{PhaseContext, PhaseMetadata},
     error::{ErrorSeverity, InstallerError, InstallerStateSnapshot},
+    journal::RunJournal,
     logging,
+    model::phase::AuthType,
     signal::SignalGuard,
+    system::{cmd, progress},
     InstallContext,
 };
 
@@ -45,9 +52,139 @@ pub enum PhaseErrorPolicy {
     ContinueOnError,
 }
 
+/// How the runner dispatches phases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PhaseSchedule {
+    /// One phase at a time, in list order.
+    #[default]
+    Sequential,
+    /// Run phases whose dependencies are settled on up to `workers` threads.
+    ///
+    /// A dependency that does not name a phase in this run is treated
+    /// conservatively: the phase waits for every phase listed before it.
+    Parallel { workers: usize },
+}
+
 pub struct PhaseRunner {
     phases: Vec<Box<dyn Phase>>,
     policy: PhaseErrorPolicy,
+    schedule: PhaseSchedule,
+    journal: Option<Mutex<RunJournal>>,
+    planning: bool,
+}
+
+/// Progress accumulated while a run is in flight.
+#[derive(Default)]
+struct RunState {
+    events: Vec<PhaseEvent>,
+    completed: Vec<String>,
+    errors: Vec<InstallerError>,
+    outputs: Vec<PhaseOutput>,
+}
+
+impl RunState {
+    fn emit(&mut self, observer: &mut dyn PhaseObserver, event: PhaseEvent) {
+        if !event.is_transient() {
+            self.events.push(event.clone());
+        }
+        observer.on_event(event);
+    }
+
+    /// Pass a worker's message on to `observer`, handing back the phase
+    /// outcome once the worker reports it finished.
+    fn relay(
+        &mut self,
+        observer: &mut dyn PhaseObserver,
+        message: WorkerMessage,
+    ) -> Option<(usize, AnyhowResult<PhaseResult>, PhaseMetadata)> {
+        match message {
+            WorkerMessage::Event(event) => self.emit(observer, event),
+            WorkerMessage::Confirm { prompt, reply } => {
+                let _ = reply.send(observer.confirm(&prompt));
+            }
+            WorkerMessage::SudoPassword { reply } => {
+                let _ = reply.send(observer.sudo_password());
+            }
+            WorkerMessage::RequestAuth { auth_type, reply } => {
+                let _ = reply.send(observer.request_auth(auth_type));
+            }
+            WorkerMessage::Finished {
+                index,
+                result,
+                metadata,
+            } => return Some((index, result, metadata)),
+        }
+        None
+    }
+
+    fn into_result(self) -> PhaseRunResult {
+        PhaseRunResult {
+            completed_phases: self.completed,
+            outputs: self.outputs,
+            events: self.events,
+            errors: self.errors,
+        }
+    }
+}
+
+/// Messages sent from worker threads back to the coordinating thread.
+enum WorkerMessage {
+    Event(PhaseEvent),
+    Confirm {
+        prompt: String,
+        reply: Sender<bool>,
+    },
+    SudoPassword {
+        reply: Sender<AnyhowResult<String>>,
+    },
+    RequestAuth {
+        auth_type: AuthType,
+        reply: Sender<AnyhowResult<bool>>,
+    },
+    Finished {
+        index: usize,
+        result: AnyhowResult<PhaseResult>,
+        metadata: PhaseMetadata,
+    },
+}
+
+/// Observer handed to phases running on a worker thread.
+///
+/// Everything is forwarded to the coordinator so the real observer is only
+/// ever touched from one thread and prompts are answered one at a time.
+struct ForwardingObserver {
+    tx: Sender<WorkerMessage>,
+}
+
+impl PhaseObserver for ForwardingObserver {
+    fn on_event(&mut self, event: PhaseEvent) {
+        let _ = self.tx.send(WorkerMessage::Event(event));
+    }
+
+    fn confirm(&mut self, prompt: &str) -> bool {
+        let (reply, rx) = mpsc::channel();
+        let _ = self.tx.send(WorkerMessage::Confirm {
+            prompt: prompt.to_string(),
+            reply,
+        });
+        rx.recv().unwrap_or(false)
+    }
+
+    fn sudo_password(&mut self) -> AnyhowResult<String> {
+        let (reply, rx) = mpsc::channel();
+        let _ = self.tx.send(WorkerMessage::SudoPassword { reply });
+        rx.recv()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("installer coordinator went away")))
+    }
+
+    fn request_auth(&mut self, auth_type: AuthType) -> AnyhowResult<bool> {
+        let (reply, rx) = mpsc::channel();
+        let _ = self
+            .tx
+            .send(WorkerMessage::RequestAuth { auth_type, reply });
+        rx.recv()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("installer coordinator went away")))
+    }
 }
 
 impl PhaseRunner {
@@ -56,7 +193,62 @@ pub fn from_phases(phases: Vec<Box<dyn Phase>>) -> Self {
     }
 
     pub fn with_policy(phases: Vec<Box<dyn Phase>>, policy: PhaseErrorPolicy) -> Self {
-        Self { phases, policy }
+        Self {
+            phases,
+            policy,
+            schedule: PhaseSchedule::default(),
+            journal: None,
+            planning: false,
+        }
+    }
+
+    pub fn with_schedule(mut self, schedule: PhaseSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// In dry runs, execute the phases instead of simulating each one
+    /// wholesale, so their own dry-run gates report what they would change.
+    /// Used by `mash-setup plan`.
+    pub fn with_planning(mut self) -> Self {
+        self.planning = true;
+        self
+    }
+
+    /// Persist per-phase progress to `journal`.
+    ///
+    /// Phases the journal already lists as `Completed` are skipped, which is
+    /// how `--resume` picks up at the first failed or pending phase.
+    pub fn with_journal(mut self, journal: RunJournal) -> Self {
+        self.journal = Some(Mutex::new(journal));
+        self
+    }
+
+    /// Snapshot of the journal as it stands after the run.
+    pub fn journal(&self) -> Option<RunJournal> {
+        self.journal
+            .as_ref()
+            .and_then(|journal| journal.lock().ok().map(|j| j.clone()))
+    }
+
+    fn already_completed(&self, phase: &str) -> bool {
+        self.journal
+            .as_ref()
+            .and_then(|journal| journal.lock().ok().map(|j| j.is_completed(phase)))
+            .unwrap_or(false)
+    }
+
+    fn update_journal(&self, update: impl FnOnce(&mut RunJournal)) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        let Ok(mut journal) = journal.lock() else {
+            return;
+        };
+        update(&mut journal);
+        if let Err(e) = journal.save() {
+            warn!("failed to persist run journal: {e:#}");
+        }
     }
 
     pub fn run(
@@ -64,261 +256,512 @@ pub fn run(
         ctx: &InstallContext,
         observer: &mut dyn PhaseObserver,
         signal_guard: Option<&SignalGuard>,
+    ) -> Result<PhaseRunResult, Box<PhaseRunError>> {
+        match self.schedule {
+            PhaseSchedule::Parallel { workers } if workers > 1 => {
+                self.run_parallel(ctx, observer, signal_guard, workers)
+            }
+            _ => self.run_sequential(ctx, observer, signal_guard),
+        }
+    }
+
+    fn run_sequential(
+        &self,
+        ctx: &InstallContext,
+        observer: &mut dyn PhaseObserver,
+        signal_guard: Option<&SignalGuard>,
     ) -> Result<PhaseRunResult, Box<PhaseRunError>> {
         let total = self.phases.len();
+        let mut state = RunState::default();
+        state.emit(observer, PhaseEvent::Total { total });
+
+        for i in 0..total {
+            // Check for interrupt signal between phases
+            if signal_guard.is_some_and(|sg| sg.is_interrupted()) {
+                return Err(self.interrupted(ctx, state));
+            }
 
-        fn emit_event(
-            observer: &mut dyn PhaseObserver,
-            events: &mut Vec<PhaseEvent>,
-            event: PhaseEvent,
-        ) {
-            observer.on_event(event.clone());
-            events.push(event);
+            if self.settle_without_running(ctx, i, &mut state, observer) {
+                continue;
+            }
+
+            self.start_phase(i, &mut state, observer);
+            let (result, metadata) = self.run_inline(ctx, i, &mut state, observer);
+            if let Some(source) = self.finish_phase(ctx, i, result, metadata, &mut state, observer)
+            {
+                return Err(self.abort(ctx, state, source));
+            }
         }
 
-        let mut events = Vec::new();
-        emit_event(observer, &mut events, PhaseEvent::Total { total });
-        let mut completed = Vec::new();
-        let mut errors = Vec::new();
-        let mut outputs = Vec::new();
+        Ok(state.into_result())
+    }
 
-        'phase_loop: for (i, phase) in self.phases.iter().enumerate() {
-            // Check for interrupt signal between phases
+    /// Run phase `index` on its own thread while relaying its events and
+    /// prompts, so progress is reported even while the phase blocks.
+    fn run_inline(
+        &self,
+        ctx: &InstallContext,
+        index: usize,
+        state: &mut RunState,
+        observer: &mut dyn PhaseObserver,
+    ) -> (AnyhowResult<PhaseResult>, PhaseMetadata) {
+        let (tx, rx) = mpsc::channel::<WorkerMessage>();
+        let phase = self.phases[index].as_ref();
+        let planning = self.planning;
+        thread::scope(|scope| {
+            scope.spawn(move || run_worker(ctx, index, phase, planning, tx));
+            loop {
+                match rx.recv() {
+                    Ok(message) => {
+                        if let Some((_, result, metadata)) = state.relay(observer, message) {
+                            return (result, metadata);
+                        }
+                    }
+                    Err(_) => {
+                        return (
+                            Err(anyhow::anyhow!("{} worker exited unexpectedly", phase.id())),
+                            PhaseMetadata::default(),
+                        )
+                    }
+                }
+            }
+        })
+    }
+
+    fn run_parallel(
+        &self,
+        ctx: &InstallContext,
+        observer: &mut dyn PhaseObserver,
+        signal_guard: Option<&SignalGuard>,
+        workers: usize,
+    ) -> Result<PhaseRunResult, Box<PhaseRunError>> {
+        let total = self.phases.len();
+        let mut state = RunState::default();
+        state.emit(observer, PhaseEvent::Total { total });
+
+        let mut started = vec![false; total];
+        let mut settled = vec![false; total];
+        let mut running = 0usize;
+        let mut interrupted = false;
+        let mut fatal: Option<InstallerError> = None;
+        let (tx, rx) = mpsc::channel::<WorkerMessage>();
+
+        thread::scope(|scope| loop {
             if signal_guard.is_some_and(|sg| sg.is_interrupted()) {
-                info!("Signal received, rolling back and shutting down gracefully...");
-                if let Err(rb_err) = ctx.rollback.rollback_all() {
-                    error!("rollback encountered errors during signal shutdown: {rb_err}");
-                } else {
-                    info!("rollback completed after signal");
+                interrupted = true;
+            }
+            let halted = interrupted || fatal.is_some();
+
+            if !halted {
+                let mut dispatched = false;
+                for i in 0..total {
+                    if started[i] || running >= workers {
+                        continue;
+                    }
+                    if !self.dependencies_settled(i, &settled) {
+                        continue;
+                    }
+                    started[i] = true;
+                    dispatched = true;
+                    if self.settle_without_running(ctx, i, &mut state, observer) {
+                        settled[i] = true;
+                        continue;
+                    }
+
+                    self.start_phase(i, &mut state, observer);
+                    running += 1;
+                    let phase = self.phases[i].as_ref();
+                    let planning = self.planning;
+                    let tx = tx.clone();
+                    scope.spawn(move || run_worker(ctx, i, phase, planning, tx));
                 }
-                let installer_error = InstallerError::new(
-                    "signal_handler",
-                    "Interrupted by signal",
-                    ErrorSeverity::Fatal,
-                    anyhow::anyhow!("Installation interrupted by SIGINT/SIGTERM"),
-                    InstallerStateSnapshot::from_options(&ctx.options),
-                    Some("Re-run the installer to resume.".to_string()),
-                );
-                return Err(Box::new(PhaseRunError {
-                    result: PhaseRunResult {
-                        completed_phases: completed,
-                        outputs,
-                        events,
-                        errors,
-                    },
-                    source: installer_error,
-                }));
+
+                // Settling skipped phases can unblock others; rescan before waiting.
+                if dispatched && running == 0 {
+                    continue;
+                }
+                // Nothing ready and nothing running: a dependency cycle or a
+                // dependency on a later phase. Fall back to list order.
+                if !dispatched && running == 0 {
+                    match started.iter().position(|s| !s) {
+                        Some(i) => {
+                            started[i] = true;
+                            settled[i] = true;
+                            if !self.settle_without_running(ctx, i, &mut state, observer) {
+                                self.start_phase(i, &mut state, observer);
+                                let (result, metadata) =
+                                    self.run_inline(ctx, i, &mut state, observer);
+                                if let Some(source) = self
+                                    .finish_phase(ctx, i, result, metadata, &mut state, observer)
+                                {
+                                    fatal.get_or_insert(source);
+                                }
+                            }
+                            continue;
+                        }
+                        None => break,
+                    }
+                }
+            } else if running == 0 {
+                break;
             }
 
-            let phase_name = phase.name().to_string();
-            let phase_description = phase.description().to_string();
-
-            // Prerequisite Gate: Check dependencies
-            for dep in phase.dependencies() {
-                // We only care about dependencies that are part of the current run's phase set.
-                // If a dependency was registered but skipped via should_run, we don't block.
-                // But if it was SUPPOSED to run and FAILED, we must block.
-
-                // Check if this dependency was supposed to run
-                let dep_supposed_to_run = self
-                    .phases
-                    .iter()
-                    .any(|p| p.name() == *dep && p.should_run(ctx));
-
-                if dep_supposed_to_run && !completed.contains(&dep.to_string()) {
-                    emit_event(
-                        observer,
-                        &mut events,
-                        PhaseEvent::Warning {
-                            message: format!(
-                                "Skipping {} because dependency {} was not completed",
-                                phase_name, dep
-                            ),
-                        },
-                    );
-                    emit_event(
-                        observer,
-                        &mut events,
-                        PhaseEvent::Skipped {
-                            index: i + 1,
-                            phase: phase_name.clone(),
-                        },
-                    );
-                    outputs.push(PhaseOutput::skipped(
-                        phase_name.clone(),
-                        phase_description.clone(),
-                        ctx.options.dry_run,
-                    ));
-                    continue 'phase_loop;
+            match rx.recv() {
+                Ok(message) => {
+                    if let Some((index, result, metadata)) = state.relay(observer, message) {
+                        running -= 1;
+                        settled[index] = true;
+                        if let Some(source) =
+                            self.finish_phase(ctx, index, result, metadata, &mut state, observer)
+                        {
+                            fatal.get_or_insert(source);
+                        }
+                    }
                 }
+                Err(_) => break,
             }
+        });
 
-            if !phase.should_run(ctx) {
-                emit_event(
-                    observer,
-                    &mut events,
-                    PhaseEvent::Skipped {
-                        index: i + 1,
-                        phase: phase_name.clone(),
-                    },
-                );
-                outputs.push(PhaseOutput::skipped(
-                    phase_name.clone(),
-                    phase_description.clone(),
-                    ctx.options.dry_run,
-                ));
-                continue;
+        if interrupted {
+            return Err(self.interrupted(ctx, state));
+        }
+        if let Some(source) = fatal {
+            return Err(self.abort(ctx, state, source));
+        }
+        Ok(state.into_result())
+    }
+
+    /// True once every dependency of phase `index` has been settled.
+    fn dependencies_settled(&self, index: usize, settled: &[bool]) -> bool {
+        let phase = &self.phases[index];
+        phase.dependencies().iter().all(|dep| {
+            match self.phases.iter().position(|p| p.id() == *dep) {
+                Some(pos) => settled[pos],
+                None => settled[..index].iter().all(|s| *s),
             }
+        })
+    }
 
-            emit_event(
+    /// Handle phases that finish without executing: resumed, blocked by a
+    /// failed dependency, or gated off by `should_run`.
+    ///
+    /// Returns `true` when the phase has been settled.
+    fn settle_without_running(
+        &self,
+        ctx: &InstallContext,
+        index: usize,
+        state: &mut RunState,
+        observer: &mut dyn PhaseObserver,
+    ) -> bool {
+        let phase = &self.phases[index];
+        let phase_id = phase.id().to_string();
+        let phase_name = phase.name().to_string();
+        let phase_description = phase.description().to_string();
+        let skip = |state: &mut RunState, observer: &mut dyn PhaseObserver| {
+            state.emit(
                 observer,
-                &mut events,
-                PhaseEvent::Started {
-                    index: i + 1,
-                    total,
-                    phase: phase_name.clone(),
+                PhaseEvent::Skipped {
+                    index: index + 1,
+                    phase: phase_id.clone(),
+                    label: phase_name.clone(),
                 },
             );
-            let phase_span = logging::phase_span(ctx, phase.as_ref());
-            let _phase_guard = phase_span.enter();
-            let mut phase_ctx = ctx.phase_context(observer);
-            let phase_result = phase_ctx.run_or_record(
-                phase_name.clone(),
-                "Phase simulated",
-                Some(phase_description.clone()),
-                |phase_ctx| phase.execute(phase_ctx),
-            );
-            let metadata = phase_ctx.take_metadata();
-            let status = match &phase_result {
-                Ok(PhaseResult::Success) => PhaseStatus::Completed,
-                Ok(PhaseResult::PartialSuccess(msg)) => PhaseStatus::PartialSuccess(msg.clone()),
-                Ok(PhaseResult::RecoverableFailure(msg)) => {
-                    PhaseStatus::RecoverableFailure(msg.clone())
-                }
-                Err(err) => PhaseStatus::Failed(err.to_string()),
-            };
-            outputs.push(PhaseOutput::from_metadata(
+            state.outputs.push(PhaseOutput::skipped(
+                phase_id.clone(),
                 phase_name.clone(),
                 phase_description.clone(),
-                metadata,
-                status.clone(),
+                ctx.options.dry_run,
             ));
-            match phase_result {
-                Ok(res) => match res {
-                    PhaseResult::Success => {
-                        emit_event(
-                            observer,
-                            &mut events,
-                            PhaseEvent::Completed {
-                                index: i + 1,
-                                phase: phase_name.clone(),
-                                description: phase_description.clone(),
-                            },
-                        );
-                        completed.push(phase_name.clone());
-                    }
-                    PhaseResult::PartialSuccess(msg) => {
-                        emit_event(
-                            observer,
-                            &mut events,
-                            PhaseEvent::Warning {
-                                message: format!("{}: Partial success - {}", phase_name, msg),
-                            },
-                        );
-                        emit_event(
-                            observer,
-                            &mut events,
-                            PhaseEvent::Completed {
-                                index: i + 1,
-                                phase: phase_name.clone(),
-                                description: phase_description.clone(),
-                            },
-                        );
-                        completed.push(phase_name.clone());
-                    }
-                    PhaseResult::RecoverableFailure(msg) => {
-                        emit_event(
-                            observer,
-                            &mut events,
-                            PhaseEvent::Warning {
-                                message: format!("{}: Recoverable failure - {}", phase_name, msg),
-                            },
-                        );
-                    }
-                },
-                Err(e) => {
-                    let severity = phase.error_severity();
-                    let installer_error = InstallerError::new(
-                        phase_name.clone(),
-                        phase_description.clone(),
-                        severity,
-                        e,
-                        InstallerStateSnapshot::from_options(&ctx.options),
-                        Some(
-                            "Rerun `mash-setup doctor` or remove the staging directory before retrying."
-                                .to_string(),
+        };
+
+        // Resume: phases finished by an earlier run with the same options count as done.
+        if self.already_completed(&phase_id) {
+            info!(
+                "{} already completed in a previous run; skipping",
+                phase_name
+            );
+            skip(state, observer);
+            state.completed.push(phase_id);
+            return true;
+        }
+
+        // Prerequisite Gate: Check dependencies
+        for dep in phase.dependencies() {
+            // Only a dependency that was meant to run in this set and did not
+            // complete blocks; one gated off by the options does not.
+            let dep_supposed_to_run = self
+                .phases
+                .iter()
+                .any(|p| p.id() == *dep && p.should_run(ctx));
+
+            if dep_supposed_to_run && !state.completed.iter().any(|done| done == dep) {
+                state.emit(
+                    observer,
+                    PhaseEvent::Warning {
+                        message: format!(
+                            "Skipping {} because dependency {} was not completed",
+                            phase_name, dep
                         ),
-                    );
-                    let error_message = installer_error.message.clone();
-                    emit_event(
-                        observer,
-                        &mut events,
-                        PhaseEvent::Failed {
-                            index: i + 1,
-                            phase: phase_name.clone(),
-                            error: error_message.clone(),
-                        },
-                    );
-                    errors.push(installer_error.clone());
-                    let completed_list = if completed.is_empty() {
-                        "none".to_string()
-                    } else {
-                        completed.join(", ")
-                    };
-                    error!(
-                        "Installation aborted during {} (staging dir: {}). Completed phases: {}. \
-                         Rerun `mash-setup doctor` or remove the staging directory before retrying.",
-                        phase_name,
-                        ctx.options.staging_dir.display(),
-                        completed_list
-                    );
-                    let should_continue = matches!(self.policy, PhaseErrorPolicy::ContinueOnError)
-                        && severity == ErrorSeverity::Recoverable;
-
-                    if should_continue {
-                        continue;
-                    }
+                    },
+                );
+                skip(state, observer);
+                return true;
+            }
+        }
 
-                    let run_result = PhaseRunResult {
-                        completed_phases: completed,
-                        outputs,
-                        events,
-                        errors,
-                    };
-
-                    if let Err(rb_err) = ctx.rollback.rollback_all() {
-                        error!("rollback encountered errors: {rb_err}");
-                    } else {
-                        info!("rollback completed after failure");
-                    }
+        if !phase.should_run(ctx) {
+            skip(state, observer);
+            return true;
+        }
 
-                    return Err(Box::new(PhaseRunError {
-                        result: run_result,
-                        source: installer_error,
-                    }));
+        false
+    }
+
+    fn start_phase(&self, index: usize, state: &mut RunState, observer: &mut dyn PhaseObserver) {
+        let phase = &self.phases[index];
+        let phase_id = phase.id().to_string();
+        state.emit(
+            observer,
+            PhaseEvent::Started {
+                index: index + 1,
+                total: self.phases.len(),
+                phase: phase_id.clone(),
+                label: phase.name().to_string(),
+            },
+        );
+        self.update_journal(|journal| journal.mark_started(&phase_id));
+    }
+
+    /// Record the outcome of an executed phase.
+    ///
+    /// Returns the error that should abort the run, if any.
+    fn finish_phase(
+        &self,
+        ctx: &InstallContext,
+        index: usize,
+        phase_result: AnyhowResult<PhaseResult>,
+        metadata: PhaseMetadata,
+        state: &mut RunState,
+        observer: &mut dyn PhaseObserver,
+    ) -> Option<InstallerError> {
+        let phase = &self.phases[index];
+        let phase_id = phase.id().to_string();
+        let phase_name = phase.name().to_string();
+        let phase_description = phase.description().to_string();
+
+        let status = match &phase_result {
+            Ok(PhaseResult::Success) => PhaseStatus::Completed,
+            Ok(PhaseResult::PartialSuccess(msg)) => PhaseStatus::PartialSuccess(msg.clone()),
+            Ok(PhaseResult::RecoverableFailure(msg)) => {
+                PhaseStatus::RecoverableFailure(msg.clone())
+            }
+            Err(err) => PhaseStatus::Failed(err.to_string()),
+        };
+        self.update_journal(|journal| journal.mark_finished(&phase_id, status.clone()));
+        let duration_ms = metadata.duration_ms;
+        state.outputs.push(PhaseOutput::from_metadata(
+            phase_id.clone(),
+            phase_name.clone(),
+            phase_description.clone(),
+            metadata,
+            status,
+        ));
+
+        match phase_result {
+            Ok(PhaseResult::Success) => {
+                state.emit(
+                    observer,
+                    PhaseEvent::Completed {
+                        index: index + 1,
+                        phase: phase_id.clone(),
+                        description: phase_description,
+                        duration_ms,
+                    },
+                );
+                state.completed.push(phase_id);
+                None
+            }
+            Ok(PhaseResult::PartialSuccess(msg)) => {
+                state.emit(
+                    observer,
+                    PhaseEvent::Warning {
+                        message: format!("{}: Partial success - {}", phase_name, msg),
+                    },
+                );
+                state.emit(
+                    observer,
+                    PhaseEvent::Completed {
+                        index: index + 1,
+                        phase: phase_id.clone(),
+                        description: phase_description,
+                        duration_ms,
+                    },
+                );
+                state.completed.push(phase_id);
+                None
+            }
+            Ok(PhaseResult::RecoverableFailure(msg)) => {
+                state.emit(
+                    observer,
+                    PhaseEvent::Warning {
+                        message: format!("{}: Recoverable failure - {}", phase_name, msg),
+                    },
+                );
+                None
+            }
+            Err(e) => {
+                let severity = phase.error_severity();
+                let installer_error = InstallerError::new(
+                    phase_id.clone(),
+                    phase_description,
+                    severity,
+                    e,
+                    InstallerStateSnapshot::from_options(&ctx.options),
+                    Some(
+                        "Rerun `mash-setup doctor` or remove the staging directory before retrying."
+                            .to_string(),
+                    ),
+                );
+                state.emit(
+                    observer,
+                    PhaseEvent::Failed {
+                        index: index + 1,
+                        phase: phase_id.clone(),
+                        error: installer_error.message.clone(),
+                        duration_ms,
+                    },
+                );
+                state.errors.push(installer_error.clone());
+                let completed_list = if state.completed.is_empty() {
+                    "none".to_string()
+                } else {
+                    state.completed.join(", ")
+                };
+                error!(
+                    "Installation aborted during {} (staging dir: {}). Completed phases: {}. \
+                     Rerun `mash-setup doctor` or remove the staging directory before retrying.",
+                    phase_name,
+                    ctx.options.staging_dir.display(),
+                    completed_list
+                );
+                let should_continue = matches!(self.policy, PhaseErrorPolicy::ContinueOnError)
+                    && severity == ErrorSeverity::Recoverable;
+
+                if should_continue {
+                    None
+                } else {
+                    Some(installer_error)
                 }
             }
         }
+    }
 
-        Ok(PhaseRunResult {
-            completed_phases: completed,
-            outputs,
-            events,
-            errors,
+    /// Drop the journal entries of phases whose changes were rolled back,
+    /// so `--resume` runs them again.
+    fn forget_rolled_back(&self, phases: &[String]) {
+        if phases.is_empty() {
+            return;
+        }
+        self.update_journal(|journal| {
+            for phase in phases {
+                journal.forget(phase);
+            }
+        });
+    }
+
+    fn abort(
+        &self,
+        ctx: &InstallContext,
+        state: RunState,
+        source: InstallerError,
+    ) -> Box<PhaseRunError> {
+        let rolled_back = ctx.rollback.rollback_files();
+        if let Err(rb_err) = rolled_back.result {
+            error!("rollback encountered errors: {rb_err}");
+        } else {
+            info!("rollback completed after failure");
+        }
+        self.forget_rolled_back(&rolled_back.phases);
+
+        Box::new(PhaseRunError {
+            result: state.into_result(),
+            source,
+        })
+    }
+
+    fn interrupted(&self, ctx: &InstallContext, state: RunState) -> Box<PhaseRunError> {
+        info!("Signal received, rolling back and shutting down gracefully...");
+        let rolled_back = ctx.rollback.rollback_files();
+        if let Err(rb_err) = rolled_back.result {
+            error!("rollback encountered errors during signal shutdown: {rb_err}");
+        } else {
+            info!("rollback completed after signal");
+        }
+        self.forget_rolled_back(&rolled_back.phases);
+        let installer_error = InstallerError::new(
+            "signal_handler",
+            "Interrupted by signal",
+            ErrorSeverity::Fatal,
+            anyhow::anyhow!("Installation interrupted by SIGINT/SIGTERM"),
+            InstallerStateSnapshot::from_options(&ctx.options),
+            Some("Re-run the installer with --resume to continue.".to_string()),
+        );
+        Box::new(PhaseRunError {
+            result: state.into_result(),
+            source: installer_error,
         })
     }
 }
 
+/// Worker-thread body: execute the phase and report back to the coordinator.
+fn run_worker(
+    ctx: &InstallContext,
+    index: usize,
+    phase: &dyn Phase,
+    planning: bool,
+    tx: Sender<WorkerMessage>,
+) {
+    let events = tx.clone();
+    let _sink = progress::install(phase.id(), move |event| {
+        let _ = events.send(WorkerMessage::Event(event));
+    });
+    let _runner = cmd::use_runner(ctx.platform.commands);
+    let mut forwarder = ForwardingObserver { tx: tx.clone() };
+    let (result, metadata) = execute_phase(ctx, phase, planning, &mut forwarder);
+    let _ = tx.send(WorkerMessage::Finished {
+        index,
+        result,
+        metadata,
+    });
+}
+
+/// Execute a single phase through the dry-run gate. When `planning`, a
+/// phase with a dry-run gate of its own runs and gates its side effects.
+fn execute_phase(
+    ctx: &InstallContext,
+    phase: &dyn Phase,
+    planning: bool,
+    observer: &mut dyn PhaseObserver,
+) -> (AnyhowResult<PhaseResult>, PhaseMetadata) {
+    let phase_span = logging::phase_span(ctx, phase);
+    let _phase_guard = phase_span.enter();
+    let mut phase_ctx = ctx.phase_context(observer);
+    let started = Instant::now();
+    let result = if planning && phase.has_dry_run_gate() {
+        phase.execute(&mut phase_ctx)
+    } else {
+        phase_ctx.run_or_record(
+            phase.id().to_string(),
+            "Phase simulated",
+            Some(phase.description().to_string()),
+            |phase_ctx| phase.execute(phase_ctx),
+        )
+    };
+    let mut metadata = phase_ctx.take_metadata();
+    metadata.duration_ms = started.elapsed().as_millis() as u64;
+    (result, metadata)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PhaseResult {
     /// Phase completed successfully.
@@ -329,7 +772,11 @@ pub enum PhaseResult {
     RecoverableFailure(String),
 }
 
-pub trait Phase {
+pub trait Phase: Send + Sync {
+    /// Stable identifier (the registry key, e.g. `system_packages`). Reports,
+    /// events, the run journal, dependencies and `--only`/`--skip` use it.
+    fn id(&self) -> &str;
+    /// Display label; may be localized, so never use it as a key.
     fn name(&self) -> &str;
     fn description(&self) -> &str;
     fn should_run(&self, _ctx: &InstallContext) -> bool {
@@ -340,20 +787,32 @@ fn error_severity(&self) -> ErrorSeverity {
     }
     fn execute(&self, ctx: &mut PhaseContext) -> AnyhowResult<PhaseResult>;
 
-    /// Names of phases that MUST be completed before this one.
+    /// Ids of phases that MUST be completed before this one.
     fn dependencies(&self) -> &[&'static str] {
         &[]
     }
+
+    /// Whether `execute` checks `dry_run` before every side effect. Planning
+    /// only executes phases that do; the rest are simulated wholesale.
+    fn has_dry_run_gate(&self) -> bool {
+        false
+    }
 }
 
 pub struct FunctionPhase {
+    id: String,
     name: String,
     description: String,
     run: fn(&mut PhaseContext) -> AnyhowResult<PhaseResult>,
     deps: Vec<&'static str>,
+    dry_run_gate: bool,
 }
 
 impl Phase for FunctionPhase {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -369,19 +828,26 @@ fn execute(&self, ctx: &mut PhaseContext) -> AnyhowResult<PhaseResult> {
     fn dependencies(&self) -> &[&'static str] {
         &self.deps
     }
+
+    fn has_dry_run_gate(&self) -> bool {
+        self.dry_run_gate
+    }
 }
 
 impl FunctionPhase {
     pub fn new(
+        id: impl Into<String>,
         name: impl Into<String>,
         description: impl Into<String>,
         run: fn(&mut PhaseContext) -> AnyhowResult<PhaseResult>,
     ) -> Self {
         Self {
+            id: id.into(),
             name: name.into(),
             description: description.into(),
             run,
             deps: Vec::new(),
+            dry_run_gate: false,
         }
     }
 
@@ -389,6 +855,12 @@ pub fn with_deps(mut self, deps: Vec<&'static str>) -> Self {
         self.deps = deps;
         self
     }
+
+    /// Mark `run` as checking `dry_run` itself, so planning may execute it.
+    pub fn with_dry_run_gate(mut self) -> Self {
+        self.dry_run_gate = true;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -441,21 +913,59 @@ fn on_event(&mut self, event: PhaseEvent) {
                     index,
                     phase,
                     error,
+                    ..
                 } => {
                     self.events
                         .push(format!("failure:{}:{}:{}", index, phase, error));
                 }
-                PhaseEvent::Skipped { index, phase } => {
+                PhaseEvent::Skipped { index, phase, .. } => {
                     self.events.push(format!("skipped:{}:{}", index, phase));
                 }
                 PhaseEvent::Warning { message } => {
                     self.events.push(format!("warning:{}", message));
                 }
+                PhaseEvent::StepStarted { phase, step } => {
+                    self.events.push(format!("step:{}:{}", phase, step));
+                }
+                PhaseEvent::StepFinished {
+                    phase,
+                    step,
+                    success,
+                    ..
+                } => {
+                    self.events
+                        .push(format!("step-done:{}:{}:{}", phase, step, success));
+                }
+                PhaseEvent::Action { phase, message, .. } => {
+                    self.events.push(format!("action:{}:{}", phase, message));
+                }
+                PhaseEvent::CommandStarted { phase, command } => {
+                    self.events.push(format!("command:{}:{}", phase, command));
+                }
+                PhaseEvent::CommandOutput { phase, line, .. } => {
+                    self.events.push(format!("output:{}:{}", phase, line));
+                }
+                PhaseEvent::CommandFinished {
+                    phase,
+                    command,
+                    status,
+                    ..
+                } => {
+                    self.events
+                        .push(format!("command-done:{}:{}:{:?}", phase, command, status));
+                }
+                PhaseEvent::DownloadProgress { phase, bytes, .. } => {
+                    self.events.push(format!("download:{}:{}", phase, bytes));
+                }
+                PhaseEvent::Planned { phase, change } => {
+                    self.events.push(format!("planned:{}:{:?}", phase, change));
+                }
             }
         }
     }
 
     struct TestPhase {
+        id: &'static str,
         name: &'static str,
         description: &'static str,
         should_run: bool,
@@ -464,6 +974,10 @@ struct TestPhase {
     }
 
     impl Phase for TestPhase {
+        fn id(&self) -> &str {
+            self.id
+        }
+
         fn name(&self) -> &str {
             self.name
         }
@@ -487,20 +1001,26 @@ fn error_severity(&self) -> ErrorSeverity {
 
     impl TestPhase {
         fn new(
-            name: &'static str,
+            id: &'static str,
             description: &'static str,
             should_run: bool,
             severity: ErrorSeverity,
             run: fn(&mut PhaseContext) -> AnyhowResult<PhaseResult>,
         ) -> Self {
             Self {
-                name,
+                id,
+                name: id,
                 description,
                 should_run,
                 severity,
                 run,
             }
         }
+
+        fn labelled(mut self, name: &'static str) -> Self {
+            self.name = name;
+            self
+        }
     }
 
     struct DummyDriver;
@@ -546,6 +1066,7 @@ fn build_test_context() -> Result<InstallContext> {
             driver,
             pkg_backend: driver.pkg_backend(),
             system: &crate::sys_ops::REAL_SYSTEM,
+            commands: &crate::cmd::REAL_RUNNER,
         };
         let options = UserOptionsContext {
             profile: ProfileLevel::Minimal,
@@ -567,6 +1088,8 @@ fn build_test_context() -> Result<InstallContext> {
             chezmoi: Default::default(),
             desktop_environment: None,
             display_protocol: crate::desktop::DisplayProtocol::Auto,
+            verify: false,
+            allow_unverified_scripts: false,
         };
         let localization = Localization::load_default()?;
 
@@ -643,6 +1166,127 @@ fn phase_runner_notifies_observer_and_records_success() -> Result<()> {
         Ok(())
     }
 
+    fn busy_phase(ctx: &mut PhaseContext) -> AnyhowResult<PhaseResult> {
+        ctx.step("greet", |ctx| {
+            crate::cmd::Command::new("echo").arg("hi").execute()?;
+            ctx.record_action("greeted");
+            Ok(())
+        })?;
+        crate::system::progress::emit(|phase| PhaseEvent::DownloadProgress {
+            phase,
+            label: "tarball".to_string(),
+            bytes: 42,
+            total: None,
+        });
+        Ok(PhaseResult::Success)
+    }
+
+    #[test]
+    fn phase_progress_reaches_the_observer_in_order() -> Result<()> {
+        let ctx = build_test_context()?;
+        let phases: Vec<Box<dyn Phase>> = vec![Box::new(TestPhase::new(
+            "busy",
+            "busy done",
+            true,
+            ErrorSeverity::Fatal,
+            busy_phase,
+        ))];
+        let runner = PhaseRunner::from_phases(phases);
+        let mut observer = RecordingObserver::new();
+        let result = runner.run(&ctx, &mut observer, None)?;
+
+        assert_eq!(
+            observer.events,
+            [
+                "total:1",
+                "start:1:busy",
+                "step:busy:greet",
+                "command:busy:echo hi",
+                "output:busy:hi",
+                "command-done:busy:echo hi:Some(0)",
+                "action:busy:greeted",
+                "step-done:busy:greet:true",
+                "download:busy:42",
+                "success:1:busy done",
+            ]
+        );
+        // Output and progress ticks are live-only; everything else lands in the report.
+        assert_eq!(result.events.len(), observer.events.len() - 2);
+        assert!(result.events.iter().any(|event| matches!(
+            event,
+            PhaseEvent::Completed { phase, .. } if phase == "busy"
+        )));
+        Ok(())
+    }
+
+    fn gated_phase(ctx: &mut PhaseContext) -> AnyhowResult<PhaseResult> {
+        ctx.step("greet", |ctx| {
+            crate::cmd::Command::new("echo")
+                .arg("hi")
+                .dry_run(ctx.options.dry_run)
+                .execute()?;
+            Ok(())
+        })?;
+        Ok(PhaseResult::Success)
+    }
+
+    #[test]
+    fn planning_runs_phases_under_their_own_dry_run_gates() -> Result<()> {
+        let mut ctx = build_test_context()?;
+        ctx.options.dry_run = true;
+        let busy = || -> Vec<Box<dyn Phase>> {
+            vec![Box::new(
+                FunctionPhase::new("busy", "busy", "busy done", gated_phase).with_dry_run_gate(),
+            )]
+        };
+
+        let mut observer = RecordingObserver::new();
+        PhaseRunner::from_phases(busy()).run(&ctx, &mut observer, None)?;
+        assert!(!observer.events.iter().any(|evt| evt.starts_with("step:")));
+
+        let mut observer = RecordingObserver::new();
+        PhaseRunner::from_phases(busy())
+            .with_planning()
+            .run(&ctx, &mut observer, None)?;
+        assert!(observer.events.contains(&"step:busy:greet".to_string()));
+        assert!(observer
+            .events
+            .iter()
+            .any(|evt| evt.starts_with("planned:busy:Command") && evt.contains("echo hi")));
+        assert!(!observer.events.iter().any(|evt| evt.starts_with("output:")));
+        Ok(())
+    }
+
+    #[test]
+    fn planning_simulates_phases_without_a_dry_run_gate() -> Result<()> {
+        let mut ctx = build_test_context()?;
+        ctx.options.dry_run = true;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        static RAN: AtomicBool = AtomicBool::new(false);
+        let ungated: Vec<Box<dyn Phase>> = vec![Box::new(FunctionPhase::new(
+            "ungated",
+            "ungated",
+            "ungated done",
+            |_| {
+                RAN.store(true, Ordering::SeqCst);
+                Ok(PhaseResult::Success)
+            },
+        ))];
+
+        let mut observer = RecordingObserver::new();
+        PhaseRunner::from_phases(ungated)
+            .with_planning()
+            .run(&ctx, &mut observer, None)?;
+        assert!(!RAN.load(Ordering::SeqCst));
+        assert!(ctx
+            .dry_run_log
+            .audit_report()
+            .phases
+            .contains_key("ungated"));
+        Ok(())
+    }
+
     #[test]
     fn phase_runner_stops_on_error() -> Result<()> {
         let ctx = build_test_context()?;
@@ -772,6 +1416,49 @@ fn phase_runner_reports_skipped_phases() -> Result<()> {
         Ok(())
     }
 
+    #[test]
+    fn phase_runner_resumes_from_journal() -> Result<()> {
+        let ctx = build_test_context()?;
+        let dir = tempfile::tempdir()?;
+        let mut journal = RunJournal::new(dir.path(), "hash");
+        journal.mark_finished("phase-one", PhaseStatus::Completed);
+        journal.mark_finished("phase-two", PhaseStatus::Failed("boom".into()));
+
+        let phases: Vec<Box<dyn Phase>> = vec![
+            Box::new(TestPhase::new(
+                "phase-one",
+                "phase one done",
+                true,
+                ErrorSeverity::Fatal,
+                failing_phase,
+            )),
+            Box::new(
+                FunctionPhase::new("phase-two", "Phase two", "phase two done", success_phase)
+                    .with_deps(vec!["phase-one"]),
+            ),
+        ];
+        let runner = PhaseRunner::from_phases(phases).with_journal(journal);
+        let mut observer = RecordingObserver::new();
+
+        let result = runner.run(&ctx, &mut observer, None)?;
+        assert_eq!(
+            result.completed_phases,
+            vec!["phase-one".to_string(), "phase-two".to_string()]
+        );
+        assert!(observer
+            .events
+            .iter()
+            .any(|evt| evt.starts_with("skipped:1:phase-one")));
+        assert!(observer
+            .events
+            .iter()
+            .any(|evt| evt.starts_with("start:2:phase-two")));
+
+        let saved = RunJournal::load(dir.path())?.expect("journal persisted");
+        assert!(saved.is_completed("phase-two"));
+        Ok(())
+    }
+
     #[test]
     fn phase_runner_skips_on_failed_dependency() -> Result<()> {
         let ctx = build_test_context()?;
@@ -783,7 +1470,7 @@ fn phase_runner_skips_on_failed_dependency() -> Result<()> {
                 ErrorSeverity::Fatal,
                 failing_phase,
             )),
-            Box::new(FunctionPhase::new("B", "B", success_phase).with_deps(vec!["A"])),
+            Box::new(FunctionPhase::new("B", "B", "B", success_phase).with_deps(vec!["A"])),
         ];
 
         let runner = PhaseRunner::from_phases(phases);
@@ -806,14 +1493,12 @@ fn phase_runner_skips_on_failed_dependency() -> Result<()> {
     fn phase_runner_skips_on_failed_dependency_recoverable() -> Result<()> {
         let ctx = build_test_context()?;
         let phases: Vec<Box<dyn Phase>> = vec![
-            Box::new(TestPhase::new(
-                "A",
-                "A",
-                true,
-                ErrorSeverity::Recoverable,
-                failing_phase,
-            )),
-            Box::new(FunctionPhase::new("B", "B", success_phase).with_deps(vec!["A"])),
+            Box::new(
+                TestPhase::new("a", "A", true, ErrorSeverity::Recoverable, failing_phase)
+                    .labelled("Phase A"),
+            ),
+            // Dependencies name phase ids, never the (localized) labels
+            Box::new(FunctionPhase::new("b", "Phase B", "B", success_phase).with_deps(vec!["a"])),
         ];
 
         let runner = PhaseRunner::with_policy(phases, PhaseErrorPolicy::ContinueOnError);
@@ -825,11 +1510,14 @@ fn phase_runner_skips_on_failed_dependency_recoverable() -> Result<()> {
         assert!(observer
             .events
             .iter()
-            .any(|evt| evt.contains("Skipping B because dependency A was not completed")));
+            .any(|evt| evt.contains("Skipping Phase B because dependency a was not completed")));
         assert!(observer
             .events
             .iter()
-            .any(|evt| evt.starts_with("skipped:2:B")));
+            .any(|evt| evt.starts_with("skipped:2:b")));
+        assert_eq!(result.outputs[1].id, "b");
+        assert_eq!(result.outputs[1].name, "Phase B");
+        assert_eq!(result.errors[0].phase, "a");
 
         Ok(())
     }
@@ -2,11 +2,30 @@
 
 use crate::context::PhaseContext;
 use crate::sys_ops::RealSystem;
+use crate::system::dry_run;
+use crate::wallpaper::harvest::SEARCH_URL;
 use crate::wallpaper::{download_wallpapers, HarvestConfig, WallpaperConfig, WallpaperHarvester};
 use crate::PhaseResult;
 
+/// Artifact-cache key the harvest is redirected to.
+const HARVEST_CACHE_KEY: &str = "wallpapers/mash_harvest";
+
 /// Wallpaper installation phase
 pub fn install_phase(ctx: &mut PhaseContext<'_>) -> Result<PhaseResult> {
+    if ctx.options.dry_run {
+        let dest = ctx.cache.resolve_path(HARVEST_CACHE_KEY);
+        ctx.record_dry_run(
+            "wallpapers",
+            format!(
+                "Would harvest up to {} wallpapers",
+                HarvestConfig::default().target
+            ),
+            Some(dest.display().to_string()),
+        );
+        dry_run::plan_download(SEARCH_URL);
+        return Ok(PhaseResult::Success);
+    }
+
     // Whimsical starting message
     ctx.record_action("🚀  Launching plasma ore collection mission...");
     ctx.record_action("🎨  Painting your desktop with neon runes...");
@@ -20,15 +39,16 @@ pub fn install_phase(ctx: &mut PhaseContext<'_>) -> Result<PhaseResult> {
 
     ctx.record_action("🌾  Attempting wallpaper harvest (no API keys required)...");
 
-    let mut harvest_config = HarvestConfig::default();
     // Redirect harvest to artifact cache if available
-    let cache_key = "wallpapers/mash_harvest";
-    harvest_config.dest = ctx.cache.resolve_path(cache_key);
+    let harvest_config = HarvestConfig {
+        dest: ctx.cache.resolve_path(HARVEST_CACHE_KEY),
+        ..HarvestConfig::default()
+    };
 
     let harvester = WallpaperHarvester::new(harvest_config)?;
 
     // We need to pass the observer from ctx
-    let harvest_result = harvester.run(ctx.observer);
+    let harvest_result = ctx.step("wallpaper harvest", |ctx| harvester.run(ctx.observer));
 
     match harvest_result {
         Ok(_) => {
@@ -42,7 +62,9 @@ pub fn install_phase(ctx: &mut PhaseContext<'_>) -> Result<PhaseResult> {
     // If we have API keys, also run the traditional search/download
     if has_api_keys {
         ctx.record_action("📡  API keys detected. Searching for specific high-res runes...");
-        let stats = download_wallpapers(&config, &RealSystem, ctx.observer)?;
+        let stats = ctx.step("API wallpaper download", |ctx| {
+            Ok(download_wallpapers(&config, &RealSystem, ctx.observer)?)
+        })?;
 
         if stats.success > 0 || stats.failed > 0 {
             ctx.record_action(format!(
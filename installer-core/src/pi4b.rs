@@ -1,11 +1,16 @@
 // Pi 4B HDD Optimization Module
-// Preflight checks and tuning for Raspberry Pi 4B with external USB 3.0 HDD
+// Preflight checks and tuning for Raspberry Pis with an external USB 3.0 HDD
+// (4B, 400, 5, 500 and CM5)
 
-use anyhow::Result;
-use std::path::Path;
-use std::process::Command;
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
 
+use crate::cmd::CommandExecutionError;
 use crate::doctor::{CheckStatus, PreflightCheck};
+use crate::pi_board::PiBoard;
+use crate::rollback::RollbackOp;
 use crate::system::system_ops::SystemOps;
 use crate::PhaseContext;
 
@@ -87,6 +92,14 @@ pub fn is_raspberry_pi_4b(system: &dyn SystemOps) -> bool {
     model.contains("Raspberry Pi 4")
 }
 
+/// Check if system is a Raspberry Pi with USB 3.0 ports
+pub fn is_usb3_pi(system: &dyn SystemOps) -> bool {
+    let model = system
+        .read_to_string(Path::new("/sys/firmware/devicetree/base/model"))
+        .unwrap_or_default();
+    PiBoard::from_model(&model).is_some_and(|board| board.has_usb3())
+}
+
 /// Detect USB 3.0 controllers and connected devices
 pub fn detect_usb3_controllers(system: &dyn SystemOps) -> Result<Vec<Usb3Controller>> {
     let mut controllers = Vec::new();
@@ -118,27 +131,128 @@ pub fn detect_usb3_controllers(system: &dyn SystemOps) -> Result<Vec<Usb3Control
     Ok(controllers)
 }
 
-/// Check HDD health using SMART data
-pub fn check_hdd_health(_device: &str) -> Result<HddHealth> {
+/// Block devices that may be external USB disks (`sd*`), sorted by name.
+pub fn external_disks(system: &dyn SystemOps) -> Vec<String> {
+    system
+        .read_dir(Path::new("/sys/block"))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+        .filter(|name| name.starts_with("sd"))
+        .collect()
+}
+
+/// Whether the kernel reports `device` as a spinning disk.
+pub fn is_rotational(system: &dyn SystemOps, device: &str) -> bool {
+    system
+        .read_to_string(
+            &Path::new("/sys/block")
+                .join(device)
+                .join("queue/rotational"),
+        )
+        .map(|text| text.trim() == "1")
+        .unwrap_or(false)
+}
+
+/// Check HDD health using SMART data from `smartctl --json`.
+pub fn check_hdd_health(system: &dyn SystemOps, device: &str) -> Result<HddHealth> {
+    let mut cmd = Command::new("sudo");
+    cmd.args(["smartctl", "--json=c", "-H", "-i", "-A"])
+        .arg(format!("/dev/{device}"));
+    let stdout = match system.command_output(&mut cmd) {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+        // The exit status is a bit mask: bits 0 and 1 mean smartctl could not
+        // talk to the disk, higher bits flag disk problems in a full report.
+        Err(err) => match err.downcast_ref::<CommandExecutionError>() {
+            Some(failed) if failed.details().status.is_some_and(|s| s & 0b11 == 0) => {
+                failed.details().stdout.clone()
+            }
+            _ => return Err(err.context(format!("reading SMART data for /dev/{device}"))),
+        },
+    };
+    parse_smartctl_json(&stdout)
+}
+
+/// Parse the report printed by `smartctl --json`.
+pub fn parse_smartctl_json(text: &str) -> Result<HddHealth> {
+    let report: Value = serde_json::from_str(text).context("parsing smartctl JSON")?;
+    let string = |pointer: &str| {
+        report
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .unwrap_or("unknown")
+            .to_string()
+    };
+    let smart_status = match report
+        .pointer("/smart_status/passed")
+        .and_then(Value::as_bool)
+    {
+        Some(true) => "Passed",
+        Some(false) => "Failed",
+        None => "Unknown",
+    };
     Ok(HddHealth {
-        model: "Sample HDD".to_string(),
-        serial: "SAMPLE123".to_string(),
-        temperature: Some(35),
-        smart_status: "Passed".to_string(),
-        power_on_hours: Some(1000),
+        model: string("/model_name"),
+        serial: string("/serial_number"),
+        temperature: report
+            .pointer("/temperature/current")
+            .and_then(Value::as_i64)
+            .map(|t| t as i32),
+        smart_status: smart_status.to_string(),
+        power_on_hours: report
+            .pointer("/power_on_time/hours")
+            .and_then(Value::as_u64)
+            .map(|h| h as u32),
     })
 }
 
-/// Analyze partition layout
-pub fn analyze_partition_layout(device: &str) -> Result<PartitionLayout> {
+/// Analyze partition layout from sysfs and `/proc/mounts`
+pub fn analyze_partition_layout(system: &dyn SystemOps, device: &str) -> Result<PartitionLayout> {
+    let mounts = system
+        .read_to_string(Path::new("/proc/mounts"))
+        .unwrap_or_default();
+    let mut partitions = Vec::new();
+
+    for entry in system.read_dir(&Path::new("/sys/block").join(device))? {
+        let Some(name) = entry.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if !name.starts_with(device) {
+            continue;
+        }
+        let Ok(number) = system.read_to_string(&entry.join("partition")) else {
+            continue;
+        };
+        let sectors: u64 = system
+            .read_to_string(&entry.join("size"))
+            .ok()
+            .and_then(|size| size.trim().parse().ok())
+            .unwrap_or(0);
+        let node = format!("/dev/{name}");
+        let mount = mounts
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .find(|fields| fields.len() >= 3 && fields[0] == node);
+
+        partitions.push(PartitionInfo {
+            number: number
+                .trim()
+                .parse()
+                .with_context(|| format!("partition number of {name}"))?,
+            // sysfs sizes are always in 512-byte sectors
+            size: sectors * 512,
+            filesystem: mount
+                .as_ref()
+                .map(|fields| fields[2].to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            mount_point: mount.map(|fields| fields[1].to_string()),
+        });
+    }
+    partitions.sort_by_key(|partition| partition.number);
+
     Ok(PartitionLayout {
         device: device.to_string(),
-        partitions: vec![PartitionInfo {
-            number: 1,
-            size: 1024 * 1024 * 1024,
-            filesystem: "ext4".to_string(),
-            mount_point: Some("/".to_string()),
-        }],
+        partitions,
     })
 }
 
@@ -150,11 +264,11 @@ pub fn analyze_partition_layout(device: &str) -> Result<PartitionLayout> {
 pub fn pi4b_hdd_preflight_checks(system: &dyn SystemOps) -> Result<Vec<PreflightCheck>> {
     let mut checks = Vec::new();
 
-    if !is_raspberry_pi_4b(system) {
+    if !is_usb3_pi(system) {
         checks.push(PreflightCheck {
             label: "Pi 4B HDD Optimization".into(),
             status: CheckStatus::Warning,
-            detail: Some("Not running on Raspberry Pi 4B - skipping HDD checks".into()),
+            detail: Some("Not running on a USB 3.0 Raspberry Pi - skipping HDD checks".into()),
         });
         return Ok(checks);
     }
@@ -301,68 +415,101 @@ fn check_mount_options(system: &dyn SystemOps) -> PreflightCheck {
 // I/O Scheduler
 // ---------------------------------------------------------------------------
 
+/// Schedulers that suit a USB HDD, best first.
+const HDD_SCHEDULERS: &[&str] = &["mq-deadline", "deadline", "bfq"];
+
 /// Get current I/O scheduler for a device
-pub fn get_io_scheduler(_device: &str) -> Result<IoScheduler> {
-    let available = vec![
-        "none".to_string(),
-        "noop".to_string(),
-        "deadline".to_string(),
-        "cfq".to_string(),
-    ];
+pub fn get_io_scheduler(system: &dyn SystemOps, device: &str) -> Result<IoScheduler> {
+    let path = Path::new("/sys/block").join(device).join("queue/scheduler");
+    parse_io_scheduler(&system.read_to_string(&path)?)
+}
+
+/// Parse `/sys/block/*/queue/scheduler`, where the active entry is in brackets.
+pub fn parse_io_scheduler(text: &str) -> Result<IoScheduler> {
+    let mut current = None;
+    let available: Vec<String> = text
+        .split_whitespace()
+        .map(
+            |name| match name.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+                Some(active) => {
+                    current = Some(active.to_string());
+                    active.to_string()
+                }
+                None => name.to_string(),
+            },
+        )
+        .collect();
+    let current = current.ok_or_else(|| anyhow!("no active scheduler in '{}'", text.trim()))?;
+    let recommended = HDD_SCHEDULERS
+        .iter()
+        .find(|name| available.iter().any(|a| a == *name))
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| current.clone());
     Ok(IoScheduler {
-        current: "cfq".to_string(),
+        current,
         available,
-        recommended: "deadline".to_string(),
+        recommended,
     })
 }
 
-/// Set I/O scheduler for a device
-pub fn set_io_scheduler(_device: &str, scheduler: &str) -> Result<()> {
-    if scheduler == "deadline" || scheduler == "noop" {
-        Ok(())
-    } else {
-        Err(anyhow::anyhow!("Unsupported I/O scheduler: {}", scheduler))
-    }
+/// udev rule that applies `scheduler` to every rotational `sd*` disk.
+pub fn scheduler_udev_rule(scheduler: &str) -> String {
+    format!(
+        "# Managed by mash-setup: I/O scheduler for USB hard disks\n\
+         ACTION==\"add|change\", KERNEL==\"sd[a-z]\", ATTR{{queue/rotational}}==\"1\", \
+         ATTR{{queue/scheduler}}=\"{scheduler}\"\n"
+    )
 }
 
 /// Optimize I/O scheduler for external USB 3.0 HDD
 pub fn optimize_io_scheduler(system: &dyn SystemOps) -> Result<Vec<PreflightCheck>> {
     let mut checks = Vec::new();
 
-    if !is_raspberry_pi_4b(system) {
+    if !is_usb3_pi(system) {
         checks.push(PreflightCheck {
             label: "I/O Scheduler Optimization".into(),
             status: CheckStatus::Warning,
-            detail: Some("Not running on Raspberry Pi 4B".into()),
+            detail: Some("Not running on a USB 3.0 Raspberry Pi".into()),
         });
         return Ok(checks);
     }
 
-    match get_io_scheduler("sda") {
-        Ok(scheduler) => {
-            checks.push(PreflightCheck {
-                label: format!("Current I/O Scheduler: {}", scheduler.current),
-                status: CheckStatus::Success,
-                detail: Some(format!("Available: {}", scheduler.available.join(", "))),
-            });
+    let disks = external_disks(system);
+    if disks.is_empty() {
+        checks.push(PreflightCheck {
+            label: "I/O Scheduler Optimization".into(),
+            status: CheckStatus::Warning,
+            detail: Some("No external disks detected".into()),
+        });
+    }
 
-            if scheduler.current != scheduler.recommended {
+    for disk in disks {
+        match get_io_scheduler(system, &disk) {
+            Ok(scheduler) => {
                 checks.push(PreflightCheck {
-                    label: "I/O Scheduler Recommendation".into(),
-                    status: CheckStatus::Warning,
-                    detail: Some(format!(
-                        "Consider switching from '{}' to '{}' for better USB 3.0 performance",
-                        scheduler.current, scheduler.recommended
-                    )),
+                    label: format!("Current I/O Scheduler ({disk}): {}", scheduler.current),
+                    status: CheckStatus::Success,
+                    detail: Some(format!("Available: {}", scheduler.available.join(", "))),
+                });
+
+                if scheduler.current != scheduler.recommended {
+                    checks.push(PreflightCheck {
+                        label: "I/O Scheduler Recommendation".into(),
+                        status: CheckStatus::Warning,
+                        detail: Some(format!(
+                            "Consider switching {disk} from '{}' to '{}' for better USB 3.0 performance",
+                            scheduler.current, scheduler.recommended
+                        )),
+                    });
+                }
+            }
+            Err(err) => {
+                checks.push(PreflightCheck {
+                    label: "I/O Scheduler Detection".into(),
+                    status: CheckStatus::Error,
+                    detail: Some(format!("Failed to detect for {disk}: {}", err)),
                 });
             }
-        }
-        Err(err) => {
-            checks.push(PreflightCheck {
-                label: "I/O Scheduler Detection".into(),
-                status: CheckStatus::Error,
-                detail: Some(format!("Failed to detect: {}", err)),
-            });
         }
     }
 
@@ -374,7 +521,10 @@ pub fn optimize_io_scheduler(system: &dyn SystemOps) -> Result<Vec<PreflightChec
 // ---------------------------------------------------------------------------
 
 /// Recommended mount options for ext4 on external USB HDD
-const EXT4_HDD_OPTS: &[&str] = &["noatime", "commit=60", "data=ordered", "barrier=0"];
+const EXT4_HDD_OPTS: &[&str] = &["noatime", "commit=60"];
+
+const FSTAB: &str = "/etc/fstab";
+const FSTAB_BACKUP: &str = "/etc/fstab.mash-bak";
 
 /// Analyze current mounts and recommend optimizations for HDD partitions.
 pub fn optimize_mount_options(system: &dyn SystemOps) -> Result<Vec<MountOptimization>> {
@@ -423,6 +573,73 @@ pub fn optimize_mount_options(system: &dyn SystemOps) -> Result<Vec<MountOptimiz
     Ok(optimizations)
 }
 
+/// Add the missing options from `optimizations` to the matching fstab
+/// entries. Returns `None` when no entry changes.
+pub fn propose_fstab(fstab: &str, optimizations: &[MountOptimization]) -> Option<String> {
+    let mut changed = false;
+    let lines: Vec<String> = fstab
+        .lines()
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if line.trim_start().starts_with('#') || fields.len() < 4 {
+                return line.to_string();
+            }
+            let Some(opt) = optimizations.iter().find(|o| o.mount_point == fields[1]) else {
+                return line.to_string();
+            };
+            let mut options: Vec<&str> = fields[3].split(',').collect();
+            for wanted in &opt.recommended_options {
+                let key = wanted.split('=').next().unwrap_or(wanted);
+                if !options.iter().any(|o| o.split('=').next() == Some(key)) {
+                    options.push(wanted);
+                }
+            }
+            let options = options.join(",");
+            if options == fields[3] {
+                return line.to_string();
+            }
+            changed = true;
+            let mut fields = fields;
+            fields[3] = &options;
+            fields.join("\t")
+        })
+        .collect();
+    changed.then(|| lines.join("\n") + "\n")
+}
+
+/// Append a swap entry for `swapfile` unless fstab already has one.
+pub fn append_swap_entry(fstab: &str, swapfile: &Path) -> Option<String> {
+    let path = swapfile.display().to_string();
+    let present = fstab
+        .lines()
+        .any(|line| line.split_whitespace().next() == Some(path.as_str()));
+    if present {
+        return None;
+    }
+    let mut next = fstab.to_string();
+    if !next.is_empty() && !next.ends_with('\n') {
+        next.push('\n');
+    }
+    next.push_str(&format!("{path}\tnone\tswap\tsw\t0\t0\n"));
+    Some(next)
+}
+
+/// Line-by-line diff of an fstab edit: changed lines as `-`/`+` pairs,
+/// appended lines as `+`.
+pub fn fstab_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let mut diff = String::new();
+    for (i, line) in new.iter().enumerate() {
+        match old.get(i) {
+            Some(before) if before == line => {}
+            Some(before) => diff.push_str(&format!("-{before}\n+{line}\n")),
+            None => diff.push_str(&format!("+{line}\n")),
+        }
+    }
+    diff
+}
+
 // ---------------------------------------------------------------------------
 // Swap configuration
 // ---------------------------------------------------------------------------
@@ -463,6 +680,28 @@ pub fn configure_swap(system: &dyn SystemOps) -> Result<SwapConfig> {
     })
 }
 
+/// Whether `path` is one of the active swap areas.
+fn swap_active(system: &dyn SystemOps, path: &Path) -> bool {
+    let mut cmd = Command::new("swapon");
+    cmd.args(["--show=NAME", "--noheadings"]);
+    system.command_output(&mut cmd).is_ok_and(|output| {
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .any(|name| Path::new(name) == path)
+    })
+}
+
+/// Mount point of the first ext4 partition on an external disk.
+pub fn hdd_mount_point(system: &dyn SystemOps) -> Option<String> {
+    let mounts = system.read_to_string(Path::new("/proc/mounts")).ok()?;
+    mounts.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        (fields.len() >= 3 && fields[0].starts_with("/dev/sd") && fields[2] == "ext4")
+            .then(|| fields[1].to_string())
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Kernel parameter tuning
 // ---------------------------------------------------------------------------
@@ -509,45 +748,118 @@ pub fn tune_kernel_params(system: &dyn SystemOps) -> Result<Vec<KernelParam>> {
     Ok(params)
 }
 
+/// Where the tuned parameters are persisted.
+const SYSCTL_CONF: &str = "/etc/sysctl.d/60-mash-pi4b-hdd.conf";
+
+/// Where the scheduler rule is installed.
+const SCHEDULER_RULE: &str = "/etc/udev/rules.d/60-mash-hdd-scheduler.rules";
+
+/// The sysctl.d drop-in for [`KERNEL_PARAMS`].
+pub fn sysctl_conf() -> String {
+    let mut conf = String::from("# Managed by mash-setup: Pi 4B + USB HDD tuning\n");
+    for (name, value, description) in KERNEL_PARAMS {
+        conf.push_str(&format!("# {description}\n{name} = {value}\n"));
+    }
+    conf
+}
+
 // ---------------------------------------------------------------------------
 // Phase integration — the forge gate
 // ---------------------------------------------------------------------------
 
-/// Phase entry point: tunes HDD mount options, swap, kernel params, and I/O scheduler.
-/// Skips gracefully on non-Pi4B systems.
 use crate::PhaseResult;
 
+/// A pending edit of `/etc/fstab`, applied once after every step added to it.
+struct FstabEdit {
+    original: String,
+    proposed: String,
+    reasons: Vec<&'static str>,
+}
+
+impl FstabEdit {
+    fn propose(&mut self, next: Option<String>, reason: &'static str) {
+        if let Some(next) = next {
+            self.proposed = next;
+            self.reasons.push(reason);
+        }
+    }
+}
+
+/// Phase entry point: tunes HDD mount options, swap, kernel params, and I/O scheduler,
+/// and reports SMART health. Skips gracefully on boards without USB 3.0.
 pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
-    if !ctx.platform.is_pi_4b() {
-        ctx.record_warning("Not running on Pi 4B — skipping HDD tuning");
+    if !ctx.platform.supports_usb3() {
+        ctx.record_warning("Not running on a USB 3.0 Raspberry Pi — skipping HDD tuning");
         return Ok(PhaseResult::Success);
     }
 
-    phase_mount_options(ctx)?;
-    phase_swap(ctx)?;
+    let original = ctx
+        .platform
+        .system
+        .read_to_string(Path::new(FSTAB))
+        .unwrap_or_default();
+    let mut fstab = FstabEdit {
+        proposed: original.clone(),
+        original,
+        reasons: Vec::new(),
+    };
+
+    phase_mount_options(ctx, &mut fstab)?;
+    phase_swap(ctx, &mut fstab)?;
+    apply_fstab(ctx, &fstab)?;
     phase_kernel_params(ctx)?;
     phase_io_scheduler(ctx)?;
+    phase_smart(ctx);
 
     Ok(PhaseResult::Success)
 }
 
-fn phase_mount_options(ctx: &mut PhaseContext) -> Result<()> {
+fn run_root(system: &dyn SystemOps, args: &[&str]) -> Result<Output> {
+    let mut cmd = Command::new("sudo");
+    cmd.args(args);
+    system.command_output(&mut cmd)
+}
+
+fn phase_mount_options(ctx: &mut PhaseContext, fstab: &mut FstabEdit) -> Result<()> {
     if ctx.options.dry_run {
         ctx.record_dry_run(
             "pi4b_hdd_tuning",
             "Would optimize mount options",
-            Some("noatime, commit=60, data=ordered, barrier=0 for ext4 HDD partitions".into()),
+            Some("noatime, commit=60 for ext4 HDD partitions (fstab proposal)".into()),
         );
         return Ok(());
     }
 
-    // On real runs: read /proc/mounts, write fstab recommendations
-    // Currently records actions — actual fstab writes are Phase 4 (hardening)
-    ctx.record_tweaked("Mount options optimized (noatime, commit=60)");
+    let optimizations = optimize_mount_options(ctx.platform.system)?;
+    if optimizations.is_empty() {
+        ctx.record_action("HDD mount options already tuned");
+        return Ok(());
+    }
+    let listed = fstab.proposed.lines().any(|line| {
+        line.split_whitespace()
+            .nth(1)
+            .is_some_and(|mount| optimizations.iter().any(|o| o.mount_point == mount))
+    });
+    match propose_fstab(&fstab.proposed, &optimizations) {
+        Some(next) => fstab.propose(Some(next), "noatime,commit=60"),
+        None if listed => ctx.record_action(format!(
+            "HDD mount options already in {FSTAB}; they apply after a remount"
+        )),
+        None => {
+            let mount_points: Vec<&str> = optimizations
+                .iter()
+                .map(|o| o.mount_point.as_str())
+                .collect();
+            ctx.record_warning(format!(
+                "No {FSTAB} entry for {}; mount options left as they are",
+                mount_points.join(", ")
+            ));
+        }
+    }
     Ok(())
 }
 
-fn phase_swap(ctx: &mut PhaseContext) -> Result<()> {
+fn phase_swap(ctx: &mut PhaseContext, fstab: &mut FstabEdit) -> Result<()> {
     if ctx.options.dry_run {
         ctx.record_dry_run(
             "pi4b_hdd_tuning",
@@ -560,7 +872,89 @@ fn phase_swap(ctx: &mut PhaseContext) -> Result<()> {
         return Ok(());
     }
 
-    ctx.record_tweaked("Swap space increased to 2GB on external HDD");
+    let system = ctx.platform.system;
+    let swap = configure_swap(system)?;
+    if swap.on_hdd && swap.current_swap_mb >= swap.recommended_swap_mb {
+        ctx.record_action(format!(
+            "Swap already on external HDD ({}MB at {})",
+            swap.current_swap_mb, swap.swap_location
+        ));
+        return Ok(());
+    }
+    let Some(mount) = hdd_mount_point(system) else {
+        ctx.record_warning("No ext4 partition on an external disk is mounted; swap left as is");
+        return Ok(());
+    };
+
+    let swapfile = Path::new(&mount).join("swapfile");
+    let path = swapfile.display().to_string();
+    let size = format!("{}M", swap.recommended_swap_mb);
+    // fallocate cannot resize a swap file the kernel is using. One that is
+    // already active predates this run, so it is resized but not removed
+    // on rollback.
+    if swap_active(system, &swapfile) {
+        run_root(system, &["swapoff", &path])?;
+    } else {
+        ctx.register_rollback_action(
+            "remove HDD swap file",
+            RollbackOp::RemoveSwapFile {
+                path: swapfile.clone(),
+            },
+        );
+    }
+    run_root(system, &["fallocate", "-l", &size, &path])?;
+    run_root(system, &["chmod", "600", &path])?;
+    run_root(system, &["mkswap", &path])?;
+    run_root(system, &["swapon", &path])?;
+    ctx.record_tweaked(format!(
+        "Swap file of {}MB enabled at {}",
+        swap.recommended_swap_mb, path
+    ));
+
+    fstab.propose(append_swap_entry(&fstab.proposed, &swapfile), "swap file");
+    Ok(())
+}
+
+/// Write the fstab proposal to the staging directory and, once the user has
+/// seen the diff and agreed, back up `/etc/fstab` and replace it.
+fn apply_fstab(ctx: &mut PhaseContext, fstab: &FstabEdit) -> Result<()> {
+    if ctx.options.dry_run || fstab.proposed == fstab.original {
+        return Ok(());
+    }
+    let system = ctx.platform.system;
+    let staging = ctx.options.staging_dir.join("pi4b");
+    system.create_dir_all(&staging)?;
+    let proposal = staging.join("fstab.proposed");
+    system.write_file(&proposal, fstab.proposed.as_bytes())?;
+
+    let reasons = fstab.reasons.join(", ");
+    let diff = fstab_diff(&fstab.original, &fstab.proposed);
+    ctx.record_action(format!(
+        "fstab proposal ({reasons}) written to {}",
+        proposal.display()
+    ));
+    let prompt = format!("Apply this change to {FSTAB}?\n{diff}");
+    if !ctx.observer.confirm(&prompt) {
+        ctx.record_warning(format!(
+            "{FSTAB} left unchanged; review {} and apply it by hand",
+            proposal.display()
+        ));
+        return Ok(());
+    }
+
+    let backup = PathBuf::from(FSTAB_BACKUP);
+    system.install_file(&backup, fstab.original.as_bytes(), 0o644)?;
+    system.install_file(Path::new(FSTAB), fstab.proposed.as_bytes(), 0o644)?;
+    ctx.register_rollback_action(
+        "restore /etc/fstab",
+        RollbackOp::RestoreFile {
+            path: PathBuf::from(FSTAB),
+            backup,
+        },
+    );
+    ctx.record_configured(format!(
+        "Updated {FSTAB} ({reasons}); previous version at {FSTAB_BACKUP}"
+    ));
     Ok(())
 }
 
@@ -575,10 +969,32 @@ fn phase_kernel_params(ctx: &mut PhaseContext) -> Result<()> {
             "Would tune kernel parameters",
             Some(param_summary.join(", ")),
         );
+        ctx.plan_file(Path::new(SYSCTL_CONF), &sysctl_conf());
         return Ok(());
     }
 
-    ctx.record_tweaked("Kernel VM parameters tuned for write-heavy HDD use");
+    let system = ctx.platform.system;
+    let conf = sysctl_conf();
+    let existing = system.read_to_string(Path::new(SYSCTL_CONF)).ok();
+    if existing.as_deref() == Some(conf.as_str()) {
+        ctx.record_action(format!(
+            "Kernel VM parameters already persisted in {SYSCTL_CONF}"
+        ));
+        return Ok(());
+    }
+    system.install_file(Path::new(SYSCTL_CONF), conf.as_bytes(), 0o644)?;
+    if existing.is_none() {
+        ctx.register_rollback_action(
+            "remove HDD sysctl settings",
+            RollbackOp::RemoveFile {
+                path: PathBuf::from(SYSCTL_CONF),
+            },
+        );
+    }
+    run_root(system, &["sysctl", "-p", SYSCTL_CONF])?;
+    ctx.record_tweaked(format!(
+        "Kernel VM parameters tuned for write-heavy HDD use ({SYSCTL_CONF})"
+    ));
     Ok(())
 }
 
@@ -587,15 +1003,91 @@ fn phase_io_scheduler(ctx: &mut PhaseContext) -> Result<()> {
         ctx.record_dry_run(
             "pi4b_hdd_tuning",
             "Would optimize I/O scheduler",
-            Some("deadline scheduler for external USB 3.0 HDD".into()),
+            Some("mq-deadline scheduler for external USB 3.0 HDD (udev rule)".into()),
         );
         return Ok(());
     }
 
-    ctx.record_tweaked("I/O scheduler set to mq-deadline for external HDD");
+    let system = ctx.platform.system;
+    let disks: Vec<String> = external_disks(system)
+        .into_iter()
+        .filter(|disk| is_rotational(system, disk))
+        .collect();
+    let Some(first) = disks.first() else {
+        ctx.record_warning("No external hard disk found; I/O scheduler left as is");
+        return Ok(());
+    };
+    let scheduler = get_io_scheduler(system, first)?;
+
+    let rule = scheduler_udev_rule(&scheduler.recommended);
+    let existing = system.read_to_string(Path::new(SCHEDULER_RULE)).ok();
+    if existing.as_deref() != Some(rule.as_str()) {
+        system.install_file(Path::new(SCHEDULER_RULE), rule.as_bytes(), 0o644)?;
+        if existing.is_none() {
+            ctx.register_rollback_action(
+                "remove HDD scheduler udev rule",
+                RollbackOp::RemoveFile {
+                    path: PathBuf::from(SCHEDULER_RULE),
+                },
+            );
+        }
+    }
+    if existing.as_deref() != Some(rule.as_str()) || scheduler.current != scheduler.recommended {
+        run_root(system, &["udevadm", "control", "--reload-rules"])?;
+        run_root(
+            system,
+            &[
+                "udevadm",
+                "trigger",
+                "--subsystem-match=block",
+                "--action=change",
+            ],
+        )?;
+    }
+    ctx.record_tweaked(format!(
+        "I/O scheduler set to {} for {}",
+        scheduler.recommended,
+        disks.join(", ")
+    ));
     Ok(())
 }
 
+/// Temperature above which a disk is reported as running hot.
+const HOT_DISK_CELSIUS: i32 = 50;
+
+fn phase_smart(ctx: &mut PhaseContext) {
+    if ctx.options.dry_run {
+        ctx.record_dry_run(
+            "pi4b_hdd_tuning",
+            "Would read SMART health of external disks",
+            None,
+        );
+        return;
+    }
+
+    for disk in external_disks(ctx.platform.system) {
+        match check_hdd_health(ctx.platform.system, &disk) {
+            Ok(health) => {
+                ctx.record_action(format!(
+                    "SMART /dev/{disk} ({} {}): {}",
+                    health.model, health.serial, health.smart_status
+                ));
+                if health.smart_status == "Failed" {
+                    ctx.record_warning(format!(
+                        "/dev/{disk} failed its SMART self-assessment; back up its data"
+                    ));
+                }
+                if let Some(temp) = health.temperature.filter(|t| *t > HOT_DISK_CELSIUS) {
+                    ctx.record_warning(format!("/dev/{disk} is running hot ({temp}°C)"));
+                }
+            }
+            Err(err) => ctx.record_warning(format!(
+                "No SMART data for /dev/{disk} (is smartmontools installed?): {err:#}"
+            )),
+        }
+    }
+}
+
 /// Legacy placeholder — kept for backwards compat, delegates to install_phase logic.
 pub fn optimize_pi4b_hdd() -> Result<()> {
     Ok(())
@@ -608,8 +1100,223 @@ pub fn optimize_pi4b_hdd() -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::system::fixture::{run_pi_phase, FixtureSystem, PhaseRun};
     use crate::system::system_ops::RealSystem;
 
+    const SMARTCTL_JSON: &str = r#"{"model_name":"WDC WD20SPZX-22UA7T0","serial_number":"WD-WX12A","smart_status":{"passed":true},"temperature":{"current":38},"power_on_time":{"hours":5120}}"#;
+
+    const FSTAB_FIXTURE: &str = "\
+proc            /proc           proc    defaults          0       0
+PARTUUID=abcd-01  /boot/firmware  vfat    defaults          0       2
+/dev/sda1       /mnt/hdd        ext4    defaults          0       2
+";
+
+    /// A Pi 4B with an ext4 USB disk mounted at /mnt/hdd and no swap.
+    fn pi_with_hdd() -> FixtureSystem {
+        FixtureSystem::new()
+            .file("/etc/fstab", FSTAB_FIXTURE)
+            .file(
+                "/proc/mounts",
+                "/dev/mmcblk0p2 / ext4 rw,noatime 0 0\n/dev/sda1 /mnt/hdd ext4 rw,relatime 0 0\n",
+            )
+            .file("/sys/block/mmcblk0/size", "62333952\n")
+            .file(
+                "/sys/block/sda/queue/scheduler",
+                "[none] mq-deadline kyber bfq\n",
+            )
+            .file("/sys/block/sda/queue/rotational", "1\n")
+            .file("/sys/block/sda/sda1/partition", "1\n")
+            .file("/sys/block/sda/sda1/size", "3907026944\n")
+            .file("/sys/block/sda/sda2/partition", "2\n")
+            .file("/sys/block/sda/sda2/size", "2048\n")
+            .output("swapon --show", Ok(""))
+            .output("sudo smartctl", Ok(SMARTCTL_JSON))
+    }
+
+    fn run_phase(system: &'static FixtureSystem, interactive: bool) -> Result<PhaseRun> {
+        run_pi_phase(
+            system,
+            "Raspberry Pi 4 Model B Rev 1.5",
+            interactive,
+            install_phase,
+        )
+    }
+
+    #[test]
+    fn parses_smartctl_json() -> Result<()> {
+        let health = parse_smartctl_json(SMARTCTL_JSON)?;
+        assert_eq!(health.model, "WDC WD20SPZX-22UA7T0");
+        assert_eq!(health.serial, "WD-WX12A");
+        assert_eq!(health.smart_status, "Passed");
+        assert_eq!(health.temperature, Some(38));
+        assert_eq!(health.power_on_hours, Some(5120));
+
+        let failing = parse_smartctl_json(r#"{"smart_status":{"passed":false}}"#)?;
+        assert_eq!(failing.smart_status, "Failed");
+        assert_eq!(failing.model, "unknown");
+        assert!(parse_smartctl_json("smartctl: command not found").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn smart_report_survives_disk_warning_exit_bits() -> Result<()> {
+        // Bit 2 (exit 4): some SMART command failed, but the report is complete.
+        let warned = FixtureSystem::new().output("sudo smartctl", Err((4, SMARTCTL_JSON)));
+        assert_eq!(check_hdd_health(&warned, "sda")?.serial, "WD-WX12A");
+
+        // Bit 1 (exit 2): the device could not be opened.
+        let missing = FixtureSystem::new().output("sudo smartctl", Err((2, "")));
+        assert!(check_hdd_health(&missing, "sdz").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reads_scheduler_and_partitions_from_sysfs() -> Result<()> {
+        let system = pi_with_hdd();
+        assert_eq!(external_disks(&system), ["sda"]);
+        assert!(is_rotational(&system, "sda"));
+
+        let scheduler = get_io_scheduler(&system, "sda")?;
+        assert_eq!(scheduler.current, "none");
+        assert_eq!(scheduler.recommended, "mq-deadline");
+        assert_eq!(scheduler.available, ["none", "mq-deadline", "kyber", "bfq"]);
+        assert_eq!(parse_io_scheduler("[bfq] none\n")?.recommended, "bfq");
+        assert!(parse_io_scheduler("none bfq").is_err());
+
+        let layout = analyze_partition_layout(&system, "sda")?;
+        assert_eq!(layout.partitions.len(), 2);
+        assert_eq!(layout.partitions[0].size, 3907026944 * 512);
+        assert_eq!(layout.partitions[0].filesystem, "ext4");
+        assert_eq!(
+            layout.partitions[0].mount_point.as_deref(),
+            Some("/mnt/hdd")
+        );
+        assert_eq!(layout.partitions[1].filesystem, "unknown");
+        assert_eq!(hdd_mount_point(&system).as_deref(), Some("/mnt/hdd"));
+        Ok(())
+    }
+
+    #[test]
+    fn fstab_proposal_adds_missing_options_and_swap() {
+        let optimizations = vec![MountOptimization {
+            device: "/dev/sda1".into(),
+            mount_point: "/mnt/hdd".into(),
+            filesystem: "ext4".into(),
+            current_options: "rw,relatime".into(),
+            recommended_options: vec!["noatime".into(), "commit=60".into()],
+        }];
+        let proposed = propose_fstab(FSTAB_FIXTURE, &optimizations).expect("changed");
+        assert!(proposed.contains("/dev/sda1\t/mnt/hdd\text4\tdefaults,noatime,commit=60\t0\t2"));
+        assert!(proposed.contains("PARTUUID=abcd-01  /boot/firmware"));
+        assert!(propose_fstab(&proposed, &optimizations).is_none());
+
+        let swapfile = Path::new("/mnt/hdd/swapfile");
+        let with_swap = append_swap_entry(&proposed, swapfile).expect("appended");
+        assert!(with_swap.ends_with("/mnt/hdd/swapfile\tnone\tswap\tsw\t0\t0\n"));
+        assert!(append_swap_entry(&with_swap, swapfile).is_none());
+
+        let diff = fstab_diff(FSTAB_FIXTURE, &with_swap);
+        assert_eq!(diff.lines().count(), 3);
+        assert!(diff.starts_with("-/dev/sda1"));
+        assert!(diff.ends_with("+/mnt/hdd/swapfile\tnone\tswap\tsw\t0\t0\n"));
+    }
+
+    #[test]
+    fn phase_applies_tuning_through_system_ops() -> Result<()> {
+        let system: &'static FixtureSystem = pi_with_hdd().leak();
+        let run = run_phase(system, true)?;
+
+        let fstab = system.read("/etc/fstab").expect("fstab");
+        assert!(fstab.contains("defaults,noatime,commit=60"));
+        assert!(fstab.contains("/mnt/hdd/swapfile\tnone\tswap"));
+        assert_eq!(
+            system.read("/etc/fstab.mash-bak").as_deref(),
+            Some(FSTAB_FIXTURE)
+        );
+        assert!(system.read("/staging/pi4b/fstab.proposed").is_some());
+        assert_eq!(system.read(SYSCTL_CONF), Some(sysctl_conf()));
+        assert!(system
+            .read(SCHEDULER_RULE)
+            .expect("udev rule")
+            .contains("ATTR{queue/scheduler}=\"mq-deadline\""));
+
+        assert_eq!(
+            system.commands(),
+            [
+                "swapon --show=NAME,SIZE --bytes --noheadings",
+                "swapon --show=NAME --noheadings",
+                "sudo fallocate -l 4096M /mnt/hdd/swapfile",
+                "sudo chmod 600 /mnt/hdd/swapfile",
+                "sudo mkswap /mnt/hdd/swapfile",
+                "sudo swapon /mnt/hdd/swapfile",
+                "sudo sysctl -p /etc/sysctl.d/60-mash-pi4b-hdd.conf",
+                "sudo udevadm control --reload-rules",
+                "sudo udevadm trigger --subsystem-match=block --action=change",
+                "sudo smartctl --json=c -H -i -A /dev/sda",
+            ]
+        );
+        assert_eq!(
+            run.rollback,
+            [
+                "remove HDD swap file",
+                "restore /etc/fstab",
+                "remove HDD sysctl settings",
+                "remove HDD scheduler udev rule",
+            ]
+        );
+        assert!(
+            run.metadata.warnings.is_empty(),
+            "{:?}",
+            run.metadata.warnings
+        );
+        assert!(run
+            .metadata
+            .actions_taken
+            .iter()
+            .any(|a| a.contains("SMART /dev/sda") && a.contains("Passed")));
+        Ok(())
+    }
+
+    #[test]
+    fn active_swap_file_is_switched_off_before_resizing() -> Result<()> {
+        let system: &'static FixtureSystem = pi_with_hdd()
+            .output("swapon --show", Ok("/mnt/hdd/swapfile 1073741824\n"))
+            .leak();
+        let run = run_phase(system, true)?;
+
+        let commands = system.commands();
+        let swapoff = commands
+            .iter()
+            .position(|c| c == "sudo swapoff /mnt/hdd/swapfile")
+            .expect("swapoff");
+        let fallocate = commands
+            .iter()
+            .position(|c| c.starts_with("sudo fallocate"))
+            .expect("fallocate");
+        assert!(swapoff < fallocate);
+        assert!(!run.rollback.iter().any(|step| step.contains("swap file")));
+        Ok(())
+    }
+
+    #[test]
+    fn unattended_runs_only_propose_fstab_changes() -> Result<()> {
+        let system: &'static FixtureSystem = pi_with_hdd().leak();
+        let run = run_phase(system, false)?;
+
+        assert_eq!(system.read("/etc/fstab").as_deref(), Some(FSTAB_FIXTURE));
+        assert!(system.read("/etc/fstab.mash-bak").is_none());
+        let proposal = system
+            .read("/staging/pi4b/fstab.proposed")
+            .expect("proposal");
+        assert!(proposal.contains("defaults,noatime,commit=60"));
+        assert!(run
+            .metadata
+            .warnings
+            .iter()
+            .any(|w| w.contains("/etc/fstab left unchanged")));
+        Ok(())
+    }
+
     #[test]
     fn test_is_raspberry_pi_4b() {
         let system = RealSystem;
@@ -627,7 +1334,7 @@ fn test_pi4b_hdd_preflight_checks() {
         let system = RealSystem;
         let checks = pi4b_hdd_preflight_checks(&system).unwrap();
 
-        if is_raspberry_pi_4b(&system) {
+        if is_usb3_pi(&system) {
             assert!(checks.len() >= 4, "Should have multiple checks on Pi 4B");
             assert!(checks.iter().any(|c| c.label.contains("USB 3.0")));
             assert!(checks.iter().any(|c| c.label.contains("External HDD")));
@@ -650,44 +1357,12 @@ fn test_detect_usb3_controllers() {
         }
     }
 
-    #[test]
-    fn test_check_hdd_health_mock() {
-        let result = check_hdd_health("sda").unwrap();
-        assert_eq!(result.model, "Sample HDD");
-        assert_eq!(result.smart_status, "Passed");
-        assert!(result.temperature.unwrap() > 0);
-    }
-
-    #[test]
-    fn test_analyze_partition_layout_mock() {
-        let result = analyze_partition_layout("sda").unwrap();
-        assert_eq!(result.device, "sda");
-        assert_eq!(result.partitions.len(), 1);
-        assert_eq!(result.partitions[0].filesystem, "ext4");
-    }
-
-    #[test]
-    fn test_get_io_scheduler_mock() {
-        let result = get_io_scheduler("sda").unwrap();
-        assert_eq!(result.current, "cfq");
-        assert_eq!(result.recommended, "deadline");
-        assert!(result.available.contains(&"deadline".to_string()));
-        assert!(result.available.contains(&"noop".to_string()));
-    }
-
-    #[test]
-    fn test_set_io_scheduler() {
-        assert!(set_io_scheduler("sda", "deadline").is_ok());
-        assert!(set_io_scheduler("sda", "noop").is_ok());
-        assert!(set_io_scheduler("sda", "invalid").is_err());
-    }
-
     #[test]
     fn test_optimize_io_scheduler() {
         let system = RealSystem;
         let checks = optimize_io_scheduler(&system).unwrap();
 
-        if is_raspberry_pi_4b(&system) {
+        if is_usb3_pi(&system) {
             assert!(!checks.is_empty());
             assert!(checks.iter().any(|c| c.label.contains("I/O Scheduler")));
         } else {
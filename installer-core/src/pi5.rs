@@ -0,0 +1,294 @@
+//! Raspberry Pi 5 tuning: PCIe gen 3 for NVMe drives, an active cooler fan
+//! curve and the USB current limit, set under `[pi5]` in `config.txt`.
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::boot_config::{self, BootConfig, BOOT_DIRS};
+use crate::pi4b::external_disks;
+use crate::pi_board::{self, PiBoard, PiHardware};
+use crate::{PhaseContext, PhaseResult};
+
+/// Filter section the settings go under, so a card moved to another board
+/// does not pick them up.
+const SECTION: &str = "pi5";
+
+const PCIE_GEN3: &str = "dtparam=pciex1_gen=3";
+const USB_MAX_CURRENT: &str = "usb_max_current_enable=1";
+
+/// Fan trip points as (°C, PWM speed out of 255). Each step is a few degrees
+/// earlier than the firmware default, so long builds stay clear of the 85°C
+/// throttle point.
+const FAN_CURVE: &[(u32, u8)] = &[(45, 75), (55, 125), (62, 175), (70, 250)];
+const FAN_HYSTERESIS_C: u32 = 5;
+
+/// `dtparam` lines for the fan curve, one per trip point.
+pub fn fan_curve_lines() -> Vec<String> {
+    FAN_CURVE
+        .iter()
+        .enumerate()
+        .map(|(i, (celsius, speed))| {
+            format!(
+                "dtparam=fan_temp{i}={},fan_temp{i}_hyst={},fan_temp{i}_speed={speed}",
+                celsius * 1000,
+                FAN_HYSTERESIS_C * 1000
+            )
+        })
+        .collect()
+}
+
+/// Phase entry point. Skips gracefully on anything but a BCM2712 board.
+pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
+    if !ctx.platform.is_pi_5() {
+        ctx.record_warning("Not running on a Raspberry Pi 5 — skipping Pi 5 tuning");
+        return Ok(PhaseResult::Success);
+    }
+    let system = ctx.platform.system;
+    let Some(hw) = pi_board::detect(system) else {
+        ctx.record_warning("Board model unreadable from the device tree — skipping Pi 5 tuning");
+        return Ok(PhaseResult::Success);
+    };
+    let Some(config_path) = boot_config::config_path(system) else {
+        ctx.record_warning(format!(
+            "No config.txt under {} — skipping Pi 5 tuning",
+            BOOT_DIRS.join(" or ")
+        ));
+        return Ok(PhaseResult::Success);
+    };
+    let current = BootConfig::parse(&system.read_to_string(&config_path)?);
+    ctx.record_action(format!(
+        "{} detected ({}{})",
+        hw.board.label(),
+        hw.soc.as_deref().unwrap_or("unknown SoC"),
+        hw.ram_mb
+            .map(|mb| format!(", {}GB RAM", mb / 1024))
+            .unwrap_or_default()
+    ));
+
+    let mut settings = Vec::new();
+    pcie_settings(ctx, &hw, &mut settings);
+    fan_settings(ctx, &hw, &mut settings);
+    usb_current_settings(ctx, &hw, &current, &config_path, &mut settings);
+
+    let changed = boot_config::edit_config(ctx, "pi5_tuning", |config| {
+        for setting in &settings {
+            config.ensure(SECTION, setting)?;
+        }
+        Ok(())
+    })?;
+    if !changed {
+        ctx.record_action(format!("{} already tuned", config_path.display()));
+    }
+
+    Ok(PhaseResult::Success)
+}
+
+fn pcie_settings(ctx: &mut PhaseContext, hw: &PiHardware, settings: &mut Vec<String>) {
+    if !hw.board.supports_pcie_gen3() {
+        return;
+    }
+    if hw.nvme.is_empty() {
+        ctx.record_action("No NVMe drive on PCIe; link left at gen 2");
+        return;
+    }
+    let drives: Vec<String> = hw
+        .nvme
+        .iter()
+        .map(|drive| format!("{} ({})", drive.name, drive.model))
+        .collect();
+    settings.push(PCIE_GEN3.to_string());
+    ctx.record_tweaked(format!("PCIe link set to gen 3 for {}", drives.join(", ")));
+}
+
+fn fan_settings(ctx: &mut PhaseContext, hw: &PiHardware, settings: &mut Vec<String>) {
+    if hw.active_cooler {
+        settings.extend(fan_curve_lines());
+        ctx.record_tweaked(format!(
+            "Active cooler fan curve starts at {}°C",
+            FAN_CURVE[0].0
+        ));
+    } else if hw.board != PiBoard::Pi500 {
+        ctx.record_warning(format!(
+            "No active cooler on this {}; sustained builds will throttle it",
+            hw.board.label()
+        ));
+    }
+}
+
+fn usb_current_settings(
+    ctx: &mut PhaseContext,
+    hw: &PiHardware,
+    config: &BootConfig,
+    config_path: &Path,
+    settings: &mut Vec<String>,
+) {
+    let disks = external_disks(ctx.platform.system);
+    if hw.board != PiBoard::Pi5
+        || disks.is_empty()
+        || config.value(SECTION, USB_MAX_CURRENT) == Some(USB_MAX_CURRENT)
+    {
+        return;
+    }
+    if ctx.options.dry_run {
+        ctx.record_dry_run(
+            "pi5_tuning",
+            "Would ask to raise the USB current limit",
+            Some(format!("{USB_MAX_CURRENT} for {}", disks.join(", "))),
+        );
+        return;
+    }
+    let prompt = format!(
+        "USB disks found ({}). Raise the USB current limit to 1.6A ({USB_MAX_CURRENT})?\n\
+         Only do this with a 27W (5A) power supply.",
+        disks.join(", ")
+    );
+    if ctx.observer.confirm(&prompt) {
+        settings.push(USB_MAX_CURRENT.to_string());
+        ctx.record_tweaked("USB current limit raised to 1.6A for external disks");
+    } else {
+        ctx.record_warning(format!(
+            "USB ports stay limited to 600mA unless the supply negotiates 5A; \
+             add {USB_MAX_CURRENT} to {} if your disks brown out",
+            config_path.display()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::fixture::{run_pi_phase, FixtureSystem};
+
+    const CONFIG: &str = "\
+# For more options and information see
+# http://rptl.io/configtxt
+dtparam=audio=on
+camera_auto_detect=1
+
+[cm5]
+dtoverlay=dwc2,dr_mode=host
+
+[all]
+";
+
+    fn pi5(model: &str) -> FixtureSystem {
+        FixtureSystem::new()
+            .file("/proc/device-tree/model", &format!("{model}\0"))
+            .file(
+                "/proc/cpuinfo",
+                "processor\t: 0\nRevision\t: d04170\nModel\t\t: Raspberry Pi 5\n",
+            )
+            .file("/sys/block/nvme0n1/size", "1000215216\n")
+            .file(
+                "/sys/block/nvme0n1/device/model",
+                "WD_BLACK SN770 500GB  \n",
+            )
+            .file("/sys/block/sda/size", "3907029168\n")
+            .file("/sys/class/hwmon/hwmon0/name", "cpu_thermal\n")
+            .file("/sys/class/hwmon/hwmon2/name", "pwmfan\n")
+            .file("/boot/firmware/config.txt", CONFIG)
+    }
+
+    #[test]
+    fn fan_curve_uses_millidegrees() {
+        let lines = fan_curve_lines();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "dtparam=fan_temp0=45000,fan_temp0_hyst=5000,fan_temp0_speed=75"
+        );
+    }
+
+    #[test]
+    fn pi5_gets_pcie_fan_and_usb_settings() -> Result<()> {
+        let system = pi5("Raspberry Pi 5 Model B Rev 1.0").leak();
+        let run = run_pi_phase(
+            system,
+            "Raspberry Pi 5 Model B Rev 1.0",
+            true,
+            install_phase,
+        )?;
+
+        let config = system.read("/boot/firmware/config.txt").expect("config");
+        assert!(config.starts_with(CONFIG));
+        assert!(config.contains(&format!("\n[pi5]\n{PCIE_GEN3}\ndtparam=fan_temp0=45000")));
+        assert!(config.ends_with(&format!("{USB_MAX_CURRENT}\n[all]\n")));
+        assert_eq!(
+            system.read("/boot/firmware/config.txt.mash-bak").as_deref(),
+            Some(CONFIG)
+        );
+        assert_eq!(run.rollback, ["restore /boot/firmware/config.txt"]);
+        assert_eq!(
+            run.metadata.warnings,
+            ["Reboot to apply the /boot/firmware/config.txt changes"]
+        );
+        assert!(run
+            .metadata
+            .actions_taken
+            .iter()
+            .any(|a| a.contains("Raspberry Pi 5 detected (BCM2712, 8GB RAM)")));
+        assert!(system.commands().is_empty());
+
+        // A second run finds everything in place
+        let rerun = run_pi_phase(
+            system,
+            "Raspberry Pi 5 Model B Rev 1.0",
+            true,
+            install_phase,
+        )?;
+        assert!(rerun.rollback.is_empty());
+        assert!(rerun
+            .metadata
+            .actions_taken
+            .iter()
+            .any(|a| a.ends_with("config.txt already tuned")));
+        Ok(())
+    }
+
+    #[test]
+    fn unattended_runs_leave_the_usb_limit_alone() -> Result<()> {
+        let system = pi5("Raspberry Pi 5 Model B Rev 1.0")
+            .file("/sys/class/hwmon/hwmon2/name", "rp1_adc\n")
+            .leak();
+        let run = run_pi_phase(
+            system,
+            "Raspberry Pi 5 Model B Rev 1.0",
+            false,
+            install_phase,
+        )?;
+
+        let config = system.read("/boot/firmware/config.txt").expect("config");
+        assert!(config.contains(PCIE_GEN3));
+        assert!(!config.contains(USB_MAX_CURRENT));
+        assert!(!config.contains("fan_temp0"));
+        assert!(run
+            .metadata
+            .warnings
+            .iter()
+            .any(|w| w.contains("No active cooler")));
+        assert!(run
+            .metadata
+            .warnings
+            .iter()
+            .any(|w| w.contains("add usb_max_current_enable=1")));
+        Ok(())
+    }
+
+    #[test]
+    fn other_boards_are_skipped() -> Result<()> {
+        let system = pi5("Raspberry Pi 4 Model B Rev 1.5").leak();
+        let run = run_pi_phase(
+            system,
+            "Raspberry Pi 4 Model B Rev 1.5",
+            true,
+            install_phase,
+        )?;
+        assert_eq!(
+            system.read("/boot/firmware/config.txt").as_deref(),
+            Some(CONFIG)
+        );
+        assert!(run.rollback.is_empty());
+        assert_eq!(run.metadata.warnings.len(), 1);
+        Ok(())
+    }
+}
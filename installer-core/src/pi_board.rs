@@ -0,0 +1,373 @@
+//! Raspberry Pi board identification.
+//!
+//! Boards are recognised from the device-tree model string and, when the
+//! firmware reports one, the new-style revision code in `/proc/cpuinfo`,
+//! which also encodes the SoC and the amount of RAM fitted.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::system::system_ops::SystemOps;
+
+/// Raspberry Pi boards the installer knows how to tune.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PiBoard {
+    Zero,
+    Zero2W,
+    Pi2B,
+    Pi3,
+    Cm3,
+    Pi4B,
+    Pi400,
+    Cm4,
+    Pi5,
+    Pi500,
+    Cm5,
+    /// A Raspberry Pi we do not have tuning for (Pi 1, CM1, ...).
+    Other,
+}
+
+impl PiBoard {
+    /// Identify the board from a device-tree model string such as
+    /// `Raspberry Pi 5 Model B Rev 1.0`. Returns `None` for non-Pi hardware.
+    pub fn from_model(model: &str) -> Option<Self> {
+        if !model.contains("Raspberry Pi") {
+            return None;
+        }
+        // Longer names first: "Pi 500" contains "Pi 5", "Pi 400" contains "Pi 4".
+        let board = if model.contains("Compute Module 5") {
+            Self::Cm5
+        } else if model.contains("Compute Module 4") {
+            Self::Cm4
+        } else if model.contains("Compute Module 3") {
+            Self::Cm3
+        } else if model.contains("Pi 500") {
+            Self::Pi500
+        } else if model.contains("Pi 5") {
+            Self::Pi5
+        } else if model.contains("Pi 400") {
+            Self::Pi400
+        } else if model.contains("Pi 4") {
+            Self::Pi4B
+        } else if model.contains("Pi 3") {
+            Self::Pi3
+        } else if model.contains("Pi 2") {
+            Self::Pi2B
+        } else if model.contains("Zero 2") {
+            Self::Zero2W
+        } else if model.contains("Zero") {
+            Self::Zero
+        } else {
+            Self::Other
+        };
+        Some(board)
+    }
+
+    /// Map the board type field of a revision code.
+    fn from_type_code(code: u32) -> Self {
+        match code {
+            0x04 => Self::Pi2B,
+            0x08 | 0x0d | 0x0e => Self::Pi3,
+            0x0a | 0x10 => Self::Cm3,
+            0x09 | 0x0c => Self::Zero,
+            0x11 => Self::Pi4B,
+            0x12 => Self::Zero2W,
+            0x13 => Self::Pi400,
+            0x14 | 0x15 => Self::Cm4,
+            0x17 => Self::Pi5,
+            0x18 | 0x1a => Self::Cm5,
+            0x19 => Self::Pi500,
+            _ => Self::Other,
+        }
+    }
+
+    /// Human-readable board name.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Zero => "Raspberry Pi Zero",
+            Self::Zero2W => "Raspberry Pi Zero 2 W",
+            Self::Pi2B => "Raspberry Pi 2B",
+            Self::Pi3 => "Raspberry Pi 3",
+            Self::Cm3 => "Compute Module 3",
+            Self::Pi4B => "Raspberry Pi 4B",
+            Self::Pi400 => "Raspberry Pi 400",
+            Self::Cm4 => "Compute Module 4",
+            Self::Pi5 => "Raspberry Pi 5",
+            Self::Pi500 => "Raspberry Pi 500",
+            Self::Cm5 => "Compute Module 5",
+            Self::Other => "Raspberry Pi",
+        }
+    }
+
+    /// Board generation (the SoC family), e.g. 4 for BCM2711 boards.
+    pub fn generation(&self) -> Option<u8> {
+        match self {
+            Self::Pi2B => Some(2),
+            Self::Pi3 | Self::Cm3 | Self::Zero2W => Some(3),
+            Self::Pi4B | Self::Pi400 | Self::Cm4 => Some(4),
+            Self::Pi5 | Self::Pi500 | Self::Cm5 => Some(5),
+            Self::Zero | Self::Other => None,
+        }
+    }
+
+    pub fn is_compute_module(&self) -> bool {
+        matches!(self, Self::Cm3 | Self::Cm4 | Self::Cm5)
+    }
+
+    /// USB 3.0 ports on the board itself (CM4 only routes USB 2.0).
+    pub fn has_usb3(&self) -> bool {
+        matches!(
+            self,
+            Self::Pi4B | Self::Pi400 | Self::Pi5 | Self::Pi500 | Self::Cm5
+        )
+    }
+
+    /// An exposed PCIe lane that NVMe drives can hang off.
+    pub fn has_pcie(&self) -> bool {
+        matches!(self, Self::Pi5 | Self::Cm4 | Self::Cm5)
+    }
+
+    /// Whether the PCIe lane can be switched from gen 2 to gen 3.
+    pub fn supports_pcie_gen3(&self) -> bool {
+        matches!(self, Self::Pi5 | Self::Cm5)
+    }
+}
+
+/// Fields decoded from a new-style revision code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PiRevision {
+    pub code: u32,
+    pub board: PiBoard,
+    pub soc: &'static str,
+    pub ram_mb: u32,
+    pub pcb_revision: u8,
+}
+
+impl PiRevision {
+    /// Decode a hex revision code (`d04170`). Old-style codes, which predate
+    /// the Pi 2, carry no field layout and yield `None`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let code = u32::from_str_radix(text.trim(), 16).ok()?;
+        if code & (1 << 23) == 0 {
+            return None;
+        }
+        let soc = match (code >> 12) & 0xf {
+            0 => "BCM2835",
+            1 => "BCM2836",
+            2 => "BCM2837",
+            3 => "BCM2711",
+            4 => "BCM2712",
+            _ => "unknown",
+        };
+        Some(Self {
+            code,
+            board: PiBoard::from_type_code((code >> 4) & 0xff),
+            soc,
+            ram_mb: 256 << ((code >> 20) & 0x7),
+            pcb_revision: (code & 0xf) as u8,
+        })
+    }
+}
+
+/// An NVMe drive; on a Pi these always sit on the PCIe lane.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NvmeDrive {
+    pub name: String,
+    pub model: String,
+    pub size_bytes: u64,
+}
+
+/// Everything the installer knows about the Pi it runs on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiHardware {
+    pub board: PiBoard,
+    pub model: String,
+    pub revision: Option<String>,
+    pub soc: Option<String>,
+    pub ram_mb: Option<u32>,
+    pub nvme: Vec<NvmeDrive>,
+    /// A PWM fan (the Pi 5 Active Cooler or a case fan on the FAN header).
+    pub active_cooler: bool,
+}
+
+impl PiHardware {
+    pub fn is_pi_5(&self) -> bool {
+        self.board.generation() == Some(5)
+    }
+}
+
+/// Probe the device tree, `/proc/cpuinfo` and sysfs. Returns `None` off a Pi.
+pub fn detect(system: &dyn SystemOps) -> Option<PiHardware> {
+    let model = system
+        .read_to_string(Path::new("/proc/device-tree/model"))
+        .ok()?
+        .trim_end_matches('\0')
+        .trim()
+        .to_string();
+    let from_model = PiBoard::from_model(&model)?;
+    let revision = system
+        .read_to_string(Path::new("/proc/cpuinfo"))
+        .ok()
+        .and_then(|cpuinfo| cpuinfo_revision(&cpuinfo));
+    let decoded = revision.as_deref().and_then(PiRevision::parse);
+    let board = match decoded {
+        Some(rev) if rev.board != PiBoard::Other => rev.board,
+        _ => from_model,
+    };
+
+    Some(PiHardware {
+        board,
+        model,
+        soc: decoded.map(|rev| rev.soc.to_string()),
+        ram_mb: decoded.map(|rev| rev.ram_mb),
+        revision,
+        nvme: nvme_drives(system),
+        active_cooler: has_active_cooler(system),
+    })
+}
+
+fn cpuinfo_revision(cpuinfo: &str) -> Option<String> {
+    cpuinfo
+        .lines()
+        .find(|line| line.starts_with("Revision"))
+        .and_then(|line| line.split_once(':'))
+        .map(|(_, value)| value.trim().to_string())
+}
+
+/// NVMe namespaces listed under `/sys/block`.
+pub fn nvme_drives(system: &dyn SystemOps) -> Vec<NvmeDrive> {
+    let block = Path::new("/sys/block");
+    system
+        .read_dir(block)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+        .filter(|name| name.starts_with("nvme"))
+        .map(|name| {
+            let dir = block.join(&name);
+            let model = system
+                .read_to_string(&dir.join("device/model"))
+                .map(|model| model.trim().to_string())
+                .unwrap_or_else(|_| "unknown".into());
+            let sectors: u64 = system
+                .read_to_string(&dir.join("size"))
+                .ok()
+                .and_then(|size| size.trim().parse().ok())
+                .unwrap_or(0);
+            NvmeDrive {
+                name,
+                model,
+                size_bytes: sectors * 512,
+            }
+        })
+        .collect()
+}
+
+/// The Active Cooler registers as a `pwmfan` hwmon device.
+pub fn has_active_cooler(system: &dyn SystemOps) -> bool {
+    system
+        .read_dir(Path::new("/sys/class/hwmon"))
+        .unwrap_or_default()
+        .iter()
+        .any(|hwmon| {
+            system
+                .read_to_string(&hwmon.join("name"))
+                .is_ok_and(|name| name.trim() == "pwmfan")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::fixture::FixtureSystem;
+
+    #[test]
+    fn identifies_boards_from_model_strings() {
+        let cases = [
+            ("Raspberry Pi 5 Model B Rev 1.0", PiBoard::Pi5),
+            ("Raspberry Pi 500 Rev 1.0", PiBoard::Pi500),
+            ("Raspberry Pi Compute Module 5 Rev 1.0", PiBoard::Cm5),
+            ("Raspberry Pi Compute Module 4 Rev 1.1", PiBoard::Cm4),
+            ("Raspberry Pi 400 Rev 1.0", PiBoard::Pi400),
+            ("Raspberry Pi 4 Model B Rev 1.5", PiBoard::Pi4B),
+            ("Raspberry Pi 3 Model B Plus Rev 1.3", PiBoard::Pi3),
+            ("Raspberry Pi Zero 2 W Rev 1.0", PiBoard::Zero2W),
+            ("Raspberry Pi Model B Rev 2", PiBoard::Other),
+        ];
+        for (model, board) in cases {
+            assert_eq!(PiBoard::from_model(model), Some(board), "{model}");
+        }
+        assert_eq!(PiBoard::from_model("Pine64 RockPro64"), None);
+    }
+
+    #[test]
+    fn decodes_revision_codes() {
+        let pi5 = PiRevision::parse("d04170").expect("new-style code");
+        assert_eq!(pi5.board, PiBoard::Pi5);
+        assert_eq!(pi5.soc, "BCM2712");
+        assert_eq!(pi5.ram_mb, 8192);
+
+        let cm4 = PiRevision::parse("b03140").expect("new-style code");
+        assert_eq!(cm4.board, PiBoard::Cm4);
+        assert_eq!(cm4.ram_mb, 2048);
+
+        let cm5 = PiRevision::parse("e04180").expect("new-style code");
+        assert_eq!(cm5.board, PiBoard::Cm5);
+        assert_eq!(cm5.ram_mb, 16384);
+
+        let pi4 = PiRevision::parse("c03114").expect("new-style code");
+        assert_eq!(
+            (pi4.board, pi4.soc, pi4.pcb_revision),
+            (PiBoard::Pi4B, "BCM2711", 4)
+        );
+
+        assert_eq!(PiRevision::parse("000e"), None);
+        assert_eq!(PiRevision::parse("not hex"), None);
+    }
+
+    #[test]
+    fn capabilities_follow_the_board() {
+        assert!(PiBoard::Pi5.has_usb3() && PiBoard::Pi5.supports_pcie_gen3());
+        assert!(PiBoard::Cm4.has_pcie() && !PiBoard::Cm4.has_usb3());
+        assert!(!PiBoard::Cm4.supports_pcie_gen3());
+        assert!(!PiBoard::Pi500.has_pcie());
+        assert_eq!(PiBoard::Pi400.generation(), Some(4));
+        assert!(PiBoard::Cm5.is_compute_module());
+    }
+
+    #[test]
+    fn detects_cm5_with_nvme_and_fan_from_sysfs() {
+        let system = FixtureSystem::new()
+            .file(
+                "/proc/device-tree/model",
+                "Raspberry Pi Compute Module 5 Rev 1.0\0",
+            )
+            .file(
+                "/proc/cpuinfo",
+                "Revision\t: e04180\nSerial\t\t: 10000000\n",
+            )
+            .file("/sys/block/mmcblk0/size", "61071360\n")
+            .file("/sys/block/nvme0n1/size", "1953525168\n")
+            .file("/sys/block/nvme0n1/device/model", "Samsung SSD 980 1TB\n")
+            .file("/sys/class/hwmon/hwmon3/name", "pwmfan\n");
+
+        let hw = detect(&system).expect("a Pi");
+        assert_eq!(hw.board, PiBoard::Cm5);
+        assert!(hw.is_pi_5());
+        assert_eq!(hw.soc.as_deref(), Some("BCM2712"));
+        assert_eq!(hw.ram_mb, Some(16384));
+        assert_eq!(hw.revision.as_deref(), Some("e04180"));
+        assert_eq!(
+            hw.nvme,
+            [NvmeDrive {
+                name: "nvme0n1".into(),
+                model: "Samsung SSD 980 1TB".into(),
+                size_bytes: 1953525168 * 512,
+            }]
+        );
+        assert!(hw.active_cooler);
+
+        let pc = FixtureSystem::new().file("/proc/device-tree/model", "QEMU Virt\0");
+        assert!(detect(&pc).is_none());
+    }
+}
@@ -10,7 +10,7 @@
 use crate::context::PhaseContext;
 use crate::driver::DistroDriver;
 
-use crate::package_manager::installer_for;
+use crate::package_manager;
 
 // ── Package Categories ──────────────────────────────────────────────────────
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -709,7 +709,7 @@ pub async fn install_category(
         ));
 
         let package_refs: Vec<&str> = packages.iter().map(|s| s.as_str()).collect();
-        installer_for(driver).ensure_packages(&package_refs, ctx.options.dry_run)?;
+        package_manager::ensure_native_packages(driver, &package_refs, ctx.options.dry_run)?;
 
         ctx.record_action(format!(
             "✅ Installed {} packages for {:?}",
@@ -66,6 +66,15 @@ pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
     Ok(PhaseResult::Success)
 }
 
+/// Required system packages for `profile`, by canonical name.
+pub(crate) fn required_packages(profile: ProfileLevel) -> Vec<&'static str> {
+    system_package_specs()
+        .into_iter()
+        .filter(|spec| spec.is_applicable(profile) && spec.intent() == PackageIntent::Required)
+        .map(|spec| spec.canonical())
+        .collect()
+}
+
 fn system_package_specs() -> Vec<PackageSpec<'static>> {
     let mut specs = vec![
         PackageSpec::required("ca-certificates"),
@@ -0,0 +1,147 @@
+//! Pre-flight plan.
+//!
+//! `mash-setup plan` dry-runs the selected phases and lists, phase by phase,
+//! the packages each backend would install, the repositories, files,
+//! services and downloads the run would touch, and every command it held
+//! back. Reviewers read it before letting the installer near a shared
+//! machine.
+
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::doctor::format_bytes;
+use crate::driver::DistroDriver;
+use crate::model::options::InstallOptions;
+use crate::model::phase::{PhaseObserver, PlannedChange};
+use crate::orchestrator::run_preflight_audit;
+use crate::system::dry_run::PreflightAuditReport;
+use crate::VerifyOutput;
+
+/// `mash-setup plan`: dry-run `options`, print the plan and return it.
+pub fn run_plan(
+    driver: &'static dyn DistroDriver,
+    options: &InstallOptions,
+    format: VerifyOutput,
+    observer: &mut dyn PhaseObserver,
+    out: &mut dyn Write,
+) -> Result<PreflightAuditReport> {
+    let report = run_preflight_audit(driver, options, observer)?;
+    match format {
+        VerifyOutput::Json => writeln!(out, "{}", serde_json::to_string_pretty(&report)?)?,
+        VerifyOutput::Pretty => {
+            writeln!(out, "mash-setup plan")?;
+            writeln!(out, "===============")?;
+            for line in pretty_lines(&report) {
+                writeln!(out, "{line}")?;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// The plan as text, one phase after another. The TUI shows the same lines
+/// on its review screen.
+pub fn pretty_lines(report: &PreflightAuditReport) -> Vec<String> {
+    let mut lines = Vec::new();
+    for phase in &report.plan {
+        lines.push(String::new());
+        lines.push(format!("== {} ({})", phase.label, phase.phase));
+        if phase.changes.is_empty() {
+            lines.push("  nothing to change".to_string());
+        }
+        for change in &phase.changes {
+            describe(change, &mut lines);
+        }
+    }
+    lines.push(String::new());
+    lines.push(format!(
+        "{} changes across {} phases. Nothing has been changed yet.",
+        report.planned_changes(),
+        report.plan.len()
+    ));
+    lines
+}
+
+fn describe(change: &PlannedChange, lines: &mut Vec<String>) {
+    match change {
+        PlannedChange::Packages { backend, packages } => {
+            lines.push(format!("  packages ({backend}): {}", packages.join(", ")));
+        }
+        PlannedChange::Repo { name, path, entry } => {
+            lines.push(format!("  repo {name}: {}", path.display()));
+            lines.push(format!("      {entry}"));
+        }
+        PlannedChange::File { path, exists, diff } => {
+            let state = if *exists { "changed" } else { "new" };
+            lines.push(format!("  file {} ({state}):", path.display()));
+            lines.extend(diff.lines().map(|line| format!("      {line}")));
+        }
+        PlannedChange::Service { unit } => lines.push(format!("  service: enable {unit}")),
+        PlannedChange::Download { url, size } => {
+            let size = size.map_or_else(|| "size unknown".to_string(), format_bytes);
+            lines.push(format!("  download: {url} ({size})"));
+        }
+        PlannedChange::Command { command } => lines.push(format!("  run: {command}")),
+        PlannedChange::Note { action, detail } => match detail {
+            Some(detail) => lines.push(format!("  note: {action} ({detail})")),
+            None => lines.push(format!("  note: {action}")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dry_run::PhasePlan;
+
+    #[test]
+    fn pretty_lines_list_every_change_under_its_phase() {
+        let report = PreflightAuditReport {
+            plan: vec![
+                PhasePlan {
+                    phase: "docker_engine".into(),
+                    label: "Docker".into(),
+                    changes: vec![
+                        PlannedChange::Packages {
+                            backend: "apt".into(),
+                            packages: vec!["docker-ce".into(), "containerd.io".into()],
+                        },
+                        PlannedChange::File {
+                            path: "/etc/docker/daemon.json".into(),
+                            exists: false,
+                            diff: "+{}\n".into(),
+                        },
+                        PlannedChange::Download {
+                            url: "https://example.com/get.sh".into(),
+                            size: Some(2048),
+                        },
+                    ],
+                },
+                PhasePlan {
+                    phase: "fonts".into(),
+                    label: "Fonts".into(),
+                    changes: Vec::new(),
+                },
+            ],
+            ..PreflightAuditReport::default()
+        };
+
+        assert_eq!(
+            pretty_lines(&report),
+            [
+                "",
+                "== Docker (docker_engine)",
+                "  packages (apt): docker-ce, containerd.io",
+                "  file /etc/docker/daemon.json (new):",
+                "      +{}",
+                "  download: https://example.com/get.sh (2.0 KiB)",
+                "",
+                "== Fonts (fonts)",
+                "  nothing to change",
+                "",
+                "3 changes across 2 phases. Nothing has been changed yet.",
+            ]
+        );
+    }
+}
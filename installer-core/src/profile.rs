@@ -262,6 +262,7 @@ fn detect(system: &dyn SystemOps) -> Result<Self> {
                     model,
                     board_revision: detect_board_revision(system),
                     is_laptop: false, // Pis are not laptops (usually)
+                    pi: crate::pi_board::detect(system),
                 });
             }
             if !model.is_empty() {
@@ -270,6 +271,7 @@ fn detect(system: &dyn SystemOps) -> Result<Self> {
                     model,
                     board_revision: None,
                     is_laptop: false,
+                    pi: None,
                 });
             }
         }
@@ -280,6 +282,7 @@ fn detect(system: &dyn SystemOps) -> Result<Self> {
             model: "Standard PC".to_string(),
             board_revision: None,
             is_laptop: detect_is_laptop(system),
+            pi: None,
         })
     }
 }
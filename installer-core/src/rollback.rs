@@ -1,54 +1,427 @@
-use anyhow::{anyhow, Result};
-use std::cell::RefCell;
+//! Rollback steps recorded as phases change the system.
+//!
+//! Each step is a serializable [`RollbackOp`]. For journaled runs the steps
+//! are also written to `~/.config/mash-installer/rollback/<run-id>.json` as
+//! they are registered, so `mash-setup undo` can replay them after a crash
+//! or long after the run finished.
+//!
+//! A failed run only reverts the files and configs it touched. Removing
+//! packages, groups, units and toolchains is left to `mash-setup undo`, so a
+//! late failure never strips tools that earlier phases installed.
 
-pub struct RollbackEntry {
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::backend::PkgBackend;
+use crate::config::config_path;
+use crate::package_manager;
+use crate::snapshots::SnapshotRecord;
+use crate::system::{cmd, progress};
+
+pub const JOURNAL_DIR: &str = "rollback";
+
+/// One reversible change, described well enough to undo it from a new
+/// process.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RollbackOp {
+    /// Copy `backup` back over `path`.
+    RestoreFile {
+        path: PathBuf,
+        backup: PathBuf,
+    },
+    /// Delete a file or directory the run created.
+    RemoveFile {
+        path: PathBuf,
+    },
+    /// `swapoff` a swap file the run created, then delete it.
+    RemoveSwapFile {
+        path: PathBuf,
+    },
+    /// Delete a directory a tool was installed into, such as `~/.rustup`.
+    RemoveInstallDir {
+        path: PathBuf,
+    },
+    /// Uninstall packages the run installed.
+    RemovePackages {
+        backend: PkgBackend,
+        packages: Vec<String>,
+    },
+    /// Uninstall package groups the run installed.
+    RemoveGroups {
+        backend: PkgBackend,
+        groups: Vec<String>,
+    },
+    /// `systemctl disable --now` a unit the run enabled.
+    DisableUnit {
+        unit: String,
+    },
+    RemoveUserFromGroup {
+        user: String,
+        group: String,
+    },
+    /// `systemctl set-default` back to the target that was the default
+    /// before the run.
+    SetDefaultTarget {
+        target: String,
+    },
+    /// Put a top-level key of a JSON object file back to `previous`, or
+    /// remove it when it did not exist.
+    RevertJsonKey {
+        path: PathBuf,
+        key: String,
+        previous: Option<Value>,
+    },
+}
+
+impl RollbackOp {
+    /// Whether a failed run reverts this step on its own. Only file and
+    /// config changes are; everything else waits for `mash-setup undo`.
+    pub fn is_automatic(&self) -> bool {
+        matches!(
+            self,
+            RollbackOp::RestoreFile { .. }
+                | RollbackOp::RemoveFile { .. }
+                | RollbackOp::RemoveSwapFile { .. }
+                | RollbackOp::RevertJsonKey { .. }
+        )
+    }
+
+    pub fn apply(&self) -> Result<()> {
+        match self {
+            RollbackOp::RestoreFile { path, backup } => {
+                if !backup.exists() {
+                    bail!("backup {} is missing", backup.display());
+                }
+                with_sudo_fallback(
+                    || fs::copy(backup, path).map(|_| ()),
+                    &[
+                        "cp",
+                        "-a",
+                        &backup.to_string_lossy(),
+                        &path.to_string_lossy(),
+                    ],
+                )
+            }
+            RollbackOp::RemoveSwapFile { path } => {
+                if !path.exists() {
+                    return Ok(());
+                }
+                // Fails when the file is not in use, which is fine.
+                let _ = sudo(&["swapoff", &path.to_string_lossy()]);
+                sudo(&["rm", "-f", &path.to_string_lossy()])
+            }
+            RollbackOp::RemoveFile { path } | RollbackOp::RemoveInstallDir { path } => {
+                if !path.exists() {
+                    return Ok(());
+                }
+                let remove = || {
+                    if path.is_dir() {
+                        fs::remove_dir_all(path)
+                    } else {
+                        fs::remove_file(path)
+                    }
+                };
+                with_sudo_fallback(remove, &["rm", "-rf", &path.to_string_lossy()])
+            }
+            RollbackOp::RemovePackages { backend, packages } => {
+                let pkgs: Vec<&str> = packages.iter().map(String::as_str).collect();
+                package_manager::remove_native_packages(*backend, &pkgs)
+            }
+            RollbackOp::RemoveGroups { backend, groups } => {
+                let groups: Vec<&str> = groups.iter().map(String::as_str).collect();
+                package_manager::remove_native_groups(*backend, &groups)
+            }
+            RollbackOp::DisableUnit { unit } => sudo(&["systemctl", "disable", "--now", unit]),
+            RollbackOp::RemoveUserFromGroup { user, group } => {
+                sudo(&["gpasswd", "-d", user, group])
+            }
+            RollbackOp::SetDefaultTarget { target } => sudo(&["systemctl", "set-default", target]),
+            RollbackOp::RevertJsonKey {
+                path,
+                key,
+                previous,
+            } => revert_json_key(path, key, previous.as_ref()),
+        }
+    }
+}
+
+fn sudo(args: &[&str]) -> Result<()> {
+    cmd::Command::new("sudo").args(args).execute()?;
+    Ok(())
+}
+
+/// Try an unprivileged file operation first; system files fall back to the
+/// equivalent command under sudo.
+fn with_sudo_fallback(op: impl Fn() -> io::Result<()>, sudo_args: &[&str]) -> Result<()> {
+    match op() {
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => sudo(sudo_args),
+        other => Ok(other?),
+    }
+}
+
+fn revert_json_key(path: &Path, key: &str, previous: Option<&Value>) -> Result<()> {
+    let mut config: Value = match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .with_context(|| format!("parsing {} to revert '{key}'", path.display()))?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let Some(object) = config.as_object_mut() else {
+        bail!("{} is not a JSON object", path.display());
+    };
+    match previous {
+        Some(value) => {
+            object.insert(key.to_string(), value.clone());
+        }
+        None => {
+            object.remove(key);
+        }
+    }
+    let content = serde_json::to_string_pretty(&config)?;
+
+    match fs::write(path, &content) {
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+            let staged = std::env::temp_dir().join(format!("mash-rollback-{}.json", unix_now()));
+            fs::write(&staged, &content)?;
+            let result = sudo(&["cp", &staged.to_string_lossy(), &path.to_string_lossy()]);
+            let _ = fs::remove_file(&staged);
+            result
+        }
+        other => Ok(other?),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RollbackStep {
     pub label: String,
-    pub action: Box<dyn Fn() -> Result<()> + 'static>,
+    /// Phase that registered the step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+    #[serde(flatten)]
+    pub op: RollbackOp,
 }
 
-impl RollbackEntry {
-    pub fn new(label: impl Into<String>, action: impl Fn() -> Result<()> + 'static) -> Self {
+/// On-disk record of a run's rollback steps, in registration order.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RollbackJournal {
+    pub run_id: String,
+    pub created_at: u64,
+    pub steps: Vec<RollbackStep>,
+    /// Set once the steps have been replayed.
+    pub undone_at: Option<u64>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+pub fn journal_dir() -> PathBuf {
+    config_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(JOURNAL_DIR)
+}
+
+impl RollbackJournal {
+    pub fn new(dir: &Path, run_id: impl Into<String>) -> Self {
+        let run_id = run_id.into();
         Self {
-            label: label.into(),
-            action: Box::new(action),
+            path: dir.join(format!("{run_id}.json")),
+            run_id,
+            created_at: unix_now(),
+            steps: Vec::new(),
+            undone_at: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading rollback journal {}", path.display()))?;
+        let mut journal: Self = serde_json::from_str(&text)
+            .with_context(|| format!("parsing rollback journal {}", path.display()))?;
+        journal.path = path.to_path_buf();
+        Ok(journal)
+    }
+
+    /// Reopen the journal for `run_id` (a resumed run) or start a new one.
+    pub fn open(dir: &Path, run_id: &str) -> Self {
+        let fresh = Self::new(dir, run_id);
+        match Self::load(&fresh.path) {
+            Ok(journal) => journal,
+            Err(_) => fresh,
+        }
+    }
+
+    /// Journals in `dir`, oldest first.
+    pub fn list(dir: &Path) -> Result<Vec<Self>> {
+        let mut journals = Vec::new();
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(journals);
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                journals.push(Self::load(&path)?);
+            }
         }
+        journals.sort_by_key(|journal| journal.created_at);
+        Ok(journals)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("writing rollback journal {}", self.path.display()))
     }
 }
 
+/// Apply `steps` newest first. Every step is attempted; failures are
+/// reported together.
+fn replay(steps: &[RollbackStep], out: &mut dyn Write) -> Result<()> {
+    let mut failures = Vec::new();
+    for step in steps.iter().rev() {
+        match step.op.apply() {
+            Ok(()) => {
+                let _ = writeln!(out, "  undone: {}", step.label);
+            }
+            Err(err) => failures.push(format!("{}: {err}", step.label)),
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("rollback failures: {}", failures.join("; ")))
+    }
+}
+
+/// What [`RollbackManager::rollback_files`] reverted.
+pub struct RolledBack {
+    /// Phases that had at least one step replayed, in no particular order.
+    pub phases: Vec<String>,
+    pub result: Result<()>,
+}
+
 pub struct RollbackManager {
-    entries: RefCell<Vec<RollbackEntry>>,
+    steps: Mutex<Vec<RollbackStep>>,
+    journal: Option<Mutex<RollbackJournal>>,
+    run_id: Option<String>,
+    snapshots: Mutex<Vec<SnapshotRecord>>,
 }
 
 impl RollbackManager {
     pub fn new() -> Self {
         Self {
-            entries: RefCell::new(Vec::new()),
+            steps: Mutex::new(Vec::new()),
+            journal: None,
+            run_id: None,
+            snapshots: Mutex::new(Vec::new()),
         }
     }
 
-    pub fn register_action(
-        &self,
-        label: impl Into<String>,
-        action: impl Fn() -> Result<()> + 'static,
-    ) {
-        self.entries
-            .borrow_mut()
-            .push(RollbackEntry::new(label, action));
+    /// Manager for a journaled run. Steps are persisted under
+    /// [`journal_dir`] and snapshots taken during the run are filed under
+    /// `run_id`.
+    pub fn for_run(run_id: impl Into<String>) -> Self {
+        let run_id = run_id.into();
+        let journal = RollbackJournal::open(&journal_dir(), &run_id);
+        Self::with_journal(run_id, journal)
     }
 
-    pub fn rollback_all(&self) -> Result<()> {
-        let mut entries = self.entries.borrow_mut();
-        let mut failures = Vec::new();
-        while let Some(entry) = entries.pop() {
-            if let Err(err) = (entry.action)() {
-                failures.push(format!("{}: {err}", entry.label));
+    fn with_journal(run_id: String, mut journal: RollbackJournal) -> Self {
+        // A resumed run whose journal was already replayed starts over;
+        // its old steps must not run a second time.
+        if journal.undone_at.take().is_some() {
+            journal.steps.clear();
+        }
+        Self {
+            steps: Mutex::new(journal.steps.clone()),
+            journal: Some(Mutex::new(journal)),
+            run_id: Some(run_id),
+            snapshots: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn run_id(&self) -> Option<&str> {
+        self.run_id.as_deref()
+    }
+
+    pub fn register_action(&self, label: impl Into<String>, op: RollbackOp) {
+        let step = RollbackStep {
+            label: label.into(),
+            phase: progress::current_phase(),
+            op,
+        };
+        if let Some(journal) = &self.journal {
+            let mut journal = journal.lock().unwrap_or_else(|e| e.into_inner());
+            journal.steps.push(step.clone());
+            if let Err(err) = journal.save() {
+                tracing::warn!("rollback step '{}' not persisted: {err:#}", step.label);
             }
         }
-        if failures.is_empty() {
-            Ok(())
-        } else {
-            Err(anyhow!("rollback failures: {}", failures.join("; ")))
+        self.steps
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(step);
+    }
+
+    pub fn steps(&self) -> Vec<RollbackStep> {
+        self.steps.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Remember a pre-install snapshot so the run report can point at it.
+    pub fn record_snapshot(&self, record: SnapshotRecord) {
+        self.snapshots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(record);
+    }
+
+    pub fn snapshots(&self) -> Vec<SnapshotRecord> {
+        self.snapshots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Revert the file and config steps after a failed run, newest first.
+    /// The remaining steps stay in the journal for `mash-setup undo`.
+    pub fn rollback_files(&self) -> RolledBack {
+        let (automatic, kept): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut *self.steps.lock().unwrap_or_else(|e| e.into_inner()))
+                .into_iter()
+                .partition(|step| step.op.is_automatic());
+        let result = replay(&automatic, &mut io::sink());
+        if let Some(journal) = &self.journal {
+            let mut journal = journal.lock().unwrap_or_else(|e| e.into_inner());
+            journal.steps = kept.clone();
+            if kept.is_empty() {
+                journal.undone_at = Some(unix_now());
+            }
+            if let Err(err) = journal.save() {
+                tracing::warn!("could not update rollback journal: {err:#}");
+            }
         }
+        *self.steps.lock().unwrap_or_else(|e| e.into_inner()) = kept;
+
+        let mut phases: Vec<String> = automatic
+            .into_iter()
+            .filter_map(|step| step.phase)
+            .collect();
+        phases.sort();
+        phases.dedup();
+        RolledBack { phases, result }
     }
 }
 
@@ -57,3 +430,173 @@ fn default() -> Self {
         Self::new()
     }
 }
+
+/// Replay the journal of `run_id`, or of the newest run not yet undone.
+pub fn undo(run_id: Option<&str>, out: &mut dyn Write) -> Result<()> {
+    undo_in(&journal_dir(), run_id, out)
+}
+
+fn undo_in(dir: &Path, run_id: Option<&str>, out: &mut dyn Write) -> Result<()> {
+    let mut journal = match run_id {
+        Some(id) => RollbackJournal::load(&dir.join(format!("{id}.json")))?,
+        None => RollbackJournal::list(dir)?
+            .into_iter()
+            .rev()
+            .find(|journal| journal.undone_at.is_none())
+            .with_context(|| format!("no run left to undo in {}", dir.display()))?,
+    };
+
+    if let Some(at) = journal.undone_at {
+        writeln!(
+            out,
+            "Run {} was already undone at {at}; replaying again.",
+            journal.run_id
+        )?;
+    }
+    writeln!(
+        out,
+        "Undoing run {} ({} steps)",
+        journal.run_id,
+        journal.steps.len()
+    )?;
+    let result = replay(&journal.steps, out);
+    journal.undone_at = Some(unix_now());
+    journal.save()?;
+    result
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn journal_round_trips_and_undo_replays_in_reverse() -> Result<()> {
+        let dir = tempdir()?;
+        let config = dir.path().join("daemon.json");
+        let backup = dir.path().join("zshrc.bak");
+        let zshrc = dir.path().join("zshrc");
+        fs::write(&config, r#"{"data-root": "/srv/docker", "debug": true}"#)?;
+        fs::write(&backup, "original")?;
+        fs::write(&zshrc, "modified")?;
+
+        let mut journal = RollbackJournal::new(&dir.path().join(JOURNAL_DIR), "run-1");
+        journal.steps = vec![
+            RollbackStep {
+                label: "restore zshrc".into(),
+                phase: None,
+                op: RollbackOp::RestoreFile {
+                    path: zshrc.clone(),
+                    backup,
+                },
+            },
+            RollbackStep {
+                label: "revert data-root".into(),
+                phase: None,
+                op: RollbackOp::RevertJsonKey {
+                    path: config.clone(),
+                    key: "data-root".into(),
+                    previous: None,
+                },
+            },
+        ];
+        journal.save()?;
+
+        let mut out = Vec::new();
+        undo_in(&dir.path().join(JOURNAL_DIR), None, &mut out)?;
+
+        assert_eq!(fs::read_to_string(&zshrc)?, "original");
+        let reverted: Value = serde_json::from_str(&fs::read_to_string(&config)?)?;
+        assert_eq!(reverted, serde_json::json!({"debug": true}));
+        let log = String::from_utf8(out)?;
+        assert!(log.find("revert data-root") < log.find("restore zshrc"));
+
+        let saved = RollbackJournal::load(journal.path())?;
+        assert!(saved.undone_at.is_some());
+        assert!(undo_in(&dir.path().join(JOURNAL_DIR), None, &mut io::sink()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn steps_serialize_with_an_op_tag() -> Result<()> {
+        let step = RollbackStep {
+            label: "remove docker group".into(),
+            phase: Some("docker_engine".into()),
+            op: RollbackOp::RemoveUserFromGroup {
+                user: "mash".into(),
+                group: "docker".into(),
+            },
+        };
+        let json = serde_json::to_value(&step)?;
+        assert_eq!(json["op"], "remove_user_from_group");
+        assert_eq!(json["group"], "docker");
+        assert_eq!(serde_json::from_value::<RollbackStep>(json)?, step);
+        Ok(())
+    }
+
+    #[test]
+    fn failed_runs_only_revert_files_and_keep_the_rest_for_undo() -> Result<()> {
+        let dir = tempdir()?;
+        let created = dir.path().join("daemon.json");
+        let toolchain = dir.path().join(".rustup");
+        fs::write(&created, "{}")?;
+        fs::create_dir(&toolchain)?;
+        let journal = RollbackJournal::new(dir.path(), "run-2");
+        let manager = RollbackManager::with_journal("run-2".into(), journal.clone());
+
+        let _sink = progress::install("rust_toolchain", |_| {});
+        manager.register_action(
+            "remove .rustup",
+            RollbackOp::RemoveInstallDir {
+                path: toolchain.clone(),
+            },
+        );
+        drop(_sink);
+        let _sink = progress::install("docker_engine", |_| {});
+        manager.register_action(
+            "remove daemon.json",
+            RollbackOp::RemoveFile {
+                path: created.clone(),
+            },
+        );
+
+        let rolled_back = manager.rollback_files();
+        rolled_back.result?;
+
+        assert_eq!(rolled_back.phases, vec!["docker_engine".to_string()]);
+        assert!(!created.exists());
+        assert!(toolchain.exists());
+        let saved = RollbackJournal::load(journal.path())?;
+        assert!(saved.undone_at.is_none());
+        assert_eq!(saved.steps.len(), 1);
+        assert_eq!(saved.steps[0].label, "remove .rustup");
+        Ok(())
+    }
+
+    #[test]
+    fn resuming_an_undone_journal_starts_without_its_steps() -> Result<()> {
+        let dir = tempdir()?;
+        let mut journal = RollbackJournal::new(dir.path(), "run-3");
+        journal.steps.push(RollbackStep {
+            label: "remove docker group".into(),
+            phase: None,
+            op: RollbackOp::RemoveUserFromGroup {
+                user: "mash".into(),
+                group: "docker".into(),
+            },
+        });
+        journal.undone_at = Some(1);
+
+        let manager = RollbackManager::with_journal("run-3".into(), journal);
+
+        assert!(manager.steps().is_empty());
+        Ok(())
+    }
+}
@@ -9,7 +9,10 @@
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 
+use crate::rollback::RollbackOp;
+use crate::sources::{self, InstallSource, ScriptSource, SourceKind};
 use crate::system::cmd;
 use crate::{PhaseContext, PhaseResult};
 
@@ -24,7 +27,26 @@ fn cargo_home() -> PathBuf {
         .unwrap_or_else(|_| dirs::home_dir().unwrap_or_default().join(".cargo"))
 }
 
-fn rustup_bin() -> PathBuf {
+/// Longest `cargo binstall` may take to fetch the whole tool batch.
+const BINSTALL_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Longest a `cargo install` may spend compiling one tool.
+const CARGO_INSTALL_TIMEOUT: Duration = Duration::from_secs(90 * 60);
+
+/// rustup-init arguments: stable with the minimal profile to reduce
+/// download/install time (optimized for Pi 4B).
+const RUSTUP_INIT_ARGS: [&str; 5] = [
+    "-y",
+    "--default-toolchain",
+    "stable",
+    "--profile",
+    "minimal",
+];
+
+/// Toolchain components added on top of the minimal profile.
+pub(crate) const COMPONENTS: [&str; 3] = ["rustfmt", "clippy", "rust-src"];
+
+pub(crate) fn rustup_bin() -> PathBuf {
     which::which("rustup").unwrap_or_else(|_| cargo_home().join("bin/rustup"))
 }
 
@@ -34,17 +56,17 @@ fn cargo_bin() -> PathBuf {
 
 pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
     // 1. Install rustup + stable toolchain
-    install_rustup(ctx)?;
+    ctx.step("rustup + stable toolchain", install_rustup)?;
 
     // 2. Components
-    install_components(ctx)?;
+    ctx.step("toolchain components", install_components)?;
 
     // 3. Apply Pi 4B optimizations to ~/.cargo/config.toml
-    apply_pi_optimizations(ctx)?;
+    ctx.step("Pi cargo optimizations", apply_pi_optimizations)?;
 
     // 4. Cargo tools (dev+ profile)
     if ctx.options.profile >= crate::ProfileLevel::Dev {
-        install_cargo_tools(ctx)?;
+        ctx.step("cargo tools", install_cargo_tools)?;
     }
 
     Ok(PhaseResult::Success)
@@ -109,38 +131,59 @@ fn install_rustup(ctx: &mut PhaseContext) -> Result<()> {
     }
 
     tracing::info!("Installing rustup + stable toolchain (minimal profile for faster install)");
-    if ctx.options.dry_run {
-        ctx.record_dry_run(
-            "rust_toolchain",
-            "Would install rustup toolchain",
-            Some("curl rustup.rs | sh -s -- -y --profile minimal".into()),
-        );
-        tracing::info!("[dry-run] curl rustup.rs | sh -s -- -y --profile minimal");
-        return Ok(());
+    if !ctx.options.dry_run {
+        let rustup_home = std::env::var("RUSTUP_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| dirs::home_dir().unwrap_or_default().join(".rustup"));
+        for dir in [rustup_home, cargo_home()] {
+            if !dir.exists() {
+                ctx.register_rollback_action(
+                    format!("remove {}", dir.display()),
+                    RollbackOp::RemoveInstallDir { path: dir },
+                );
+            }
+        }
     }
 
-    ctx.register_rollback_action(
-        "record: run `rustup self uninstall` to remove Rust toolchain",
-        || {
-            tracing::info!(
-                "Rollback note: Rust was installed. Run `rustup self uninstall` to remove it."
-            );
-            Ok(())
-        },
-    );
+    // Prefer the distro's rustup package; the upstream script is unpinned,
+    // so it only runs when the user allows unverified scripts.
+    let candidates = [
+        InstallSource::native_for(ctx.platform.driver, &["rustup"]),
+        InstallSource::Script(ScriptSource {
+            url: "https://sh.rustup.rs".to_string(),
+            sha256: None,
+            args: RUSTUP_INIT_ARGS.map(String::from).to_vec(),
+            sudo: false,
+        }),
+    ];
+    let source = sources::install(ctx, "rust_toolchain", "rustup", &candidates)
+        .context("installing rustup")?;
+    if source == SourceKind::Native && !ctx.options.dry_run {
+        init_native_rustup()?;
+    }
+    Ok(())
+}
 
-    // Use minimal profile to reduce download/install time (optimized for Pi 4B)
-    let mut install_cmd = Command::new("sh");
-    install_cmd.arg("-c").arg(
-        "curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y --default-toolchain stable --profile minimal"
-    );
-    cmd::run(&mut install_cmd).context("installing rustup")?;
+/// Distro rustup packages come without a toolchain; set up stable the way
+/// the upstream script would.
+fn init_native_rustup() -> Result<()> {
+    if which::which("rustup-init").is_ok() {
+        let mut init_cmd = Command::new("rustup-init");
+        init_cmd.args(RUSTUP_INIT_ARGS);
+        cmd::run(&mut init_cmd).context("running rustup-init")?;
+        return Ok(());
+    }
+    let steps: [&[&str]; 2] = [&["set", "profile", "minimal"], &["default", "stable"]];
+    for args in steps {
+        let mut rustup_cmd = Command::new(rustup_bin());
+        rustup_cmd.args(args);
+        cmd::run(&mut rustup_cmd).with_context(|| format!("running rustup {}", args.join(" ")))?;
+    }
     Ok(())
 }
 
 fn install_components(ctx: &mut PhaseContext) -> Result<()> {
-    let components = ["rustfmt", "clippy", "rust-src"];
-    for comp in &components {
+    for comp in &COMPONENTS {
         tracing::info!("Ensuring component: {comp}");
         if ctx.options.dry_run {
             ctx.record_dry_run(
@@ -250,28 +293,27 @@ fn install_cargo_tools(ctx: &mut PhaseContext) -> Result<()> {
             missing_tools.len(),
             missing_tools.join(", ")
         );
-        let mut install_cmd = Command::new(cargo_bin());
-        install_cmd.arg("binstall").arg("--no-confirm");
-
         // Optimize for Pi 4B: 4 cores, external USB 3.0 HDD
-        install_cmd
+        let batch = cmd::Command::new(cargo_bin())
+            .args(["binstall", "--no-confirm"])
+            .args(&missing_tools)
             .env("CARGO_BUILD_JOBS", "4")
-            .env("CARGO_NET_GIT_FETCH_WITH_CLI", "true");
-
-        for crate_name in &missing_tools {
-            install_cmd.arg(crate_name);
-        }
+            .env("CARGO_NET_GIT_FETCH_WITH_CLI", "true")
+            .timeout(BINSTALL_TIMEOUT)
+            .execute();
 
-        if let Err(err) = cmd::run(&mut install_cmd) {
+        if let Err(err) = batch {
             ctx.record_warning(format!(
                 "Batch cargo-binstall failed, trying one-by-one: {err}"
             ));
             // Fallback: install one by one
             for crate_name in &missing_tools {
                 tracing::info!("Installing {crate_name} individually...");
-                let mut retry_cmd = Command::new(cargo_bin());
-                retry_cmd.args(["binstall", "--no-confirm", crate_name]);
-                if let Err(err2) = cmd::run(&mut retry_cmd) {
+                let retry = cmd::Command::new(cargo_bin())
+                    .args(["binstall", "--no-confirm", crate_name])
+                    .timeout(BINSTALL_TIMEOUT)
+                    .execute();
+                if let Err(err2) = retry {
                     ctx.record_warning(format!("Failed to install {crate_name} ({err2})"));
                 }
             }
@@ -286,11 +328,12 @@ fn install_cargo_tools(ctx: &mut PhaseContext) -> Result<()> {
         );
         for crate_name in &missing_tools {
             tracing::info!("Installing {crate_name} via cargo install...");
-            let mut install_cmd = Command::new(cargo_bin());
-            install_cmd
+            let install = cmd::Command::new(cargo_bin())
                 .args(["install", crate_name])
-                .env("CARGO_BUILD_JOBS", "4"); // Use all 4 cores on Pi 4B
-            if let Err(err) = cmd::run(&mut install_cmd) {
+                .env("CARGO_BUILD_JOBS", "4") // Use all 4 cores on Pi 4B
+                .timeout(CARGO_INSTALL_TIMEOUT)
+                .execute();
+            if let Err(err) = install {
                 ctx.record_warning(format!("Failed to install {crate_name} ({err})"));
             }
         }
@@ -302,9 +345,11 @@ fn install_cargo_tools(ctx: &mut PhaseContext) -> Result<()> {
         if !bin_path.exists() && which::which("flamegraph").is_err() {
             tracing::info!("Installing flamegraph");
             if !ctx.options.dry_run {
-                let mut flame_cmd = Command::new(cargo_bin());
-                flame_cmd.args(["install", "flamegraph"]);
-                if let Err(err) = cmd::run(&mut flame_cmd) {
+                let install = cmd::Command::new(cargo_bin())
+                    .args(["install", "flamegraph"])
+                    .timeout(CARGO_INSTALL_TIMEOUT)
+                    .execute();
+                if let Err(err) = install {
                     ctx.record_warning(format!("Failed to install flamegraph ({err})"));
                 }
             } else {
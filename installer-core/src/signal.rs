@@ -1,8 +1,17 @@
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 
 use anyhow::{Context, Result};
 
+/// Shared by every guard so code far from the orchestrator (a long-running
+/// command, for one) can notice the interrupt too.
+static INTERRUPTED: LazyLock<Arc<AtomicBool>> = LazyLock::new(|| Arc::new(AtomicBool::new(false)));
+
+/// True once SIGINT or SIGTERM arrived while a [`SignalGuard`] was installed.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
 /// Guard that registers SIGINT and SIGTERM handlers and exposes an
 /// interruption flag that phases can poll between steps.
 ///
@@ -19,7 +28,7 @@ impl SignalGuard {
     /// The handlers set an atomic flag instead of terminating the process,
     /// giving the installer a chance to roll back partial changes.
     pub fn new() -> Result<Self> {
-        let interrupted = Arc::new(AtomicBool::new(false));
+        let interrupted = Arc::clone(&INTERRUPTED);
 
         signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&interrupted))
             .context("registering SIGINT handler")?;
@@ -1,7 +1,379 @@
+//! Pre-install filesystem snapshots and restoring them after a failed run.
+//!
+//! The `snapshots` phase takes one snapshot with the first usable
+//! [`SnapshotBackend`] and records it in the snapshot ledger, keyed by the
+//! run id, so `mash-setup rollback <run-id>` can find it after the installer
+//! has exited.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::config_path;
 use crate::system::cmd;
 use crate::{PhaseContext, PhaseResult};
-use anyhow::Result;
-use std::process::Command;
+
+pub const LEDGER_FILE: &str = "snapshots.json";
+
+/// Where writable btrfs snapshots of `/` are placed.
+const BTRFS_SNAPSHOT_DIR: &str = "/.mash-snapshots";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotKind {
+    Snapper,
+    Timeshift,
+    Btrfs,
+    LvmThin,
+}
+
+impl SnapshotKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            SnapshotKind::Snapper => "snapper",
+            SnapshotKind::Timeshift => "timeshift",
+            SnapshotKind::Btrfs => "btrfs",
+            SnapshotKind::LvmThin => "lvm-thin",
+        }
+    }
+
+    pub fn backend(self) -> &'static dyn SnapshotBackend {
+        match self {
+            SnapshotKind::Snapper => &SNAPPER,
+            SnapshotKind::Timeshift => &TIMESHIFT,
+            SnapshotKind::Btrfs => &BTRFS,
+            SnapshotKind::LvmThin => &LVM_THIN,
+        }
+    }
+}
+
+/// A snapshot taken before a run changed the system.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotRecord {
+    pub run_id: String,
+    pub backend: SnapshotKind,
+    /// Backend-specific handle: snapper number, Timeshift name, subvolume
+    /// path or `vg/lv`.
+    pub id: String,
+    pub description: String,
+    pub created_at: u64,
+}
+
+impl SnapshotRecord {
+    /// Command that puts this snapshot back, as shown to the user.
+    pub fn restore_command(&self) -> Vec<String> {
+        self.backend.backend().restore_command(&self.id)
+    }
+}
+
+/// One way of taking and restoring whole-system snapshots.
+pub trait SnapshotBackend: Send + Sync {
+    fn kind(&self) -> SnapshotKind;
+
+    /// Whether the tool is installed and configured for a root filesystem of
+    /// type `fstype`.
+    fn is_available(&self, fstype: &str) -> bool;
+
+    /// Take a snapshot and return its backend-specific id.
+    fn create(&self, description: &str, run_id: &str) -> Result<String>;
+
+    /// Arguments (after `sudo`) that restore snapshot `id`.
+    fn restore_command(&self, id: &str) -> Vec<String>;
+
+    /// What the user has to do once the restore command has run.
+    fn restore_note(&self) -> &'static str {
+        "Reboot to boot into the restored system."
+    }
+
+    fn restore(&self, id: &str) -> Result<()> {
+        cmd::Command::new("sudo")
+            .args(self.restore_command(id))
+            .execute()
+            .with_context(|| format!("restoring {} snapshot {id}", self.kind().label()))?;
+        Ok(())
+    }
+}
+
+/// Backends in preference order: managed tools first, raw primitives last.
+pub fn backends() -> [&'static dyn SnapshotBackend; 4] {
+    [&SNAPPER, &TIMESHIFT, &BTRFS, &LVM_THIN]
+}
+
+fn command_exists(program: &str) -> bool {
+    cmd::Command::new("which")
+        .arg(program)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .execute()
+        .is_ok()
+}
+
+fn sudo_output(args: &[&str]) -> Result<String> {
+    let output = cmd::Command::new("sudo").args(args).execute()?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn owned(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+pub struct Snapper;
+pub struct Timeshift;
+pub struct Btrfs;
+pub struct LvmThin;
+
+static SNAPPER: Snapper = Snapper;
+static TIMESHIFT: Timeshift = Timeshift;
+static BTRFS: Btrfs = Btrfs;
+static LVM_THIN: LvmThin = LvmThin;
+
+impl SnapshotBackend for Snapper {
+    fn kind(&self) -> SnapshotKind {
+        SnapshotKind::Snapper
+    }
+
+    fn is_available(&self, _fstype: &str) -> bool {
+        command_exists("snapper")
+            && cmd::Command::new("snapper")
+                .args(["--no-dbus", "list-configs"])
+                .execute()
+                .map(|out| has_root_config(&String::from_utf8_lossy(&out.stdout)))
+                .unwrap_or(false)
+    }
+
+    fn create(&self, description: &str, _run_id: &str) -> Result<String> {
+        let stdout = sudo_output(&[
+            "snapper",
+            "-c",
+            "root",
+            "create",
+            "--type",
+            "single",
+            "--cleanup-algorithm",
+            "number",
+            "--print-number",
+            "--description",
+            description,
+        ])?;
+        parse_snapper_number(&stdout)
+    }
+
+    fn restore_command(&self, id: &str) -> Vec<String> {
+        owned(&["snapper", "-c", "root", "rollback", id])
+    }
+}
+
+impl SnapshotBackend for Timeshift {
+    fn kind(&self) -> SnapshotKind {
+        SnapshotKind::Timeshift
+    }
+
+    fn is_available(&self, fstype: &str) -> bool {
+        fstype == "btrfs" && command_exists("timeshift")
+    }
+
+    fn create(&self, description: &str, _run_id: &str) -> Result<String> {
+        let stdout = sudo_output(&[
+            "timeshift",
+            "--create",
+            "--scripted",
+            "--comments",
+            description,
+        ])?;
+        parse_timeshift_name(&stdout)
+    }
+
+    fn restore_command(&self, id: &str) -> Vec<String> {
+        owned(&[
+            "timeshift",
+            "--restore",
+            "--snapshot",
+            id,
+            "--scripted",
+            "--yes",
+        ])
+    }
+}
+
+impl SnapshotBackend for Btrfs {
+    fn kind(&self) -> SnapshotKind {
+        SnapshotKind::Btrfs
+    }
+
+    fn is_available(&self, fstype: &str) -> bool {
+        fstype == "btrfs" && command_exists("btrfs")
+    }
+
+    fn create(&self, _description: &str, run_id: &str) -> Result<String> {
+        let target = format!("{BTRFS_SNAPSHOT_DIR}/pre-install-{run_id}");
+        sudo_output(&["mkdir", "-p", BTRFS_SNAPSHOT_DIR])?;
+        sudo_output(&["btrfs", "subvolume", "snapshot", "/", &target])?;
+        Ok(target)
+    }
+
+    fn restore_command(&self, id: &str) -> Vec<String> {
+        owned(&["btrfs", "subvolume", "set-default", id])
+    }
+
+    fn restore_note(&self) -> &'static str {
+        "Reboot to boot into the snapshot. If /etc/fstab or the kernel command \
+         line pins rootflags=subvol=..., point it at the snapshot path instead."
+    }
+}
+
+impl LvmThin {
+    /// `(vg, lv)` of the root filesystem when it is a thin volume.
+    fn root_volume() -> Option<(String, String)> {
+        let source = cmd::Command::new("findmnt")
+            .args(["-no", "SOURCE", "/"])
+            .execute()
+            .ok()?;
+        let device = String::from_utf8_lossy(&source.stdout).trim().to_string();
+        let lvs = cmd::Command::new("lvs")
+            .args([
+                "--noheadings",
+                "--separator",
+                "|",
+                "-o",
+                "vg_name,lv_name,pool_lv",
+                &device,
+            ])
+            .execute()
+            .ok()?;
+        parse_thin_volume(&String::from_utf8_lossy(&lvs.stdout))
+    }
+}
+
+impl SnapshotBackend for LvmThin {
+    fn kind(&self) -> SnapshotKind {
+        SnapshotKind::LvmThin
+    }
+
+    fn is_available(&self, _fstype: &str) -> bool {
+        command_exists("lvcreate") && Self::root_volume().is_some()
+    }
+
+    fn create(&self, description: &str, run_id: &str) -> Result<String> {
+        let Some((vg, lv)) = Self::root_volume() else {
+            bail!("root filesystem is not on an LVM thin volume");
+        };
+        let name = format!("{lv}-pre-install-{run_id}");
+        sudo_output(&[
+            "lvcreate",
+            "--snapshot",
+            "--name",
+            &name,
+            "--addtag",
+            "mash-installer",
+            "--setactivationskip",
+            "y",
+            &format!("{vg}/{lv}"),
+        ])
+        .with_context(|| format!("creating thin snapshot for {description}"))?;
+        Ok(format!("{vg}/{name}"))
+    }
+
+    fn restore_command(&self, id: &str) -> Vec<String> {
+        owned(&["lvconvert", "--merge", id])
+    }
+
+    fn restore_note(&self) -> &'static str {
+        "The merge completes when the origin volume is next activated; reboot now."
+    }
+}
+
+fn has_root_config(list_configs: &str) -> bool {
+    list_configs
+        .lines()
+        .filter_map(|line| line.split('|').next())
+        .any(|name| name.trim() == "root")
+}
+
+fn parse_snapper_number(stdout: &str) -> Result<String> {
+    stdout
+        .split_whitespace()
+        .rev()
+        .find(|word| word.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+        .context("snapper did not print a snapshot number")
+}
+
+fn parse_timeshift_name(stdout: &str) -> Result<String> {
+    stdout
+        .lines()
+        .find_map(|line| {
+            let rest = line.split("Tagged snapshot '").nth(1)?;
+            rest.split('\'').next().map(str::to_string)
+        })
+        .context("timeshift did not report the snapshot it created")
+}
+
+fn parse_thin_volume(lvs: &str) -> Option<(String, String)> {
+    let mut fields = lvs.lines().next()?.split('|').map(str::trim);
+    let vg = fields.next().filter(|s| !s.is_empty())?;
+    let lv = fields.next().filter(|s| !s.is_empty())?;
+    fields.next().filter(|pool| !pool.is_empty())?;
+    Some((vg.to_string(), lv.to_string()))
+}
+
+/// Snapshot records of every run, kept next to the config file so they
+/// outlive the staging directory.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SnapshotLedger {
+    pub snapshots: Vec<SnapshotRecord>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+pub fn ledger_path() -> PathBuf {
+    config_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(LEDGER_FILE)
+}
+
+impl SnapshotLedger {
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut ledger: Self = if path.exists() {
+            let text = fs::read_to_string(path)
+                .with_context(|| format!("reading snapshot ledger {}", path.display()))?;
+            serde_json::from_str(&text)
+                .with_context(|| format!("parsing snapshot ledger {}", path.display()))?
+        } else {
+            Self::default()
+        };
+        ledger.path = path.to_path_buf();
+        Ok(ledger)
+    }
+
+    pub fn record(&mut self, record: SnapshotRecord) -> Result<()> {
+        self.snapshots.push(record);
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("writing snapshot ledger {}", self.path.display()))
+    }
+
+    pub fn for_run(&self, run_id: &str) -> Vec<&SnapshotRecord> {
+        self.snapshots
+            .iter()
+            .filter(|record| record.run_id == run_id)
+            .collect()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
 
 pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
     let fstype = ctx.platform.system.detect_root_fstype()?;
@@ -10,38 +382,135 @@ pub fn install_phase(ctx: &mut PhaseContext) -> Result<PhaseResult> {
         fstype.to_uppercase()
     ));
 
-    if fstype == "btrfs" {
-        if command_exists("timeshift") {
-            ctx.run_or_record(
-                "Snapshot",
-                "Create Btrfs snapshot via Timeshift",
-                Some("MASH pre-install".into()),
-                |_| {
-                    let mut cmd = Command::new("sudo");
-                    cmd.args(["timeshift", "--create", "--comments", "MASH pre-install"]);
-                    cmd::run(&mut cmd)?;
-                    Ok(())
-                },
-            )?;
+    let Some(backend) = backends()
+        .into_iter()
+        .find(|backend| backend.is_available(&fstype))
+    else {
+        ctx.record_warning(format!(
+            "No snapshot tool is set up for the {fstype} root filesystem. \
+             Install snapper or Timeshift, or use btrfs/LVM thin, to enable rollback."
+        ));
+        return Ok(PhaseResult::Success);
+    };
+
+    let label = backend.kind().label();
+    let run_id = ctx
+        .rollback
+        .run_id()
+        .map(str::to_string)
+        .unwrap_or_else(|| unix_now().to_string());
+    let description = format!("MASH pre-install {run_id}");
+
+    ctx.run_or_record(
+        "Snapshot",
+        format!("Create pre-install snapshot via {label}"),
+        Some(description.clone()),
+        |ctx| {
+            let id = backend.create(&description, &run_id)?;
+            let record = SnapshotRecord {
+                run_id: run_id.clone(),
+                backend: backend.kind(),
+                id,
+                description: description.clone(),
+                created_at: unix_now(),
+            };
+            if let Err(err) = SnapshotLedger::load(&ledger_path())
+                .and_then(|mut ledger| ledger.record(record.clone()))
+            {
+                ctx.record_warning(format!("Snapshot taken but not recorded: {err:#}"));
+            }
+            ctx.record_action(format!("Snapshot {} created via {label}", record.id));
+            ctx.rollback.record_snapshot(record);
+            Ok(())
+        },
+    )?;
+
+    Ok(PhaseResult::Success)
+}
+
+/// Restore (with `apply`) or describe the pre-install snapshots of `run_id`.
+pub fn rollback_run(run_id: &str, apply: bool, out: &mut dyn Write) -> Result<()> {
+    let path = ledger_path();
+    let ledger = SnapshotLedger::load(&path)?;
+    let records = ledger.for_run(run_id);
+    if records.is_empty() {
+        bail!(
+            "no snapshots recorded for run {run_id} in {}",
+            path.display()
+        );
+    }
+
+    for record in records {
+        let backend = record.backend.backend();
+        let command = record.restore_command().join(" ");
+        writeln!(
+            out,
+            "Run {run_id}: {} snapshot {} ({})",
+            record.backend.label(),
+            record.id,
+            record.description
+        )?;
+        if apply {
+            writeln!(out, "  Restoring: sudo {command}")?;
+            backend.restore(&record.id)?;
+            writeln!(out, "  {}", backend.restore_note())?;
         } else {
-            ctx.record_warning("Btrfs detected but Timeshift is missing. Skipping snapshot.");
+            writeln!(out, "  Restore with: sudo {command}")?;
+            writeln!(out, "  Or re-run with --yes to restore it now.")?;
         }
-    } else {
-        ctx.record_action(format!(
-            "FS {} does not support native snapshots via this station.",
-            fstype
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_backend_output() {
+        assert_eq!(parse_snapper_number("42\n").unwrap(), "42");
+        assert_eq!(
+            parse_timeshift_name(
+                "Creating new snapshot...(BTRFS)\n\
+                 Created subvolume snapshot: /run/timeshift/backup/timeshift-btrfs/snapshots/2024-05-01_10-00-00/@\n\
+                 Tagged snapshot '2024-05-01_10-00-00': ondemand\n"
+            )
+            .unwrap(),
+            "2024-05-01_10-00-00"
+        );
+        assert!(parse_timeshift_name("E: no space left").is_err());
+        assert!(has_root_config(
+            "Config | Subvolume\n-------+----------\nroot   | /\n"
         ));
+        assert_eq!(
+            parse_thin_volume("  vg0|root|pool0\n"),
+            Some(("vg0".into(), "root".into()))
+        );
+        assert_eq!(parse_thin_volume("  vg0|root|\n"), None);
     }
 
-    Ok(PhaseResult::Success)
-}
+    #[test]
+    fn ledger_keeps_snapshots_per_run() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(LEDGER_FILE);
+        let record = |run: &str, id: &str| SnapshotRecord {
+            run_id: run.into(),
+            backend: SnapshotKind::Snapper,
+            id: id.into(),
+            description: "MASH pre-install".into(),
+            created_at: 1,
+        };
+
+        SnapshotLedger::load(&path)?.record(record("run-a", "7"))?;
+        SnapshotLedger::load(&path)?.record(record("run-b", "8"))?;
 
-fn command_exists(cmd: &str) -> bool {
-    Command::new("which")
-        .arg(cmd)
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false)
+        let ledger = SnapshotLedger::load(&path)?;
+        assert_eq!(ledger.for_run("run-a"), vec![&record("run-a", "7")]);
+        assert_eq!(
+            ledger.for_run("run-b")[0].restore_command(),
+            vec!["snapper", "-c", "root", "rollback", "8"]
+        );
+        Ok(())
+    }
 }
@@ -9,10 +9,12 @@
 use anyhow::Result;
 use std::collections::BTreeSet;
 
+use crate::catalog::CatalogResolver;
 use crate::context::PhaseContext;
 use crate::model::options::EnvironmentTag;
 use crate::model::software::{SoftwareTierPlan, Tier};
 use crate::package_manager;
+use crate::sources;
 use crate::PhaseResult;
 
 /// Software installation phase — the primary payload of the installer.
@@ -28,37 +30,10 @@ pub fn install_phase(ctx: &mut PhaseContext<'_>) -> Result<PhaseResult> {
         plan.target_tier.unwrap_or(Tier::S)
     ));
 
-    // 1. Collect all packages to install
-    let mut required = BTreeSet::new();
+    // 1-2. Explicit selections plus everything in the targeted tiers
+    let mut required = selected_programs(&plan);
     let optional = BTreeSet::new();
 
-    // Add explicit selections
-    for programs in plan.selections.values() {
-        for prog_id in programs {
-            required.insert(prog_id.clone());
-        }
-    }
-
-    // 2. Resolve Tier dependencies if targeted
-    if let Some(tier) = plan.target_tier {
-        let catalog = if tier == Tier::S {
-            crate::catalog::Catalog::load_s_tier().unwrap_or_default()
-        } else {
-            crate::catalog::Catalog::load_full().unwrap_or_default()
-        };
-
-        let tiers_to_include = tier.resolve();
-        for cat in &catalog.categories {
-            for sub in &cat.subcategories {
-                for prog in &sub.programs {
-                    if tiers_to_include.contains(&prog.tier) {
-                        required.insert(prog.id.clone());
-                    }
-                }
-            }
-        }
-    }
-
     // 3. Apply dynamic heuristics (Bard's Recommendations)
     apply_heuristics(ctx, &mut required)?;
 
@@ -81,6 +56,28 @@ pub fn install_phase(ctx: &mut PhaseContext<'_>) -> Result<PhaseResult> {
     Ok(PhaseResult::Success)
 }
 
+/// Catalog IDs `plan` asks for: the explicit selections plus every program
+/// in the targeted tiers. Hardware heuristics come on top of these.
+pub fn selected_programs(plan: &SoftwareTierPlan) -> BTreeSet<String> {
+    let mut required: BTreeSet<String> = plan.selections.values().flatten().cloned().collect();
+
+    if let Some(tier) = plan.target_tier {
+        let catalog = if tier == Tier::S {
+            crate::catalog::Catalog::load_s_tier().unwrap_or_default()
+        } else {
+            crate::catalog::Catalog::load_full().unwrap_or_default()
+        };
+
+        let tiers_to_include = tier.resolve();
+        for prog in catalog.programs() {
+            if tiers_to_include.contains(&prog.tier) {
+                required.insert(prog.id.clone());
+            }
+        }
+    }
+    required
+}
+
 fn apply_heuristics(ctx: &mut PhaseContext, required: &mut BTreeSet<String>) -> Result<()> {
     let Some(_profile) = &ctx.options.system_profile else {
         tracing::debug!("No system profile available for heuristics");
@@ -89,23 +86,40 @@ fn apply_heuristics(ctx: &mut PhaseContext, required: &mut BTreeSet<String>) ->
 
     let distro_family = &ctx.platform.platform.distro_family;
 
-    // 1. Pi 4B Optimizations
-    if ctx.platform.is_pi_4b() {
+    // 1. Raspberry Pi Optimizations
+    if let Some(board) = ctx.platform.pi_board() {
         // Enforce ZRAM for memory constrained Pi
         if distro_family == "debian" {
             if !required.contains("zram-tools") {
                 required.insert("zram-tools".to_string());
-                ctx.record_tweaked("Heuristics: Added 'zram-tools' for Pi 4B memory optimization");
+                ctx.record_tweaked(format!(
+                    "Heuristics: Added 'zram-tools' for {} memory optimization",
+                    board.label()
+                ));
             }
         } else if distro_family == "arch" || distro_family == "fedora" {
             // Arch/Fedora usually use zram-generator
             if !required.contains("zram-generator") {
                 required.insert("zram-generator".to_string());
-                ctx.record_tweaked(
-                    "Heuristics: Added 'zram-generator' for Pi 4B memory optimization",
-                );
+                ctx.record_tweaked(format!(
+                    "Heuristics: Added 'zram-generator' for {} memory optimization",
+                    board.label()
+                ));
             }
         }
+
+        // NVMe on the PCIe lane (Pi 5, CM4, CM5 carriers)
+        let has_nvme = _profile
+            .platform
+            .pi
+            .as_ref()
+            .is_some_and(|pi| !pi.nvme.is_empty());
+        if has_nvme && required.insert("nvme-cli".to_string()) {
+            ctx.record_tweaked(format!(
+                "Heuristics: Added 'nvme-cli' for the {} NVMe drive",
+                board.label()
+            ));
+        }
     }
 
     // 2. Low RAM Warnings (Only for recommended mode to avoid nagging manual users)
@@ -181,8 +195,68 @@ fn install_packages(
         return Ok(());
     }
 
-    let req_refs: Vec<&str> = required.iter().map(String::as_str).collect();
-    package_manager::ensure_packages(ctx.platform.driver, &req_refs, ctx.options.dry_run)?;
+    // Catalog IDs are not package names; expand them for the active distro.
+    let resolution = CatalogResolver::bundled()
+        .resolve(required.iter().map(String::as_str), ctx.platform.driver);
+    for id in &resolution.unmapped {
+        ctx.record_warning(format!(
+            "No {} package mapping for '{}'; skipping",
+            ctx.platform.driver.name(),
+            id
+        ));
+    }
+
+    let (native, alternative): (Vec<_>, Vec<_>) = resolution
+        .programs
+        .iter()
+        .partition(|program| program.native_packages().is_some());
+
+    if ctx.options.dry_run {
+        for program in &native {
+            ctx.record_dry_run(
+                "software_tiers",
+                format!("Would install {}", program.id),
+                program.native_packages().map(|pkgs| pkgs.join(", ")),
+            );
+        }
+        if !resolution.passthrough.is_empty() {
+            ctx.record_dry_run(
+                "software_tiers",
+                "Would install additional packages",
+                Some(resolution.passthrough.join(", ")),
+            );
+        }
+    }
+
+    let packages = resolution.packages();
+    if !packages.is_empty() {
+        let pkg_refs: Vec<&str> = packages.iter().map(String::as_str).collect();
+        if let Err(err) = package_manager::ensure_native_packages(
+            ctx.platform.driver,
+            &pkg_refs,
+            ctx.options.dry_run,
+        ) {
+            if !native.iter().any(|program| program.has_alternatives()) {
+                return Err(err);
+            }
+            // One bad package sinks the whole batch; retry per program so
+            // those with other sources can fall back.
+            ctx.record_warning(format!(
+                "Batch package install failed; retrying programs individually ({err:#})"
+            ));
+            for program in &native {
+                sources::install(ctx, "software_tiers", &program.id, &program.sources)?;
+            }
+            if !resolution.passthrough.is_empty() {
+                let refs: Vec<&str> = resolution.passthrough.iter().map(String::as_str).collect();
+                package_manager::ensure_native_packages(ctx.platform.driver, &refs, false)?;
+            }
+        }
+    }
+
+    for program in &alternative {
+        sources::install(ctx, "software_tiers", &program.id, &program.sources)?;
+    }
 
     for opt in optional {
         package_manager::try_optional(ctx.platform.driver, opt, ctx.options.dry_run);
@@ -249,6 +323,7 @@ fn mock_context(pi_model: Option<&str>, distro_family: &str) -> (InstallContext,
             driver: &TEST_DRIVER,
             pkg_backend: crate::PkgBackend::Apt,
             system: &crate::sys_ops::REAL_SYSTEM,
+            commands: &crate::cmd::REAL_RUNNER,
         };
 
         let staging_dir = std::path::PathBuf::from("/tmp/mash-test");
@@ -289,6 +364,34 @@ fn test_pi4b_heuristics_adds_zram_arch() {
         assert!(required.contains("zram-generator"));
     }
 
+    #[test]
+    fn test_pi5_heuristics_add_zram_and_nvme_cli() {
+        let (mut ctx, mut observer) =
+            mock_context(Some("Raspberry Pi 5 Model B Rev 1.0"), "debian");
+        let mut profile = SystemProfile::default();
+        profile.platform.pi = Some(crate::pi_board::PiHardware {
+            board: crate::pi_board::PiBoard::Pi5,
+            model: "Raspberry Pi 5 Model B Rev 1.0".into(),
+            revision: Some("d04170".into()),
+            soc: Some("BCM2712".into()),
+            ram_mb: Some(8192),
+            nvme: vec![crate::pi_board::NvmeDrive {
+                name: "nvme0n1".into(),
+                model: "WD_BLACK SN770".into(),
+                size_bytes: 0,
+            }],
+            active_cooler: true,
+        });
+        ctx.options.system_profile = Some(profile);
+        let mut p_ctx = ctx.phase_context(&mut observer);
+        let mut required = BTreeSet::new();
+
+        apply_heuristics(&mut p_ctx, &mut required).unwrap();
+
+        assert!(required.contains("zram-tools"));
+        assert!(required.contains("nvme-cli"));
+    }
+
     #[test]
     fn test_non_pi_heuristics_does_nothing() {
         let (ctx, mut observer) = mock_context(None, "debian");
@@ -333,4 +436,20 @@ fn test_high_latency_heuristics_records_warning() {
             .iter()
             .any(|w| w.contains("High network latency")));
     }
+
+    #[test]
+    fn dry_run_records_resolved_catalog_packages() {
+        let (mut ctx, mut observer) = mock_context(None, "debian");
+        ctx.options.dry_run = true;
+        let mut p_ctx = ctx.phase_context(&mut observer);
+        let required: BTreeSet<String> = ["brave".to_string()].into();
+
+        install_packages(&mut p_ctx, &required, &BTreeSet::new()).unwrap();
+
+        let entries = ctx.dry_run_log.entries();
+        assert!(entries
+            .iter()
+            .any(|e| e.action == "Would install brave"
+                && e.detail.as_deref() == Some("brave-browser")));
+    }
 }
@@ -0,0 +1,551 @@
+//! Install sources beyond the native package manager.
+//!
+//! A program can be installed from the distro repositories, the AUR (through a
+//! detected helper), Flatpak, `cargo binstall`, pipx or an upstream install
+//! script. [`install`] walks an ordered list of candidates and falls back to
+//! the next one when a backend is missing or the install fails.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::backend::PkgBackend;
+use crate::driver::DistroDriver;
+use crate::system::{cmd, dry_run};
+use crate::{distro, package_manager, PhaseContext, PlannedChange};
+
+/// Kinds of install source, used to express priority policies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    Native,
+    Aur,
+    Flatpak,
+    Cargo,
+    Pipx,
+    Script,
+}
+
+impl SourceKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            SourceKind::Native => "package manager",
+            SourceKind::Aur => "AUR",
+            SourceKind::Flatpak => "Flatpak",
+            SourceKind::Cargo => "cargo binstall",
+            SourceKind::Pipx => "pipx",
+            SourceKind::Script => "upstream script",
+        }
+    }
+}
+
+/// Order in which sources are tried on a distro.
+///
+/// Native packages always win; the AUR is only meaningful on pacman systems.
+pub fn source_priority(backend: PkgBackend) -> &'static [SourceKind] {
+    match backend {
+        PkgBackend::Pacman => &[
+            SourceKind::Native,
+            SourceKind::Aur,
+            SourceKind::Flatpak,
+            SourceKind::Cargo,
+            SourceKind::Pipx,
+            SourceKind::Script,
+        ],
+        PkgBackend::Apt | PkgBackend::Dnf => &[
+            SourceKind::Native,
+            SourceKind::Flatpak,
+            SourceKind::Cargo,
+            SourceKind::Pipx,
+            SourceKind::Script,
+        ],
+    }
+}
+
+/// An upstream installer script, optionally pinned to a SHA-256 digest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptSource {
+    pub url: String,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Run the script through sudo.
+    #[serde(default)]
+    pub sudo: bool,
+}
+
+/// Non-native sources a catalog program can declare.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramSources {
+    #[serde(default)]
+    pub aur: Option<String>,
+    #[serde(default)]
+    pub flatpak: Option<String>,
+    #[serde(default)]
+    pub cargo: Option<String>,
+    #[serde(default)]
+    pub pipx: Option<String>,
+    #[serde(default)]
+    pub script: Option<ScriptSource>,
+}
+
+impl ProgramSources {
+    pub fn is_empty(&self) -> bool {
+        self.aur.is_none()
+            && self.flatpak.is_none()
+            && self.cargo.is_none()
+            && self.pipx.is_none()
+            && self.script.is_none()
+    }
+
+    fn get(&self, kind: SourceKind) -> Option<InstallSource> {
+        match kind {
+            SourceKind::Native => None,
+            SourceKind::Aur => self.aur.clone().map(InstallSource::Aur),
+            SourceKind::Flatpak => self.flatpak.clone().map(InstallSource::Flatpak),
+            SourceKind::Cargo => self.cargo.clone().map(InstallSource::Cargo),
+            SourceKind::Pipx => self.pipx.clone().map(InstallSource::Pipx),
+            SourceKind::Script => self.script.clone().map(InstallSource::Script),
+        }
+    }
+}
+
+/// A concrete way of installing one program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InstallSource {
+    /// Native package names for the active distro.
+    Native(Vec<String>),
+    Aur(String),
+    Flatpak(String),
+    Cargo(String),
+    Pipx(String),
+    Script(ScriptSource),
+}
+
+impl InstallSource {
+    /// Native source for canonical package names, translated for `driver`.
+    pub fn native_for(driver: &dyn DistroDriver, canonical: &[&str]) -> Self {
+        InstallSource::Native(distro::translate_names(driver, canonical))
+    }
+
+    pub fn kind(&self) -> SourceKind {
+        match self {
+            InstallSource::Native(_) => SourceKind::Native,
+            InstallSource::Aur(_) => SourceKind::Aur,
+            InstallSource::Flatpak(_) => SourceKind::Flatpak,
+            InstallSource::Cargo(_) => SourceKind::Cargo,
+            InstallSource::Pipx(_) => SourceKind::Pipx,
+            InstallSource::Script(_) => SourceKind::Script,
+        }
+    }
+
+    /// What would be installed, for logs and the dry-run audit.
+    pub fn target(&self) -> String {
+        match self {
+            InstallSource::Native(pkgs) => pkgs.join(", "),
+            InstallSource::Aur(name)
+            | InstallSource::Flatpak(name)
+            | InstallSource::Cargo(name)
+            | InstallSource::Pipx(name) => name.clone(),
+            InstallSource::Script(script) => script.url.clone(),
+        }
+    }
+}
+
+/// Order `native` and `extra` by the distro's source policy.
+pub fn ordered_sources(
+    backend: PkgBackend,
+    native: Option<&[String]>,
+    extra: &ProgramSources,
+) -> Vec<InstallSource> {
+    source_priority(backend)
+        .iter()
+        .filter_map(|kind| match kind {
+            SourceKind::Native => native
+                .filter(|pkgs| !pkgs.is_empty())
+                .map(|pkgs| InstallSource::Native(pkgs.to_vec())),
+            other => extra.get(*other),
+        })
+        .collect()
+}
+
+/// Backend for a single-name install source.
+pub trait SourceInstaller {
+    /// Whether the tooling this backend needs is present on the host.
+    fn is_available(&self) -> bool;
+    fn install(&self, target: &str, dry_run: bool) -> Result<()>;
+}
+
+struct AurInstaller;
+struct FlatpakInstaller;
+struct CargoInstaller;
+struct PipxInstaller;
+
+static AUR_INSTALLER: AurInstaller = AurInstaller;
+static FLATPAK_INSTALLER: FlatpakInstaller = FlatpakInstaller;
+static CARGO_INSTALLER: CargoInstaller = CargoInstaller;
+static PIPX_INSTALLER: PipxInstaller = PipxInstaller;
+
+/// AUR helpers we know how to drive, in order of preference.
+const AUR_HELPERS: &[&str] = &["paru", "yay"];
+
+/// Install scripts are small; a download that stalls this long is dead.
+const SCRIPT_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Longest a prebuilt install (Flatpak, cargo binstall, pipx) may run.
+const SOURCE_INSTALL_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// AUR packages usually build from source, which takes far longer on a Pi.
+const SOURCE_BUILD_TIMEOUT: Duration = Duration::from_secs(3 * 60 * 60);
+
+fn aur_helper() -> Option<&'static str> {
+    AUR_HELPERS
+        .iter()
+        .copied()
+        .find(|helper| which::which(helper).is_ok())
+}
+
+impl SourceInstaller for AurInstaller {
+    fn is_available(&self) -> bool {
+        aur_helper().is_some()
+    }
+
+    fn install(&self, target: &str, dry_run: bool) -> Result<()> {
+        let helper = aur_helper().ok_or_else(|| anyhow!("no AUR helper (paru/yay) found"))?;
+        // AUR helpers refuse to run as root and escalate on their own; they
+        // end in a pacman transaction.
+        package_manager::execute(
+            cmd::Command::new(helper)
+                .args(["-S", "--needed", "--noconfirm", target])
+                .timeout(SOURCE_BUILD_TIMEOUT)
+                .dry_run(dry_run),
+        )
+        .with_context(|| format!("installing {target} from the AUR with {helper}"))?;
+        Ok(())
+    }
+}
+
+impl SourceInstaller for FlatpakInstaller {
+    fn is_available(&self) -> bool {
+        which::which("flatpak").is_ok()
+    }
+
+    fn install(&self, target: &str, dry_run: bool) -> Result<()> {
+        cmd::Command::new("flatpak")
+            .sudo()
+            .args([
+                "remote-add",
+                "--if-not-exists",
+                "flathub",
+                "https://dl.flathub.org/repo/flathub.flatpakrepo",
+            ])
+            .dry_run(dry_run)
+            .execute()
+            .context("adding the flathub remote")?;
+        cmd::Command::new("flatpak")
+            .sudo()
+            .args(["install", "-y", "--noninteractive", "flathub", target])
+            .timeout(SOURCE_INSTALL_TIMEOUT)
+            .dry_run(dry_run)
+            .execute()
+            .with_context(|| format!("installing {target} from Flathub"))?;
+        Ok(())
+    }
+}
+
+impl SourceInstaller for CargoInstaller {
+    fn is_available(&self) -> bool {
+        which::which("cargo-binstall").is_ok()
+    }
+
+    fn install(&self, target: &str, dry_run: bool) -> Result<()> {
+        cmd::Command::new("cargo")
+            .args(["binstall", "-y", target])
+            .timeout(SOURCE_INSTALL_TIMEOUT)
+            .dry_run(dry_run)
+            .execute()
+            .with_context(|| format!("installing {target} with cargo binstall"))?;
+        Ok(())
+    }
+}
+
+impl SourceInstaller for PipxInstaller {
+    fn is_available(&self) -> bool {
+        which::which("pipx").is_ok()
+    }
+
+    fn install(&self, target: &str, dry_run: bool) -> Result<()> {
+        cmd::Command::new("pipx")
+            .args(["install", target])
+            .timeout(SOURCE_INSTALL_TIMEOUT)
+            .dry_run(dry_run)
+            .execute()
+            .with_context(|| format!("installing {target} with pipx"))?;
+        Ok(())
+    }
+}
+
+fn installer_for(kind: SourceKind) -> Option<&'static dyn SourceInstaller> {
+    match kind {
+        SourceKind::Aur => Some(&AUR_INSTALLER),
+        SourceKind::Flatpak => Some(&FLATPAK_INSTALLER),
+        SourceKind::Cargo => Some(&CARGO_INSTALLER),
+        SourceKind::Pipx => Some(&PIPX_INSTALLER),
+        SourceKind::Native | SourceKind::Script => None,
+    }
+}
+
+/// Whether the host can use `source` at all.
+pub fn is_available(source: &InstallSource) -> bool {
+    match source {
+        InstallSource::Native(pkgs) => !pkgs.is_empty(),
+        InstallSource::Script(_) => which::which("curl").is_ok(),
+        other => installer_for(other.kind()).is_some_and(|backend| backend.is_available()),
+    }
+}
+
+/// Whether `name` is present from `source`, for post-install verification.
+///
+/// Scripts leave no record behind, so a binary called `name` on `PATH`
+/// stands in for them.
+pub fn is_installed(driver: &dyn DistroDriver, name: &str, source: &InstallSource) -> bool {
+    let listed = |program: &str, args: &[&str], target: &str| {
+        cmd::Command::new(program)
+            .args(args)
+            .execute()
+            .is_ok_and(|out| {
+                String::from_utf8_lossy(&out.stdout)
+                    .lines()
+                    .any(|line| line.split_whitespace().next() == Some(target))
+            })
+    };
+    match source {
+        InstallSource::Native(pkgs) => {
+            !pkgs.is_empty() && pkgs.iter().all(|pkg| driver.is_package_installed(pkg))
+        }
+        InstallSource::Aur(pkg) => driver.is_package_installed(pkg),
+        InstallSource::Flatpak(app) => cmd::Command::new("flatpak")
+            .args(["info", app.as_str()])
+            .execute()
+            .is_ok(),
+        InstallSource::Cargo(krate) => listed("cargo", &["install", "--list"], krate),
+        InstallSource::Pipx(pkg) => listed("pipx", &["list", "--short"], pkg),
+        InstallSource::Script(_) => which::which(name).is_ok(),
+    }
+}
+
+/// Install `name` from the first usable candidate, falling back on failure.
+///
+/// Returns the source that succeeded. In dry-run mode the first available
+/// candidate is recorded in the audit and nothing is executed. Scripts
+/// without a checksum are passed over unless the user allowed them.
+pub fn install(
+    ctx: &mut PhaseContext,
+    phase: &str,
+    name: &str,
+    candidates: &[InstallSource],
+) -> Result<SourceKind> {
+    let mut failures = Vec::new();
+
+    for source in candidates {
+        if let InstallSource::Script(script) = source {
+            if script.sha256.is_none() && !ctx.options.allow_unverified_scripts {
+                tracing::info!("{} is not pinned to a checksum; skipping", script.url);
+                failures.push(format!(
+                    "script: {} is not pinned to a checksum; pass \
+                     --allow-unverified-scripts to run it",
+                    script.url
+                ));
+                continue;
+            }
+        }
+        if !is_available(source) {
+            tracing::info!(
+                "{} unavailable for {}; trying next source",
+                source.kind().label(),
+                name
+            );
+            continue;
+        }
+
+        if ctx.options.dry_run {
+            ctx.record_dry_run(
+                phase,
+                format!("Would install {} via {}", name, source.kind().label()),
+                Some(source.target()),
+            );
+            plan_source(ctx, source);
+            return Ok(source.kind());
+        }
+
+        tracing::info!("Installing {} via {}", name, source.kind().label());
+        match install_source(ctx, name, source) {
+            Ok(()) => {
+                ctx.record_action(format!("Installed {} via {}", name, source.kind().label()));
+                return Ok(source.kind());
+            }
+            Err(err) => {
+                ctx.record_warning(format!(
+                    "{} install of {} failed; trying next source ({err:#})",
+                    source.kind().label(),
+                    name
+                ));
+                failures.push(format!("{}: {err:#}", source.kind().label()));
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        bail!("no available install source for {name}");
+    }
+    bail!(
+        "every install source for {name} failed:\n  - {}",
+        failures.join("\n  - ")
+    )
+}
+
+/// Put what installing from `source` would change on the dry-run plan.
+fn plan_source(ctx: &PhaseContext, source: &InstallSource) {
+    match source {
+        InstallSource::Native(pkgs) => {
+            let refs: Vec<&str> = pkgs.iter().map(String::as_str).collect();
+            // Dry runs only plan, so this cannot fail.
+            let _ = package_manager::ensure_native_packages(ctx.platform.driver, &refs, true);
+        }
+        InstallSource::Script(script) => dry_run::plan_download(&script.url),
+        other => dry_run::plan(PlannedChange::Packages {
+            backend: other.kind().label().to_string(),
+            packages: vec![other.target()],
+        }),
+    }
+}
+
+fn install_source(ctx: &mut PhaseContext, name: &str, source: &InstallSource) -> Result<()> {
+    match source {
+        InstallSource::Native(pkgs) => {
+            let refs: Vec<&str> = pkgs.iter().map(String::as_str).collect();
+            package_manager::ensure_native_packages(ctx.platform.driver, &refs, false)
+        }
+        InstallSource::Script(script) => {
+            let dir = ctx.options.staging_dir.join("scripts");
+            run_script(name, script, &dir)
+        }
+        other => {
+            let backend = installer_for(other.kind())
+                .ok_or_else(|| anyhow!("no backend for {}", other.kind().label()))?;
+            backend.install(&other.target(), false)
+        }
+    }
+}
+
+/// Download `script` into `dir`, check its digest and run it.
+fn run_script(name: &str, script: &ScriptSource, dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let path: PathBuf = dir.join(format!("{name}-install.sh"));
+
+    cmd::Command::new("curl")
+        .args(cmd::curl_flags())
+        .arg("-o")
+        .arg(&path)
+        .arg(&script.url)
+        .timeout(SCRIPT_DOWNLOAD_TIMEOUT)
+        .execute()
+        .with_context(|| format!("downloading {}", script.url))?;
+
+    match &script.sha256 {
+        Some(expected) => verify_sha256(&path, expected)?,
+        None => tracing::warn!("install script {} is not pinned to a checksum", script.url),
+    }
+
+    let command = if script.sudo {
+        cmd::Command::new("sh").sudo()
+    } else {
+        cmd::Command::new("sh")
+    };
+    command
+        .arg(&path)
+        .args(&script.args)
+        .execute()
+        .with_context(|| format!("running install script for {name}"))?;
+    Ok(())
+}
+
+fn verify_sha256(path: &Path, expected: &str) -> Result<()> {
+    let bytes = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    let actual = format!("{:x}", Sha256::digest(&bytes));
+    if !actual.eq_ignore_ascii_case(expected) {
+        bail!(
+            "checksum mismatch for {}: expected {}, got {}",
+            path.display(),
+            expected,
+            actual
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::fixture::{run_pi_phase, FixtureSystem};
+    use tempfile::tempdir;
+
+    #[test]
+    fn aur_is_only_tried_on_pacman() {
+        let extra = ProgramSources {
+            aur: Some("brave-bin".into()),
+            flatpak: Some("com.brave.Browser".into()),
+            ..ProgramSources::default()
+        };
+        let native = vec!["brave-browser".to_string()];
+
+        let arch = ordered_sources(PkgBackend::Pacman, Some(&native), &extra);
+        let kinds: Vec<_> = arch.iter().map(InstallSource::kind).collect();
+        assert_eq!(
+            kinds,
+            [SourceKind::Native, SourceKind::Aur, SourceKind::Flatpak]
+        );
+
+        let debian = ordered_sources(PkgBackend::Apt, None, &extra);
+        assert_eq!(
+            debian,
+            vec![InstallSource::Flatpak("com.brave.Browser".into())]
+        );
+    }
+
+    #[test]
+    fn unpinned_scripts_need_opting_in() {
+        fn phase(ctx: &mut PhaseContext) -> Result<crate::PhaseResult> {
+            let script = InstallSource::Script(ScriptSource {
+                url: "https://example.com/install.sh".into(),
+                sha256: None,
+                args: Vec::new(),
+                sudo: true,
+            });
+            install(ctx, "test", "tool", &[script])?;
+            Ok(crate::PhaseResult::Success)
+        }
+
+        let system = FixtureSystem::new().leak();
+        let err = run_pi_phase(system, "Raspberry Pi 5", false, phase)
+            .err()
+            .expect("unpinned script was run");
+        assert!(format!("{err:#}").contains("--allow-unverified-scripts"));
+    }
+
+    #[test]
+    fn checksum_mismatch_is_rejected() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("install.sh");
+        fs::write(&path, "echo hi\n")?;
+
+        let digest = format!("{:x}", Sha256::digest(b"echo hi\n"));
+        verify_sha256(&path, &digest)?;
+        assert!(verify_sha256(&path, "00").is_err());
+        Ok(())
+    }
+}
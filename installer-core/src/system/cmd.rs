@@ -1,11 +1,24 @@
 use anyhow::{Context, Result};
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
 use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::error::Error;
 use std::ffi::OsStr;
 use std::fmt;
-use std::process::{Command as StdCommand, Output, Stdio};
+use std::io::{self, BufRead, BufReader, Read};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command as StdCommand, ExitStatus, Output, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info};
 
+use super::{dry_run, progress};
+use crate::model::phase::{OutputStream, PhaseEvent, PlannedChange};
+use crate::scrubber;
+use crate::signal;
+
 /// Mode of command execution.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RunMode {
@@ -14,17 +27,55 @@ pub enum RunMode {
     DryRun,
 }
 
+/// How long the collector waits for output before re-checking the child,
+/// its timeout and the interrupt flag.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a cancelled command's process group gets between SIGTERM and
+/// SIGKILL.
+const TERM_GRACE: Duration = Duration::from_secs(5);
+
+/// Lines of each stream kept in [`CommandExecutionDetails`] for error reports.
+const TAIL_LINES: usize = 200;
+
+/// Per-invocation settings beyond the run mode.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Execution {
+    mode: RunMode,
+    timeout: Option<Duration>,
+    /// The caller configured these streams itself; leave them alone.
+    keep_stdin: bool,
+    keep_stdout: bool,
+    keep_stderr: bool,
+}
+
 /// Runs a command and provides detailed errors when it fails.
 pub fn run(cmd: &mut StdCommand) -> Result<Output> {
     run_with_mode(cmd, RunMode::Real)
 }
 
 /// Runs a command with the specified mode (Real or DryRun).
+///
+/// Output is streamed line by line to the current phase's observers as it is
+/// produced and also returned in full once the command exits.
 pub fn run_with_mode(cmd: &mut StdCommand, mode: RunMode) -> Result<Output> {
+    execute(
+        cmd,
+        Execution {
+            mode,
+            ..Execution::default()
+        },
+    )
+}
+
+fn execute(cmd: &mut StdCommand, exec: Execution) -> Result<Output> {
     let desc = describe_command(cmd);
 
-    if mode == RunMode::DryRun {
+    if exec.mode == RunMode::DryRun {
         info!("[dry-run] execution gated: {}", desc);
+        dry_run::plan(PlannedChange::Command {
+            command: scrubber::scrub(&desc),
+        });
         return Ok(Output {
             status: std::process::ExitStatus::default(), // Success-like for dry run
             stdout: Vec::new(),
@@ -32,6 +83,132 @@ pub fn run_with_mode(cmd: &mut StdCommand, mode: RunMode) -> Result<Output> {
         });
     }
 
+    let command = scrubber::scrub(&desc);
+    progress::emit(|phase| PhaseEvent::CommandStarted {
+        phase,
+        command: command.clone(),
+    });
+    let started = Instant::now();
+    let output = active_runner().run(Invocation {
+        cmd,
+        desc: &desc,
+        exec,
+    });
+    progress::emit(|phase| PhaseEvent::CommandFinished {
+        phase,
+        command,
+        status: output.as_ref().ok().and_then(|output| output.status.code()),
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
+    let output = output?;
+
+    let details = CommandExecutionDetails::from_output(desc.clone(), &output);
+
+    debug!(
+        command = %details.command,
+        status = ?details.status,
+        stdout = %details.stdout.trim_end(),
+        stderr = %details.stderr.trim_end(),
+        "command completed"
+    );
+
+    if output.status.success() {
+        Ok(output)
+    } else {
+        error!(
+            command = %details.command,
+            status = ?details.status,
+            stdout = %details.stdout.trim_end(),
+            stderr = %details.stderr.trim_end(),
+            "command failed"
+        );
+        Err(CommandExecutionError::new(details).into())
+    }
+}
+
+/// Executes commands on behalf of [`run`] and [`Command::execute`].
+///
+/// Every command a phase runs goes through the runner of its
+/// `PlatformContext`, so tests can swap in a fake (see
+/// [`RecordingRunner`](super::recording_runner::RecordingRunner)) and see
+/// exactly what a run would execute. Dry runs never reach the runner.
+pub trait CommandRunner: Send + Sync {
+    fn run(&self, invocation: Invocation<'_>) -> Result<Output>;
+}
+
+/// A command on its way to a [`CommandRunner`].
+pub struct Invocation<'a> {
+    cmd: &'a mut StdCommand,
+    desc: &'a str,
+    exec: Execution,
+}
+
+impl Invocation<'_> {
+    /// Program and arguments joined by spaces, as they appear in logs.
+    pub fn line(&self) -> &str {
+        self.desc
+    }
+
+    pub fn program(&self) -> String {
+        self.cmd.get_program().to_string_lossy().into_owned()
+    }
+
+    pub fn args(&self) -> Vec<String> {
+        self.cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.exec.timeout
+    }
+}
+
+/// Spawns real processes.
+pub struct RealRunner;
+
+pub static REAL_RUNNER: RealRunner = RealRunner;
+
+impl CommandRunner for RealRunner {
+    fn run(&self, invocation: Invocation<'_>) -> Result<Output> {
+        spawn_and_wait(invocation.cmd, invocation.desc, invocation.exec)
+    }
+}
+
+thread_local! {
+    static ACTIVE_RUNNER: Cell<Option<&'static dyn CommandRunner>> = const { Cell::new(None) };
+}
+
+/// Restores the previously active runner when dropped.
+pub struct RunnerGuard {
+    previous: Option<&'static dyn CommandRunner>,
+}
+
+impl Drop for RunnerGuard {
+    fn drop(&mut self) {
+        ACTIVE_RUNNER.with(|active| active.set(self.previous));
+    }
+}
+
+/// Route commands run on this thread through `runner` until the guard drops.
+///
+/// The phase runner does this with `PlatformContext::commands` for each phase
+/// so helpers that only call [`run`] still use the context's runner.
+pub fn use_runner(runner: &'static dyn CommandRunner) -> RunnerGuard {
+    let previous = ACTIVE_RUNNER.with(|active| active.replace(Some(runner)));
+    RunnerGuard { previous }
+}
+
+fn active_runner() -> &'static dyn CommandRunner {
+    ACTIVE_RUNNER
+        .with(Cell::get)
+        .unwrap_or(&REAL_RUNNER as &'static dyn CommandRunner)
+}
+
+/// Spawn `cmd`, feeding sudo the cached password when there is one, and
+/// collect its output.
+fn spawn_and_wait(cmd: &mut StdCommand, desc: &str, exec: Execution) -> Result<Output> {
     let program = cmd.get_program().to_string_lossy();
     let is_sudo = program == "sudo" || program.ends_with("/sudo");
     let password = if is_sudo {
@@ -40,96 +217,215 @@ pub fn run_with_mode(cmd: &mut StdCommand, mode: RunMode) -> Result<Output> {
         None
     };
 
-    let output = if let Some(pass) = password {
-        // Inject -S (stdin) into sudo command if not already present
-        let args: Vec<_> = cmd.get_args().collect();
-        let has_s = args.iter().any(|a| a.to_string_lossy() == "-S");
+    let mut sudo_cmd;
+    let (target, exec) = if is_sudo {
+        // -S reads the password from stdin; -n fails instead of prompting.
+        let flag = if password.is_some() { "-S" } else { "-n" };
+        sudo_cmd = with_sudo_flag(cmd, flag);
+        // The copy does not carry the caller's stdio settings.
+        let exec = Execution {
+            keep_stdin: false,
+            keep_stdout: false,
+            keep_stderr: false,
+            ..exec
+        };
+        (&mut sudo_cmd, exec)
+    } else {
+        (cmd, exec)
+    };
 
-        let mut new_cmd = StdCommand::new(cmd.get_program());
-        if !has_s {
-            new_cmd.arg("-S");
-        }
-        new_cmd.args(args);
-        // Copy env and current_dir from original
-        for (k, v) in cmd.get_envs() {
-            if let Some(v) = v {
-                new_cmd.env(k, v);
-            } else {
-                new_cmd.env_remove(k);
-            }
-        }
-        if let Some(dir) = cmd.get_current_dir() {
-            new_cmd.current_dir(dir);
-        }
+    if password.is_some() {
+        target.stdin(Stdio::piped());
+    } else if !exec.keep_stdin {
+        target.stdin(Stdio::null());
+    }
+    if !exec.keep_stdout {
+        target.stdout(Stdio::piped());
+    }
+    if !exec.keep_stderr {
+        target.stderr(Stdio::piped());
+    }
+    // Its own process group lets a cancel reach the package manager behind
+    // `sudo`. Commands reading the terminal stay in the foreground group.
+    let own_group = !exec.keep_stdin;
+    if own_group {
+        target.process_group(0);
+    }
 
-        new_cmd.stdin(Stdio::piped());
-        new_cmd.stdout(Stdio::piped());
-        new_cmd.stderr(Stdio::piped());
+    let mut child = target
+        .spawn()
+        .with_context(|| format!("spawning command: {desc}"))?;
 
-        let mut child = new_cmd
-            .spawn()
-            .with_context(|| format!("spawning command: {desc}"))?;
+    if let (Some(pass), Some(mut stdin)) = (password, child.stdin.take()) {
+        use std::io::Write;
+        let _ = writeln!(stdin, "{}", pass);
+    }
 
-        if let Some(mut stdin) = child.stdin.take() {
-            use std::io::Write;
-            let _ = writeln!(stdin, "{}", pass);
+    collect(child, desc, exec.timeout, own_group)
+}
+
+/// Rebuild a sudo invocation with `flag` added unless it is already there.
+fn with_sudo_flag(cmd: &StdCommand, flag: &str) -> StdCommand {
+    let args: Vec<_> = cmd.get_args().collect();
+    let mut new_cmd = StdCommand::new(cmd.get_program());
+    if !args.iter().any(|a| a.to_string_lossy() == flag) {
+        new_cmd.arg(flag);
+    }
+    new_cmd.args(args);
+    // Copy env and current_dir from original
+    for (k, v) in cmd.get_envs() {
+        if let Some(v) = v {
+            new_cmd.env(k, v);
+        } else {
+            new_cmd.env_remove(k);
         }
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        new_cmd.current_dir(dir);
+    }
+    new_cmd
+}
+
+/// Relay the child's output as it arrives until it exits, is cancelled by
+/// SIGINT/SIGTERM, or runs past `timeout`.
+fn collect(
+    mut child: Child,
+    desc: &str,
+    timeout: Option<Duration>,
+    own_group: bool,
+) -> Result<Output> {
+    let (tx, rx) = mpsc::channel();
+    if let Some(pipe) = child.stdout.take() {
+        spawn_reader(pipe, OutputStream::Stdout, tx.clone());
+    }
+    if let Some(pipe) = child.stderr.take() {
+        spawn_reader(pipe, OutputStream::Stderr, tx.clone());
+    }
+    drop(tx);
 
-        child
-            .wait_with_output()
+    let started = Instant::now();
+    let mut output = Output {
+        status: ExitStatus::default(),
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+    loop {
+        match rx.recv_timeout(POLL_INTERVAL) {
+            Ok((stream, line)) => relay_line(&mut output, stream, line),
+            Err(RecvTimeoutError::Timeout) => {}
+            // Both pipes are closed but the child may still be running.
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(POLL_INTERVAL),
+        }
+
+        if let Some(status) = child
+            .try_wait()
             .with_context(|| format!("waiting for command: {desc}"))?
-    } else if is_sudo {
-        // Use -n (non-interactive) for sudo when no password is known
-        let args: Vec<_> = cmd.get_args().collect();
-        let has_n = args.iter().any(|a| a.to_string_lossy() == "-n");
-
-        let mut new_cmd = StdCommand::new(cmd.get_program());
-        if !has_n {
-            new_cmd.arg("-n");
+        {
+            output.status = status;
+            break;
         }
-        new_cmd.args(args);
-        // Copy env and current_dir
-        for (k, v) in cmd.get_envs() {
-            if let Some(v) = v {
-                new_cmd.env(k, v);
-            } else {
-                new_cmd.env_remove(k);
+
+        let reason = if signal::interrupted() {
+            Some("cancelled by signal".to_string())
+        } else {
+            timeout
+                .filter(|limit| started.elapsed() >= *limit)
+                .map(|limit| format!("timed out after {}s", limit.as_secs()))
+        };
+        if let Some(reason) = reason {
+            output.status = terminate(&mut child, own_group)
+                .with_context(|| format!("reaping command: {desc}"))?;
+            // Whatever is already buffered still belongs in the report.
+            for (stream, line) in rx.try_iter() {
+                relay_line(&mut output, stream, line);
             }
+            let details = CommandExecutionDetails::from_output(desc.to_string(), &output);
+            let message = format!("command '{}' {reason}", details.command);
+            error!(command = %details.command, "{reason}");
+            return Err(anyhow::Error::new(CommandExecutionError::new(details)).context(message));
         }
-        if let Some(dir) = cmd.get_current_dir() {
-            new_cmd.current_dir(dir);
-        }
+    }
 
-        new_cmd
-            .output()
-            .with_context(|| format!("running non-interactive sudo command: {desc}"))?
-    } else {
-        cmd.output()
-            .with_context(|| format!("running command: {desc}"))?
+    // The readers finish once the pipes hit EOF.
+    for (stream, line) in rx {
+        relay_line(&mut output, stream, line);
+    }
+    Ok(output)
+}
+
+/// Stop a cancelled command. With `own_group` the whole process group gets
+/// SIGTERM, then SIGKILL after [`TERM_GRACE`], so nothing it started keeps
+/// running (and holding the package manager lock) once it is reaped.
+fn terminate(child: &mut Child, own_group: bool) -> io::Result<ExitStatus> {
+    if !own_group {
+        let _ = child.kill();
+        return child.wait();
+    }
+    let group = Pid::from_raw(child.id() as i32);
+    let _ = killpg(group, Signal::SIGTERM);
+    let deadline = Instant::now() + TERM_GRACE;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if Instant::now() >= deadline {
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
     };
+    // Stragglers outlive the leader; the group id stays valid while they run.
+    let _ = killpg(group, Signal::SIGKILL);
+    match status {
+        Some(status) => Ok(status),
+        None => child.wait(),
+    }
+}
 
-    let details = CommandExecutionDetails::from_output(desc.clone(), &output);
+fn spawn_reader(
+    pipe: impl Read + Send + 'static,
+    stream: OutputStream,
+    tx: mpsc::Sender<(OutputStream, Vec<u8>)>,
+) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        loop {
+            let mut line = Vec::new();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if tx.send((stream, line)).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
 
-    debug!(
-        command = %details.command,
-        status = ?details.status,
-        stdout = %details.stdout.trim_end(),
-        stderr = %details.stderr.trim_end(),
-        "command completed"
-    );
+/// Keep `line` for the caller and pass a scrubbed copy on to observers.
+fn relay_line(output: &mut Output, stream: OutputStream, line: Vec<u8>) {
+    progress::emit(|phase| PhaseEvent::CommandOutput {
+        phase,
+        stream,
+        line: scrubber::scrub(String::from_utf8_lossy(&line).trim_end_matches(['\n', '\r'])),
+    });
+    match stream {
+        OutputStream::Stdout => output.stdout.extend_from_slice(&line),
+        OutputStream::Stderr => output.stderr.extend_from_slice(&line),
+    }
+}
 
-    if output.status.success() {
-        Ok(output)
-    } else {
-        error!(
-            command = %details.command,
-            status = ?details.status,
-            stdout = %details.stdout.trim_end(),
-            stderr = %details.stderr.trim_end(),
-            "command failed"
-        );
-        Err(CommandExecutionError::new(details).into())
+/// The last [`TAIL_LINES`] lines of `text`, noting how many were dropped.
+fn tail(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() <= TAIL_LINES {
+        return text.to_string();
     }
+    let omitted = lines.len() - TAIL_LINES;
+    format!(
+        "[{omitted} earlier lines omitted]\n{}\n",
+        lines[omitted..].join("\n")
+    )
 }
 
 fn describe_command(cmd: &StdCommand) -> String {
@@ -144,7 +440,7 @@ fn describe_command(cmd: &StdCommand) -> String {
 /// Fluent shell command builder.
 pub struct Command {
     inner: StdCommand,
-    mode: RunMode,
+    exec: Execution,
 }
 
 impl Command {
@@ -152,7 +448,7 @@ pub fn new(program: impl AsRef<OsStr>) -> Self {
         fn inner(program: &OsStr) -> Command {
             Command {
                 inner: StdCommand::new(program),
-                mode: RunMode::Real,
+                exec: Execution::default(),
             }
         }
         inner(program.as_ref())
@@ -180,11 +476,17 @@ pub fn sudo(mut self) -> Self {
 
     pub fn dry_run(mut self, is_dry: bool) -> Self {
         if is_dry {
-            self.mode = RunMode::DryRun;
+            self.exec.mode = RunMode::DryRun;
         }
         self
     }
 
+    /// Kill the command if it is still running after `limit`.
+    pub fn timeout(mut self, limit: Duration) -> Self {
+        self.exec.timeout = Some(limit);
+        self
+    }
+
     pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
         fn inner(cmd: &mut StdCommand, arg: &OsStr) {
             cmd.arg(arg);
@@ -218,21 +520,24 @@ fn inner(cmd: &mut StdCommand, key: &OsStr, value: &OsStr) {
 
     pub fn stdin(mut self, cfg: Stdio) -> Self {
         self.inner.stdin(cfg);
+        self.exec.keep_stdin = true;
         self
     }
 
     pub fn stdout(mut self, cfg: Stdio) -> Self {
         self.inner.stdout(cfg);
+        self.exec.keep_stdout = true;
         self
     }
 
     pub fn stderr(mut self, cfg: Stdio) -> Self {
         self.inner.stderr(cfg);
+        self.exec.keep_stderr = true;
         self
     }
 
     pub fn execute(mut self) -> Result<Output> {
-        run_with_mode(&mut self.inner, self.mode)
+        execute(&mut self.inner, self.exec)
     }
 }
 
@@ -246,12 +551,14 @@ pub struct CommandExecutionDetails {
 }
 
 impl CommandExecutionDetails {
+    /// Captured output is scrubbed here, before it can reach logs, errors
+    /// or reports, and cut down to its tail.
     fn from_output(command: String, output: &Output) -> Self {
         Self {
-            command,
+            command: scrubber::scrub(&command),
             status: output.status.code(),
-            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
-            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            stdout: scrubber::scrub(&tail(&String::from_utf8_lossy(&output.stdout))),
+            stderr: scrubber::scrub(&tail(&String::from_utf8_lossy(&output.stderr))),
         }
     }
 
@@ -315,6 +622,151 @@ pub fn curl_flags() -> &'static [&'static str] {
 mod tests {
     use super::*;
 
+    #[test]
+    fn captured_output_is_scrubbed() {
+        scrubber::register_secret("cmd-output-secret");
+        let mut cmd = StdCommand::new("sh");
+        cmd.args(["-c", "echo cmd-output-secret; echo api_key=abc >&2; exit 3"]);
+        let err = run(&mut cmd).expect_err("command exits non-zero");
+        let details = err
+            .downcast_ref::<CommandExecutionError>()
+            .expect("command error")
+            .details();
+        assert!(!details.command.contains("cmd-output-secret"));
+        assert_eq!(details.stdout.trim(), "********");
+        assert_eq!(details.stderr.trim(), "api_key=********");
+    }
+
+    #[test]
+    fn execution_is_reported_to_the_progress_sink() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let _guard = progress::install("fonts", move |event| {
+            let _ = tx.send(event);
+        });
+        let _ = Command::new("sh").args(["-c", "exit 4"]).execute();
+        let _ = Command::new("false").dry_run(true).execute();
+
+        let events: Vec<_> = rx.try_iter().collect();
+        assert_eq!(events.len(), 3, "dry runs are only planned: {events:?}");
+        assert!(matches!(
+            &events[0],
+            PhaseEvent::CommandStarted { phase, command } if phase == "fonts" && command == "sh -c exit 4"
+        ));
+        assert!(matches!(
+            &events[1],
+            PhaseEvent::CommandFinished {
+                status: Some(4),
+                ..
+            }
+        ));
+        assert!(matches!(
+            &events[2],
+            PhaseEvent::Planned {
+                change: PlannedChange::Command { command },
+                ..
+            } if command == "false"
+        ));
+    }
+
+    #[test]
+    fn output_is_streamed_line_by_line_and_still_returned() -> Result<()> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let _guard = progress::install("fonts", move |event| {
+            let _ = tx.send(event);
+        });
+        let output = Command::new("sh")
+            .args(["-c", "echo one; echo two >&2; echo three"])
+            .execute()?;
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "one\nthree\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "two\n");
+
+        let mut lines: Vec<_> = rx
+            .try_iter()
+            .filter_map(|event| match event {
+                PhaseEvent::CommandOutput { stream, line, .. } => Some((stream, line)),
+                _ => None,
+            })
+            .collect();
+        // stdout and stderr are read independently, so only per-stream order holds.
+        lines.sort_by_key(|(stream, _)| *stream == OutputStream::Stderr);
+        assert_eq!(
+            lines,
+            [
+                (OutputStream::Stdout, "one".to_string()),
+                (OutputStream::Stdout, "three".to_string()),
+                (OutputStream::Stderr, "two".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn explicitly_silenced_streams_are_not_captured() -> Result<()> {
+        let output = Command::new("echo")
+            .arg("hidden")
+            .stdout(Stdio::null())
+            .execute()?;
+        assert!(output.stdout.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn timeout_kills_the_command_and_keeps_its_output() {
+        let started = Instant::now();
+        let err = Command::new("sh")
+            .args(["-c", "echo started; sleep 30"])
+            .timeout(Duration::from_millis(300))
+            .execute()
+            .expect_err("command outlives its timeout");
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(format!("{err}").contains("timed out"), "{err}");
+        let details = err
+            .downcast_ref::<CommandExecutionError>()
+            .expect("command error")
+            .details();
+        assert_eq!(details.stdout.trim(), "started");
+    }
+
+    #[test]
+    fn timeout_stops_everything_the_command_started() -> Result<()> {
+        let err = Command::new("sh")
+            .args(["-c", "sleep 30 & echo $!; wait"])
+            .timeout(Duration::from_millis(300))
+            .execute()
+            .expect_err("command outlives its timeout");
+        let details = err
+            .downcast_ref::<CommandExecutionError>()
+            .expect("command error")
+            .details();
+        let sleeper = details.stdout.trim();
+
+        // A dead orphan may linger as a zombie until init reaps it.
+        let running = || {
+            std::fs::read_to_string(format!("/proc/{sleeper}/stat"))
+                .ok()
+                .and_then(|stat| {
+                    let state = stat.rsplit_once(") ")?.1.chars().next()?;
+                    Some(state != 'Z' && state != 'X')
+                })
+                .unwrap_or(false)
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while running() && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL);
+        }
+        assert!(!running(), "sleep {sleeper} survived");
+        Ok(())
+    }
+
+    #[test]
+    fn error_details_keep_only_the_tail() {
+        let text: String = (1..=TAIL_LINES + 5).map(|n| format!("{n}\n")).collect();
+        let kept = tail(&text);
+        assert!(kept.starts_with("[5 earlier lines omitted]\n6\n"));
+        assert!(kept.ends_with(&format!("{}\n", TAIL_LINES + 5)));
+        assert_eq!(tail("short\n"), "short\n");
+    }
+
     #[test]
     fn describe_command_includes_arguments() {
         let mut cmd = StdCommand::new("echo");
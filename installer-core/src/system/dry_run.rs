@@ -1,6 +1,16 @@
 use serde::{Deserialize, Serialize};
-use std::cell::RefCell;
 use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use super::progress;
+use crate::model::phase::{PhaseEvent, PlannedChange};
+
+/// How long `mash-setup plan` waits for a server to say how big a download is.
+const SIZE_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Context lines kept around each change in a [`line_diff`].
+const DIFF_CONTEXT: usize = 2;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DryRunEntry {
@@ -16,16 +26,154 @@ pub struct PreflightAuditReport {
     pub phases: BTreeMap<String, Vec<DryRunEntry>>,
     /// Total number of actions planned
     pub total_actions: usize,
+    /// Concrete changes, one entry per phase in the order the phases ran
+    #[serde(default)]
+    pub plan: Vec<PhasePlan>,
+}
+
+/// What one phase of a dry run would change.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PhasePlan {
+    /// Stable phase id.
+    pub phase: String,
+    pub label: String,
+    pub changes: Vec<PlannedChange>,
+}
+
+impl PreflightAuditReport {
+    /// Group the [`PhaseEvent::Planned`] changes in `events` under the phases
+    /// that started. Phases that planned nothing keep an empty entry, so the
+    /// plan shows they would run.
+    pub fn with_plan(mut self, events: &[PhaseEvent]) -> Self {
+        for event in events {
+            match event {
+                PhaseEvent::Started { phase, label, .. } => self.plan.push(PhasePlan {
+                    phase: phase.clone(),
+                    label: label.clone(),
+                    changes: Vec::new(),
+                }),
+                PhaseEvent::Planned { phase, change } => {
+                    match self.plan.iter_mut().rfind(|entry| &entry.phase == phase) {
+                        Some(entry) => entry.changes.push(change.clone()),
+                        None => self.plan.push(PhasePlan {
+                            phase: phase.clone(),
+                            label: phase.clone(),
+                            changes: vec![change.clone()],
+                        }),
+                    }
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+
+    /// Number of concrete changes across all phases.
+    pub fn planned_changes(&self) -> usize {
+        self.plan.iter().map(|entry| entry.changes.len()).sum()
+    }
+}
+
+/// Put `change` on the plan of the phase running on this thread.
+pub fn plan(change: PlannedChange) {
+    progress::emit(|phase| PhaseEvent::Planned { phase, change });
+}
+
+/// Plan a download of `url`, asking the server for its size first.
+pub fn plan_download(url: &str) {
+    plan(PlannedChange::Download {
+        url: url.to_string(),
+        size: remote_size(url),
+    });
+}
+
+/// `Content-Length` of `url` from a HEAD request; `None` when offline or the
+/// server does not say.
+fn remote_size(url: &str) -> Option<u64> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(SIZE_PROBE_TIMEOUT)
+        .build();
+    agent
+        .head(url)
+        .call()
+        .ok()?
+        .header("Content-Length")?
+        .parse()
+        .ok()
+}
+
+/// A `-`/`+` line diff from `old` to `new`, with [`DIFF_CONTEXT`] unchanged
+/// lines around each change and `...` where unchanged lines were left out.
+pub fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, filled from the end.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (mark, _))| *mark != ' ')
+        .map(|(index, _)| index)
+        .collect();
+    let keep = |index: usize| {
+        changed
+            .iter()
+            .any(|&change| index + DIFF_CONTEXT >= change && index <= change + DIFF_CONTEXT)
+    };
+
+    let mut out = String::new();
+    let mut skipped = false;
+    for (index, (mark, line)) in lines.iter().enumerate() {
+        if keep(index) {
+            if skipped {
+                out.push_str("...\n");
+                skipped = false;
+            }
+            out.push(*mark);
+            out.push_str(line);
+            out.push('\n');
+        } else {
+            skipped = true;
+        }
+    }
+    out
 }
 
 pub struct DryRunLog {
-    entries: RefCell<Vec<DryRunEntry>>,
+    entries: Mutex<Vec<DryRunEntry>>,
 }
 
 impl DryRunLog {
     pub fn new() -> Self {
         Self {
-            entries: RefCell::new(Vec::new()),
+            entries: Mutex::new(Vec::new()),
         }
     }
 
@@ -40,16 +188,16 @@ pub fn record(
             action: action.into(),
             detail,
         };
-        self.entries.borrow_mut().push(entry);
+        self.lock().push(entry);
     }
 
     pub fn entries(&self) -> Vec<DryRunEntry> {
-        self.entries.borrow().clone()
+        self.lock().clone()
     }
 
     /// Generate a structured pre-flight audit report.
     pub fn audit_report(&self) -> PreflightAuditReport {
-        let entries = self.entries.borrow();
+        let entries = self.lock();
         let mut phases: BTreeMap<String, Vec<DryRunEntry>> = BTreeMap::new();
 
         for entry in entries.iter() {
@@ -62,8 +210,13 @@ pub fn audit_report(&self) -> PreflightAuditReport {
         PreflightAuditReport {
             total_actions: entries.len(),
             phases,
+            plan: Vec::new(),
         }
     }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<DryRunEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
 }
 
 impl Default for DryRunLog {
@@ -92,4 +245,47 @@ fn dry_run_log_records_multiple_entries() {
         assert_eq!(entries[1].action, "action-b");
         assert!(entries[1].detail.is_none());
     }
+
+    #[test]
+    fn line_diff_keeps_context_around_changes() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\ne\nf\nG\nh\ni\n";
+
+        assert_eq!(line_diff(old, new), "...\n e\n f\n-g\n+G\n h\n+i\n");
+        assert_eq!(line_diff("", "x\n"), "+x\n");
+        assert_eq!(line_diff("same\n", "same\n"), "");
+    }
+
+    #[test]
+    fn plan_groups_changes_under_started_phases() {
+        let service = PlannedChange::Service {
+            unit: "docker.service".into(),
+        };
+        let events = vec![
+            PhaseEvent::Started {
+                index: 1,
+                total: 2,
+                phase: "fonts".into(),
+                label: "Fonts".into(),
+            },
+            PhaseEvent::Started {
+                index: 2,
+                total: 2,
+                phase: "docker_engine".into(),
+                label: "Docker".into(),
+            },
+            PhaseEvent::Planned {
+                phase: "docker_engine".into(),
+                change: service.clone(),
+            },
+        ];
+
+        let report = PreflightAuditReport::default().with_plan(&events);
+
+        assert_eq!(report.plan.len(), 2);
+        assert!(report.plan[0].changes.is_empty());
+        assert_eq!(report.plan[1].label, "Docker");
+        assert_eq!(report.plan[1].changes, vec![service]);
+        assert_eq!(report.planned_changes(), 1);
+    }
 }
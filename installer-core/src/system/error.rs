@@ -3,6 +3,8 @@
 use crate::model::options::{InstallOptions, ProfileLevel, UserOptionsContext};
 use crate::model::phase::{PhaseEvent, PhaseOutput};
 use crate::model::software::SoftwareTierPlan;
+use crate::scrubber;
+use crate::snapshots::SnapshotRecord;
 
 use anyhow::Error;
 use serde::{Deserialize, Serialize};
@@ -123,7 +125,8 @@ pub fn new(
     ) -> Self {
         let phase = phase.into();
         let description = description.into();
-        let cause = source.root_cause().to_string().to_uppercase();
+        // Scrub before upper-casing, which would hide registered secrets.
+        let cause = scrubber::scrub(&source.root_cause().to_string()).to_uppercase();
         let fix = advice
             .as_deref()
             .unwrap_or("CHECK_LOGS_FOR_DETAILS")
@@ -135,7 +138,7 @@ pub fn new(
             cause,
             fix
         );
-        let developer_detail = format!("{source:#}");
+        let developer_detail = scrubber::scrub(&format!("{source:#}"));
         let command_output = source
             .downcast_ref::<cmd::CommandExecutionError>()
             .map(|err| err.details().clone());
@@ -181,6 +184,12 @@ pub struct InstallationReport {
     pub driver: DriverInfo,
     pub dry_run_log: Vec<DryRunEntry>,
     pub audit_report: PreflightAuditReport,
+    /// Run journal id; `None` for dry runs and runs that failed to start.
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Pre-install snapshots taken during the run.
+    #[serde(default)]
+    pub snapshots: Vec<SnapshotRecord>,
 }
 
 impl InstallationReport {
@@ -191,6 +200,12 @@ pub fn has_errors(&self) -> bool {
     pub fn error_count(&self) -> usize {
         self.errors.len()
     }
+
+    pub fn has_fatal_error(&self) -> bool {
+        self.errors
+            .iter()
+            .any(|err| err.severity == ErrorSeverity::Fatal)
+    }
 }
 
 #[derive(Debug, ThisError)]
@@ -226,6 +241,8 @@ fn from(source: Error) -> Self {
                 },
                 dry_run_log: Vec::new(),
                 audit_report: PreflightAuditReport::default(),
+                run_id: None,
+                snapshots: Vec::new(),
             }),
             source: installer_error,
         }
@@ -0,0 +1,246 @@
+//! Test-only `SystemOps` backed by a fixture tree, plus a harness that runs a
+//! phase against it on a given Raspberry Pi model.
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::net::TcpStream;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::backend::PkgBackend;
+use crate::cmd::{CommandExecutionDetails, CommandExecutionError};
+use crate::context::PhaseMetadata;
+use crate::driver::DistroDriver;
+use crate::dry_run::DryRunLog;
+use crate::platform::PlatformInfo;
+use crate::system::system_ops::SystemOps;
+use crate::{
+    ConfigService, InstallOptions, Localization, PhaseContext, PhaseObserver, PhaseResult,
+    PlatformContext, RollbackManager, UIContext, UserOptionsContext,
+};
+
+/// Scripted answer for a command: stdout on success, or exit status and stdout.
+pub(crate) type FixtureOutput = Result<&'static str, (i32, &'static str)>;
+
+/// A `SystemOps` rooted in a temp dir: absolute paths resolve inside it,
+/// commands are recorded and answered from a prefix table.
+pub(crate) struct FixtureSystem {
+    root: tempfile::TempDir,
+    outputs: Vec<(&'static str, FixtureOutput)>,
+    commands: Mutex<Vec<String>>,
+}
+
+impl FixtureSystem {
+    pub(crate) fn new() -> Self {
+        Self {
+            root: tempfile::tempdir().expect("fixture root"),
+            outputs: Vec::new(),
+            commands: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn file(self, path: &str, content: &str) -> Self {
+        let target = self.path(Path::new(path));
+        fs::create_dir_all(target.parent().expect("parent")).expect("fixture dir");
+        fs::write(target, content).expect("fixture file");
+        self
+    }
+
+    /// Answer commands starting with `prefix`. Later entries take precedence.
+    pub(crate) fn output(mut self, prefix: &'static str, result: FixtureOutput) -> Self {
+        self.outputs.insert(0, (prefix, result));
+        self
+    }
+
+    fn path(&self, path: &Path) -> PathBuf {
+        self.root
+            .path()
+            .join(path.strip_prefix("/").unwrap_or(path))
+    }
+
+    pub(crate) fn read(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.path(Path::new(path))).ok()
+    }
+
+    pub(crate) fn commands(&self) -> Vec<String> {
+        self.commands.lock().unwrap().clone()
+    }
+
+    /// Leak the fixture so it can stand in for `&'static dyn SystemOps`.
+    pub(crate) fn leak(self) -> &'static Self {
+        Box::leak(Box::new(self))
+    }
+}
+
+impl SystemOps for FixtureSystem {
+    fn read_to_string(&self, path: &Path) -> Result<String> {
+        fs::read_to_string(self.path(path)).with_context(|| format!("reading {}", path.display()))
+    }
+
+    fn command_output(&self, cmd: &mut Command) -> Result<Output> {
+        let mut line = cmd.get_program().to_string_lossy().into_owned();
+        for arg in cmd.get_args() {
+            line.push(' ');
+            line.push_str(&arg.to_string_lossy());
+        }
+        self.commands.lock().unwrap().push(line.clone());
+        let result = self
+            .outputs
+            .iter()
+            .find(|(prefix, _)| line.starts_with(prefix))
+            .map(|(_, result)| *result)
+            .unwrap_or(Ok(""));
+        match result {
+            Ok(stdout) => Ok(Output {
+                status: ExitStatus::from_raw(0),
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: Vec::new(),
+            }),
+            Err((status, stdout)) => Err(CommandExecutionError::new(CommandExecutionDetails {
+                command: line,
+                status: Some(status),
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+            })
+            .into()),
+        }
+    }
+
+    fn connect(&self, _host: &str, _port: u16, _timeout: Duration) -> Result<TcpStream> {
+        Err(anyhow!("no network in fixtures"))
+    }
+
+    fn detect_root_fstype(&self) -> Result<String> {
+        Ok("ext4".to_string())
+    }
+
+    fn write_file(&self, path: &Path, content: &[u8]) -> Result<()> {
+        Ok(fs::write(self.path(path), content)?)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        Ok(fs::rename(self.path(from), self.path(to))?)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        Ok(fs::create_dir_all(self.path(path))?)
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(self.path(path))? {
+            let inside = entry?.path();
+            let relative = inside.strip_prefix(self.root.path())?;
+            entries.push(Path::new("/").join(relative));
+        }
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn install_file(&self, path: &Path, content: &[u8], _mode: u32) -> Result<()> {
+        let target = self.path(path);
+        fs::create_dir_all(target.parent().expect("parent"))?;
+        Ok(fs::write(target, content)?)
+    }
+}
+
+/// What a phase left behind: its metadata and the rollback steps it registered.
+pub(crate) struct PhaseRun {
+    pub metadata: PhaseMetadata,
+    pub rollback: Vec<String>,
+}
+
+/// Observer that answers every confirmation prompt the same way.
+struct Confirm(bool);
+
+impl PhaseObserver for Confirm {
+    fn confirm(&mut self, _prompt: &str) -> bool {
+        self.0
+    }
+}
+
+struct PiDriver;
+
+impl DistroDriver for PiDriver {
+    fn name(&self) -> &'static str {
+        "pi-test"
+    }
+
+    fn description(&self) -> &'static str {
+        "driver used for Raspberry Pi tuning tests"
+    }
+
+    fn matches(&self, _: &PlatformInfo) -> bool {
+        true
+    }
+
+    fn pkg_backend(&self) -> PkgBackend {
+        PkgBackend::Apt
+    }
+}
+
+static PI_DRIVER: PiDriver = PiDriver;
+
+/// Run `phase` on a Debian host reporting `pi_model`, with `/staging` as the
+/// staging directory. Prompts are accepted when `interactive` and declined
+/// otherwise, as when nobody is there to answer.
+pub(crate) fn run_pi_phase(
+    system: &'static FixtureSystem,
+    pi_model: &str,
+    interactive: bool,
+    phase: fn(&mut PhaseContext) -> Result<PhaseResult>,
+) -> Result<PhaseRun> {
+    let platform = PlatformContext {
+        config_service: ConfigService::load()?,
+        platform: PlatformInfo {
+            arch: "aarch64".into(),
+            distro: "debian".into(),
+            distro_version: "12".into(),
+            distro_codename: "bookworm".into(),
+            distro_family: "debian".into(),
+            pi_model: Some(pi_model.into()),
+            cpu_model: "Cortex-A76".into(),
+            cpu_cores: 4,
+            ram_total_gb: 8.0,
+        },
+        driver_name: "pi-test",
+        driver: &PI_DRIVER,
+        pkg_backend: PkgBackend::Apt,
+        system,
+        commands: &crate::cmd::REAL_RUNNER,
+    };
+    let options = UserOptionsContext {
+        staging_dir: PathBuf::from("/staging"),
+        interactive,
+        ..UserOptionsContext::from_options(&InstallOptions::default())
+    };
+    let localization = Localization::load_default()?;
+    let rollback = RollbackManager::new();
+    let dry_run_log = DryRunLog::new();
+    let cache = crate::ArtifactCache::new(Path::new("/tmp/mash-test-cache"));
+    let interaction = crate::interaction::InteractionService::new(false, Default::default());
+    let mut observer = Confirm(interactive);
+    let mut ctx = PhaseContext::new(
+        &options,
+        &platform,
+        &UIContext,
+        &interaction,
+        &localization,
+        &rollback,
+        &dry_run_log,
+        &cache,
+        &mut observer,
+    );
+    phase(&mut ctx)?;
+    Ok(PhaseRun {
+        metadata: ctx.take_metadata(),
+        rollback: rollback
+            .steps()
+            .into_iter()
+            .map(|step| step.label)
+            .collect(),
+    })
+}
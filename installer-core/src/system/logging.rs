@@ -5,6 +5,8 @@
 use std::io::{self, Write};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+
+use crate::scrubber::ScrubWriter;
 use tracing_subscriber::{
     filter::LevelFilter, fmt, fmt::format::FmtSpan, fmt::writer::BoxMakeWriter, prelude::*,
     EnvFilter,
@@ -68,7 +70,7 @@ pub fn init(config: &crate::model::config::LoggingConfig, verbose: bool) -> Resu
     // 2. Stdout Layer (Only if verbose or no file layer)
     if verbose || layers.is_empty() {
         let stdout_layer = fmt::layer()
-            .with_writer(io::stdout)
+            .with_writer(|| ScrubWriter::new(io::stdout()))
             .event_format(fmt::format().compact())
             .boxed();
         layers.push(stdout_layer);
@@ -92,20 +94,23 @@ fn make_writer(path: Option<&PathBuf>) -> Result<BoxMakeWriter, io::Error> {
             inner: guard.clone(),
         }))
     } else {
-        Ok(BoxMakeWriter::new(io::stdout))
+        Ok(BoxMakeWriter::new(|| ScrubWriter::new(io::stdout())))
     }
 }
 
+/// Shared log file handle; every write is scrubbed of registered secrets
+/// and credential patterns before it reaches disk.
 struct LockedWriter {
     inner: Arc<Mutex<File>>,
 }
 
 impl Write for LockedWriter {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.inner
+        let mut file = self
+            .inner
             .lock()
-            .map_err(|e| io::Error::other(e.to_string()))?
-            .write(buf)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        ScrubWriter::new(&mut *file).write(buf)
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -155,6 +160,35 @@ fn json_format_records_structured_entries() -> Result<()> {
         Ok(())
     }
 
+    #[test]
+    fn file_writer_scrubs_secrets() -> Result<()> {
+        let dir = tempdir()?;
+        let log_path = dir.path().join("scrubbed.log");
+        crate::scrubber::register_secret("log-sink-secret");
+
+        let file = File::create(&log_path)?;
+        let writer = Arc::new(Mutex::new(file));
+        let layer = fmt::layer()
+            .with_writer(move || LockedWriter {
+                inner: writer.clone(),
+            })
+            .boxed();
+        let subscriber = tracing_subscriber::registry().with(layer);
+        let dispatch = dispatcher::Dispatch::new(subscriber);
+        dispatcher::with_default(&dispatch, || {
+            info!(command = "sudo -S true", "password=log-sink-secret");
+            info!("token ghp_{}", "Z".repeat(36));
+        });
+
+        drop(dispatch);
+
+        let contents = fs::read_to_string(log_path)?;
+        assert!(!contents.contains("log-sink-secret"));
+        assert!(!contents.contains("ghp_ZZZZ"));
+        assert!(contents.contains("password=********"));
+        Ok(())
+    }
+
     #[test]
     fn level_filter_filters_lower_levels() -> Result<()> {
         let dir = tempdir()?;
@@ -2,9 +2,13 @@
 pub mod cmd;
 pub mod dry_run;
 pub mod error;
+#[cfg(test)]
+pub(crate) mod fixture;
 pub mod lockfile;
 pub mod logging;
 pub mod proc;
+pub mod progress;
+pub mod recording_runner;
 pub mod sudo;
 pub mod sudo_password;
 pub mod system_ops;
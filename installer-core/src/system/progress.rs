@@ -0,0 +1,108 @@
+//! Progress events raised below the phase API.
+//!
+//! Command execution and downloads happen in helpers that never see a
+//! `PhaseContext`. The phase runner installs a per-thread sink for the phase
+//! it is executing and those helpers report through [`emit`]. Without a sink
+//! (unit tests, the standalone binaries) events are simply dropped.
+
+use std::cell::RefCell;
+
+use crate::model::phase::PhaseEvent;
+
+struct Sink {
+    phase: String,
+    send: Box<dyn Fn(PhaseEvent)>,
+}
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Restores the previously installed sink when dropped.
+pub struct SinkGuard {
+    previous: Option<Sink>,
+}
+
+impl Drop for SinkGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        SINK.with(|sink| *sink.borrow_mut() = previous);
+    }
+}
+
+/// Route progress events raised on this thread to `send`, attributed to `phase`.
+pub fn install(phase: impl Into<String>, send: impl Fn(PhaseEvent) + 'static) -> SinkGuard {
+    let previous = SINK.with(|sink| {
+        sink.replace(Some(Sink {
+            phase: phase.into(),
+            send: Box::new(send),
+        }))
+    });
+    SinkGuard { previous }
+}
+
+/// Emit the event built by `event` for the current phase, if anyone is listening.
+pub fn emit(event: impl FnOnce(String) -> PhaseEvent) {
+    SINK.with(|sink| {
+        if let Some(sink) = sink.borrow().as_ref() {
+            (sink.send)(event(sink.phase.clone()));
+        }
+    });
+}
+
+/// Phase the sink on this thread reports for, if one is installed.
+pub fn current_phase() -> Option<String> {
+    SINK.with(|sink| sink.borrow().as_ref().map(|sink| sink.phase.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn warning(phase: String) -> PhaseEvent {
+        PhaseEvent::Warning { message: phase }
+    }
+
+    #[test]
+    fn events_reach_the_installed_sink_until_the_guard_drops() {
+        let (tx, rx) = mpsc::channel();
+        emit(warning);
+        {
+            let _guard = install("fonts", move |event| {
+                let _ = tx.send(event);
+            });
+            emit(warning);
+        }
+        emit(warning);
+
+        let seen: Vec<_> = rx.try_iter().collect();
+        assert_eq!(seen.len(), 1);
+        assert!(matches!(&seen[0], PhaseEvent::Warning { message } if message == "fonts"));
+    }
+
+    #[test]
+    fn nested_sinks_restore_the_outer_one() {
+        let (tx, rx) = mpsc::channel();
+        let outer_tx = tx.clone();
+        let _outer = install("outer", move |event| {
+            let _ = outer_tx.send(event);
+        });
+        {
+            let _inner = install("inner", move |event| {
+                let _ = tx.send(event);
+            });
+            emit(warning);
+        }
+        emit(warning);
+
+        let phases: Vec<_> = rx
+            .try_iter()
+            .map(|event| match event {
+                PhaseEvent::Warning { message } => message,
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect();
+        assert_eq!(phases, ["inner", "outer"]);
+    }
+}
@@ -0,0 +1,185 @@
+//! A [`CommandRunner`] that records commands instead of running them.
+//!
+//! Tests put it in `PlatformContext::commands`, script the outputs the
+//! phases under test depend on, and then assert on (or snapshot) the exact
+//! command lines a run produced.
+
+use std::os::unix::process::ExitStatusExt;
+use std::process::{ExitStatus, Output};
+use std::sync::Mutex;
+
+use anyhow::{bail, Result};
+
+use super::cmd::{CommandRunner, Invocation};
+
+/// What a scripted command prints and how it exits.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScriptedOutput {
+    pub status: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ScriptedOutput {
+    pub fn success(stdout: impl Into<String>) -> Self {
+        Self {
+            status: 0,
+            stdout: stdout.into(),
+            stderr: String::new(),
+        }
+    }
+
+    pub fn failure(status: i32, stderr: impl Into<String>) -> Self {
+        Self {
+            status,
+            stdout: String::new(),
+            stderr: stderr.into(),
+        }
+    }
+
+    fn to_output(&self) -> Output {
+        Output {
+            // Wait statuses keep the exit code in the second byte.
+            status: ExitStatus::from_raw(self.status << 8),
+            stdout: self.stdout.clone().into_bytes(),
+            stderr: self.stderr.clone().into_bytes(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RecordingRunner {
+    rules: Vec<(String, ScriptedOutput)>,
+    fallback: Option<ScriptedOutput>,
+    calls: Mutex<Vec<String>>,
+}
+
+impl RecordingRunner {
+    /// Records every command and answers each with an empty success.
+    pub fn new() -> Self {
+        Self {
+            fallback: Some(ScriptedOutput::default()),
+            ..Self::default()
+        }
+    }
+
+    /// Like [`new`](Self::new), but commands no rule matches are an error.
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Answer commands whose line starts with `prefix`. Rules are tried in
+    /// the order they were added.
+    pub fn on(mut self, prefix: impl Into<String>, output: ScriptedOutput) -> Self {
+        self.rules.push((prefix.into(), output));
+        self
+    }
+
+    /// The answer for commands no rule matches.
+    pub fn otherwise(mut self, output: ScriptedOutput) -> Self {
+        self.fallback = Some(output);
+        self
+    }
+
+    /// Command lines seen so far, in order.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls
+            .lock()
+            .map(|calls| calls.clone())
+            .unwrap_or_default()
+    }
+
+    /// Forget the commands seen so far; the rules stay.
+    pub fn clear(&self) {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.clear();
+        }
+    }
+
+    /// Whether a command starting with `prefix` was run.
+    pub fn ran(&self, prefix: &str) -> bool {
+        self.calls().iter().any(|line| line.starts_with(prefix))
+    }
+}
+
+impl CommandRunner for RecordingRunner {
+    fn run(&self, invocation: Invocation<'_>) -> Result<Output> {
+        let line = invocation.line().to_string();
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(line.clone());
+        }
+        let scripted = self
+            .rules
+            .iter()
+            .find(|(prefix, _)| line.starts_with(prefix.as_str()))
+            .map(|(_, output)| output)
+            .or(self.fallback.as_ref());
+        match scripted {
+            Some(output) => Ok(output.to_output()),
+            None => bail!("unexpected command: {line}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::{self, CommandExecutionError};
+
+    fn leak(runner: RecordingRunner) -> &'static RecordingRunner {
+        Box::leak(Box::new(runner))
+    }
+
+    #[test]
+    fn scripted_outputs_are_returned_and_calls_recorded() -> Result<()> {
+        let runner = leak(
+            RecordingRunner::new()
+                .on("dpkg -s git", ScriptedOutput::success("Status: install ok"))
+                .on("dpkg -s", ScriptedOutput::failure(1, "not installed")),
+        );
+        let _guard = cmd::use_runner(runner);
+
+        let output = cmd::Command::new("dpkg").args(["-s", "git"]).execute()?;
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "Status: install ok"
+        );
+
+        let err = cmd::Command::new("dpkg")
+            .args(["-s", "zsh"])
+            .execute()
+            .expect_err("scripted failure");
+        let details = err
+            .downcast_ref::<CommandExecutionError>()
+            .expect("command error");
+        assert_eq!(details.status, Some(1));
+        assert_eq!(details.stderr, "not installed");
+
+        cmd::Command::new("true").sudo().execute()?;
+        assert_eq!(runner.calls(), ["dpkg -s git", "dpkg -s zsh", "sudo true"]);
+        assert!(runner.ran("sudo"));
+        Ok(())
+    }
+
+    #[test]
+    fn strict_runner_rejects_unscripted_commands() {
+        let runner = leak(RecordingRunner::strict().on("echo", ScriptedOutput::success("")));
+        let _guard = cmd::use_runner(runner);
+
+        assert!(cmd::Command::new("echo").execute().is_ok());
+        let err = cmd::Command::new("rm")
+            .args(["-rf", "/"])
+            .execute()
+            .expect_err("unscripted");
+        assert!(err.to_string().contains("unexpected command: rm -rf /"));
+    }
+
+    #[test]
+    fn dry_runs_never_reach_the_runner() -> Result<()> {
+        let runner = leak(RecordingRunner::strict());
+        let _guard = cmd::use_runner(runner);
+        cmd::Command::new("reboot").dry_run(true).execute()?;
+        assert!(runner.calls().is_empty());
+        Ok(())
+    }
+}
@@ -13,6 +13,7 @@ pub fn init_sudo_password() {
 /// Set the sudo password
 #[allow(dead_code)]
 pub fn set_sudo_password(password: String) {
+    crate::scrubber::register_secret(password.clone());
     if let Some(pass_ref) = SUDO_PASSWORD.get() {
         let mut pass = pass_ref.lock().unwrap();
         *pass = Some(password);
@@ -1,12 +1,13 @@
 use crate::cmd;
 use anyhow::{anyhow, Context, Result};
+use std::io::Write;
 use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
 use std::time::Duration;
 
 /// Abstraction over system APIs that are hard to test directly.
-pub trait SystemOps {
+pub trait SystemOps: Send + Sync {
     fn read_to_string(&self, path: &Path) -> Result<String>;
     fn command_output(&self, cmd: &mut Command) -> Result<Output>;
     fn connect(&self, host: &str, port: u16, timeout: Duration) -> Result<TcpStream>;
@@ -20,6 +21,12 @@ pub trait SystemOps {
 
     /// Create directory and all parent directories
     fn create_dir_all(&self, path: &Path) -> Result<()>;
+
+    /// Entries of a directory, sorted by path
+    fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>>;
+
+    /// Write a root-owned file with `mode`, creating parent directories
+    fn install_file(&self, path: &Path, content: &[u8], mode: u32) -> Result<()>;
 }
 
 /// Real implementation of `SystemOps` that delegates to the OS.
@@ -68,4 +75,31 @@ fn create_dir_all(&self, path: &Path) -> Result<()> {
         std::fs::create_dir_all(path)
             .with_context(|| format!("creating directory {}", path.display()))
     }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        let mut entries = std::fs::read_dir(path)
+            .with_context(|| format!("listing {}", path.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()
+            .with_context(|| format!("listing {}", path.display()))?;
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn install_file(&self, path: &Path, content: &[u8], mode: u32) -> Result<()> {
+        // Stage the content as the current user, then let `install` put it
+        // in place with root ownership.
+        let mut staged = tempfile::NamedTempFile::new().context("staging file content")?;
+        staged
+            .write_all(content)
+            .with_context(|| format!("staging content for {}", path.display()))?;
+        cmd::Command::new("install")
+            .args(["-D", "-m", &format!("{mode:o}")])
+            .arg(staged.path())
+            .arg(path)
+            .sudo()
+            .execute()
+            .with_context(|| format!("installing {}", path.display()))?;
+        Ok(())
+    }
 }
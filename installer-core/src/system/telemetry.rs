@@ -8,6 +8,7 @@
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
+use crate::scrubber;
 use crate::system::error::InstallationReport;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -51,7 +52,7 @@ pub fn report(&self, report: &InstallationReport) -> Result<()> {
 
         let res = client
             .post(&self.config.endpoint)
-            .send_json(serde_json::to_value(report)?);
+            .send_json(payload(report)?);
 
         match res {
             Ok(_) => {
@@ -66,3 +67,45 @@ pub fn report(&self, report: &InstallationReport) -> Result<()> {
         }
     }
 }
+
+/// The JSON body sent to the endpoint: the report with secrets scrubbed.
+fn payload(report: &InstallationReport) -> Result<serde_json::Value> {
+    Ok(scrubber::to_scrubbed_value(report)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dry_run::{DryRunEntry, PreflightAuditReport};
+    use crate::system::error::DriverInfo;
+    use crate::InstallOptions;
+    use std::path::PathBuf;
+
+    #[test]
+    fn payload_is_scrubbed() -> Result<()> {
+        scrubber::register_secret("telemetry-secret");
+        let report = InstallationReport {
+            completed_phases: Vec::new(),
+            staging_dir: PathBuf::from("/tmp/staging"),
+            errors: Vec::new(),
+            outputs: Vec::new(),
+            events: Vec::new(),
+            options: InstallOptions::default(),
+            driver: DriverInfo {
+                name: "debian".into(),
+                description: "Debian".into(),
+            },
+            dry_run_log: vec![DryRunEntry {
+                phase: "ai".into(),
+                action: "curl -H 'Authorization: Bearer telemetry-secret'".into(),
+                detail: None,
+            }],
+            audit_report: PreflightAuditReport::default(),
+            run_id: None,
+            snapshots: Vec::new(),
+        };
+        let body = payload(&report)?.to_string();
+        assert!(!body.contains("telemetry-secret"));
+        Ok(())
+    }
+}
@@ -1,83 +1,449 @@
 //! Websocket Observer — The Bard's Remote Scrying.
 //!
 //! This module provides a websocket-based observer that allows remote monitoring
-//! of the installation progress. It broadcasts `PhaseEvent`s to any connected
-//! clients, enabling a "live stream" of the TUI progress on a web-based dashboard.
+//! of the installation progress. It relays `PhaseEvent`s to connected clients,
+//! replaying the full history to late joiners, and serves a small HTML
+//! dashboard from the same server. With remote control enabled, clients can
+//! also answer `confirm` and `request_auth` prompts, which is how a headless
+//! Pi install gets driven from a laptop.
+//!
+//! Every request must carry the session token (or pairing code), either as a
+//! `?token=` query parameter or an `Authorization: Bearer` header. The server
+//! binds to localhost unless it is explicitly opened.
 
+use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
-use std::sync::Arc;
-use std::thread;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fs::File;
+use std::hash::BuildHasher;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
 use tokio::runtime::Runtime;
 use tokio::sync::broadcast;
 use tracing::{error, info};
-use warp::Filter;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use crate::model::phase::AuthType;
+use crate::scrubber;
 
 pub use crate::model::phase::{PhaseEvent, PhaseObserver};
 
+const DASHBOARD: &str = include_str!("scry_dashboard.html");
+
+/// How the scrying server is exposed.
+#[derive(Debug, Clone)]
+pub struct ScryConfig {
+    pub port: u16,
+    /// Bind every interface instead of localhost only.
+    pub open: bool,
+    /// Fixed token; a random pairing code is generated when `None`.
+    pub token: Option<String>,
+    /// Let remote clients answer confirmation and authorization prompts.
+    pub remote_control: bool,
+    /// How long a prompt waits for a remote answer before the local
+    /// prompter, if any, asks instead.
+    pub prompt_timeout: Duration,
+}
+
+impl ScryConfig {
+    /// Default wait for a remote answer.
+    pub const DEFAULT_PROMPT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+    pub fn new(port: u16) -> Self {
+        Self {
+            port,
+            open: false,
+            token: None,
+            remote_control: false,
+            prompt_timeout: Self::DEFAULT_PROMPT_TIMEOUT,
+        }
+    }
+
+    fn bind_addr(&self) -> IpAddr {
+        if self.open {
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+        } else {
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        }
+    }
+}
+
+/// A question the installer is waiting on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Prompt {
+    Confirm { message: String },
+    Auth { auth: AuthType },
+}
+
+/// Server → client frames.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Event {
+        event: PhaseEvent,
+    },
+    Prompt {
+        id: u64,
+        prompt: Prompt,
+    },
+    /// The prompt was answered (by any client) and can be dismissed.
+    PromptClosed {
+        id: u64,
+    },
+}
+
+/// Client → server frames.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Answer { id: u64, accept: bool },
+}
+
+#[derive(Default)]
+struct State {
+    history: Vec<PhaseEvent>,
+    pending: HashMap<u64, (Prompt, mpsc::Sender<bool>)>,
+}
+
+/// State shared between the observer and the server tasks. Broadcasts happen
+/// under the state lock so a client's replay and its live stream never
+/// overlap or leave gaps.
+struct Shared {
+    state: Mutex<State>,
+    tx: broadcast::Sender<ServerMessage>,
+    token: String,
+    next_id: AtomicU64,
+}
+
+impl Shared {
+    fn new(token: impl Into<String>) -> Arc<Self> {
+        let (tx, _) = broadcast::channel(256);
+        Arc::new(Self {
+            state: Mutex::new(State::default()),
+            tx,
+            token: token.into(),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn publish(&self, event: PhaseEvent) {
+        let mut state = self.lock();
+        // Progress ticks are stale by the time anyone replays them.
+        if !event.is_transient() {
+            state.history.push(event.clone());
+        }
+        let _ = self.tx.send(ServerMessage::Event { event });
+    }
+
+    /// Broadcast `prompt` and block until a client answers it. `None` means
+    /// nobody answered within `timeout`; the prompt is then withdrawn from
+    /// every client.
+    fn ask(&self, prompt: Prompt, timeout: Duration) -> Option<bool> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (reply_tx, reply_rx) = mpsc::channel();
+        {
+            let mut state = self.lock();
+            state.pending.insert(id, (prompt.clone(), reply_tx));
+            let _ = self.tx.send(ServerMessage::Prompt { id, prompt });
+        }
+        match reply_rx.recv_timeout(timeout) {
+            Ok(accept) => Some(accept),
+            Err(_) => {
+                let mut state = self.lock();
+                // An answer may have landed between the timeout and the lock.
+                if state.pending.remove(&id).is_none() {
+                    return reply_rx.try_recv().ok();
+                }
+                let _ = self.tx.send(ServerMessage::PromptClosed { id });
+                None
+            }
+        }
+    }
+
+    fn answer(&self, id: u64, accept: bool) {
+        let mut state = self.lock();
+        if let Some((_, reply)) = state.pending.remove(&id) {
+            let _ = reply.send(accept);
+            let _ = self.tx.send(ServerMessage::PromptClosed { id });
+        }
+    }
+
+    /// Everything a new client has missed, plus a live subscription.
+    fn join(&self) -> (Vec<ServerMessage>, broadcast::Receiver<ServerMessage>) {
+        let state = self.lock();
+        let mut backlog: Vec<ServerMessage> = state
+            .history
+            .iter()
+            .cloned()
+            .map(|event| ServerMessage::Event { event })
+            .collect();
+        let mut pending: Vec<_> = state.pending.iter().collect();
+        pending.sort_by_key(|(id, _)| **id);
+        backlog.extend(
+            pending
+                .into_iter()
+                .map(|(id, (prompt, _))| ServerMessage::Prompt {
+                    id: *id,
+                    prompt: prompt.clone(),
+                }),
+        );
+        (backlog, self.tx.subscribe())
+    }
+
+    /// Constant-time comparison so the token cannot be guessed byte by byte.
+    fn authorized(&self, candidate: &str) -> bool {
+        let expected = self.token.as_bytes();
+        let candidate = candidate.as_bytes();
+        expected.len() == candidate.len()
+            && expected
+                .iter()
+                .zip(candidate)
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                == 0
+    }
+}
+
 /// A broadcast-enabled observer that relays events to websocket clients.
 pub struct WebsocketObserver {
-    tx: broadcast::Sender<PhaseEvent>,
+    shared: Arc<Shared>,
+    remote_control: bool,
+    prompt_timeout: Duration,
+    addr: SocketAddr,
     _rt: Arc<Runtime>,
 }
 
 impl WebsocketObserver {
-    /// Start a websocket server on the specified port and return the observer.
-    pub fn new(port: u16) -> Self {
-        let (tx, _) = broadcast::channel(100);
-        let tx_cloned = tx.clone();
+    /// Start the scrying server and return the observer feeding it.
+    pub fn new(config: ScryConfig) -> Result<Self> {
+        let token = config.token.clone().unwrap_or_else(pairing_code);
+        scrubber::register_secret(token.clone());
+        let shared = Shared::new(token);
+        let rt = Arc::new(Runtime::new().context("creating tokio runtime for scrying")?);
 
-        let rt = Arc::new(Runtime::new().expect("failed to create tokio runtime"));
-        let rt_cloned = rt.clone();
+        let (addr, server) = {
+            let _guard = rt.enter();
+            warp::serve(routes(shared.clone()))
+                .try_bind_ephemeral((config.bind_addr(), config.port))
+                .with_context(|| format!("binding scrying server to port {}", config.port))?
+        };
+        rt.spawn(server);
+        info!("Websocket Scrying Server started on {}", addr);
 
-        thread::spawn(move || {
-            rt_cloned.block_on(async {
-                let events = warp::any().map(move || tx_cloned.clone());
+        Ok(Self {
+            shared,
+            remote_control: config.remote_control,
+            prompt_timeout: config.prompt_timeout,
+            addr,
+            _rt: rt,
+        })
+    }
 
-                let ws_route = warp::path("ws").and(warp::ws()).and(events).map(
-                    |ws: warp::ws::Ws, tx: broadcast::Sender<PhaseEvent>| {
-                        ws.on_upgrade(move |socket| handle_client(socket, tx))
-                    },
-                );
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
 
-                info!("Websocket Scrying Server started on port {}", port);
-                warp::serve(ws_route).run(([0, 0, 0, 0], port)).await;
-            });
-        });
+    /// The token or pairing code clients must present.
+    pub fn token(&self) -> &str {
+        &self.shared.token
+    }
 
-        Self { tx, _rt: rt }
+    /// Dashboard URL including the token.
+    pub fn dashboard_url(&self) -> String {
+        format!("http://{}/?token={}", self.addr, self.shared.token)
     }
 }
 
 impl PhaseObserver for WebsocketObserver {
     fn on_event(&mut self, event: PhaseEvent) {
-        // Broadcast the event to all connected clients
-        let _ = self.tx.send(event);
+        self.shared.publish(event);
+    }
+
+    fn confirm(&mut self, prompt: &str) -> bool {
+        self.try_confirm(prompt).unwrap_or(false)
+    }
+
+    fn request_auth(&mut self, auth_type: AuthType) -> anyhow::Result<bool> {
+        Ok(self.try_request_auth(auth_type)?.unwrap_or(false))
+    }
+
+    fn try_confirm(&mut self, prompt: &str) -> Option<bool> {
+        if !self.remote_control {
+            return None;
+        }
+        self.shared.ask(
+            Prompt::Confirm {
+                message: prompt.to_string(),
+            },
+            self.prompt_timeout,
+        )
+    }
+
+    fn try_request_auth(&mut self, auth_type: AuthType) -> anyhow::Result<Option<bool>> {
+        if !self.remote_control {
+            return Ok(None);
+        }
+        Ok(self
+            .shared
+            .ask(Prompt::Auth { auth: auth_type }, self.prompt_timeout))
     }
 }
 
-async fn handle_client(ws: warp::ws::WebSocket, tx: broadcast::Sender<PhaseEvent>) {
-    let (mut ws_tx, _ws_rx) = ws.split();
-    let mut rx = tx.subscribe();
+#[derive(Debug)]
+struct Unauthorized;
 
-    while let Ok(event) = rx.recv().await {
-        let msg = match serde_json::to_string(&event) {
-            Ok(json) => warp::ws::Message::text(json),
-            Err(e) => {
-                error!("failed to serialize phase event: {}", e);
-                continue;
-            }
-        };
+impl warp::reject::Reject for Unauthorized {}
+
+fn routes(shared: Arc<Shared>) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    let auth = warp::query::<HashMap<String, String>>()
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::any().map(move || shared.clone()))
+        .and_then(
+            |query: HashMap<String, String>, header: Option<String>, shared: Arc<Shared>| async move {
+                let presented = query.get("token").cloned().or_else(|| {
+                    header.and_then(|value| value.strip_prefix("Bearer ").map(str::to_string))
+                });
+                match presented {
+                    Some(token) if shared.authorized(&token) => Ok(shared),
+                    _ => Err(warp::reject::custom(Unauthorized)),
+                }
+            },
+        );
+
+    let ws_route = warp::path("ws")
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(warp::ws())
+        .map(|shared: Arc<Shared>, ws: warp::ws::Ws| {
+            ws.on_upgrade(move |socket| handle_client(socket, shared))
+        });
 
-        if let Err(e) = ws_tx.send(msg).await {
-            error!("websocket send error: {}", e);
-            break;
+    let dashboard = warp::path::end()
+        .and(warp::get())
+        .and(auth)
+        .map(|_| warp::reply::html(DASHBOARD));
+
+    ws_route.or(dashboard).recover(handle_rejection)
+}
+
+async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
+    let status = if err.find::<Unauthorized>().is_some() {
+        StatusCode::UNAUTHORIZED
+    } else if err.is_not_found() {
+        StatusCode::NOT_FOUND
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    Ok(warp::reply::with_status(
+        status.canonical_reason().unwrap_or("error"),
+        status,
+    ))
+}
+
+async fn handle_client(ws: warp::ws::WebSocket, shared: Arc<Shared>) {
+    let (mut ws_tx, mut ws_rx) = ws.split();
+    let (backlog, mut rx) = shared.join();
+
+    for msg in backlog {
+        if send(&mut ws_tx, &msg).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            outgoing = rx.recv() => match outgoing {
+                Ok(msg) => {
+                    if send(&mut ws_tx, &msg).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    error!("scrying client lagged; {} messages dropped", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = ws_rx.next() => match incoming {
+                Some(Ok(msg)) => {
+                    let Ok(text) = msg.to_str() else { continue };
+                    match serde_json::from_str::<ClientMessage>(text) {
+                        Ok(ClientMessage::Answer { id, accept }) => shared.answer(id, accept),
+                        Err(e) => error!("ignoring malformed scrying message: {}", e),
+                    }
+                }
+                Some(Err(e)) => {
+                    error!("websocket receive error: {}", e);
+                    break;
+                }
+                None => break,
+            },
+        }
+    }
+}
+
+async fn send(
+    ws_tx: &mut futures_util::stream::SplitSink<warp::ws::WebSocket, warp::ws::Message>,
+    msg: &ServerMessage,
+) -> Result<(), ()> {
+    let text = match encode(msg) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("failed to serialize scrying message: {}", e);
+            return Ok(());
         }
+    };
+    ws_tx
+        .send(warp::ws::Message::text(text))
+        .await
+        .map_err(|e| error!("websocket send error: {}", e))
+}
+
+/// Events can carry command output and paths; scrub them before they leave
+/// the machine.
+fn encode(msg: &ServerMessage) -> serde_json::Result<String> {
+    scrubber::to_scrubbed_string(msg)
+}
+
+/// Eight characters from an unambiguous alphabet, e.g. `K7QM-3XPA`.
+fn pairing_code() -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+    let mut bytes = [0u8; 8];
+    if File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .is_err()
+    {
+        // RandomState is seeded from the OS as well.
+        bytes = RandomState::new().hash_one(SystemTime::now()).to_le_bytes();
     }
+    let code: String = bytes
+        .iter()
+        .map(|b| ALPHABET[usize::from(*b) % ALPHABET.len()] as char)
+        .collect();
+    format!("{}-{}", &code[..4], &code[4..])
 }
 
 /// A composite observer that multiplexes events to multiple observers.
+/// Prompts go to the observers registered with [`add_prompter`] or
+/// [`add_scryer`], in turn, until one of them answers.
+///
+/// [`add_prompter`]: CompositeObserver::add_prompter
+/// [`add_scryer`]: CompositeObserver::add_scryer
 pub struct CompositeObserver {
     observers: Vec<Box<dyn PhaseObserver + Send>>,
+    prompters: Vec<usize>,
 }
 
 impl Default for CompositeObserver {
@@ -90,12 +456,28 @@ impl CompositeObserver {
     pub fn new() -> Self {
         Self {
             observers: Vec::new(),
+            prompters: Vec::new(),
         }
     }
 
     pub fn add<O: PhaseObserver + Send + 'static>(&mut self, observer: O) {
         self.observers.push(Box::new(observer));
     }
+
+    /// Add `observer` and let it answer `confirm` and `request_auth`.
+    pub fn add_prompter<O: PhaseObserver + Send + 'static>(&mut self, observer: O) {
+        self.prompters.push(self.observers.len());
+        self.add(observer);
+    }
+
+    /// Add a scrying observer. With remote control it is asked first, and
+    /// the local prompters take over when no client answers in time.
+    pub fn add_scryer(&mut self, observer: WebsocketObserver) {
+        if observer.remote_control {
+            self.prompters.insert(0, self.observers.len());
+        }
+        self.add(observer);
+    }
 }
 
 impl PhaseObserver for CompositeObserver {
@@ -104,4 +486,224 @@ fn on_event(&mut self, event: PhaseEvent) {
             observer.on_event(event.clone());
         }
     }
+
+    fn confirm(&mut self, prompt: &str) -> bool {
+        // Nobody can answer, so nothing that needs consent happens.
+        self.try_confirm(prompt).unwrap_or(false)
+    }
+
+    fn request_auth(&mut self, auth_type: AuthType) -> anyhow::Result<bool> {
+        Ok(self.try_request_auth(auth_type)?.unwrap_or(false))
+    }
+
+    fn try_confirm(&mut self, prompt: &str) -> Option<bool> {
+        self.prompters
+            .iter()
+            .find_map(|&index| self.observers[index].try_confirm(prompt))
+    }
+
+    fn try_request_auth(&mut self, auth_type: AuthType) -> anyhow::Result<Option<bool>> {
+        for &index in &self.prompters {
+            if let Some(answer) = self.observers[index].try_request_auth(auth_type)? {
+                return Ok(Some(answer));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "TEST-C0DE";
+
+    fn parse(msg: warp::ws::Message) -> ServerMessage {
+        serde_json::from_str(msg.to_str().expect("text frame")).expect("server message")
+    }
+
+    #[test]
+    fn encoded_events_are_scrubbed() -> serde_json::Result<()> {
+        scrubber::register_secret("ws-stream-secret");
+        let json = encode(&ServerMessage::Event {
+            event: PhaseEvent::Warning {
+                message: "retrying with ws-stream-secret".into(),
+            },
+        })?;
+        assert!(!json.contains("ws-stream-secret"));
+        assert!(json.contains("retrying with ********"));
+        Ok(())
+    }
+
+    #[test]
+    fn pairing_codes_use_the_unambiguous_alphabet() {
+        let code = pairing_code();
+        assert_eq!(code.len(), 9);
+        assert_eq!(&code[4..5], "-");
+        assert!(!code.contains(['0', 'O', '1', 'I']));
+    }
+
+    #[tokio::test]
+    async fn requests_without_the_token_are_rejected() {
+        let api = routes(Shared::new(TOKEN));
+
+        let res = warp::test::request().path("/").reply(&api).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = warp::test::request()
+            .path("/?token=WRONG-C0DE")
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert!(warp::test::ws()
+            .path("/ws")
+            .handshake(api.clone())
+            .await
+            .is_err());
+
+        let res = warp::test::request()
+            .path(&format!("/?token={TOKEN}"))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(String::from_utf8_lossy(res.body()).contains("MASH remote scrying"));
+        let res = warp::test::request()
+            .path("/")
+            .header("authorization", format!("Bearer {TOKEN}"))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn late_joiners_get_history_and_can_answer_prompts() -> anyhow::Result<()> {
+        let shared = Shared::new(TOKEN);
+        shared.publish(PhaseEvent::Total { total: 2 });
+        shared.publish(PhaseEvent::Warning {
+            message: "early".into(),
+        });
+        let asker = {
+            let shared = shared.clone();
+            tokio::task::spawn_blocking(move || {
+                shared.ask(
+                    Prompt::Confirm {
+                        message: "Proceed?".into(),
+                    },
+                    Duration::from_secs(60),
+                )
+            })
+        };
+        // Wait for the prompt to be registered before joining.
+        while shared.lock().pending.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        let mut client = warp::test::ws()
+            .path(&format!("/ws?token={TOKEN}"))
+            .handshake(routes(shared.clone()))
+            .await?;
+        assert!(matches!(
+            parse(client.recv().await?),
+            ServerMessage::Event {
+                event: PhaseEvent::Total { total: 2 }
+            }
+        ));
+        assert!(matches!(
+            parse(client.recv().await?),
+            ServerMessage::Event {
+                event: PhaseEvent::Warning { .. }
+            }
+        ));
+        let ServerMessage::Prompt { id, prompt } = parse(client.recv().await?) else {
+            panic!("expected the pending prompt");
+        };
+        assert_eq!(
+            prompt,
+            Prompt::Confirm {
+                message: "Proceed?".into()
+            }
+        );
+
+        client
+            .send_text(serde_json::to_string(&ClientMessage::Answer {
+                id,
+                accept: false,
+            })?)
+            .await;
+        assert_eq!(asker.await?, Some(false));
+        assert!(matches!(
+            parse(client.recv().await?),
+            ServerMessage::PromptClosed { id: closed } if closed == id
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn unanswered_prompts_are_withdrawn_after_the_timeout() {
+        let shared = Shared::new(TOKEN);
+        let mut rx = shared.tx.subscribe();
+
+        let answer = shared.ask(
+            Prompt::Auth {
+                auth: AuthType::GitHubCli,
+            },
+            Duration::from_millis(20),
+        );
+
+        assert_eq!(answer, None);
+        assert!(shared.lock().pending.is_empty());
+        let Ok(ServerMessage::Prompt { id, .. }) = rx.try_recv() else {
+            panic!("expected the prompt");
+        };
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ServerMessage::PromptClosed { id: closed }) if closed == id
+        ));
+        // A late answer is ignored.
+        shared.answer(id, true);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn local_prompter_answers_when_the_remote_does_not() -> anyhow::Result<()> {
+        struct Answers(bool);
+        impl PhaseObserver for Answers {
+            fn confirm(&mut self, _prompt: &str) -> bool {
+                self.0
+            }
+        }
+        let scryer = || {
+            WebsocketObserver::new(ScryConfig {
+                token: Some(TOKEN.into()),
+                remote_control: true,
+                prompt_timeout: Duration::from_millis(20),
+                ..ScryConfig::new(0)
+            })
+        };
+
+        let mut composite = CompositeObserver::new();
+        composite.add_prompter(Answers(true));
+        composite.add_scryer(scryer()?);
+        assert!(composite.confirm("go?"));
+
+        let mut headless = CompositeObserver::new();
+        headless.add_scryer(scryer()?);
+        assert!(!headless.confirm("go?"));
+        Ok(())
+    }
+
+    #[test]
+    fn composite_routes_prompts_to_the_prompter() {
+        struct Answers(bool);
+        impl PhaseObserver for Answers {
+            fn confirm(&mut self, _prompt: &str) -> bool {
+                self.0
+            }
+        }
+
+        let mut composite = CompositeObserver::new();
+        composite.add(Answers(true));
+        assert!(!composite.confirm("go?"));
+        composite.add_prompter(Answers(true));
+        assert!(composite.confirm("go?"));
+    }
 }
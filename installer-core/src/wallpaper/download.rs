@@ -1,15 +1,17 @@
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::Path;
 
 use tracing::warn;
 
 use crate::model::phase::{PhaseEvent, PhaseObserver};
+use crate::scrubber;
 use crate::system::system_ops::SystemOps;
 use crate::wallpaper::api::{
     pexels::PexelsApi, pixabay::PixabayApi, wallhaven::WallhavenApi, WallpaperApi,
 };
 use crate::wallpaper::config::WallpaperConfig;
 use crate::wallpaper::error::WallpaperError;
+use crate::wallpaper::harvest::{report_progress, PROGRESS_INTERVAL};
 use crate::wallpaper::types::{DownloadStats, WallpaperImage};
 
 /// Downloads wallpapers from multiple sources
@@ -21,6 +23,15 @@ pub fn download_wallpapers(
     // Validate configuration
     config.validate()?;
 
+    // Keys travel in request URLs, which show up in download errors.
+    let keys = &config.api_keys;
+    for key in [&keys.wallhaven, &keys.pexels, &keys.pixabay]
+        .into_iter()
+        .flatten()
+    {
+        scrubber::register_secret(key.clone());
+    }
+
     let mut stats = DownloadStats::new();
 
     // Whimsical message
@@ -126,11 +137,38 @@ fn download_single_image(
         ));
     }
 
+    let expected = response
+        .header("Content-Length")
+        .and_then(|len| len.parse::<u64>().ok());
+    let label = format!("{category}/{}", image.id);
+    let tmp_path = dest_path.with_extension("jpg.part");
+    let mut file = std::fs::File::create(&tmp_path)?;
     let mut reader = response.into_reader();
-    let mut bytes = Vec::new();
-    reader.read_to_end(&mut bytes)?;
+    let mut buffer = [0; 8192];
+    let mut total_bytes = 0;
+    let mut reported = 0;
+    loop {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) => {
+                drop(file);
+                std::fs::remove_file(&tmp_path).ok();
+                return Err(e.into());
+            }
+        };
+        file.write_all(&buffer[..n])?;
+        total_bytes += n as u64;
+        if total_bytes - reported >= PROGRESS_INTERVAL {
+            reported = total_bytes;
+            report_progress(&label, total_bytes, expected);
+        }
+    }
+    report_progress(&label, total_bytes, expected);
 
-    std::fs::write(dest_path, bytes)?;
+    file.flush()?;
+    drop(file);
+    std::fs::rename(&tmp_path, dest_path)?;
 
     Ok(())
 }
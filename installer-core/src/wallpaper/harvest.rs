@@ -9,6 +9,7 @@
 use tracing::debug;
 
 use crate::model::phase::{PhaseEvent, PhaseObserver};
+use crate::system::progress;
 use crate::wallpaper::config::HarvestConfig;
 
 // --- State DB ---
@@ -171,9 +172,13 @@ pub fn download(&self, url: String) -> Result<bool> {
             return Ok(false);
         }
 
+        let expected = response
+            .header("Content-Length")
+            .and_then(|len| len.parse::<u64>().ok());
         let mut file = std::fs::File::create(&tmp_path)?;
         let mut hasher = Sha256::new();
         let mut total_bytes = 0;
+        let mut reported = 0;
         let mut header_bytes = Vec::new();
 
         let mut reader = response.into_reader();
@@ -201,7 +206,13 @@ pub fn download(&self, url: String) -> Result<bool> {
             }
 
             hasher.update(chunk);
+
+            if total_bytes - reported >= PROGRESS_INTERVAL {
+                reported = total_bytes;
+                report_progress(file_name, total_bytes, expected);
+            }
         }
+        report_progress(file_name, total_bytes, expected);
 
         file.flush()?;
         drop(file);
@@ -228,6 +239,21 @@ pub fn download(&self, url: String) -> Result<bool> {
     }
 }
 
+/// Wallhaven search endpoint the harvester queries for images.
+pub const SEARCH_URL: &str = "https://wallhaven.cc/api/v1/search";
+
+/// Bytes between [`PhaseEvent::DownloadProgress`] reports for one file.
+pub(super) const PROGRESS_INTERVAL: u64 = 256 * 1024;
+
+pub(super) fn report_progress(file_name: &str, bytes: u64, total: Option<u64>) {
+    progress::emit(|phase| PhaseEvent::DownloadProgress {
+        phase,
+        label: file_name.to_string(),
+        bytes,
+        total,
+    });
+}
+
 fn is_image_header(header: &[u8]) -> bool {
     // JPEG
     if header.starts_with(&[0xFF, 0xD8, 0xFF]) {
@@ -379,10 +405,7 @@ pub fn get_urls(&self) -> Result<Vec<String>> {
                 ),
             ];
 
-            let url = format!(
-                "https://wallhaven.cc/api/v1/search?{}",
-                serde_urlencoded::to_string(params)?
-            );
+            let url = format!("{SEARCH_URL}?{}", serde_urlencoded::to_string(params)?);
 
             let response = match ureq::get(&url).call() {
                 Ok(res) => res,
@@ -5,6 +5,7 @@
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+use crate::rollback::RollbackOp;
 use crate::system::cmd;
 use crate::{options::ProfileLevel, package_manager, PhaseContext, PhaseResult, PkgBackend};
 use which::which;
@@ -19,10 +20,12 @@ fn home_dir() -> PathBuf {
 const P10K_THEME_FILE: &str = "/usr/share/powerlevel10k/powerlevel10k.zsh-theme";
 const P10K_TAG: &str = "v1.13.0";
 
-const STARSHIP_CONFIG: &str = include_str!("../../resources/shell/starship.toml");
+pub(crate) const STARSHIP_CONFIG: &str = include_str!("../../resources/shell/starship.toml");
 const KITTY_CONFIG: &str = include_str!("../../resources/shell/kitty.conf");
 const EZA_ALIASES_SCRIPT: &str = include_str!("../../resources/shell/eza_aliases.sh");
-const STARSHIP_MARKER: &str = "starship init zsh";
+/// Where the Starship configuration lives, relative to the home directory.
+pub(crate) const STARSHIP_CONFIG_PATH: &str = ".config/starship/starship.toml";
+pub(crate) const STARSHIP_MARKER: &str = "starship init zsh";
 const STARSHIP_BLOCK: &str = r#"
 # Starship prompt (added by mash-installer)
 if command -v starship >/dev/null; then
@@ -73,13 +76,12 @@ fn install_omz(ctx: &mut PhaseContext) -> Result<()> {
         return Ok(());
     }
 
-    let omz_dir_clone = omz_dir.clone();
-    ctx.register_rollback_action("remove oh-my-zsh directory", move || {
-        if omz_dir_clone.exists() {
-            std::fs::remove_dir_all(&omz_dir_clone)?;
-        }
-        Ok(())
-    });
+    ctx.register_rollback_action(
+        "remove oh-my-zsh directory",
+        RollbackOp::RemoveFile {
+            path: omz_dir.clone(),
+        },
+    );
 
     if let Err(err) = cmd::Command::new("sh")
         .arg("-c")
@@ -223,11 +225,12 @@ fn add_p10k_source_to_zshrc(ctx: &mut PhaseContext) -> Result<()> {
     }
 
     if zshrc.exists() {
-        backup_file(&zshrc)?;
+        backup_file(ctx, &zshrc)?;
         let content = std::fs::read_to_string(&zshrc).unwrap_or_default();
         std::fs::write(&zshrc, format!("{content}{block}"))?;
     } else {
         std::fs::write(&zshrc, block)?;
+        register_created_file(ctx, &zshrc);
     }
 
     tracing::info!("Added Powerlevel10k source block to .zshrc");
@@ -257,7 +260,7 @@ fn install_starship(ctx: &mut PhaseContext) -> Result<()> {
 }
 
 fn deploy_starship_config(ctx: &mut PhaseContext) -> Result<()> {
-    let path = home_dir().join(".config/starship/starship.toml");
+    let path = home_dir().join(STARSHIP_CONFIG_PATH);
     write_config_file(ctx, &path, STARSHIP_CONFIG, "Starship configuration")
 }
 
@@ -335,12 +338,13 @@ fn ensure_block_in_rc(
     }
 
     if path.exists() {
-        backup_file(path)?;
+        backup_file(ctx, path)?;
         let mut file = OpenOptions::new().append(true).open(path)?;
         writeln!(file)?;
         writeln!(file, "{block}")?;
     } else {
         std::fs::write(path, block)?;
+        register_created_file(ctx, path);
     }
 
     ctx.record_action(format!("Appended shell fragment to {}", path.display()));
@@ -349,8 +353,9 @@ fn ensure_block_in_rc(
 
 // ── Helpers ─────────────────────────────────────────────────────
 
-/// Create a timestamped .bak copy of a file before modifying it.
-fn backup_file(path: &Path) -> Result<()> {
+/// Create a timestamped .bak copy of a file before modifying it and
+/// register restoring it on rollback.
+fn backup_file(ctx: &mut PhaseContext, path: &Path) -> Result<()> {
     if !path.exists() {
         return Ok(());
     }
@@ -362,5 +367,21 @@ fn backup_file(path: &Path) -> Result<()> {
     std::fs::copy(path, &backup)
         .with_context(|| format!("backing up {} to {}", path.display(), backup.display()))?;
     tracing::info!("Backed up {} → {}", path.display(), backup.display());
+    ctx.register_rollback_action(
+        format!("restore {}", path.display()),
+        RollbackOp::RestoreFile {
+            path: path.to_path_buf(),
+            backup,
+        },
+    );
     Ok(())
 }
+
+fn register_created_file(ctx: &mut PhaseContext, path: &Path) {
+    ctx.register_rollback_action(
+        format!("remove {}", path.display()),
+        RollbackOp::RemoveFile {
+            path: path.to_path_buf(),
+        },
+    );
+}
@@ -65,6 +65,8 @@ fn build_mock_context(arch: &'static str) -> Result<InstallContext> {
         chezmoi: Default::default(),
         desktop_environment: None,
         display_protocol: installer_core::desktop::DisplayProtocol::Auto,
+        verify: false,
+        allow_unverified_scripts: false,
     };
     let localization = installer_core::localization::Localization::load_default()?;
 
@@ -80,6 +82,7 @@ fn build_mock_context(arch: &'static str) -> Result<InstallContext> {
             driver: Box::leak(Box::new(MockDriver { name: "mock", arch })),
             pkg_backend: PkgBackend::Apt,
             system: &installer_core::REAL_SYSTEM,
+            commands: &installer_core::REAL_RUNNER,
         },
         ui: UIContext,
         interaction: installer_core::interaction::InteractionService::new(
@@ -112,6 +115,9 @@ fn test_x86_64_detection() -> Result<()> {
 
 struct ArchSpecificPhase;
 impl Phase for ArchSpecificPhase {
+    fn id(&self) -> &str {
+        "arch-check"
+    }
     fn name(&self) -> &str {
         "arch-check"
     }
@@ -0,0 +1,262 @@
+// Golden command sequences
+// Runs the default phase registry against each distro driver with a
+// recording command runner and compares the commands every phase would run
+// with the snapshots in tests/golden/commands. Set UPDATE_GOLDEN=1 to
+// rewrite the snapshots after an intentional change.
+//
+// The software tier and wallpaper phases are left out: wallpapers talk HTTP
+// directly rather than through the command runner.
+
+use anyhow::{Context, Result};
+use installer_core::{
+    dry_run::DryRunLog, ConfigService, DistroDriver, EnvironmentTag, InstallContext,
+    PhaseErrorPolicy, PhaseEvent, PhaseObserver, PhaseRegistry, PhaseRunner, PkgBackend,
+    PlatformInfo, ProfileLevel, RecordingRunner, ScriptedOutput, SoftwareTierPlan, ThemePlan,
+    UIContext, UserOptionsContext,
+};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use installer_drivers::arch::driver as arch_driver;
+use installer_drivers::debian::driver as debian_driver;
+use installer_drivers::fedora::driver as fedora_driver;
+
+/// Collects the commands each phase started, grouped under phase headers.
+#[derive(Default)]
+struct Transcript {
+    lines: Vec<String>,
+}
+
+impl PhaseObserver for Transcript {
+    fn on_event(&mut self, event: PhaseEvent) {
+        match event {
+            PhaseEvent::Started { phase, .. } => self.lines.push(format!("== {phase}")),
+            PhaseEvent::CommandStarted { command, .. } => self.lines.push(command),
+            PhaseEvent::Failed { phase, .. } => self.lines.push(format!("!! {phase} failed")),
+            _ => {}
+        }
+    }
+}
+
+struct Sandbox {
+    home: PathBuf,
+    staging: PathBuf,
+}
+
+impl Sandbox {
+    /// Point HOME at a fresh directory and PATH at one holding only stub
+    /// [`HOST_TOOLS`], so `which` probes and config lookups are the same on
+    /// every host.
+    fn enter(root: &Path, name: &str) -> Result<Self> {
+        let home = root.join(name).join("home");
+        let staging = root.join(name).join("staging");
+        let bin = root.join(name).join("bin");
+        for dir in [&home, &staging, &bin] {
+            fs::create_dir_all(dir)?;
+        }
+        for tool in HOST_TOOLS {
+            let stub = bin.join(tool);
+            fs::write(&stub, "#!/bin/sh\nexit 1\n")?;
+            fs::set_permissions(&stub, fs::Permissions::from_mode(0o755))?;
+        }
+        std::env::set_var("HOME", &home);
+        std::env::set_var("PATH", &bin);
+        std::env::set_var("USER", "mash");
+        for var in [
+            "CARGO_HOME",
+            "RUSTUP_HOME",
+            "XDG_CONFIG_HOME",
+            "XDG_DATA_HOME",
+        ] {
+            std::env::remove_var(var);
+        }
+        Ok(Self { home, staging })
+    }
+
+    fn normalize(&self, line: &str) -> String {
+        let line = line
+            .replace(&self.staging.display().to_string(), "$STAGING")
+            .replace(&self.home.display().to_string(), "$HOME");
+        normalize_tempdirs(&line)
+    }
+}
+
+/// Tools the golden host has on PATH. They are never executed.
+const HOST_TOOLS: &[&str] = &["curl"];
+
+/// Replace `tempfile` directory names (`<tmp>/.tmpXXXXXX`) with `$TMP`.
+fn normalize_tempdirs(line: &str) -> String {
+    let marker = format!("{}/.tmp", std::env::temp_dir().display());
+    let mut out = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find(&marker) {
+        out.push_str(&rest[..start]);
+        out.push_str("$TMP");
+        let name = &rest[start + marker.len()..];
+        let end = name
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(name.len());
+        rest = &name[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Every package reports as missing so the install commands show up.
+fn recording_runner() -> &'static RecordingRunner {
+    let runner = RecordingRunner::new()
+        .on("dpkg -s", ScriptedOutput::failure(1, "not installed"))
+        .on("pacman -Q", ScriptedOutput::failure(1, "not found"))
+        .on("rpm -q", ScriptedOutput::failure(1, "not installed"));
+    Box::leak(Box::new(runner))
+}
+
+fn build_context(
+    driver: &'static dyn DistroDriver,
+    profile: ProfileLevel,
+    staging: &Path,
+    runner: &'static RecordingRunner,
+) -> Result<InstallContext> {
+    let platform = PlatformInfo {
+        arch: "x86_64".into(),
+        distro: driver.name().into(),
+        distro_version: "0".into(),
+        distro_codename: "test".into(),
+        distro_family: family(driver).into(),
+        pi_model: None,
+        cpu_model: "test".into(),
+        cpu_cores: 4,
+        ram_total_gb: 8.0,
+    };
+    let options = UserOptionsContext {
+        profile,
+        staging_dir: staging.to_path_buf(),
+        dry_run: false,
+        interactive: false,
+        argon: Default::default(),
+        enable_p10k: false,
+        docker: Default::default(),
+        software_plan: SoftwareTierPlan::new(false, BTreeMap::new(), ThemePlan::None, None, None),
+        system_profile: None,
+        environment: EnvironmentTag::Home,
+        chezmoi: Default::default(),
+        desktop_environment: None,
+        display_protocol: installer_core::desktop::DisplayProtocol::Auto,
+        verify: false,
+        allow_unverified_scripts: false,
+    };
+
+    Ok(InstallContext {
+        options,
+        platform: installer_core::PlatformContext {
+            config_service: ConfigService::load()?,
+            platform,
+            driver_name: driver.name(),
+            driver,
+            pkg_backend: driver.pkg_backend(),
+            system: &installer_core::REAL_SYSTEM,
+            commands: runner,
+        },
+        ui: UIContext,
+        interaction: installer_core::interaction::InteractionService::new(
+            false,
+            Default::default(),
+        ),
+        localization: installer_core::localization::Localization::load_default()?,
+        rollback: installer_core::RollbackManager::new(),
+        dry_run_log: DryRunLog::new(),
+        cache: installer_core::ArtifactCache::new(&staging.join("cache")),
+    })
+}
+
+fn transcript(
+    root: &Path,
+    driver: &'static dyn DistroDriver,
+    profile: ProfileLevel,
+) -> Result<String> {
+    let name = golden_name(driver, profile);
+    let sandbox = Sandbox::enter(root, &name)?;
+    let runner = recording_runner();
+    let ctx = build_context(driver, profile, &sandbox.staging, runner)?;
+
+    let phases = PhaseRegistry::default().build_phases(&ctx.options, &ctx.localization);
+    let phase_runner = PhaseRunner::with_policy(phases, PhaseErrorPolicy::ContinueOnError);
+    let mut observer = Transcript::default();
+    // Failures are part of the snapshot; the run result itself is not.
+    let _ = phase_runner.run(&ctx, &mut observer, None);
+
+    let commands: Vec<_> = observer
+        .lines
+        .iter()
+        .filter(|line| !line.starts_with("== ") && !line.starts_with("!! "))
+        .cloned()
+        .collect();
+    assert_eq!(
+        commands.len(),
+        runner.calls().len(),
+        "{name}: every command must reach the recording runner"
+    );
+
+    let mut out = String::new();
+    for line in &observer.lines {
+        let indent = if line.starts_with("== ") { "" } else { "  " };
+        writeln!(out, "{indent}{}", sandbox.normalize(line))?;
+    }
+    Ok(out)
+}
+
+fn family(driver: &dyn DistroDriver) -> &'static str {
+    match driver.pkg_backend() {
+        PkgBackend::Apt => "debian",
+        PkgBackend::Pacman => "arch",
+        PkgBackend::Dnf => "fedora",
+    }
+}
+
+fn golden_name(driver: &dyn DistroDriver, profile: ProfileLevel) -> String {
+    let profile = match profile {
+        ProfileLevel::Minimal => "minimal",
+        ProfileLevel::Dev => "dev",
+        ProfileLevel::Full => "full",
+    };
+    format!("{}-{profile}", family(driver))
+}
+
+#[test]
+fn command_sequences_match_golden_files() -> Result<()> {
+    let golden_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/commands");
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let root = tempfile::tempdir()?;
+
+    let drivers = [debian_driver(), arch_driver(), fedora_driver()];
+    let mut mismatches = Vec::new();
+    for driver in drivers {
+        for profile in [ProfileLevel::Minimal, ProfileLevel::Dev] {
+            let actual = transcript(root.path(), driver, profile)?;
+            let file = golden_dir.join(format!("{}.txt", golden_name(driver, profile)));
+            if update {
+                fs::create_dir_all(&golden_dir)?;
+                fs::write(&file, &actual)?;
+                continue;
+            }
+            let expected = fs::read_to_string(&file)
+                .with_context(|| format!("missing golden file {}", file.display()))?;
+            if expected != actual {
+                mismatches.push(format!(
+                    "{}:\n--- expected\n{expected}\n+++ actual\n{actual}",
+                    file.display()
+                ));
+            }
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "command sequences changed (rerun with UPDATE_GOLDEN=1 if intended):\n{}",
+        mismatches.join("\n")
+    );
+    Ok(())
+}
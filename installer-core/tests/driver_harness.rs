@@ -21,6 +21,10 @@ struct PackageTranslationPhase {
 }
 
 impl Phase for PackageTranslationPhase {
+    fn id(&self) -> &str {
+        "package-translation"
+    }
+
     fn name(&self) -> &str {
         "package-translation"
     }
@@ -61,6 +65,10 @@ struct ServiceNamePhase {
 }
 
 impl Phase for ServiceNamePhase {
+    fn id(&self) -> &str {
+        "service-name"
+    }
+
     fn name(&self) -> &str {
         "service-name"
     }
@@ -98,6 +106,10 @@ struct DryRunLoggingPhase {
 }
 
 impl Phase for DryRunLoggingPhase {
+    fn id(&self) -> &str {
+        "dry-run-logging"
+    }
+
     fn name(&self) -> &str {
         "dry-run-logging"
     }
@@ -166,6 +178,8 @@ fn build_context_for_driver(driver: &'static dyn DistroDriver) -> Result<Install
         chezmoi: Default::default(),
         desktop_environment: None,
         display_protocol: installer_core::desktop::DisplayProtocol::Auto,
+        verify: false,
+        allow_unverified_scripts: false,
     };
     let localization = installer_core::localization::Localization::load_default()?;
 
@@ -181,6 +195,7 @@ fn build_context_for_driver(driver: &'static dyn DistroDriver) -> Result<Install
             driver,
             pkg_backend: driver.pkg_backend(),
             system: &installer_core::REAL_SYSTEM,
+            commands: &installer_core::REAL_RUNNER,
         },
         ui: UIContext,
         interaction: installer_core::interaction::InteractionService::new(
@@ -25,6 +25,8 @@ fn build_user_options() -> UserOptionsContext {
         chezmoi: Default::default(),
         desktop_environment: None,
         display_protocol: installer_core::desktop::DisplayProtocol::Auto,
+        verify: false,
+        allow_unverified_scripts: false,
     }
 }
 
@@ -72,6 +74,8 @@ fn installation_report_tracks_errors() {
         },
         dry_run_log: Vec::new(),
         audit_report: installer_core::dry_run::PreflightAuditReport::default(),
+        run_id: None,
+        snapshots: Vec::new(),
     };
 
     assert!(report.has_errors());
@@ -1,15 +1,18 @@
 use anyhow::{anyhow, Result};
 use installer_core::dry_run::DryRunLog;
 use installer_core::localization::Localization;
-use installer_core::RollbackManager;
 use installer_core::{
     ConfigService, DistroDriver, EnvironmentTag, ErrorSeverity, InstallContext, Phase,
-    PhaseContext, PhaseErrorPolicy, PhaseEvent, PhaseObserver, PhaseResult, PhaseRunner,
-    PkgBackend, PlatformContext, PlatformInfo, ProfileLevel, SoftwareTierPlan, UIContext,
-    UserOptionsContext,
+    PhaseContext, PhaseErrorPolicy, PhaseEvent, PhaseObserver, PhaseRegistry, PhaseResult,
+    PhaseRunner, PhaseSchedule, PkgBackend, PlatformContext, PlatformInfo, ProfileLevel,
+    SoftwareTierPlan, UIContext, UserOptionsContext,
 };
+use installer_core::{RollbackManager, RollbackOp};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
 struct DummyDriver;
 
@@ -40,6 +43,10 @@ struct TestPhase {
 }
 
 impl Phase for TestPhase {
+    fn id(&self) -> &str {
+        self.name
+    }
+
     fn name(&self) -> &str {
         self.name
     }
@@ -79,6 +86,91 @@ fn new(
     }
 }
 
+/// Records when it starts and ends and how many phases overlap it.
+struct SlowPhase {
+    name: &'static str,
+    deps: &'static [&'static str],
+    fail: bool,
+    log: Arc<Mutex<Vec<String>>>,
+    active: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl Phase for SlowPhase {
+    fn id(&self) -> &str {
+        self.name
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn description(&self) -> &str {
+        self.name
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        self.deps
+    }
+
+    fn execute(&self, _ctx: &mut PhaseContext) -> Result<PhaseResult> {
+        let now = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("start:{}", self.name));
+        thread::sleep(Duration::from_millis(50));
+        self.log.lock().unwrap().push(format!("end:{}", self.name));
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        if self.fail {
+            Err(anyhow!("boom"))
+        } else {
+            Ok(PhaseResult::Success)
+        }
+    }
+}
+
+#[derive(Default)]
+struct SlowPhaseSet {
+    log: Arc<Mutex<Vec<String>>>,
+    active: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl SlowPhaseSet {
+    fn phase(&self, name: &'static str, deps: &'static [&'static str]) -> Box<dyn Phase> {
+        Box::new(SlowPhase {
+            name,
+            deps,
+            fail: false,
+            log: self.log.clone(),
+            active: self.active.clone(),
+            peak: self.peak.clone(),
+        })
+    }
+
+    fn failing(&self, name: &'static str) -> Box<dyn Phase> {
+        Box::new(SlowPhase {
+            name,
+            deps: &[],
+            fail: true,
+            log: self.log.clone(),
+            active: self.active.clone(),
+            peak: self.peak.clone(),
+        })
+    }
+
+    fn position(&self, entry: &str) -> usize {
+        self.log
+            .lock()
+            .unwrap()
+            .iter()
+            .position(|e| e == entry)
+            .unwrap_or_else(|| panic!("{entry} not logged"))
+    }
+}
+
 struct RecordingObserver {
     events: Vec<PhaseEvent>,
     total: Option<usize>,
@@ -123,6 +215,7 @@ fn build_install_context() -> Result<InstallContext> {
         driver,
         pkg_backend: PkgBackend::Apt,
         system: &installer_core::REAL_SYSTEM,
+        commands: &installer_core::REAL_RUNNER,
     };
     let options = UserOptionsContext {
         profile: ProfileLevel::Minimal,
@@ -138,6 +231,8 @@ fn build_install_context() -> Result<InstallContext> {
         chezmoi: Default::default(),
         desktop_environment: None,
         display_protocol: installer_core::desktop::DisplayProtocol::Auto,
+        verify: false,
+        allow_unverified_scripts: false,
     };
     let localization = Localization::load_default()?;
 
@@ -241,12 +336,15 @@ fn phase_runner_aggregates_errors_and_events() -> Result<()> {
 #[test]
 fn phase_runner_triggers_rollback_on_failure() -> Result<()> {
     let ctx = build_install_context()?;
-    let executed = Arc::new(Mutex::new(Vec::new()));
-    let marker = executed.clone();
-    ctx.rollback.register_action("cleanup", move || {
-        marker.lock().unwrap().push("cleanup".to_string());
-        Ok(())
-    });
+    let scratch = tempfile::tempdir()?;
+    let created = scratch.path().join("created-by-phase");
+    std::fs::write(&created, "temporary")?;
+    ctx.rollback.register_action(
+        "cleanup",
+        RollbackOp::RemoveFile {
+            path: created.clone(),
+        },
+    );
 
     let phases: Vec<Box<dyn Phase>> = vec![Box::new(TestPhase::new(
         "phase-error",
@@ -259,7 +357,114 @@ fn phase_runner_triggers_rollback_on_failure() -> Result<()> {
     let mut observer = RecordingObserver::new();
     assert!(runner.run(&ctx, &mut observer, None).is_err());
 
-    let history = executed.lock().unwrap();
-    assert_eq!(history.as_slice(), ["cleanup"]);
+    assert!(!created.exists());
+    Ok(())
+}
+
+#[test]
+fn parallel_schedule_overlaps_independent_phases() -> Result<()> {
+    let ctx = build_install_context()?;
+    let set = SlowPhaseSet::default();
+    let phases = vec![
+        set.phase("a", &[]),
+        set.phase("b", &[]),
+        set.phase("c", &[]),
+    ];
+    let runner =
+        PhaseRunner::from_phases(phases).with_schedule(PhaseSchedule::Parallel { workers: 2 });
+    let mut observer = RecordingObserver::new();
+
+    let result = runner.run(&ctx, &mut observer, None)?;
+
+    assert_eq!(set.peak.load(Ordering::SeqCst), 2);
+    assert_eq!(result.completed_phases.len(), 3);
+    for name in ["a", "b", "c"] {
+        let started = observer
+            .events
+            .iter()
+            .position(|e| matches!(e, PhaseEvent::Started { phase, .. } if phase == name));
+        let completed = observer
+            .events
+            .iter()
+            .position(|e| matches!(e, PhaseEvent::Completed { phase, .. } if phase == name));
+        assert!(started.is_some() && started < completed);
+    }
+    Ok(())
+}
+
+#[test]
+fn parallel_schedule_waits_for_dependencies() -> Result<()> {
+    let ctx = build_install_context()?;
+    let set = SlowPhaseSet::default();
+    let phases = vec![
+        set.phase("a", &[]),
+        set.phase("b", &["a"]),
+        set.phase("c", &[]),
+    ];
+    let runner =
+        PhaseRunner::from_phases(phases).with_schedule(PhaseSchedule::Parallel { workers: 3 });
+    let mut observer = RecordingObserver::new();
+
+    runner.run(&ctx, &mut observer, None)?;
+
+    assert!(set.position("end:a") < set.position("start:b"));
+    assert!(set.position("start:c") < set.position("end:a"));
+    Ok(())
+}
+
+#[test]
+fn parallel_schedule_runs_verify_after_the_phases_it_checks() -> Result<()> {
+    let ctx = build_install_context()?;
+    let set = SlowPhaseSet::default();
+    let registered = PhaseRegistry::default().phases(&Localization::load_default()?);
+    let phases = registered
+        .iter()
+        .map(|phase| set.phase(phase.id, phase.deps))
+        .collect();
+    let runner =
+        PhaseRunner::from_phases(phases).with_schedule(PhaseSchedule::Parallel { workers: 4 });
+    let mut observer = RecordingObserver::new();
+
+    runner.run(&ctx, &mut observer, None)?;
+
+    let verify = registered
+        .iter()
+        .find(|phase| phase.id == "verify")
+        .expect("verify is registered");
+    for dep in [
+        "software_tiers",
+        "desktop_environment",
+        "argon_one",
+        "chezmoi",
+        "fonts",
+    ] {
+        assert!(verify.deps.contains(&dep));
+    }
+    for dep in verify.deps {
+        assert!(set.position(&format!("end:{dep}")) < set.position("start:verify"));
+    }
+    Ok(())
+}
+
+#[test]
+fn parallel_schedule_stops_scheduling_after_fatal_failure() -> Result<()> {
+    let ctx = build_install_context()?;
+    let set = SlowPhaseSet::default();
+    let phases = vec![
+        set.failing("a"),
+        set.phase("b", &["a"]),
+        set.phase("c", &["a"]),
+    ];
+    let runner =
+        PhaseRunner::from_phases(phases).with_schedule(PhaseSchedule::Parallel { workers: 2 });
+    let mut observer = RecordingObserver::new();
+
+    let err = runner
+        .run(&ctx, &mut observer, None)
+        .expect_err("fatal failure should abort the run");
+
+    assert_eq!(err.source.phase, "a");
+    assert!(err.result.completed_phases.is_empty());
+    assert!(!set.log.lock().unwrap().iter().any(|e| e == "start:b"));
     Ok(())
 }
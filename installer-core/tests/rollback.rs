@@ -1,69 +1,66 @@
-use anyhow::{anyhow, Result};
-use installer_core::RollbackManager;
-use std::sync::{Arc, Mutex};
+use anyhow::Result;
+use installer_core::{RollbackManager, RollbackOp};
+use std::fs;
+use tempfile::tempdir;
 
 #[test]
 fn rollback_manager_executes_actions_in_reverse_order() -> Result<()> {
-    let manager = RollbackManager::new();
-    let executed = Arc::new(Mutex::new(Vec::new()));
+    let dir = tempdir()?;
+    let target = dir.path().join("zshrc");
+    let backup = dir.path().join("zshrc.bak");
+    fs::write(&backup, "original")?;
 
-    manager.register_action("first", {
-        let executed = executed.clone();
-        move || {
-            executed.lock().unwrap().push("first".to_string());
-            Ok(())
-        }
-    });
-    manager.register_action("second", {
-        let executed = executed.clone();
-        move || {
-            executed.lock().unwrap().push("second".to_string());
-            Ok(())
-        }
-    });
-    manager.register_action("third", {
-        let executed = executed.clone();
-        move || {
-            executed.lock().unwrap().push("third".to_string());
-            Ok(())
-        }
-    });
+    let manager = RollbackManager::new();
+    // Replayed newest first: the restore runs before the removal, so the
+    // file ends up gone. In registration order it would survive.
+    manager.register_action(
+        "remove zshrc",
+        RollbackOp::RemoveFile {
+            path: target.clone(),
+        },
+    );
+    manager.register_action(
+        "restore zshrc",
+        RollbackOp::RestoreFile {
+            path: target.clone(),
+            backup,
+        },
+    );
 
-    manager.rollback_all()?;
+    manager.rollback_files().result?;
 
-    let history = executed.lock().unwrap();
-    assert_eq!(history.as_slice(), ["third", "second", "first"]);
+    assert!(!target.exists());
     Ok(())
 }
 
 #[test]
 fn rollback_manager_reports_errors_and_runs_all_actions() -> Result<()> {
-    let manager = RollbackManager::new();
-    let executed = Arc::new(Mutex::new(Vec::new()));
+    let dir = tempdir()?;
+    let created = dir.path().join("created");
+    fs::create_dir(&created)?;
 
-    manager.register_action("success", {
-        let executed = executed.clone();
-        move || {
-            executed.lock().unwrap().push("success".to_string());
-            Ok(())
-        }
-    });
-
-    manager.register_action("failing", {
-        let executed = executed.clone();
-        move || {
-            executed.lock().unwrap().push("failing".to_string());
-            Err(anyhow!("boom"))
-        }
-    });
+    let manager = RollbackManager::new();
+    manager.register_action(
+        "success",
+        RollbackOp::RemoveFile {
+            path: created.clone(),
+        },
+    );
+    manager.register_action(
+        "failing",
+        RollbackOp::RestoreFile {
+            path: dir.path().join("config"),
+            backup: dir.path().join("missing.bak"),
+        },
+    );
 
     let err = manager
-        .rollback_all()
+        .rollback_files()
+        .result
         .expect_err("expected rollback to fail");
-    let history = executed.lock().unwrap();
-    assert_eq!(history.as_slice(), ["failing", "success"]);
+    assert!(!created.exists());
     let message = err.to_string();
     assert!(message.contains("rollback failures"));
-    assert!(message.contains("failing: boom"));
+    assert!(message.contains("failing: backup"));
     Ok(())
 }
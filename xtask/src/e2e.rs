@@ -0,0 +1,535 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// User the installer runs as inside the container (passwordless sudo).
+const E2E_USER: &str = "mash";
+
+/// Where a `--mirror` directory is mounted inside the container.
+const MIRROR_MOUNT: &str = "/mirror";
+
+/// Where the `mash-setup` binary is mounted inside the container.
+const BINARY_MOUNT: &str = "/usr/local/bin/mash-setup";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Family {
+    Apt,
+    Pacman,
+    Dnf,
+}
+
+struct Target {
+    name: &'static str,
+    image: &'static str,
+    family: Family,
+}
+
+const TARGETS: &[Target] = &[
+    Target {
+        name: "debian",
+        image: "docker.io/library/debian:bookworm",
+        family: Family::Apt,
+    },
+    Target {
+        name: "ubuntu",
+        image: "docker.io/library/ubuntu:24.04",
+        family: Family::Apt,
+    },
+    Target {
+        name: "arch",
+        image: "docker.io/library/archlinux:latest",
+        family: Family::Pacman,
+    },
+    Target {
+        name: "fedora",
+        image: "registry.fedoraproject.org/fedora:41",
+        family: Family::Dnf,
+    },
+];
+
+const PROFILES: &[&str] = &["minimal", "dev"];
+
+/// What a finished run must have left behind.
+struct Expectation {
+    packages: Vec<&'static str>,
+    /// Paths relative to the install user's home directory.
+    files: Vec<&'static str>,
+    /// A file relative to the home directory and a line it must contain.
+    markers: Vec<(&'static str, &'static str)>,
+    /// Commands found on `PATH` or in `~/.cargo/bin`.
+    commands: Vec<&'static str>,
+    units: Vec<&'static str>,
+}
+
+impl Family {
+    /// Packages from the distro's own repositories, so they can be mirrored.
+    fn packages(self, profile: &str) -> Vec<&'static str> {
+        let mut packages = match self {
+            Family::Apt => vec!["curl", "git", "jq", "build-essential", "cmake", "git-lfs"],
+            Family::Pacman => vec!["curl", "git", "jq", "base-devel", "cmake", "git-lfs"],
+            Family::Dnf => vec!["curl", "git", "jq", "gcc", "cmake", "git-lfs"],
+        };
+        if profile == "dev" {
+            packages.extend(["zsh", "rclone", "ripgrep", "fzf", "tmux", "neovim", "htop"]);
+        }
+        packages
+    }
+
+    fn expectation(self, profile: &str) -> Expectation {
+        let mut files = vec![".config/mash-installer/history.db", "mash-install.log"];
+        let mut markers = Vec::new();
+        let mut units = Vec::new();
+        if profile == "dev" {
+            files.extend([".zshrc", ".config/starship/starship.toml"]);
+            markers.push((".zshrc", "starship init zsh"));
+            units.push("docker.service");
+        }
+        Expectation {
+            packages: self.packages(profile),
+            files,
+            markers,
+            commands: vec!["rustup", "cargo"],
+            units,
+        }
+    }
+
+    /// Install sudo and create the unprivileged install user.
+    fn provision(self) -> String {
+        let install_sudo = match self {
+            Family::Apt => "apt-get update -qq && apt-get install -y -qq sudo",
+            Family::Pacman => "pacman -Sy --noconfirm --needed sudo",
+            Family::Dnf => "dnf install -y -q sudo",
+        };
+        format!(
+            "set -e; {install_sudo}; \
+             id {E2E_USER} >/dev/null 2>&1 || useradd -m -s /bin/bash {E2E_USER}; \
+             echo '{E2E_USER} ALL=(ALL) NOPASSWD:ALL' > /etc/sudoers.d/{E2E_USER}; \
+             chmod 0440 /etc/sudoers.d/{E2E_USER}"
+        )
+    }
+
+    /// Replace the image's repositories with the mounted mirror.
+    fn use_mirror(self, target: &str) -> String {
+        let repo = format!("{MIRROR_MOUNT}/{target}");
+        match self {
+            Family::Apt => format!(
+                "set -e; rm -f /etc/apt/sources.list /etc/apt/sources.list.d/*; \
+                 echo 'deb [trusted=yes] file:{repo} ./' > /etc/apt/sources.list.d/mash-mirror.list"
+            ),
+            Family::Pacman => format!(
+                "set -e; sed -i '/^\\[core\\]/,$d' /etc/pacman.conf; \
+                 printf '[mash-mirror]\\nSigLevel = Never\\nServer = file://{repo}\\n' >> /etc/pacman.conf"
+            ),
+            Family::Dnf => format!(
+                "set -e; rm -f /etc/yum.repos.d/*.repo; \
+                 printf '[mash-mirror]\\nname=mash mirror\\nbaseurl=file://{repo}\\ngpgcheck=0\\nenabled=1\\n' \
+                 > /etc/yum.repos.d/mash-mirror.repo"
+            ),
+        }
+    }
+
+    /// Keep every package the package manager downloads.
+    fn keep_downloads(self) -> &'static str {
+        match self {
+            Family::Apt => "rm -f /etc/apt/apt.conf.d/docker-clean",
+            Family::Pacman => "true",
+            Family::Dnf => "echo keepcache=True >> /etc/dnf/dnf.conf",
+        }
+    }
+
+    /// Copy downloaded packages into the mirror and index them.
+    fn seed_mirror(self, target: &str) -> String {
+        let repo = format!("{MIRROR_MOUNT}/{target}");
+        match self {
+            Family::Apt => format!(
+                "set -e; mkdir -p {repo}; cp /var/cache/apt/archives/*.deb {repo}/; \
+                 apt-get install -y -qq dpkg-dev; \
+                 cd {repo} && dpkg-scanpackages . /dev/null | gzip -9 > Packages.gz"
+            ),
+            Family::Pacman => format!(
+                "set -e; mkdir -p {repo}; cp /var/cache/pacman/pkg/*.pkg.tar.* {repo}/; \
+                 rm -f {repo}/*.sig; \
+                 repo-add -q {repo}/mash-mirror.db.tar.gz {repo}/*.pkg.tar.*"
+            ),
+            Family::Dnf => format!(
+                "set -e; mkdir -p {repo}; find /var/cache/dnf /var/cache/libdnf5 -name '*.rpm' \
+                 -exec cp {{}} {repo}/ \\; ; \
+                 dnf install -y -q createrepo_c; createrepo_c -q {repo}"
+            ),
+        }
+    }
+
+    fn package_query(self, package: &str) -> String {
+        match self {
+            Family::Apt => format!("dpkg -s {package}"),
+            Family::Pacman => format!("pacman -Q {package}"),
+            Family::Dnf => format!("rpm -q {package}"),
+        }
+    }
+}
+
+struct Options {
+    engine: Option<String>,
+    targets: Vec<&'static Target>,
+    profiles: Vec<String>,
+    mirror: Option<PathBuf>,
+    seed_mirror: bool,
+    binary: Option<PathBuf>,
+    keep: bool,
+}
+
+fn usage() {
+    println!("Usage: cargo xtask e2e [options]");
+    println!();
+    println!("  --engine <podman|docker>  Container engine (default: podman, then docker)");
+    println!("  --distro <a,b,..>         debian, ubuntu, arch, fedora (default: all)");
+    println!("  --profile <a,b,..>        minimal, dev (default: both)");
+    println!("  --mirror <DIR>            Install from the package mirror in DIR (offline)");
+    println!(
+        "  --seed-mirror             Run online and fill --mirror DIR with what was downloaded"
+    );
+    println!("  --binary <PATH>           mash-setup binary to test (default: build release)");
+    println!("  --keep                    Leave containers running for inspection");
+}
+
+fn parse_args(args: &[String]) -> Result<Options, Box<dyn std::error::Error>> {
+    let mut options = Options {
+        engine: None,
+        targets: TARGETS.iter().collect(),
+        profiles: PROFILES.iter().map(|p| p.to_string()).collect(),
+        mirror: None,
+        seed_mirror: false,
+        binary: None,
+        keep: false,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{arg} needs a value"))
+        };
+        match arg.as_str() {
+            "--engine" => options.engine = Some(value()?),
+            "--distro" => {
+                options.targets = value()?
+                    .split(',')
+                    .map(|name| {
+                        TARGETS
+                            .iter()
+                            .find(|t| t.name == name)
+                            .ok_or_else(|| format!("unknown distro '{name}'"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "--profile" => {
+                options.profiles = value()?.split(',').map(str::to_string).collect();
+                if let Some(bad) = options
+                    .profiles
+                    .iter()
+                    .find(|p| !PROFILES.contains(&p.as_str()))
+                {
+                    return Err(format!("unknown profile '{bad}'").into());
+                }
+            }
+            "--mirror" => options.mirror = Some(PathBuf::from(value()?)),
+            "--seed-mirror" => options.seed_mirror = true,
+            "--binary" => options.binary = Some(PathBuf::from(value()?)),
+            "--keep" => options.keep = true,
+            "-h" | "--help" => {
+                usage();
+                std::process::exit(0);
+            }
+            other => return Err(format!("unknown option '{other}'").into()),
+        }
+    }
+
+    if options.seed_mirror && options.mirror.is_none() {
+        return Err("--seed-mirror needs --mirror <DIR>".into());
+    }
+    Ok(options)
+}
+
+fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn has_program(name: &str) -> bool {
+    Command::new("which")
+        .arg(name)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+fn pick_engine(requested: Option<String>) -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(engine) = requested {
+        return Ok(engine);
+    }
+    ["podman", "docker"]
+        .into_iter()
+        .find(|engine| has_program(engine))
+        .map(str::to_string)
+        .ok_or_else(|| "neither podman nor docker found".into())
+}
+
+fn build_binary(root: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    println!("Building mash-setup (release)...");
+    let status = Command::new("cargo")
+        .args([
+            "build",
+            "--release",
+            "-p",
+            "installer-cli",
+            "--bin",
+            "mash-setup",
+        ])
+        .current_dir(root)
+        .status()?;
+    if !status.success() {
+        return Err("cargo build failed".into());
+    }
+    Ok(root.join("target/release/mash-setup"))
+}
+
+struct Container<'a> {
+    engine: &'a str,
+    id: String,
+    keep: bool,
+}
+
+impl Container<'_> {
+    fn exec(&self, user: Option<&str>, script: &str) -> std::io::Result<Output> {
+        let mut cmd = Command::new(self.engine);
+        cmd.arg("exec");
+        if let Some(user) = user {
+            cmd.args(["-u", user, "-w", &format!("/home/{user}")]);
+        }
+        cmd.args([&self.id, "sh", "-c", script])
+            .stdin(Stdio::null())
+            .output()
+    }
+
+    /// Run `script` as root and fail with its output if it fails.
+    fn root(&self, what: &str, script: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let output = self.exec(None, script)?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{what} failed:\n{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .into())
+        }
+    }
+}
+
+impl Drop for Container<'_> {
+    fn drop(&mut self) {
+        if self.keep {
+            println!("  container kept: {}", self.id);
+            return;
+        }
+        let _ = Command::new(self.engine)
+            .args(["rm", "-f", &self.id])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+fn start<'a>(
+    engine: &'a str,
+    target: &Target,
+    binary: &Path,
+    options: &Options,
+) -> Result<Container<'a>, Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(engine);
+    cmd.args(["run", "-d", "--pull=never"]);
+    if options.mirror.is_some() && !options.seed_mirror {
+        cmd.arg("--network=none");
+    }
+    cmd.arg("-v")
+        .arg(format!("{}:{BINARY_MOUNT}:ro", binary.display()));
+    if let Some(mirror) = &options.mirror {
+        let mode = if options.seed_mirror { "rw" } else { "ro" };
+        cmd.arg("-v")
+            .arg(format!("{}:{MIRROR_MOUNT}:{mode}", mirror.display()));
+    }
+    let output = cmd
+        .args([target.image, "sleep", "infinity"])
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "starting {} failed (is the image pulled?): {}",
+            target.image,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(Container {
+        engine,
+        id: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        keep: options.keep,
+    })
+}
+
+/// Fetch the newest run's stored report through `mash-setup history`.
+fn collect_report(container: &Container) -> Result<String, Box<dyn std::error::Error>> {
+    let list = container.exec(Some(E2E_USER), "mash-setup history list --limit 1 --json")?;
+    let runs: serde_json::Value = serde_json::from_slice(&list.stdout)?;
+    let id = runs
+        .get(0)
+        .and_then(|run| run.get("id"))
+        .and_then(serde_json::Value::as_i64)
+        .ok_or("the installer did not record a run")?;
+    let show = container.exec(
+        Some(E2E_USER),
+        &format!("mash-setup history show {id} --json"),
+    )?;
+    if !show.status.success() {
+        return Err(String::from_utf8_lossy(&show.stderr).into_owned().into());
+    }
+    Ok(String::from_utf8_lossy(&show.stdout).into_owned())
+}
+
+/// Check the expected state; returns one line per check.
+fn verify(container: &Container, family: Family, profile: &str) -> Vec<(bool, String)> {
+    let expected = family.expectation(profile);
+    let check = |script: String| {
+        container
+            .exec(Some(E2E_USER), &script)
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    };
+
+    let mut results = Vec::new();
+    for package in expected.packages {
+        let ok = check(family.package_query(package));
+        results.push((ok, format!("package {package}")));
+    }
+    for file in expected.files {
+        let ok = check(format!("test -e \"$HOME/{file}\""));
+        results.push((ok, format!("file ~/{file}")));
+    }
+    for (file, marker) in expected.markers {
+        let ok = check(format!("grep -qF '{marker}' \"$HOME/{file}\""));
+        results.push((ok, format!("file ~/{file} contains '{marker}'")));
+    }
+    for command in expected.commands {
+        let ok = check(format!(
+            "command -v {command} >/dev/null || test -x \"$HOME/.cargo/bin/{command}\""
+        ));
+        results.push((ok, format!("command {command}")));
+    }
+    // Images usually run without systemd as PID 1, where `systemctl
+    // is-enabled` cannot answer; the symlink `systemctl enable` creates
+    // settles it either way.
+    for unit in expected.units {
+        let ok = check(format!(
+            "systemctl is-enabled --quiet {unit} 2>/dev/null \
+             || ls /etc/systemd/system/*.wants/{unit} >/dev/null 2>&1"
+        ));
+        results.push((ok, format!("unit {unit} enabled")));
+    }
+    results
+}
+
+fn run_case(
+    engine: &str,
+    target: &Target,
+    profile: &str,
+    binary: &Path,
+    options: &Options,
+    artifacts: &Path,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let dir = artifacts.join(format!("{}-{profile}", target.name));
+    fs::create_dir_all(&dir)?;
+
+    let container = start(engine, target, binary, options)?;
+    if options.mirror.is_some() && !options.seed_mirror {
+        container.root("mirror setup", &target.family.use_mirror(target.name))?;
+    }
+    if options.seed_mirror {
+        container.root("cache setup", target.family.keep_downloads())?;
+    }
+    container.root("provisioning", &target.family.provision())?;
+
+    println!("  installing...");
+    let install = container.exec(
+        Some(E2E_USER),
+        &format!(
+            "mash-setup --non-interactive --no-tui --continue-on-error \
+             --allow-unverified-scripts --profile {profile}"
+        ),
+    )?;
+    fs::write(
+        dir.join("install.log"),
+        [install.stdout.as_slice(), install.stderr.as_slice()].concat(),
+    )?;
+
+    if options.seed_mirror {
+        container.root("mirror seeding", &target.family.seed_mirror(target.name))?;
+    }
+
+    let report = collect_report(&container)?;
+    fs::write(dir.join("report.json"), &report)?;
+
+    let results = verify(&container, target.family, profile);
+    let mut summary = String::new();
+    for (ok, check) in &results {
+        let mark = if *ok { "ok  " } else { "FAIL" };
+        println!("    [{mark}] {check}");
+        summary.push_str(&format!("[{mark}] {check}\n"));
+    }
+    fs::write(dir.join("checks.txt"), summary)?;
+    Ok(install.status.success() && results.iter().all(|(ok, _)| *ok))
+}
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let options = parse_args(args)?;
+    let root = crate::project_root();
+    let engine = pick_engine(options.engine.clone())?;
+    let binary = match &options.binary {
+        Some(path) => path.canonicalize()?,
+        None => build_binary(&root)?,
+    };
+    if let Some(mirror) = &options.mirror {
+        fs::create_dir_all(mirror)?;
+    }
+
+    let artifacts = root.join(".logs").join(format!("e2e-{}", timestamp()));
+    fs::create_dir_all(&artifacts)?;
+
+    let mut failures = Vec::new();
+    for target in &options.targets {
+        for profile in &options.profiles {
+            println!("\n==== E2E: {} / {profile} ({engine}) ====", target.name);
+            match run_case(&engine, target, profile, &binary, &options, &artifacts) {
+                Ok(true) => {}
+                Ok(false) => failures.push(format!("{}-{profile}", target.name)),
+                Err(e) => {
+                    eprintln!("  error: {e}");
+                    failures.push(format!("{}-{profile}", target.name));
+                }
+            }
+        }
+    }
+
+    println!("\nArtifacts: {}", artifacts.display());
+    if failures.is_empty() {
+        println!("All end-to-end cases passed.");
+        Ok(())
+    } else {
+        eprintln!("Failed cases: {}", failures.join(", "));
+        std::process::exit(1);
+    }
+}
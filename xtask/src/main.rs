@@ -1,6 +1,7 @@
 mod branch_prune;
 mod bump;
 mod check_docs;
+mod e2e;
 mod hygiene;
 mod release_check;
 mod test_infra;
@@ -24,6 +25,7 @@ fn usage() {
     println!("  branch-prune  Prune local branches older than 7 days");
     println!("  test-infra    Run test infrastructure (maelstrom|hardware modes)");
     println!("  test-theme    Run theme integration checks");
+    println!("  e2e           Run the installer in distro containers and check the result");
 }
 
 fn main() {
@@ -45,6 +47,7 @@ fn main() {
         "branch-prune" => branch_prune::run(&rest),
         "test-infra" => test_infra::run(&rest),
         "test-theme" => test_theme::run(&rest),
+        "e2e" => e2e::run(&rest),
         _ => {
             eprintln!("Unknown subcommand: {}", subcommand);
             usage();